@@ -97,8 +97,7 @@ impl StorageManagement for FungibleToken {
     }
 
     fn storage_balance_bounds(&self) -> StorageBalanceBounds {
-        let required_storage_balance =
-            Balance::from(self.account_storage_usage) * env::storage_byte_cost();
+        let required_storage_balance = env::required_storage_deposit(self.account_storage_usage);
         StorageBalanceBounds {
             min: required_storage_balance.into(),
             max: Some(required_storage_balance.into()),
@@ -1,4 +1,4 @@
-use near_sdk::{env, require, AccountId, Balance, CryptoHash, Promise};
+use near_sdk::{env, require, AccountId, CryptoHash, Promise};
 use std::collections::HashMap;
 use std::mem::size_of;
 
@@ -16,7 +16,7 @@ where
     I: Iterator<Item = &'a AccountId>,
 {
     let storage_released: u64 = approved_account_ids.map(bytes_for_approved_account_id).sum();
-    Promise::new(account_id).transfer(Balance::from(storage_released) * env::storage_byte_cost())
+    Promise::new(account_id).transfer(env::required_storage_deposit(storage_released))
 }
 
 pub fn refund_approved_account_ids(
@@ -27,7 +27,7 @@ pub fn refund_approved_account_ids(
 }
 
 pub fn refund_deposit(storage_used: u64) {
-    let required_cost = env::storage_byte_cost() * Balance::from(storage_used);
+    let required_cost = env::required_storage_deposit(storage_used);
     let attached_deposit = env::attached_deposit();
 
     require!(
@@ -0,0 +1,136 @@
+//! `#[access_control(roles(..), admin(..))]` declares the roles of a contract, which guard its
+//! methods with `#[only(..)]`, and generates the methods managing the roles.
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::spanned::Spanned;
+use syn::{AttributeArgs, Error, Ident, ItemStruct, Meta, MetaList, NestedMeta};
+
+/// Roles of the contract and the roles which can grant and revoke them.
+struct AccessControlArgs {
+    roles: Vec<Ident>,
+    admin_roles: Vec<Ident>,
+}
+
+impl AccessControlArgs {
+    fn parse(args: &AttributeArgs) -> syn::Result<Self> {
+        let mut roles = None;
+        let mut admin_roles = vec![];
+        for arg in args {
+            let list = match arg {
+                NestedMeta::Meta(Meta::List(list)) => list,
+                _ => return Err(Error::new(arg.span(), "Expected `roles(..)` or `admin(..)`.")),
+            };
+            let idents = role_idents(list)?;
+            if list.path.is_ident("roles") {
+                roles = Some(idents);
+            } else if list.path.is_ident("admin") {
+                admin_roles = idents;
+            } else {
+                return Err(Error::new(list.path.span(), "Expected `roles(..)` or `admin(..)`."));
+            }
+        }
+        let roles = match roles {
+            Some(roles) if !roles.is_empty() => roles,
+            _ => return Err(Error::new(proc_macro2::Span::call_site(), "Expected `roles(..)`.")),
+        };
+        if let Some(role) = admin_roles.iter().find(|role| !roles.contains(role)) {
+            return Err(Error::new(
+                role.span(),
+                "The admin roles should be declared in `roles(..)`.",
+            ));
+        }
+        Ok(Self { roles, admin_roles })
+    }
+}
+
+/// Returns the roles listed as `name(Role, ..)`.
+pub(crate) fn role_idents(list: &MetaList) -> syn::Result<Vec<Ident>> {
+    list.nested
+        .iter()
+        .map(|nested| match nested {
+            NestedMeta::Meta(Meta::Path(path)) => path
+                .get_ident()
+                .cloned()
+                .ok_or_else(|| Error::new(path.span(), "Expected the name of a role.")),
+            _ => Err(Error::new(nested.span(), "Expected the name of a role.")),
+        })
+        .collect()
+}
+
+/// Generates the constants of the roles and the `acl_*` methods of the contract.
+pub fn access_control(args: &AttributeArgs, item: &ItemStruct) -> syn::Result<TokenStream2> {
+    let AccessControlArgs { roles, admin_roles } = AccessControlArgs::parse(args)?;
+    let name = &item.ident;
+    let role_consts = roles.iter().map(|role| {
+        let ident = format_ident!("__ACL_ROLE_{}", role);
+        let value = role.to_string();
+        quote! {
+            #[doc(hidden)]
+            #[allow(non_upper_case_globals)]
+            pub const #ident: &'static str = #value;
+        }
+    });
+    let role_names = roles.iter().map(|role| role.to_string());
+    let admin_names = admin_roles.iter().map(|role| role.to_string());
+    Ok(quote! {
+        impl #name {
+            #(#role_consts)*
+
+            /// Roles of the contract, declared with `#[access_control]`.
+            pub const ACL_ROLES: &'static [&'static str] = &[#(#role_names),*];
+
+            /// Roles which can grant and revoke the roles, besides the contract itself.
+            pub const ACL_ADMIN_ROLES: &'static [&'static str] = &[#(#admin_names),*];
+        }
+
+        #[near_sdk::near_bindgen]
+        impl #name {
+            /// Returns whether the account has the role.
+            pub fn acl_has_role(&self, role: String, account_id: near_sdk::AccountId) -> bool {
+                near_sdk::access_control::has_role(&role, &account_id)
+            }
+
+            /// Grants the role to the account, and returns whether the account did not have it.
+            pub fn acl_grant_role(role: String, account_id: near_sdk::AccountId) -> bool {
+                near_sdk::access_control::require_admin(Self::ACL_ADMIN_ROLES, "acl_grant_role");
+                near_sdk::access_control::require_known_role(Self::ACL_ROLES, &role);
+                near_sdk::access_control::grant_role(&role, &account_id)
+            }
+
+            /// Revokes the role from the account, and returns whether the account had it.
+            pub fn acl_revoke_role(role: String, account_id: near_sdk::AccountId) -> bool {
+                near_sdk::access_control::require_admin(Self::ACL_ADMIN_ROLES, "acl_revoke_role");
+                near_sdk::access_control::require_known_role(Self::ACL_ROLES, &role);
+                near_sdk::access_control::revoke_role(&role, &account_id)
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_args(args: TokenStream2) -> AttributeArgs {
+        let attr: syn::Attribute = syn::parse_quote! { #[access_control(#args)] };
+        match attr.parse_meta().unwrap() {
+            Meta::List(list) => list.nested.into_iter().collect(),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn roles() {
+        let args = parse_args(quote! { roles(Admin, Operator), admin(Admin) });
+        let args = AccessControlArgs::parse(&args).unwrap();
+        assert_eq!(args.roles, ["Admin", "Operator"]);
+        assert_eq!(args.admin_roles, ["Admin"]);
+    }
+
+    #[test]
+    fn undeclared_admin() {
+        let args = parse_args(quote! { roles(Operator), admin(Admin) });
+        let error = AccessControlArgs::parse(&args).map(|_| ()).unwrap_err();
+        assert_eq!(error.to_string(), "The admin roles should be declared in `roles(..)`.");
+    }
+}
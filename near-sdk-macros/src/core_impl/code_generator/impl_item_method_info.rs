@@ -2,19 +2,67 @@ use crate::core_impl::info_extractor::{
     AttrSigInfo, ImplItemMethodInfo, InputStructType, MethodType, SerializerType,
 };
 use proc_macro2::TokenStream as TokenStream2;
-use quote::quote;
+use quote::{format_ident, quote};
 use syn::{ReturnType, Signature};
 
 impl ImplItemMethodInfo {
     /// Generate wrapper method for the given method of the contract.
     pub fn method_wrapper(&self) -> TokenStream2 {
+        let panic_hook = quote! {
+            near_sdk::env::setup_panic_hook();
+        };
+        let input = quote! {
+            near_sdk::env::input().expect("Expected input since method has arguments.")
+        };
+        let body = self.wrapper_body(&input);
+        let AttrSigInfo { non_bindgen_attrs, ident, .. } = &self.attr_signature_info;
+        let non_bindgen_attrs = non_bindgen_attrs.iter().fold(TokenStream2::new(), |acc, value| {
+            quote! {
+                #acc
+                #value
+            }
+        });
+        quote! {
+            #non_bindgen_attrs
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn #ident() {
+                #panic_hook
+                #body
+            }
+        }
+    }
+
+    /// Generate the dispatcher of the given method for unit tests, which runs the code of the
+    /// wrapper with the arguments given as `input`, and returns the serialized return value, so
+    /// that the checks, the parsing of the arguments and the serialization of the result are
+    /// covered by the tests of the contract.
+    pub fn method_dispatcher(&self) -> TokenStream2 {
+        let input = quote! { input };
+        let body = self.wrapper_body(&input);
+        let AttrSigInfo { ident, returns, method_type, .. } = &self.attr_signature_info;
+        let dispatcher = format_ident!("__dispatch_{}", ident);
+        let result = match (returns, method_type) {
+            (ReturnType::Type(_, _), MethodType::Regular | MethodType::View) => quote! { result },
+            _ => quote! { ::std::vec::Vec::new() },
+        };
+        quote! {
+            #[doc(hidden)]
+            #[allow(unused_variables)]
+            pub fn #dispatcher(input: &[u8]) -> ::std::vec::Vec<u8> {
+                #body
+                #result
+            }
+        }
+    }
+
+    /// Generate the code of the wrapper after the panic hook, which reads the arguments from
+    /// `input`.
+    fn wrapper_body(&self, input: &TokenStream2) -> TokenStream2 {
         let ImplItemMethodInfo { attr_signature_info, struct_type, .. } = self;
         // Args provided by `env::input()`.
         let has_input_args = attr_signature_info.input_args().next().is_some();
 
-        let panic_hook = quote! {
-            near_sdk::env::setup_panic_hook();
-        };
         let arg_struct;
         let arg_parsing;
         if has_input_args {
@@ -23,12 +71,12 @@ impl ImplItemMethodInfo {
             let serializer_invocation = match attr_signature_info.input_serializer {
                 SerializerType::JSON => quote! {
                     near_sdk::serde_json::from_slice(
-                        &near_sdk::env::input().expect("Expected input since method has arguments.")
+                        &#input
                     ).expect("Failed to deserialize input from JSON.")
                 },
                 SerializerType::Borsh => quote! {
                     near_sdk::borsh::BorshDeserialize::try_from_slice(
-                        &near_sdk::env::input().expect("Expected input since method has arguments.")
+                        &#input
                     ).expect("Failed to deserialize input from Borsh.")
                 },
             };
@@ -45,7 +93,6 @@ impl ImplItemMethodInfo {
 
         let arg_list = attr_signature_info.arg_list();
         let AttrSigInfo {
-            non_bindgen_attrs,
             ident,
             receiver,
             returns,
@@ -53,6 +100,10 @@ impl ImplItemMethodInfo {
             method_type,
             is_payable,
             is_private,
+            is_handles_result,
+            is_only_owner,
+            only_roles,
+            paused_feature,
             ..
         } = attr_signature_info;
         let deposit_check = if *is_payable || matches!(method_type, &MethodType::View) {
@@ -77,17 +128,63 @@ impl ImplItemMethodInfo {
         } else {
             quote! {}
         };
+        let owner_check = if *is_only_owner {
+            let method = ident.to_string();
+            quote! {
+                near_sdk::ownable::require_owner(#method);
+            }
+        } else {
+            quote! {}
+        };
+        let role_check = if only_roles.is_empty() {
+            quote! {}
+        } else {
+            let method = ident.to_string();
+            // The constants of the roles are declared by `#[access_control]` on the contract.
+            let roles = only_roles.iter().map(|role| {
+                let role = format_ident!("__ACL_ROLE_{}", role);
+                quote! { <#struct_type>::#role }
+            });
+            quote! {
+                near_sdk::access_control::require_any_role(&[#(#roles),*], #method);
+            }
+        };
+        let pause_check = match paused_feature {
+            Some(feature) => {
+                let method = ident.to_string();
+                quote! {
+                    near_sdk::pausable::require_not_paused(#feature, #method);
+                }
+            }
+            None => quote! {},
+        };
+        // Methods marked with `#[handle_result]` abort with the error instead of returning it.
+        let result_handling = |value: TokenStream2| {
+            if *is_handles_result {
+                quote! {
+                    let #value = match #value {
+                        Ok(#value) => #value,
+                        Err(err) => near_sdk::env::panic_err(err),
+                    };
+                }
+            } else {
+                TokenStream2::new()
+            }
+        };
+        let contract_handling = result_handling(quote! { contract });
         let body = if matches!(method_type, &MethodType::Init) {
             quote! {
                 if near_sdk::env::state_exists() {
                     near_sdk::env::panic_str("The contract has already been initialized");
                 }
                 let contract = #struct_type::#ident(#arg_list);
+                #contract_handling
                 near_sdk::env::state_write(&contract);
             }
         } else if matches!(method_type, &MethodType::InitIgnoreState) {
             quote! {
                 let contract = #struct_type::#ident(#arg_list);
+                #contract_handling
                 near_sdk::env::state_write(&contract);
             }
         } else {
@@ -123,9 +220,10 @@ impl ImplItemMethodInfo {
                     #contract_ser
                 },
                 ReturnType::Type(_, _) => {
+                    let result_handling = result_handling(quote! { result });
                     let value_ser = match result_serializer {
                         SerializerType::JSON => quote! {
-                            let result = near_sdk::serde_json::to_vec(&result).expect("Failed to serialize the return value using JSON.");
+                            let result = near_sdk::json_types::canonical::result_to_vec(&result).expect("Failed to serialize the return value using JSON.");
                         },
                         SerializerType::Borsh => quote! {
                             let result = near_sdk::borsh::BorshSerialize::try_to_vec(&result).expect("Failed to serialize the return value using Borsh.");
@@ -134,6 +232,7 @@ impl ImplItemMethodInfo {
                     quote! {
                     #contract_deser
                     let result = #method_invocation;
+                    #result_handling
                     #value_ser
                     near_sdk::env::value_return(&result);
                     #contract_ser
@@ -141,26 +240,17 @@ impl ImplItemMethodInfo {
                 }
             }
         };
-        let non_bindgen_attrs = non_bindgen_attrs.iter().fold(TokenStream2::new(), |acc, value| {
-            quote! {
-                #acc
-                #value
-            }
-        });
         quote! {
-            #non_bindgen_attrs
-            #[cfg(target_arch = "wasm32")]
-            #[no_mangle]
-            pub extern "C" fn #ident() {
-                #panic_hook
-                #is_private_check
-                #deposit_check
-                #arg_struct
-                #arg_parsing
-                #callback_deser
-                #callback_vec_deser
-                #body
-            }
+            #is_private_check
+            #owner_check
+            #role_check
+            #pause_check
+            #deposit_check
+            #arg_struct
+            #arg_parsing
+            #callback_deser
+            #callback_vec_deser
+            #body
         }
     }
 
@@ -14,6 +14,23 @@ impl ItemImplInfo {
         res
     }
 
+    /// Generate the dispatchers of the methods, which are only compiled in unit tests.
+    pub fn dispatcher_code(&self) -> TokenStream2 {
+        use quote::quote;
+        let ty = &self.ty;
+        let dispatchers = self
+            .methods
+            .iter()
+            .filter(|method| method.is_public || self.is_trait_impl)
+            .map(|method| method.method_dispatcher());
+        quote! {
+            #[cfg(all(test, not(target_arch = "wasm32")))]
+            impl #ty {
+                #(#dispatchers)*
+            }
+        }
+    }
+
     pub fn marshall_code(&self) -> TokenStream2 {
         use quote::{format_ident, quote, ToTokens};
         let orig_name = self.ty.clone().into_token_stream();
@@ -191,7 +208,7 @@ mod tests {
                     let mut contract: Hello = near_sdk::env::state_read().unwrap_or_default();
                     let result = contract.method(k, m, );
                     let result =
-                        near_sdk::serde_json::to_vec(&result).expect("Failed to serialize the return value using JSON.");
+                        near_sdk::json_types::canonical::result_to_vec(&result).expect("Failed to serialize the return value using JSON.");
                     near_sdk::env::value_return(&result);
                     near_sdk::env::state_write(&contract);
                 }
@@ -214,7 +231,7 @@ mod tests {
                 let contract: Hello = near_sdk::env::state_read().unwrap_or_default();
                 let result = contract.method();
                 let result =
-                    near_sdk::serde_json::to_vec(&result).expect("Failed to serialize the return value using JSON.");
+                    near_sdk::json_types::canonical::result_to_vec(&result).expect("Failed to serialize the return value using JSON.");
                 near_sdk::env::value_return(&result);
             }
         );
@@ -658,6 +675,84 @@ mod tests {
         assert_eq!(expected.to_string(), actual.to_string());
     }
 
+    #[test]
+    fn only_owner_method() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = syn::parse_str("#[only_owner] pub fn set_fee(&mut self, fee: u64) { }").unwrap();
+        let method_info = ImplItemMethodInfo::new(&mut method, impl_type).unwrap();
+        let actual = method_info.method_wrapper();
+        let expected = quote!(
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn set_fee() {
+                near_sdk::env::setup_panic_hook();
+                near_sdk::ownable::require_owner("set_fee");
+                if near_sdk::env::attached_deposit() != 0 {
+                    near_sdk::env::panic_str("Method set_fee doesn't accept deposit");
+                }
+                #[derive(near_sdk :: serde :: Deserialize)]
+                #[serde(crate = "near_sdk::serde")]
+                struct Input {
+                    fee: u64,
+                }
+                let Input { fee, }: Input = near_sdk::serde_json::from_slice(
+                    &near_sdk::env::input().expect("Expected input since method has arguments.")
+                )
+                .expect("Failed to deserialize input from JSON.");
+                let mut contract: Hello = near_sdk::env::state_read().unwrap_or_default();
+                contract.set_fee(fee, );
+                near_sdk::env::state_write(&contract);
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn only_roles_method() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = syn::parse_str("#[only(Admin, Operator)] pub fn pause(&mut self) { }").unwrap();
+        let method_info = ImplItemMethodInfo::new(&mut method, impl_type).unwrap();
+        let actual = method_info.method_wrapper();
+        let expected = quote!(
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn pause() {
+                near_sdk::env::setup_panic_hook();
+                near_sdk::access_control::require_any_role(&[<Hello>::__ACL_ROLE_Admin, <Hello>::__ACL_ROLE_Operator], "pause");
+                if near_sdk::env::attached_deposit() != 0 {
+                    near_sdk::env::panic_str("Method pause doesn't accept deposit");
+                }
+                let mut contract: Hello = near_sdk::env::state_read().unwrap_or_default();
+                contract.pause();
+                near_sdk::env::state_write(&contract);
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn when_not_paused_method() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = syn::parse_str("#[when_not_paused(\"transfers\")] pub fn withdraw(&mut self) { }").unwrap();
+        let method_info = ImplItemMethodInfo::new(&mut method, impl_type).unwrap();
+        let actual = method_info.method_wrapper();
+        let expected = quote!(
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn withdraw() {
+                near_sdk::env::setup_panic_hook();
+                near_sdk::pausable::require_not_paused("transfers", "withdraw");
+                if near_sdk::env::attached_deposit() != 0 {
+                    near_sdk::env::panic_str("Method withdraw doesn't accept deposit");
+                }
+                let mut contract: Hello = near_sdk::env::state_read().unwrap_or_default();
+                contract.withdraw();
+                near_sdk::env::state_write(&contract);
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
     #[test]
     fn marshall_one_arg() {
         let impl_type: Type = syn::parse_str("Hello").unwrap();
@@ -699,4 +794,124 @@ mod tests {
         );
         assert_eq!(expected.to_string(), actual.to_string());
     }
+
+    #[test]
+    fn dispatcher_args_return_mut() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod =
+            syn::parse_str("pub fn method(&mut self, k: u64) -> Option<u64> { }").unwrap();
+        let method_info = ImplItemMethodInfo::new(&mut method, impl_type).unwrap();
+        let actual = method_info.method_dispatcher();
+        let expected = quote!(
+                #[doc(hidden)]
+                #[allow(unused_variables)]
+                pub fn __dispatch_method(input: &[u8]) -> ::std::vec::Vec<u8> {
+                    if near_sdk::env::attached_deposit() != 0 {
+                        near_sdk::env::panic_str("Method method doesn't accept deposit");
+                    }
+                    #[derive(near_sdk :: serde :: Deserialize)]
+                    #[serde(crate = "near_sdk::serde")]
+                    struct Input {
+                        k: u64,
+                    }
+                    let Input { k, }: Input = near_sdk::serde_json::from_slice(
+                        &input
+                    )
+                    .expect("Failed to deserialize input from JSON.");
+                    let mut contract: Hello = near_sdk::env::state_read().unwrap_or_default();
+                    let result = contract.method(k, );
+                    let result =
+                        near_sdk::json_types::canonical::result_to_vec(&result).expect("Failed to serialize the return value using JSON.");
+                    near_sdk::env::value_return(&result);
+                    near_sdk::env::state_write(&contract);
+                    result
+                }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn handle_result() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = parse_quote! {
+            #[handle_result]
+            pub fn method(&mut self, k: u64) -> Result<u64, Error> { }
+        };
+        let method_info = ImplItemMethodInfo::new(&mut method, impl_type).unwrap();
+        let actual = method_info.method_wrapper();
+        let expected = quote!(
+                #[cfg(target_arch = "wasm32")]
+                #[no_mangle]
+                pub extern "C" fn method() {
+                    near_sdk::env::setup_panic_hook();
+                    if near_sdk::env::attached_deposit() != 0 {
+                        near_sdk::env::panic_str("Method method doesn't accept deposit");
+                    }
+                    #[derive(near_sdk :: serde :: Deserialize)]
+                    #[serde(crate = "near_sdk::serde")]
+                    struct Input {
+                        k: u64,
+                    }
+                    let Input { k, }: Input = near_sdk::serde_json::from_slice(
+                        &near_sdk::env::input().expect("Expected input since method has arguments.")
+                    )
+                    .expect("Failed to deserialize input from JSON.");
+                    let mut contract: Hello = near_sdk::env::state_read().unwrap_or_default();
+                    let result = contract.method(k, );
+                    let result = match result {
+                        Ok(result) => result,
+                        Err(err) => near_sdk::env::panic_err(err),
+                    };
+                    let result =
+                        near_sdk::json_types::canonical::result_to_vec(&result).expect("Failed to serialize the return value using JSON.");
+                    near_sdk::env::value_return(&result);
+                    near_sdk::env::state_write(&contract);
+                }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn handle_result_init() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = parse_quote! {
+            #[init]
+            #[handle_result]
+            pub fn new() -> Result<Self, Error> { }
+        };
+        let method_info = ImplItemMethodInfo::new(&mut method, impl_type).unwrap();
+        let actual = method_info.method_wrapper();
+        let expected = quote!(
+                #[cfg(target_arch = "wasm32")]
+                #[no_mangle]
+                pub extern "C" fn new() {
+                    near_sdk::env::setup_panic_hook();
+                    if near_sdk::env::attached_deposit() != 0 {
+                        near_sdk::env::panic_str("Method new doesn't accept deposit");
+                    }
+                    if near_sdk::env::state_exists() {
+                        near_sdk::env::panic_str("The contract has already been initialized");
+                    }
+                    let contract = Hello::new();
+                    let contract = match contract {
+                        Ok(contract) => contract,
+                        Err(err) => near_sdk::env::panic_err(err),
+                    };
+                    near_sdk::env::state_write(&contract);
+                }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn handle_result_without_result() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = parse_quote! {
+            #[handle_result]
+            pub fn method(&self) -> u64 { }
+        };
+        let actual = ImplItemMethodInfo::new(&mut method, impl_type).map(|_| ()).unwrap_err();
+        let expected = "Method marked with #[handle_result] should return Result<T, E>.";
+        assert_eq!(expected, actual.to_string());
+    }
 }
@@ -0,0 +1,238 @@
+//! `#[derive(NearEvent)]` generates the NEP-297 envelope of the variants of an event enum.
+use inflector::Inflector;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::spanned::Spanned;
+use syn::{Attribute, Error, Fields, ItemEnum, Lit, Meta, NestedMeta};
+
+/// Standard and version of the events, from the `#[event_json(standard = "..", version = "..")]`
+/// attribute.
+struct EventJsonAttr {
+    standard: String,
+    version: String,
+}
+
+impl EventJsonAttr {
+    fn from_attrs(item: &ItemEnum) -> syn::Result<Self> {
+        let attr = item.attrs.iter().find(|attr| attr.path.is_ident("event_json")).ok_or_else(|| {
+            Error::new(
+                item.ident.span(),
+                "NearEvent requires the `#[event_json(standard = \"..\", version = \"..\")]` attribute.",
+            )
+        })?;
+        Self::parse(attr)
+    }
+
+    fn parse(attr: &Attribute) -> syn::Result<Self> {
+        let list = match attr.parse_meta()? {
+            Meta::List(list) => list,
+            meta => return Err(Error::new(meta.span(), "Expected `event_json(..)`.")),
+        };
+        let mut standard = None;
+        let mut version = None;
+        for nested in list.nested.iter() {
+            let (key, value) = match nested {
+                NestedMeta::Meta(Meta::NameValue(nv)) => match &nv.lit {
+                    Lit::Str(value) => (&nv.path, value.value()),
+                    lit => return Err(Error::new(lit.span(), "Expected a string.")),
+                },
+                _ => return Err(Error::new(nested.span(), "Expected `key = \"value\"`.")),
+            };
+            if key.is_ident("standard") {
+                standard = Some(value);
+            } else if key.is_ident("version") {
+                version = Some(value);
+            } else {
+                return Err(Error::new(key.span(), "Expected `standard` or `version`."));
+            }
+        }
+        match (standard, version) {
+            (Some(standard), Some(version)) => Ok(Self { standard, version }),
+            _ => Err(Error::new(list.span(), "Both `standard` and `version` are required.")),
+        }
+    }
+}
+
+/// Generates the `to_event_json` and `emit` methods of the event enum.
+pub fn near_event(item: &ItemEnum) -> syn::Result<TokenStream2> {
+    if !item.generics.params.is_empty() {
+        return Err(Error::new(
+            item.generics.params.span(),
+            "Type parameters are not supported for events.",
+        ));
+    }
+    let EventJsonAttr { standard, version } = EventJsonAttr::from_attrs(item)?;
+    let name = &item.ident;
+    let arms = item.variants.iter().map(|variant| {
+        let ident = &variant.ident;
+        let event = ident.to_string().to_snake_case();
+        let envelope = |data: TokenStream2| {
+            quote! {
+                near_sdk::EventEnvelope {
+                    standard: #standard,
+                    version: #version,
+                    event: #event,
+                    data: #data,
+                }
+                .to_json()
+            }
+        };
+        match &variant.fields {
+            Fields::Unit => {
+                let envelope = envelope(quote! { None::<()> });
+                quote! { #name::#ident => #envelope, }
+            }
+            Fields::Unnamed(fields) => {
+                let bindings: Vec<_> =
+                    (0..fields.unnamed.len()).map(|i| format_ident!("data{}", i)).collect();
+                // A single value is the data itself, and several values are a JSON array.
+                let data = if bindings.len() == 1 {
+                    quote! { Some(#(#bindings)*) }
+                } else {
+                    quote! { Some((#(#bindings),*)) }
+                };
+                let envelope = envelope(data);
+                quote! { #name::#ident(#(#bindings),*) => #envelope, }
+            }
+            Fields::Named(fields) => {
+                let idents: Vec<_> = fields.named.iter().map(|field| &field.ident).collect();
+                let data_fields = fields.named.iter().map(|field| {
+                    let ident = &field.ident;
+                    let ty = &field.ty;
+                    let serde_attrs = field.attrs.iter().filter(|attr| attr.path.is_ident("serde"));
+                    quote! {
+                        #(#serde_attrs)*
+                        #ident: &'a #ty,
+                    }
+                });
+                let envelope = envelope(quote! { Some(Data { #(#idents),* }) });
+                quote! {
+                    #name::#ident { #(#idents),* } => {
+                        #[derive(near_sdk::serde::Serialize)]
+                        #[serde(crate = "near_sdk::serde")]
+                        struct Data<'a> {
+                            #(#data_fields)*
+                        }
+                        #envelope
+                    }
+                }
+            }
+        }
+    });
+    Ok(quote! {
+        impl #name {
+            /// Returns the NEP-297 event as JSON.
+            pub fn to_event_json(&self) -> ::std::string::String {
+                match self {
+                    #(#arms)*
+                }
+            }
+
+            /// Logs the NEP-297 event as JSON after `EVENT_JSON:`.
+            pub fn emit(&self) {
+                near_sdk::env::log_str(&::std::format!(
+                    "{}{}",
+                    near_sdk::EVENT_JSON_PREFIX,
+                    self.to_event_json()
+                ));
+            }
+        }
+    })
+}
+
+/// Returns the `#[event_json(..)]` attribute of the `event_json(..)` argument of `#[near_bindgen]`,
+/// after checking it.
+pub fn event_json_attr(arg: &NestedMeta) -> syn::Result<Attribute> {
+    let attr: Attribute = syn::parse_quote! { #[#arg] };
+    if !attr.path.is_ident("event_json") {
+        return Err(Error::new(arg.span(), "Expected `event_json(..)`."));
+    }
+    EventJsonAttr::parse(&attr)?;
+    Ok(attr)
+}
+
+#[rustfmt::skip]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_variants() {
+        let item: ItemEnum = syn::parse_quote! {
+            #[event_json(standard = "nep171", version = "1.0.0")]
+            enum Event {
+                NftMint(Vec<u64>),
+                Pair(u64, String),
+                Transfer { #[serde(rename = "to")] receiver_id: AccountId, amount: U128 },
+                Paused,
+            }
+        };
+        let actual = near_event(&item).unwrap();
+        let expected = quote!(
+            impl Event {
+                /// Returns the NEP-297 event as JSON.
+                pub fn to_event_json(&self) -> ::std::string::String {
+                    match self {
+                        Event::NftMint(data0) => near_sdk::EventEnvelope {
+                            standard: "nep171",
+                            version: "1.0.0",
+                            event: "nft_mint",
+                            data: Some(data0),
+                        }
+                        .to_json(),
+                        Event::Pair(data0, data1) => near_sdk::EventEnvelope {
+                            standard: "nep171",
+                            version: "1.0.0",
+                            event: "pair",
+                            data: Some((data0, data1)),
+                        }
+                        .to_json(),
+                        Event::Transfer { receiver_id, amount } => {
+                            #[derive(near_sdk::serde::Serialize)]
+                            #[serde(crate = "near_sdk::serde")]
+                            struct Data<'a> {
+                                #[serde(rename = "to")]
+                                receiver_id: &'a AccountId,
+                                amount: &'a U128,
+                            }
+                            near_sdk::EventEnvelope {
+                                standard: "nep171",
+                                version: "1.0.0",
+                                event: "transfer",
+                                data: Some(Data { receiver_id, amount }),
+                            }
+                            .to_json()
+                        }
+                        Event::Paused => near_sdk::EventEnvelope {
+                            standard: "nep171",
+                            version: "1.0.0",
+                            event: "paused",
+                            data: None::<()>,
+                        }
+                        .to_json(),
+                    }
+                }
+
+                /// Logs the NEP-297 event as JSON after `EVENT_JSON:`.
+                pub fn emit(&self) {
+                    near_sdk::env::log_str(&::std::format!(
+                        "{}{}",
+                        near_sdk::EVENT_JSON_PREFIX,
+                        self.to_event_json()
+                    ));
+                }
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn missing_version() {
+        let item: ItemEnum = syn::parse_quote! {
+            #[event_json(standard = "nep171")]
+            enum Event { Paused }
+        };
+        let actual = near_event(&item).map(|_| ()).unwrap_err();
+        assert_eq!(actual.to_string(), "Both `standard` and `version` are required.");
+    }
+}
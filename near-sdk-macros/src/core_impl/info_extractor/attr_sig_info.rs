@@ -1,8 +1,12 @@
 use super::{ArgInfo, BindgenArgType, InitAttr, MethodType, SerializerAttr, SerializerType};
 use proc_macro2::Span;
 use quote::ToTokens;
+use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
-use syn::{Attribute, Error, FnArg, Ident, Receiver, ReturnType, Signature};
+use syn::{
+    Attribute, Error, FnArg, GenericArgument, Ident, LitStr, PathArguments, Receiver, ReturnType,
+    Signature, Token, Type,
+};
 
 /// Information extracted from method attributes and signature.
 pub struct AttrSigInfo {
@@ -18,6 +22,14 @@ pub struct AttrSigInfo {
     pub is_payable: bool,
     /// Whether method can accept calls from self (current account)
     pub is_private: bool,
+    /// Whether method returns `Result<T, E>` which aborts with the error when it is `Err`.
+    pub is_handles_result: bool,
+    /// Whether method can only be called by the owner of the contract.
+    pub is_only_owner: bool,
+    /// Roles of `#[only(..)]`, one of which the predecessor should have to call the method.
+    pub only_roles: Vec<Ident>,
+    /// Feature of `#[when_not_paused]`, which should not be paused to call the method.
+    pub paused_feature: Option<String>,
     /// The serializer that we use for `env::input()`.
     pub input_serializer: SerializerType,
     /// The serializer that we use for the return type.
@@ -61,6 +73,10 @@ impl AttrSigInfo {
         let mut method_type = MethodType::Regular;
         let mut is_payable = false;
         let mut is_private = false;
+        let mut is_handles_result = false;
+        let mut is_only_owner = false;
+        let mut only_roles = vec![];
+        let mut paused_feature = None;
         // By the default we serialize the result with JSON.
         let mut result_serializer = SerializerType::JSON;
 
@@ -83,6 +99,32 @@ impl AttrSigInfo {
                 "private" => {
                     is_private = true;
                 }
+                "handle_result" => {
+                    is_handles_result = true;
+                }
+                "only_owner" => {
+                    is_only_owner = true;
+                }
+                "only" => {
+                    let roles =
+                        attr.parse_args_with(Punctuated::<Ident, Token![,]>::parse_terminated)?;
+                    if roles.is_empty() {
+                        return Err(Error::new(
+                            attr.span(),
+                            "Expected the roles as `#[only(Role, ..)]`.",
+                        ));
+                    }
+                    only_roles.extend(roles);
+                }
+                "when_not_paused" => {
+                    // The feature of the method is its name, unless it is given.
+                    let feature = if attr.tokens.is_empty() {
+                        ident.to_string()
+                    } else {
+                        attr.parse_args::<LitStr>()?.value()
+                    };
+                    paused_feature = Some(feature);
+                }
                 "result_serializer" => {
                     let serializer: SerializerAttr = syn::parse2(attr.tokens.clone())?;
                     result_serializer = serializer.serializer_type;
@@ -128,6 +170,19 @@ impl AttrSigInfo {
         *original_attrs = non_bindgen_attrs.clone();
         let returns = original_sig.output.clone();
 
+        if is_handles_result {
+            let is_result = match &returns {
+                ReturnType::Type(_, ty) => result_ok_type(ty).is_some(),
+                ReturnType::Default => false,
+            };
+            if !is_result {
+                return Err(Error::new(
+                    returns.span(),
+                    "Method marked with #[handle_result] should return Result<T, E>.",
+                ));
+            }
+        }
+
         let mut result = Self {
             ident,
             non_bindgen_attrs,
@@ -136,6 +191,10 @@ impl AttrSigInfo {
             method_type,
             is_payable,
             is_private,
+            is_handles_result,
+            is_only_owner,
+            only_roles,
+            paused_feature,
             result_serializer,
             receiver,
             returns,
@@ -161,4 +220,31 @@ impl AttrSigInfo {
     pub fn input_args(&self) -> impl Iterator<Item = &ArgInfo> {
         self.args.iter().filter(|arg| matches!(arg.bindgen_ty, BindgenArgType::Regular))
     }
+
+    /// The type of the value returned by the method, which is `T` of `Result<T, E>` for the
+    /// methods marked with `#[handle_result]`.
+    pub fn returned_type(&self) -> Option<&Type> {
+        match &self.returns {
+            ReturnType::Type(_, ty) if self.is_handles_result => result_ok_type(ty),
+            ReturnType::Type(_, ty) => Some(ty),
+            ReturnType::Default => None,
+        }
+    }
+}
+
+/// Returns `T` of a `Result<T, E>` type.
+pub fn result_ok_type(ty: &Type) -> Option<&Type> {
+    let segment = match ty {
+        Type::Path(path) => path.path.segments.last()?,
+        _ => return None,
+    };
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) if segment.ident == "Result" => {
+            match args.args.first()? {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
 }
@@ -5,7 +5,7 @@ mod arg_info;
 pub use arg_info::{ArgInfo, BindgenArgType};
 
 mod attr_sig_info;
-pub use attr_sig_info::AttrSigInfo;
+pub use attr_sig_info::{result_ok_type, AttrSigInfo};
 
 mod impl_item_method_info;
 pub use impl_item_method_info::ImplItemMethodInfo;
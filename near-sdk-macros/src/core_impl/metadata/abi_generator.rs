@@ -0,0 +1,111 @@
+use crate::{
+    result_ok_type, ArgInfo, BindgenArgType, ImplItemMethodInfo, MethodType, SerializerType,
+};
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::Type;
+
+impl ImplItemMethodInfo {
+    /// Generates the ABI of this method, built with the `gen` ABI generator.
+    ///
+    /// # Example:
+    /// The following method:
+    /// ```ignore
+    /// #[payable]
+    /// pub fn f3(&mut self, arg0: FancyStruct, #[callback] arg1: u64) -> Result<IsOk, Error> { }
+    /// ```
+    /// will produce this struct:
+    /// ```ignore
+    /// near_sdk::abi::AbiFunction {
+    ///     name: "f3".to_string(),
+    ///     kind: near_sdk::abi::AbiFunctionKind::Call,
+    ///     modifiers: vec![near_sdk::abi::AbiFunctionModifier::Payable],
+    ///     params: vec![gen.json_parameter::<FancyStruct>("arg0")],
+    ///     callbacks: vec![gen.json_parameter::<u64>("arg1")],
+    ///     callbacks_vec: None,
+    ///     result: Some(gen.json_type::<Result<IsOk, Error> >())
+    /// }
+    /// ```
+    pub fn abi_function(&self) -> TokenStream2 {
+        let info = &self.attr_signature_info;
+        let name = info.ident.to_string();
+        let kind = match &info.method_type {
+            MethodType::View => quote! { near_sdk::abi::AbiFunctionKind::View },
+            _ => quote! { near_sdk::abi::AbiFunctionKind::Call },
+        };
+        let is_init = matches!(&info.method_type, &MethodType::Init | &MethodType::InitIgnoreState);
+        let mut modifiers = vec![];
+        if is_init {
+            modifiers.push(quote! { near_sdk::abi::AbiFunctionModifier::Init });
+        }
+        if info.is_payable {
+            modifiers.push(quote! { near_sdk::abi::AbiFunctionModifier::Payable });
+        }
+        if info.is_private {
+            modifiers.push(quote! { near_sdk::abi::AbiFunctionModifier::Private });
+        }
+        let params = info.input_args().map(|arg| abi_parameter(arg, &info.input_serializer));
+        let callbacks = info
+            .args
+            .iter()
+            .filter(|arg| {
+                matches!(
+                    arg.bindgen_ty,
+                    BindgenArgType::CallbackArg | BindgenArgType::CallbackResultArg
+                )
+            })
+            .map(|arg| abi_parameter(arg, &arg.serializer_ty));
+        let callbacks_vec = match info
+            .args
+            .iter()
+            .rfind(|arg| matches!(arg.bindgen_ty, BindgenArgType::CallbackArgVec))
+        {
+            None => quote! { None },
+            Some(arg) => {
+                let parameter = abi_parameter(arg, &arg.serializer_ty);
+                quote! { Some(#parameter) }
+            }
+        };
+        // Initialization methods return the state, which is written instead of being returned.
+        let result = match info.returned_type() {
+            Some(ty) if !is_init => {
+                let abi_type = abi_type(ty, &info.result_serializer);
+                quote! { Some(#abi_type) }
+            }
+            _ => quote! { None },
+        };
+
+        quote! {
+            near_sdk::abi::AbiFunction {
+                name: #name.to_string(),
+                kind: #kind,
+                modifiers: vec![#(#modifiers),*],
+                params: vec![#(#params),*],
+                callbacks: vec![#(#callbacks),*],
+                callbacks_vec: #callbacks_vec,
+                result: #result
+            }
+        }
+    }
+}
+
+fn abi_parameter(arg: &ArgInfo, serializer: &SerializerType) -> TokenStream2 {
+    let name = arg.ident.to_string();
+    // The error of a callback result is the failure of the promise, which has no value.
+    let ty = match arg.bindgen_ty {
+        BindgenArgType::CallbackResultArg => result_ok_type(&arg.ty).unwrap_or(&arg.ty),
+        _ => &arg.ty,
+    };
+    match serializer {
+        SerializerType::JSON => quote! { gen.json_parameter::<#ty>(#name) },
+        SerializerType::Borsh => quote! { gen.borsh_parameter::<#ty>(#name) },
+    }
+}
+
+fn abi_type(ty: &Type, serializer: &SerializerType) -> TokenStream2 {
+    match serializer {
+        SerializerType::JSON => quote! { gen.json_type::<#ty>() },
+        SerializerType::Borsh => quote! { gen.borsh_type::<#ty>() },
+    }
+}
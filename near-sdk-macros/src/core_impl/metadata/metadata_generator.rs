@@ -2,7 +2,6 @@ use crate::{BindgenArgType, ImplItemMethodInfo, InputStructType, MethodType, Ser
 
 use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
-use syn::ReturnType;
 
 impl ImplItemMethodInfo {
     /// Generates metadata struct for this method.
@@ -94,13 +93,13 @@ impl ImplItemMethodInfo {
                 }
             }
         };
-        let result = match &self.attr_signature_info.returns {
-            ReturnType::Default => {
+        let result = match self.attr_signature_info.returned_type() {
+            None => {
                 quote! {
                     None
                 }
             }
-            ReturnType::Type(_, ty) => {
+            Some(ty) => {
                 quote! {
                     Some(#ty::schema_container())
                 }
@@ -6,13 +6,16 @@ use crate::ItemImplInfo;
 
 use proc_macro2::TokenStream as TokenStream2;
 use quote::{quote, ToTokens};
+use syn::spanned::Spanned;
 use syn::visit::Visit;
-use syn::{Error, ItemImpl};
+use syn::{Attribute, Error, Generics, Ident, ItemEnum, ItemImpl, ItemStruct};
 
 /// Information relevant to metadata extracted from the `impl` section decorated with `#[near_bindgen]`.
 #[derive(Default)]
 pub struct MetadataVisitor {
     impl_item_infos: Vec<ItemImplInfo>,
+    /// Types marked with `#[event]`, which are logged as events.
+    event_types: Vec<Ident>,
     /// Errors that occured while extracting the data.
     errors: Vec<Error>,
 }
@@ -31,6 +34,16 @@ impl<'ast> Visit<'ast> for MetadataVisitor {
         }
         syn::visit::visit_item_impl(self, i);
     }
+
+    fn visit_item_struct(&mut self, i: &'ast ItemStruct) {
+        self.visit_event_type(&i.attrs, &i.ident, &i.generics);
+        syn::visit::visit_item_struct(self, i);
+    }
+
+    fn visit_item_enum(&mut self, i: &'ast ItemEnum) {
+        self.visit_event_type(&i.attrs, &i.ident, &i.generics);
+        syn::visit::visit_item_enum(self, i);
+    }
 }
 
 impl MetadataVisitor {
@@ -38,6 +51,23 @@ impl MetadataVisitor {
         Default::default()
     }
 
+    fn visit_event_type(&mut self, attrs: &[Attribute], ident: &Ident, generics: &Generics) {
+        let is_event = attrs
+            .iter()
+            .any(|attr| matches!(attr.path.segments.last(), Some(s) if s.ident == "event"));
+        if !is_event {
+            return;
+        }
+        if !generics.params.is_empty() {
+            self.errors.push(Error::new(
+                generics.params.span(),
+                "Type parameters are not supported for events.",
+            ));
+        } else {
+            self.event_types.push(ident.clone());
+        }
+    }
+
     pub fn generate_metadata_method(&self) -> syn::Result<TokenStream2> {
         if !self.errors.is_empty() {
             return Err(self.errors[0].clone());
@@ -65,6 +95,41 @@ impl MetadataVisitor {
             }
         })
     }
+
+    /// Generates the `__near_abi` function, which returns the ABI of the contract, and the
+    /// `__contract_abi` view method, which returns it as JSON.
+    pub fn generate_abi_method(&self) -> syn::Result<TokenStream2> {
+        if !self.errors.is_empty() {
+            return Err(self.errors[0].clone());
+        }
+        let functions: Vec<TokenStream2> = self
+            .impl_item_infos
+            .iter()
+            .flat_map(|i| i.methods.iter().filter(move |m| m.is_public || i.is_trait_impl))
+            .map(|m| m.abi_function())
+            .collect();
+        let event_types = &self.event_types;
+        Ok(quote! {
+            #[doc(hidden)]
+            pub fn __near_abi() -> near_sdk::abi::ContractAbi {
+                let mut gen = near_sdk::abi::AbiGenerator::new();
+                let functions = vec![
+                    #(#functions),*
+                ];
+                let events = vec![#(gen.event::<#event_types>()),*];
+                gen.into_abi(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"), functions, events)
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn __contract_abi() {
+                near_sdk::env::setup_panic_hook();
+                let data = near_sdk::serde_json::to_vec(&__near_abi())
+                    .expect("Failed to serialize the ABI using JSON");
+                near_sdk::env::value_return(&data);
+            }
+        })
+    }
 }
 
 #[rustfmt::skip]
@@ -156,4 +221,91 @@ mod tests {
         );
         assert_eq!(expected.to_string(), actual.to_string());
     }
+
+    #[test]
+    fn abi_methods() {
+        let code = quote! {
+            #[near_bindgen]
+            impl Hello {
+                #[init]
+                pub fn new(arg0: u64) -> Self { }
+                pub fn f1(&self) -> Option<u64> { }
+                #[private]
+                pub fn f2(&mut self, #[callback_unwrap] arg0: FancyStruct, #[callback_result] arg1: Result<u64, PromiseError>) { }
+                #[payable]
+                #[result_serializer(borsh)]
+                pub fn f3(&mut self, #[serializer(borsh)] arg0: Vec<u8>) -> u128 { }
+                fn internal(&self) { }
+            }
+
+            #[event]
+            enum HelloEvent { }
+        };
+
+        let file: syn::File = syn::parse2(code).unwrap();
+
+        let mut visitor = MetadataVisitor::new();
+        visitor.visit_file(&file);
+
+        let actual = visitor.generate_abi_method().unwrap();
+        let expected = quote!(
+            #[doc(hidden)]
+            pub fn __near_abi() -> near_sdk::abi::ContractAbi {
+                let mut gen = near_sdk::abi::AbiGenerator::new();
+                let functions = vec![
+                    near_sdk::abi::AbiFunction {
+                        name: "new".to_string(),
+                        kind: near_sdk::abi::AbiFunctionKind::Call,
+                        modifiers: vec![near_sdk::abi::AbiFunctionModifier::Init],
+                        params: vec![gen.json_parameter::<u64>("arg0")],
+                        callbacks: vec![],
+                        callbacks_vec: None,
+                        result: None
+                    },
+                    near_sdk::abi::AbiFunction {
+                        name: "f1".to_string(),
+                        kind: near_sdk::abi::AbiFunctionKind::View,
+                        modifiers: vec![],
+                        params: vec![],
+                        callbacks: vec![],
+                        callbacks_vec: None,
+                        result: Some(gen.json_type::<Option<u64> >())
+                    },
+                    near_sdk::abi::AbiFunction {
+                        name: "f2".to_string(),
+                        kind: near_sdk::abi::AbiFunctionKind::Call,
+                        modifiers: vec![near_sdk::abi::AbiFunctionModifier::Private],
+                        params: vec![],
+                        callbacks: vec![
+                            gen.json_parameter::<FancyStruct>("arg0"),
+                            gen.json_parameter::<u64>("arg1")
+                        ],
+                        callbacks_vec: None,
+                        result: None
+                    },
+                    near_sdk::abi::AbiFunction {
+                        name: "f3".to_string(),
+                        kind: near_sdk::abi::AbiFunctionKind::Call,
+                        modifiers: vec![near_sdk::abi::AbiFunctionModifier::Payable],
+                        params: vec![gen.borsh_parameter::<Vec<u8> >("arg0")],
+                        callbacks: vec![],
+                        callbacks_vec: None,
+                        result: Some(gen.borsh_type::<u128>())
+                    }
+                ];
+                let events = vec![gen.event::<HelloEvent>()];
+                gen.into_abi(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"), functions, events)
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn __contract_abi() {
+                near_sdk::env::setup_panic_hook();
+                let data = near_sdk::serde_json::to_vec(&__near_abi())
+                    .expect("Failed to serialize the ABI using JSON");
+                near_sdk::env::value_return(&data);
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
 }
@@ -1,2 +1,3 @@
+pub mod abi_generator;
 pub mod metadata_generator;
 pub mod metadata_visitor;
@@ -1,6 +1,16 @@
+mod access_control;
 mod code_generator;
+mod event;
 mod info_extractor;
 mod metadata;
+mod ownable;
+mod pausable;
+mod versioned_state;
+pub use access_control::access_control;
 pub use code_generator::*;
+pub use event::{event_json_attr, near_event};
 pub use info_extractor::*;
 pub use metadata::metadata_visitor::MetadataVisitor;
+pub use ownable::ownable;
+pub use pausable::pausable;
+pub use versioned_state::versioned_state;
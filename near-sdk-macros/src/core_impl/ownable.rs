@@ -0,0 +1,36 @@
+//! `#[derive(Ownable)]` generates the methods managing the owner of a contract, whose methods are
+//! guarded with `#[only_owner]`.
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::ItemStruct;
+
+/// Generates the `owner`, `pending_owner`, `transfer_ownership` and `accept_ownership` methods of
+/// the contract.
+pub fn ownable(item: &ItemStruct) -> TokenStream2 {
+    let name = &item.ident;
+    quote! {
+        #[near_sdk::near_bindgen]
+        impl #name {
+            /// Returns the owner of the contract.
+            pub fn owner(&self) -> Option<near_sdk::AccountId> {
+                near_sdk::ownable::owner()
+            }
+
+            /// Returns the proposed owner of the contract, until it accepts the ownership.
+            pub fn pending_owner(&self) -> Option<near_sdk::AccountId> {
+                near_sdk::ownable::pending_owner()
+            }
+
+            /// Proposes the new owner, which becomes the owner when it calls `accept_ownership`.
+            pub fn transfer_ownership(new_owner: near_sdk::AccountId) {
+                near_sdk::ownable::require_owner("transfer_ownership");
+                near_sdk::ownable::propose_owner(&new_owner);
+            }
+
+            /// Makes the proposed owner, which calls this method, the owner of the contract.
+            pub fn accept_ownership() {
+                near_sdk::ownable::accept_ownership();
+            }
+        }
+    }
+}
@@ -0,0 +1,98 @@
+//! `#[pausable(manager_roles(..))]` generates the methods pausing the features of a contract,
+//! whose methods are guarded with `#[when_not_paused]`.
+use crate::core_impl::access_control::role_idents;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::spanned::Spanned;
+use syn::{AttributeArgs, Error, Ident, ItemStruct, Meta, NestedMeta};
+
+/// Parses the roles which can pause and unpause the features, given as `manager_roles(..)`.
+fn manager_roles(args: &AttributeArgs) -> syn::Result<Vec<Ident>> {
+    let mut roles = vec![];
+    for arg in args {
+        match arg {
+            NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("manager_roles") => {
+                roles = role_idents(list)?;
+            }
+            _ => return Err(Error::new(arg.span(), "Expected `manager_roles(..)`.")),
+        }
+    }
+    Ok(roles)
+}
+
+/// Generates the `pa_*` methods of the contract.
+pub fn pausable(args: &AttributeArgs, item: &ItemStruct) -> syn::Result<TokenStream2> {
+    let roles = manager_roles(args)?;
+    let name = &item.ident;
+    // The constants of the roles are declared by `#[access_control]` on the contract.
+    let roles: Vec<_> = roles
+        .iter()
+        .map(|role| {
+            let role = format_ident!("__ACL_ROLE_{}", role);
+            quote! { Self::#role }
+        })
+        .collect();
+    Ok(quote! {
+        #[near_sdk::near_bindgen]
+        impl #name {
+            /// Returns whether the feature is paused, or all the features are.
+            pub fn pa_is_paused(&self, feature: String) -> bool {
+                near_sdk::pausable::is_paused(&feature)
+            }
+
+            /// Pauses the feature, and returns whether it was not paused.
+            pub fn pa_pause_feature(feature: String) -> bool {
+                near_sdk::access_control::require_admin(&[#(#roles),*], "pa_pause_feature");
+                near_sdk::pausable::pause(&feature)
+            }
+
+            /// Unpauses the feature, and returns whether it was paused.
+            pub fn pa_unpause_feature(feature: String) -> bool {
+                near_sdk::access_control::require_admin(&[#(#roles),*], "pa_unpause_feature");
+                near_sdk::pausable::unpause(&feature)
+            }
+        }
+    })
+}
+
+#[rustfmt::skip]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pausable_methods() {
+        let args: AttributeArgs = vec![syn::parse_quote! { manager_roles(Admin) }];
+        let item: ItemStruct = syn::parse_quote! { struct Contract {} };
+        let actual = pausable(&args, &item).unwrap();
+        let expected = quote!(
+            #[near_sdk::near_bindgen]
+            impl Contract {
+                /// Returns whether the feature is paused, or all the features are.
+                pub fn pa_is_paused(&self, feature: String) -> bool {
+                    near_sdk::pausable::is_paused(&feature)
+                }
+
+                /// Pauses the feature, and returns whether it was not paused.
+                pub fn pa_pause_feature(feature: String) -> bool {
+                    near_sdk::access_control::require_admin(&[Self::__ACL_ROLE_Admin], "pa_pause_feature");
+                    near_sdk::pausable::pause(&feature)
+                }
+
+                /// Unpauses the feature, and returns whether it was paused.
+                pub fn pa_unpause_feature(feature: String) -> bool {
+                    near_sdk::access_control::require_admin(&[Self::__ACL_ROLE_Admin], "pa_unpause_feature");
+                    near_sdk::pausable::unpause(&feature)
+                }
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn unexpected_argument() {
+        let args: AttributeArgs = vec![syn::parse_quote! { roles(Admin) }];
+        let actual = manager_roles(&args).unwrap_err();
+        assert_eq!(actual.to_string(), "Expected `manager_roles(..)`.");
+    }
+}
@@ -0,0 +1,104 @@
+//! `#[derive(VersionedState)]` generates the upgrade of the versions of a stored enum to the
+//! current version.
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Error, Fields, ItemEnum};
+
+/// Generates the implementation of `near_sdk::store::VersionedState` for the enum of versions.
+pub fn versioned_state(item: &ItemEnum) -> syn::Result<TokenStream2> {
+    if item.variants.is_empty() || item.variants.len() > 256 {
+        return Err(Error::new_spanned(
+            &item.ident,
+            "VersionedState requires between 1 and 256 versions.",
+        ));
+    }
+    let mut versions = Vec::with_capacity(item.variants.len());
+    for variant in item.variants.iter() {
+        match &variant.fields {
+            Fields::Unnamed(fields)
+                if fields.unnamed.len() == 1 && variant.discriminant.is_none() =>
+            {
+                versions.push((&variant.ident, &fields.unnamed[0].ty));
+            }
+            _ => {
+                return Err(Error::new_spanned(
+                    variant,
+                    "Each VersionedState variant must hold a single version, as in `V1(T)`.",
+                ))
+            }
+        }
+    }
+
+    let name = &item.ident;
+    let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+    let current_version = (versions.len() - 1) as u8;
+    let (current_ident, current_ty) = versions[versions.len() - 1];
+    let upgrades = versions.windows(2).map(|pair| {
+        let (old, next) = (pair[0].0, pair[1].0);
+        quote! {
+            Self::#old(value) => near_sdk::store::VersionedState::into_current(
+                Self::#next(::core::convert::From::from(value)),
+            ),
+        }
+    });
+    Ok(quote! {
+        impl #impl_generics near_sdk::store::VersionedState for #name #ty_generics #where_clause {
+            type Current = #current_ty;
+            const CURRENT_VERSION: u8 = #current_version;
+
+            fn into_current(self) -> Self::Current {
+                match self {
+                    #(#upgrades)*
+                    Self::#current_ident(value) => value,
+                }
+            }
+        }
+    })
+}
+
+#[rustfmt::skip]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn upgrades() {
+        let item: ItemEnum = syn::parse_quote! {
+            enum State {
+                V1(StateV1),
+                V2(StateV2),
+            }
+        };
+        let actual = versioned_state(&item).unwrap();
+        let expected = quote!(
+            impl near_sdk::store::VersionedState for State {
+                type Current = StateV2;
+                const CURRENT_VERSION: u8 = 1u8;
+
+                fn into_current(self) -> Self::Current {
+                    match self {
+                        Self::V1(value) => near_sdk::store::VersionedState::into_current(
+                            Self::V2(::core::convert::From::from(value)),
+                        ),
+                        Self::V2(value) => value,
+                    }
+                }
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn named_fields() {
+        let item: ItemEnum = syn::parse_quote! {
+            enum State {
+                V1 { value: u64 },
+            }
+        };
+        let actual = versioned_state(&item).unwrap_err();
+        assert_eq!(
+            actual.to_string(),
+            "Each VersionedState variant must hold a single version, as in `V1(T)`."
+        );
+    }
+}
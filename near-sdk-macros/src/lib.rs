@@ -12,8 +12,26 @@ use syn::visit::Visit;
 use syn::{File, ItemEnum, ItemImpl, ItemStruct, ItemTrait};
 
 #[proc_macro_attribute]
-pub fn near_bindgen(_attr: TokenStream, item: TokenStream) -> TokenStream {
-    if let Ok(input) = syn::parse::<ItemStruct>(item.clone()) {
+pub fn near_bindgen(attr: TokenStream, item: TokenStream) -> TokenStream {
+    if let Ok(input) = syn::parse::<ItemEnum>(item.clone()) {
+        // `#[near_bindgen(event_json(standard = "..", version = ".."))]` declares NEP-297 events.
+        let args = syn::parse_macro_input!(attr as syn::AttributeArgs);
+        let event_json = match args.as_slice() {
+            [arg] => event_json_attr(arg),
+            _ => Err(syn::Error::new(
+                Span::call_site(),
+                "near_bindgen can only be used on enums as `#[near_bindgen(event_json(..))]`.",
+            )),
+        };
+        match event_json {
+            Ok(event_json) => TokenStream::from(quote! {
+                #[derive(near_sdk::NearEvent)]
+                #event_json
+                #input
+            }),
+            Err(err) => err.to_compile_error().into(),
+        }
+    } else if let Ok(input) = syn::parse::<ItemStruct>(item.clone()) {
         let struct_proxy = generate_proxy_struct(&input);
         TokenStream::from(quote! {
             #input
@@ -27,12 +45,14 @@ pub fn near_bindgen(_attr: TokenStream, item: TokenStream) -> TokenStream {
             }
         };
         let generated_code = item_impl_info.wrapper_code();
+        let dispatcher_code = item_impl_info.dispatcher_code();
         // Add helper type for simulation testing only if not wasm32
         let marshalled_code = item_impl_info.marshall_code();
         TokenStream::from(quote! {
             #marshalled_code
             #input
             #generated_code
+            #dispatcher_code
         })
     } else {
         TokenStream::from(
@@ -77,6 +97,61 @@ pub fn ext_contract(attr: TokenStream, item: TokenStream) -> TokenStream {
     }
 }
 
+/// `access_control` declares the roles of the contract on its struct, as
+/// `#[access_control(roles(Admin, Operator), admin(Admin))]`, so that the methods of its
+/// `#[near_bindgen]` sections can be restricted to the accounts with one of the roles with
+/// `#[only(Admin, Operator)]`. It also generates the `acl_has_role` view method, and the
+/// `acl_grant_role` and `acl_revoke_role` methods, which can be called by the contract itself and
+/// by the accounts with one of the `admin` roles.
+#[proc_macro_attribute]
+pub fn access_control(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = syn::parse_macro_input!(attr as syn::AttributeArgs);
+    let input = match syn::parse::<ItemStruct>(item) {
+        Ok(input) => input,
+        Err(_) => {
+            return TokenStream::from(
+                syn::Error::new(Span::call_site(), "access_control can only be used on structs.")
+                    .to_compile_error(),
+            )
+        }
+    };
+    match core_impl::access_control(&args, &input) {
+        Ok(generated) => TokenStream::from(quote! {
+            #input
+            #generated
+        }),
+        Err(err) => TokenStream::from(err.to_compile_error()),
+    }
+}
+
+/// `pausable` generates the methods pausing the features of the contract on its struct, as
+/// `#[pausable(manager_roles(Admin))]`, so that the methods of its `#[near_bindgen]` sections
+/// marked with `#[when_not_paused]` panic while their feature is paused. The feature of a method is
+/// its name, unless it is given as `#[when_not_paused("feature")]`. The generated
+/// `pa_pause_feature` and `pa_unpause_feature` methods can be called by the contract itself and by
+/// the accounts with one of the `manager_roles`, which are declared with `#[access_control]`, and
+/// `pa_is_paused` is a view method.
+#[proc_macro_attribute]
+pub fn pausable(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = syn::parse_macro_input!(attr as syn::AttributeArgs);
+    let input = match syn::parse::<ItemStruct>(item) {
+        Ok(input) => input,
+        Err(_) => {
+            return TokenStream::from(
+                syn::Error::new(Span::call_site(), "pausable can only be used on structs.")
+                    .to_compile_error(),
+            )
+        }
+    };
+    match core_impl::pausable(&args, &input) {
+        Ok(generated) => TokenStream::from(quote! {
+            #input
+            #generated
+        }),
+        Err(err) => TokenStream::from(err.to_compile_error()),
+    }
+}
+
 // The below attributes a marker-attributes and therefore they are no-op.
 
 /// `callback` is a marker attribute it does not generate code by itself.
@@ -109,7 +184,112 @@ pub fn init(_attr: TokenStream, item: TokenStream) -> TokenStream {
     item
 }
 
+/// `event` is a marker attribute for the types logged as events, which are listed in the ABI
+/// generated by `metadata!{}`. It does not generate code by itself.
+#[proc_macro_attribute]
+pub fn event(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    item
+}
+
+/// `test` marks a unit test which sets up the mocked blockchain with `testing_env!` before it
+/// runs, from a `VMContextBuilder` configured by the arguments of the attribute:
+///
+/// ```ignore
+/// #[near_sdk::test_utils::test(predecessor = "alice.near", deposit = 1)]
+/// fn deposits() {
+///     assert_eq!(env::attached_deposit(), 1);
+/// }
+/// ```
+///
+/// The accounts `current`, `signer` and `predecessor` are strings, `view` is a boolean, and
+/// `deposit`, `balance`, `storage_usage`, `prepaid_gas`, `block_height`, `block_timestamp` and
+/// `epoch_height` are integers.
+#[proc_macro_attribute]
+pub fn test(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = syn::parse_macro_input!(attr as syn::AttributeArgs);
+    let input = match syn::parse::<syn::ItemFn>(item) {
+        Ok(input) => input,
+        Err(_) => {
+            return TokenStream::from(
+                syn::Error::new(Span::call_site(), "test can only be used on functions.")
+                    .to_compile_error(),
+            )
+        }
+    };
+    let mut setup = Vec::with_capacity(args.len());
+    for arg in args.iter() {
+        match test_context_setter(arg) {
+            Ok(setter) => setup.push(setter),
+            Err(err) => return TokenStream::from(err.to_compile_error()),
+        }
+    }
+
+    let syn::ItemFn { attrs, vis, sig, block } = input;
+    TokenStream::from(quote! {
+        #[::core::prelude::v1::test]
+        #(#attrs)*
+        #vis #sig {
+            #[allow(unused_mut)]
+            let mut context = near_sdk::test_utils::VMContextBuilder::new();
+            #(#setup)*
+            near_sdk::testing_env!(context.build());
+            #block
+        }
+    })
+}
+
+/// Returns the call of the `VMContextBuilder` method which applies an argument of `test`.
+fn test_context_setter(arg: &syn::NestedMeta) -> syn::Result<proc_macro2::TokenStream> {
+    let (key, value) = match arg {
+        syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue { path, lit, .. }))
+            if path.get_ident().is_some() =>
+        {
+            (path.get_ident().unwrap().to_string(), lit)
+        }
+        _ => return Err(syn::Error::new_spanned(arg, "Expected an argument like `deposit = 1`.")),
+    };
+    let account = |method: &str| match value {
+        syn::Lit::Str(account_id) => {
+            let method = syn::Ident::new(method, Span::call_site());
+            Ok(quote! {
+                context.#method(
+                    #account_id.parse::<near_sdk::AccountId>().expect("Invalid account ID"),
+                );
+            })
+        }
+        _ => Err(syn::Error::new_spanned(value, "Expected an account ID string.")),
+    };
+    let integer = |setter: proc_macro2::TokenStream| match value {
+        syn::Lit::Int(int) => Ok(quote! { context.#setter(#int); }),
+        _ => Err(syn::Error::new_spanned(value, "Expected an integer.")),
+    };
+    match key.as_str() {
+        "current" => account("current_account_id"),
+        "signer" => account("signer_account_id"),
+        "predecessor" => account("predecessor_account_id"),
+        "deposit" => integer(quote!(attached_deposit)),
+        "balance" => integer(quote!(account_balance)),
+        "storage_usage" => integer(quote!(storage_usage)),
+        "block_height" => integer(quote!(block_index)),
+        "block_timestamp" => integer(quote!(block_timestamp)),
+        "epoch_height" => integer(quote!(epoch_height)),
+        "prepaid_gas" => match value {
+            syn::Lit::Int(gas) => Ok(quote! { context.prepaid_gas(near_sdk::Gas(#gas)); }),
+            _ => Err(syn::Error::new_spanned(value, "Expected an integer.")),
+        },
+        "view" => match value {
+            syn::Lit::Bool(is_view) => Ok(quote! { context.is_view(#is_view); }),
+            _ => Err(syn::Error::new_spanned(value, "Expected a boolean.")),
+        },
+        _ => Err(syn::Error::new_spanned(arg, format!("Unknown test context argument `{}`.", key))),
+    }
+}
+
 /// `metadata` generates the metadata method and should be placed at the very end of the `lib.rs` file.
+///
+/// With the `abi` feature it also generates the `__near_abi` function, which returns the ABI of
+/// the methods of the `#[near_bindgen]` sections and of the types marked with `#[event]`, and the
+/// `__contract_abi` view method, which returns it as JSON.
 // TODO: Once Rust allows inner attributes and custom procedural macros for modules we should switch this
 // to be `#![metadata]` attribute at the top of the contract file instead. https://github.com/rust-lang/rust/issues/54727
 #[proc_macro]
@@ -121,9 +301,18 @@ pub fn metadata(item: TokenStream) -> TokenStream {
             Ok(x) => x,
             Err(err) => return TokenStream::from(err.to_compile_error()),
         };
+        let abi = if cfg!(feature = "abi") {
+            match visitor.generate_abi_method() {
+                Ok(x) => x,
+                Err(err) => return TokenStream::from(err.to_compile_error()),
+            }
+        } else {
+            proc_macro2::TokenStream::new()
+        };
         TokenStream::from(quote! {
             #input
             #generated
+            #abi
         })
     } else {
         TokenStream::from(
@@ -162,15 +351,71 @@ pub fn derive_no_default(item: TokenStream) -> TokenStream {
     }
 }
 
+/// `Ownable` generates the methods managing the owner of the contract, which is kept in storage and
+/// usually set in the initialization method with `near_sdk::ownable::set_owner`. The methods of its
+/// `#[near_bindgen]` sections marked with `#[only_owner]` can only be called by the owner. The
+/// ownership is transferred in two steps: the owner proposes the new owner with the generated
+/// `transfer_ownership` method, which becomes the owner when it calls `accept_ownership`. The
+/// generated `owner` and `pending_owner` methods are view methods.
+#[proc_macro_derive(Ownable)]
+pub fn derive_ownable(item: TokenStream) -> TokenStream {
+    if let Ok(input) = syn::parse::<ItemStruct>(item) {
+        TokenStream::from(core_impl::ownable(&input))
+    } else {
+        TokenStream::from(
+            syn::Error::new(Span::call_site(), "Ownable can only be used as a derive on structs.")
+                .to_compile_error(),
+        )
+    }
+}
+
+/// `VersionedState` generates implementation for the `near_sdk::store::VersionedState` trait,
+/// so that the enum can be stored through `near_sdk::store::Versioned`.
+///
+/// Each variant must hold a single version of the value, ordered from the oldest to the current
+/// version, and each version must implement `From` the previous one. The type should also derive
+/// `BorshSerialize` and `BorshDeserialize`.
+#[proc_macro_derive(VersionedState)]
+pub fn derive_versioned_state(item: TokenStream) -> TokenStream {
+    let input = match syn::parse::<ItemEnum>(item) {
+        Ok(input) => input,
+        Err(_) => {
+            return TokenStream::from(
+                syn::Error::new(
+                    Span::call_site(),
+                    "VersionedState can only be used as a derive on enums.",
+                )
+                .to_compile_error(),
+            )
+        }
+    };
+    match versioned_state(&input) {
+        Ok(generated) => TokenStream::from(generated),
+        Err(err) => TokenStream::from(err.to_compile_error()),
+    }
+}
+
 /// `BorshStorageKey` generates implementation for `BorshIntoStorageKey` trait.
 /// It allows the type to be passed as a unique prefix for persistent collections.
 /// The type should also implement or derive `BorshSerialize` trait.
+///
+/// Enum variants may have fields, which become part of the prefix, so that the prefixes of
+/// collections nested in another collection can be derived from their key. The type may be
+/// generic, for example to borrow the fields:
+///
+/// ```ignore
+/// #[derive(BorshStorageKey, BorshSerialize)]
+/// enum StorageKey<'a> {
+///     Accounts,
+///     Tokens { account_id: &'a AccountId },
+/// }
+/// ```
 #[proc_macro_derive(BorshStorageKey)]
 pub fn borsh_storage_key(item: TokenStream) -> TokenStream {
-    let name = if let Ok(input) = syn::parse::<ItemEnum>(item.clone()) {
-        input.ident
+    let (name, generics) = if let Ok(input) = syn::parse::<ItemEnum>(item.clone()) {
+        (input.ident, input.generics)
     } else if let Ok(input) = syn::parse::<ItemStruct>(item) {
-        input.ident
+        (input.ident, input.generics)
     } else {
         return TokenStream::from(
             syn::Error::new(
@@ -180,7 +425,58 @@ pub fn borsh_storage_key(item: TokenStream) -> TokenStream {
             .to_compile_error(),
         );
     };
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
     TokenStream::from(quote! {
-        impl near_sdk::BorshIntoStorageKey for #name {}
+        impl #impl_generics near_sdk::BorshIntoStorageKey for #name #ty_generics #where_clause {}
     })
 }
+
+/// `FunctionError` generates implementation for the `near_sdk::FunctionError` trait, so that the
+/// error can be returned by the methods marked with `#[handle_result]` or passed to
+/// `env::panic_err`, which abort with the error serialized as JSON.
+#[proc_macro_derive(FunctionError)]
+pub fn function_error(item: TokenStream) -> TokenStream {
+    let (name, generics) = if let Ok(input) = syn::parse::<ItemEnum>(item.clone()) {
+        (input.ident, input.generics)
+    } else if let Ok(input) = syn::parse::<ItemStruct>(item) {
+        (input.ident, input.generics)
+    } else {
+        return TokenStream::from(
+            syn::Error::new(
+                Span::call_site(),
+                "FunctionError can only be used as a derive on enums or structs.",
+            )
+            .to_compile_error(),
+        );
+    };
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    TokenStream::from(quote! {
+        impl #impl_generics near_sdk::FunctionError for #name #ty_generics #where_clause {}
+    })
+}
+
+/// `NearEvent` generates the `to_event_json` and `emit` methods of an enum of NEP-297 events,
+/// from its `#[event_json(standard = "..", version = "..")]` attribute. The name of each event is
+/// the name of its variant in snake case, and its data is the value of the variant: the value of a
+/// single field, a JSON array of several fields, an object of named fields, or no data for unit
+/// variants. The `#[serde(..)]` attributes of the fields apply to the data, without the enum
+/// deriving `Serialize`.
+#[proc_macro_derive(NearEvent, attributes(event_json, serde))]
+pub fn derive_near_event(item: TokenStream) -> TokenStream {
+    let input = match syn::parse::<ItemEnum>(item) {
+        Ok(input) => input,
+        Err(_) => {
+            return TokenStream::from(
+                syn::Error::new(
+                    Span::call_site(),
+                    "NearEvent can only be used as a derive on enums.",
+                )
+                .to_compile_error(),
+            )
+        }
+    };
+    match near_event(&input) {
+        Ok(generated) => TokenStream::from(generated),
+        Err(err) => TokenStream::from(err.to_compile_error()),
+    }
+}
@@ -19,4 +19,5 @@ fn compilation_tests() {
     t.pass("compilation_tests/cond_compilation.rs");
     t.compile_fail("compilation_tests/payable_view.rs");
     t.pass("compilation_tests/borsh_storage_key.rs");
+    t.compile_fail("compilation_tests/handle_result_not_result.rs");
 }
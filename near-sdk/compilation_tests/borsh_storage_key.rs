@@ -15,11 +15,18 @@ enum StorageKeyEnum {
     SubAccounts { account_id: String },
 }
 
+#[derive(BorshStorageKey, BorshSerialize)]
+enum BorrowedStorageKey<'a> {
+    Balances { account_id: &'a str, index: u8 },
+}
+
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize)]
 struct Contract {
     map1: LookupMap<u64, u64>,
     map2: LookupMap<String, String>,
+    map3: LookupMap<u64, u64>,
+    map4: LookupMap<u64, u64>,
 }
 
 impl Default for Contract {
@@ -27,6 +34,8 @@ impl Default for Contract {
         Self {
             map1: LookupMap::new(StorageKeyStruct { key: "bla".to_string() }),
             map2: LookupMap::new(StorageKeyEnum::Accounts),
+            map3: LookupMap::new(BorrowedStorageKey::Balances { account_id: "alice", index: 0 }),
+            map4: LookupMap::new((StorageKeyEnum::Accounts, "alice", 1u8)),
         }
     }
 }
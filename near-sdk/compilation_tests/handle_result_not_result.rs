@@ -0,0 +1,21 @@
+//! Method with `#[handle_result]` which does not return a `Result`.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::near_bindgen;
+
+#[near_bindgen]
+#[derive(Default, BorshDeserialize, BorshSerialize)]
+struct Incrementer {
+    value: u32,
+}
+
+#[near_bindgen]
+impl Incrementer {
+    #[handle_result]
+    pub fn inc(&mut self, by: u32) -> u32 {
+        self.value += by;
+        self.value
+    }
+}
+
+fn main() {}
@@ -0,0 +1,186 @@
+//! ABI of a contract: the machine-readable description of its methods and events, with the JSON
+//! schemas of their types, from which frontends and indexers can generate clients.
+//!
+//! The ABI is generated by [`metadata!{}`](crate::metadata) with the `abi` feature, as the
+//! `__near_abi` function, which returns the [`ContractAbi`], and the `__contract_abi` view method,
+//! which returns it as JSON. The types of the JSON arguments, results and events should implement
+//! [`JsonSchema`], which can be derived with [`schemars`](crate::schemars):
+//!
+//! ```ignore
+//! use near_sdk::schemars::JsonSchema;
+//! use near_sdk::serde::Serialize;
+//!
+//! #[derive(Serialize, JsonSchema)]
+//! #[serde(crate = "near_sdk::serde")]
+//! #[schemars(crate = "near_sdk::schemars")]
+//! pub struct Message {
+//!     pub text: String,
+//! }
+//! ```
+//!
+//! The ABI can be written to a file when building the contract for tests, for instance with:
+//!
+//! ```ignore
+//! #[test]
+//! fn abi() {
+//!     std::fs::write("target/abi.json", crate::__near_abi().to_json()).unwrap();
+//! }
+//! ```
+
+use borsh::BorshSchema;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use schemars::{JsonSchema, Map};
+use serde::{Deserialize, Serialize};
+
+/// Version of the ABI format.
+pub const ABI_VERSION: &str = "0.1.0";
+
+/// ABI of a contract.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ContractAbi {
+    /// Version of the ABI format.
+    pub abi_version: String,
+    /// Name of the package of the contract.
+    pub name: String,
+    /// Version of the package of the contract.
+    pub version: String,
+    /// ABI of all methods.
+    pub functions: Vec<AbiFunction>,
+    /// ABI of the events logged by the contract.
+    pub events: Vec<AbiEvent>,
+    /// Schemas of the types referenced as `#/definitions/<name>` by the other schemas.
+    pub definitions: Map<String, Schema>,
+}
+
+impl ContractAbi {
+    /// Returns the ABI as pretty JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Failed to serialize the ABI using JSON")
+    }
+}
+
+/// ABI of a single method.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AbiFunction {
+    pub name: String,
+    pub kind: AbiFunctionKind,
+    pub modifiers: Vec<AbiFunctionModifier>,
+    /// Arguments read from the input of the method.
+    pub params: Vec<AbiParameter>,
+    /// Arguments read from the results of the promises the method is a callback of.
+    pub callbacks: Vec<AbiParameter>,
+    /// Argument read from the results of all promises, if the method has one.
+    pub callbacks_vec: Option<AbiParameter>,
+    /// Type of the returned value, if the method returns one.
+    pub result: Option<AbiType>,
+}
+
+/// Whether a method can be called in a view call.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AbiFunctionKind {
+    /// The method does not modify the state and can be called in a view call.
+    View,
+    /// The method can only be called in a transaction.
+    Call,
+}
+
+/// Attribute of a method which changes how it can be called.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AbiFunctionModifier {
+    /// The method initializes the state.
+    Init,
+    /// The method accepts an attached deposit.
+    Payable,
+    /// The method can only be called by the contract itself.
+    Private,
+}
+
+/// Named argument of a method.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AbiParameter {
+    pub name: String,
+    #[serde(flatten)]
+    pub type_schema: AbiType,
+}
+
+/// Type of a value and how it is serialized.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "serialization_type", rename_all = "snake_case")]
+pub enum AbiType {
+    /// Value serialized as JSON, described by its JSON schema.
+    Json { type_schema: Schema },
+    /// Value serialized with Borsh, described by its Borsh declaration.
+    Borsh { declaration: String },
+}
+
+/// Type of an event logged by the contract.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AbiEvent {
+    pub name: String,
+    pub type_schema: Schema,
+}
+
+/// Generator of the schemas of an ABI, used by the code generated by
+/// [`metadata!{}`](crate::metadata). The definitions of the types are shared between the
+/// schemas.
+pub struct AbiGenerator {
+    gen: SchemaGenerator,
+}
+
+impl Default for AbiGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AbiGenerator {
+    pub fn new() -> Self {
+        Self { gen: SchemaGenerator::default() }
+    }
+
+    /// Returns the parameter of a method deserialized from JSON as `T`.
+    pub fn json_parameter<T: JsonSchema + ?Sized>(&mut self, name: &str) -> AbiParameter {
+        AbiParameter { name: name.to_string(), type_schema: self.json_type::<T>() }
+    }
+
+    /// Returns the parameter of a method deserialized with Borsh as `T`.
+    pub fn borsh_parameter<T: BorshSchema + ?Sized>(&mut self, name: &str) -> AbiParameter {
+        AbiParameter { name: name.to_string(), type_schema: self.borsh_type::<T>() }
+    }
+
+    /// Returns the type of a value serialized as JSON.
+    pub fn json_type<T: JsonSchema + ?Sized>(&mut self) -> AbiType {
+        AbiType::Json { type_schema: self.gen.subschema_for::<T>() }
+    }
+
+    /// Returns the type of a value serialized with Borsh.
+    pub fn borsh_type<T: BorshSchema + ?Sized>(&mut self) -> AbiType {
+        AbiType::Borsh { declaration: T::declaration() }
+    }
+
+    /// Returns the event of type `T`.
+    pub fn event<T: JsonSchema + ?Sized>(&mut self) -> AbiEvent {
+        AbiEvent { name: T::schema_name(), type_schema: self.gen.subschema_for::<T>() }
+    }
+
+    /// Returns the ABI with the methods and events, and the definitions of their types.
+    pub fn into_abi(
+        mut self,
+        name: &str,
+        version: &str,
+        functions: Vec<AbiFunction>,
+        events: Vec<AbiEvent>,
+    ) -> ContractAbi {
+        ContractAbi {
+            abi_version: ABI_VERSION.to_string(),
+            name: name.to_string(),
+            version: version.to_string(),
+            functions,
+            events,
+            definitions: self.gen.take_definitions(),
+        }
+    }
+}
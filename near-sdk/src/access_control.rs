@@ -0,0 +1,134 @@
+//! Registry of the roles of the accounts, kept in the storage of the contract, and the guards of
+//! the methods which only the accounts with a role can call.
+//!
+//! The roles of a contract are declared with `#[access_control(roles(..))]` on its struct, and the
+//! methods of its `#[near_bindgen]` sections are restricted to them with `#[only(..)]`:
+//!
+//! ```
+//! use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+//! use near_sdk::{access_control, env, near_bindgen, AccountId, PanicOnDefault};
+//!
+//! #[near_bindgen]
+//! #[access_control(roles(Admin, Operator), admin(Admin))]
+//! #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+//! pub struct Contract {
+//!     paused: bool,
+//! }
+//!
+//! #[near_bindgen]
+//! impl Contract {
+//!     #[init]
+//!     pub fn new(admin_id: AccountId) -> Self {
+//!         access_control::grant_role("Admin", &admin_id);
+//!         Self { paused: false }
+//!     }
+//!
+//!     #[only(Admin, Operator)]
+//!     pub fn pause(&mut self) {
+//!         self.paused = true;
+//!     }
+//! }
+//! ```
+//!
+//! The roles must be declared on the contract to be used with `#[only(..)]`. The contract also
+//! gets the `acl_has_role` view method, and the `acl_grant_role` and `acl_revoke_role` methods,
+//! which can be called by the contract itself and by the accounts with the roles given as
+//! `admin(..)`.
+
+use crate::{env, AccountId};
+use borsh::BorshSerialize;
+
+/// Prefix of the storage keys of the registry.
+const ACL_PREFIX: &[u8] = b"__acl";
+
+fn role_key(role: &str, account_id: &AccountId) -> Vec<u8> {
+    let mut key = ACL_PREFIX.to_vec();
+    (role, account_id).serialize(&mut key).unwrap_or_else(|_| env::abort());
+    key
+}
+
+/// Returns whether the account has the role.
+pub fn has_role(role: &str, account_id: &AccountId) -> bool {
+    env::storage_has_key(&role_key(role, account_id))
+}
+
+/// Grants the role to the account, and returns whether the account did not have it.
+pub fn grant_role(role: &str, account_id: &AccountId) -> bool {
+    !env::storage_write(&role_key(role, account_id), &[])
+}
+
+/// Revokes the role from the account, and returns whether the account had it.
+pub fn revoke_role(role: &str, account_id: &AccountId) -> bool {
+    env::storage_remove(&role_key(role, account_id))
+}
+
+/// Returns whether the account has one of the roles.
+pub fn has_any_role(roles: &[&str], account_id: &AccountId) -> bool {
+    roles.iter().any(|role| has_role(role, account_id))
+}
+
+/// Panics unless the predecessor has one of the roles, which is the guard of the methods marked
+/// with `#[only(..)]`.
+pub fn require_any_role(roles: &[&str], method: &str) {
+    if !has_any_role(roles, &env::predecessor_account_id()) {
+        env::panic_str(&format!("Method {} requires the role {}", method, roles.join(" or ")))
+    }
+}
+
+/// Panics unless the predecessor is the contract itself or has one of the `admin` roles, which is
+/// the guard of the methods changing the roles.
+pub fn require_admin(admin_roles: &[&str], method: &str) {
+    let predecessor = env::predecessor_account_id();
+    if predecessor != env::current_account_id() && !has_any_role(admin_roles, &predecessor) {
+        env::panic_str(&format!("Method {} can only be called by the contract or an admin", method))
+    }
+}
+
+/// Panics if the role is not one of the roles of the contract.
+pub fn require_known_role(roles: &[&str], role: &str) {
+    if !roles.contains(&role) {
+        env::panic_str(&format!("Unknown role {}", role))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{accounts, VMContextBuilder};
+    use crate::{assert_contract_panics, testing_env};
+
+    #[test]
+    fn grant_and_revoke() {
+        assert!(grant_role("Admin", &accounts(0)));
+        assert!(!grant_role("Admin", &accounts(0)));
+        assert!(has_role("Admin", &accounts(0)));
+        assert!(!has_role("Admin", &accounts(1)));
+        assert!(!has_role("Operator", &accounts(0)));
+        assert!(has_any_role(&["Operator", "Admin"], &accounts(0)));
+
+        assert!(revoke_role("Admin", &accounts(0)));
+        assert!(!revoke_role("Admin", &accounts(0)));
+        assert!(!has_role("Admin", &accounts(0)));
+    }
+
+    #[test]
+    fn guards() {
+        grant_role("Operator", &accounts(1));
+        testing_env!(VMContextBuilder::new().predecessor_account_id(accounts(1)).build());
+        require_any_role(&["Admin", "Operator"], "pause");
+        assert_contract_panics!(
+            require_any_role(&["Admin"], "pause"),
+            "Method pause requires the role Admin"
+        );
+        assert_contract_panics!(
+            require_admin(&["Admin"], "acl_grant_role"),
+            "Method acl_grant_role can only be called by the contract or an admin"
+        );
+        require_admin(&["Operator"], "acl_grant_role");
+
+        let contract = env::current_account_id();
+        testing_env!(VMContextBuilder::new().predecessor_account_id(contract).build());
+        require_admin(&[], "acl_grant_role");
+        assert_contract_panics!(require_known_role(&["Admin"], "Owner"), "Unknown role Owner");
+    }
+}
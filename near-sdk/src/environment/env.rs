@@ -5,17 +5,35 @@
 
 use std::convert::TryFrom;
 use std::mem::size_of;
+use std::ops::Range;
 use std::panic as std_panic;
 
+use super::rng::{self, SeedRng};
 #[cfg(not(target_arch = "wasm32"))]
 use crate::mock::MockedBlockchain;
+use crate::promise::AccessKeyAction;
+use crate::types::alt_bn128::{G1Point, G2Point, Scalar};
+use crate::types::bls12_381;
 use crate::types::{
-    AccountId, Balance, BlockHeight, Gas, PromiseIndex, PromiseResult, PublicKey, StorageUsage,
+    AccountId, Balance, BlockHeight, CryptoHash, EpochHeight, FunctionError, Gas, NearToken,
+    ParseInputError, PromiseIndex, PromiseResult, PublicKey, Secp256K1PublicKey,
+    Secp256k1Signature, StateVersionMismatch, StorageUsage,
 };
+use borsh::{BorshDeserialize, BorshSerialize};
 use near_sys as sys;
 
 const REGISTER_EXPECTED_ERR: &str =
     "Register was expected to have data because we just wrote it into it.";
+const REGISTER_BUFFER_TOO_SMALL_ERR: &str = "The buffer is smaller than the register";
+const STORAGE_DEPOSIT_OVERFLOW_ERR: &str = "The required storage deposit overflows the balance";
+#[cfg(not(target_arch = "wasm32"))]
+const RANDOM_SEED_NOT_SET_ERR: &str =
+    "The random seed is not set, set it with `VMContextBuilder::random_seed_array` to test randomness";
+
+/// Message of the panic of [`abort`] in unit tests, which is the error the runtime reports for a
+/// guest panic without a message.
+#[cfg(not(target_arch = "wasm32"))]
+const ABORT_MSG: &str = "explicit guest panic";
 
 /// Register used internally for atomic operations. This register is safe to use by the user,
 /// since it only needs to be untouched while methods of `Environment` execute, which is guaranteed
@@ -25,7 +43,7 @@ const ATOMIC_OP_REGISTER: u64 = std::u64::MAX - 2;
 const EVICTED_REGISTER: u64 = std::u64::MAX - 1;
 
 /// Key used to store the state of the contract.
-const STATE_KEY: &[u8] = b"STATE";
+pub(crate) const STATE_KEY: &[u8] = b"STATE";
 
 /// The minimum length of a valid account ID.
 const MIN_ACCOUNT_ID_LEN: u64 = 2;
@@ -76,9 +94,36 @@ macro_rules! method_into_register {
 pub fn set_blockchain_interface(blockchain_interface: MockedBlockchain) {
     crate::mock::with_mocked_blockchain(|b| {
         *b = blockchain_interface;
-    })
+    });
+    rng::reset();
+}
+
+/// Panics if the mocked blockchain is set up for a view call, with the message of the error the
+/// runtime traps with.
+#[cfg(not(target_arch = "wasm32"))]
+fn assert_not_view(method_name: &str) {
+    if crate::mock::with_mocked_blockchain(|b| b.is_view()) {
+        panic_str(&format!("{} is not allowed in view calls", method_name))
+    }
+}
+
+/// The runtime traps when a host function which is not allowed in view calls is used in one.
+#[cfg(target_arch = "wasm32")]
+#[inline(always)]
+fn assert_not_view(_method_name: &str) {}
+
+/// Panics in unit tests for the host functions which the mocked blockchain does not support,
+/// naming the host function.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn assert_supported_by_mock(method_name: &str) {
+    panic_str(&format!("{} is not supported by the mocked blockchain", method_name))
 }
 
+/// All the host functions are available on chain.
+#[cfg(target_arch = "wasm32")]
+#[inline(always)]
+pub(crate) fn assert_supported_by_mock(_method_name: &str) {}
+
 /// Implements panic hook that converts `PanicInfo` into a string and provides it through the
 /// blockchain interface.
 fn panic_hook_impl(info: &std_panic::PanicInfo) {
@@ -98,6 +143,21 @@ pub fn read_register(register_id: u64) -> Option<Vec<u8>> {
     Some(res)
 }
 
+/// Reads the content of the `register_id` into the start of `buffer`, without allocating, and
+/// returns the number of bytes read. If register is not used returns `None`.
+///
+/// # Panics
+///
+/// Panics if `buffer` is smaller than the register, which can be checked with [`register_len`].
+pub fn read_register_into(register_id: u64, buffer: &mut [u8]) -> Option<usize> {
+    let len = register_len(register_id)? as usize;
+    if buffer.len() < len {
+        panic_str(REGISTER_BUFFER_TOO_SMALL_ERR);
+    }
+    unsafe { sys::read_register(register_id, buffer.as_mut_ptr() as _) };
+    Some(len)
+}
+
 /// Returns the size of the register. If register is not used returns `None`.
 pub fn register_len(register_id: u64) -> Option<u64> {
     let len = unsafe { sys::register_len(register_id) };
@@ -119,17 +179,20 @@ pub fn current_account_id() -> AccountId {
 /// The id of the account that either signed the original transaction or issued the initial
 /// cross-contract call.
 pub fn signer_account_id() -> AccountId {
+    assert_not_view("signer_account_id");
     assert_valid_account_id(method_into_register!(signer_account_id))
 }
 
 /// The public key of the account that did the signing.
 pub fn signer_account_pk() -> PublicKey {
+    assert_not_view("signer_account_pk");
     PublicKey::try_from(method_into_register!(signer_account_pk)).unwrap_or_else(|_| abort())
 }
 
 /// The id of the account that was the previous contract in the chain of cross-contract calls.
 /// If this is the first contract, it is equal to `signer_account_id`.
 pub fn predecessor_account_id() -> AccountId {
+    assert_not_view("predecessor_account_id");
     assert_valid_account_id(method_into_register!(predecessor_account_id))
 }
 
@@ -146,6 +209,38 @@ pub fn input() -> Option<Vec<u8>> {
     try_method_into_register!(input)
 }
 
+/// The input to the contract call deserialized from JSON, which is how `#[near_bindgen]` methods
+/// receive their arguments by default.
+///
+/// # Examples
+/// ```
+/// use near_sdk::serde::Deserialize;
+/// use near_sdk::test_utils::VMContextBuilder;
+/// use near_sdk::{env, testing_env};
+///
+/// #[derive(Deserialize)]
+/// #[serde(crate = "near_sdk::serde")]
+/// struct Args {
+///     amount: u64,
+/// }
+///
+/// let mut context = VMContextBuilder::new().build();
+/// context.input = br#"{"amount":5}"#.to_vec();
+/// testing_env!(context);
+/// assert_eq!(env::input_as_json::<Args>().unwrap().amount, 5);
+/// assert!(env::input_as_borsh::<u128>().is_err());
+/// ```
+pub fn input_as_json<T: serde::de::DeserializeOwned>() -> Result<T, ParseInputError> {
+    let input = input().ok_or_else(ParseInputError::missing)?;
+    Ok(serde_json::from_slice(&input)?)
+}
+
+/// The input to the contract call deserialized from Borsh.
+pub fn input_as_borsh<T: BorshDeserialize>() -> Result<T, ParseInputError> {
+    let input = input().ok_or_else(ParseInputError::missing)?;
+    Ok(T::try_from_slice(&input)?)
+}
+
 /// Current block index.
 #[deprecated(since = "4.0.0", note = "Use block_height instead")]
 pub fn block_index() -> BlockHeight {
@@ -163,7 +258,7 @@ pub fn block_timestamp() -> u64 {
 }
 
 /// Current epoch height.
-pub fn epoch_height() -> u64 {
+pub fn epoch_height() -> EpochHeight {
     unsafe { sys::epoch_height() }
 }
 
@@ -193,6 +288,7 @@ pub fn account_locked_balance() -> Balance {
 /// The balance that was attached to the call that will be immediately deposited before the
 /// contract execution starts
 pub fn attached_deposit() -> Balance {
+    assert_not_view("attached_deposit");
     let data = [0u8; size_of::<Balance>()];
     unsafe { sys::attached_deposit(data.as_ptr() as u64) };
     Balance::from_le_bytes(data)
@@ -200,20 +296,63 @@ pub fn attached_deposit() -> Balance {
 
 /// The amount of gas attached to the call that can be used to pay for the gas fees.
 pub fn prepaid_gas() -> Gas {
+    assert_not_view("prepaid_gas");
     Gas(unsafe { sys::prepaid_gas() })
 }
 
 /// The gas that was already burnt during the contract execution (cannot exceed `prepaid_gas`)
 pub fn used_gas() -> Gas {
+    assert_not_view("used_gas");
     Gas(unsafe { sys::used_gas() })
 }
 
+/// The gas which can still be used during the contract execution, which is the prepaid gas minus
+/// the used gas.
+pub fn remaining_gas() -> Gas {
+    prepaid_gas().saturating_sub(used_gas())
+}
+
 // ############
 // # Math API #
 // ############
 /// Get random seed from the register.
+///
+/// In unit tests, this panics unless the seed was set with
+/// [`VMContextBuilder::random_seed_array`](crate::test_utils::VMContextBuilder::random_seed_array).
 pub fn random_seed() -> Vec<u8> {
-    method_into_register!(random_seed)
+    let seed = method_into_register!(random_seed);
+    #[cfg(not(target_arch = "wasm32"))]
+    if seed.is_empty() {
+        panic_str(RANDOM_SEED_NOT_SET_ERR)
+    }
+    seed
+}
+
+/// Returns a random number generator seeded from the [`random_seed`] of the block.
+///
+/// Each generator created during a function call produces a different sequence of numbers, and
+/// the sequences are the same whenever the call is executed with the same seed, as is required
+/// for the execution to be deterministic. As the seed is known to the block producer, the numbers
+/// must not be relied on where the block producer could profit from predicting them.
+///
+/// The generator implements [`RngCore`](rand_core::RngCore), so it can be used with the `rand`
+/// crate. See [`random_in_range`] and [`shuffle`] for common uses.
+pub fn rng() -> impl rand_core::RngCore {
+    SeedRng::new()
+}
+
+/// Returns a uniformly distributed random number in `range`, using a generator from [`rng`].
+///
+/// # Panics
+///
+/// Panics if the range is empty.
+pub fn random_in_range(range: Range<u64>) -> u64 {
+    rng::random_in_range(&mut SeedRng::new(), range)
+}
+
+/// Shuffles the slice uniformly at random, using a generator from [`rng`].
+pub fn shuffle<T>(slice: &mut [T]) {
+    rng::shuffle(&mut SeedRng::new(), slice)
 }
 
 /// Hashes the random sequence of bytes using sha256.
@@ -234,6 +373,255 @@ pub fn keccak512(value: &[u8]) -> Vec<u8> {
     expect_register(read_register(ATOMIC_OP_REGISTER))
 }
 
+/// Recovers the public key which signed the 32-byte `hash` with `signature` and the recovery id
+/// `v`, which is 0 or 1. Returns [`None`] if the signature is invalid.
+///
+/// Malleable signatures, with an `s` value in the upper half of the curve order, are rejected, so
+/// that a signature cannot be altered into a different valid signature of the same hash. Use
+/// [`ecrecover_malleable`] to accept them.
+///
+/// The mocked blockchain used in unit tests does not support this function, and panics when it
+/// is called.
+pub fn ecrecover(
+    hash: &[u8; 32],
+    signature: &Secp256k1Signature,
+    v: u8,
+) -> Option<Secp256K1PublicKey> {
+    ecrecover_inner(hash, signature, v, true)
+}
+
+/// Recovers the public key which signed the 32-byte `hash`, as with [`ecrecover`], but also
+/// accepts malleable signatures.
+pub fn ecrecover_malleable(
+    hash: &[u8; 32],
+    signature: &Secp256k1Signature,
+    v: u8,
+) -> Option<Secp256K1PublicKey> {
+    ecrecover_inner(hash, signature, v, false)
+}
+
+fn ecrecover_inner(
+    hash: &[u8; 32],
+    signature: &Secp256k1Signature,
+    v: u8,
+    check_malleability: bool,
+) -> Option<Secp256K1PublicKey> {
+    assert_supported_by_mock("ecrecover");
+    let recovered = unsafe {
+        sys::ecrecover(
+            hash.len() as _,
+            hash.as_ptr() as _,
+            signature.0.len() as _,
+            signature.0.as_ptr() as _,
+            v as u64,
+            check_malleability as u64,
+            ATOMIC_OP_REGISTER,
+        )
+    };
+    if recovered == 0 {
+        return None;
+    }
+    let bytes = expect_register(read_register(ATOMIC_OP_REGISTER));
+    Some(Secp256K1PublicKey(<[u8; 64]>::try_from(bytes).unwrap_or_else(|_| abort())))
+}
+
+/// Returns `true` if `signature` is a valid ed25519 signature of `message` by `public_key`.
+///
+/// The mocked blockchain used in unit tests does not support this function, and panics when it
+/// is called.
+///
+/// See [`Ed25519Signature::verify`](crate::Ed25519Signature::verify) for a typed equivalent.
+pub fn ed25519_verify(signature: &[u8; 64], message: &[u8], public_key: &[u8; 32]) -> bool {
+    assert_supported_by_mock("ed25519_verify");
+    unsafe {
+        sys::ed25519_verify(
+            signature.len() as _,
+            signature.as_ptr() as _,
+            message.len() as _,
+            message.as_ptr() as _,
+            public_key.len() as _,
+            public_key.as_ptr() as _,
+        ) == 1
+    }
+}
+
+/// Computes the sum of the points multiplied by their scalars on the alt_bn128 curve,
+/// `sum_i(scalar_i * point_i)`.
+///
+/// Panics if a point is not on the curve or a scalar is not in the scalar field.
+pub fn alt_bn128_g1_multiexp(items: &[(G1Point, Scalar)]) -> G1Point {
+    let data = serialize_alt_bn128_input(items);
+    assert_supported_by_mock("alt_bn128_g1_multiexp");
+    unsafe { sys::alt_bn128_g1_multiexp(data.len() as _, data.as_ptr() as _, ATOMIC_OP_REGISTER) };
+    deserialize_alt_bn128_point(expect_register(read_register(ATOMIC_OP_REGISTER)))
+}
+
+/// Computes the sum of the points on the alt_bn128 curve, subtracting the points for which the
+/// sign is `true`, `sum_i((-1)^sign_i * point_i)`.
+///
+/// Panics if a point is not on the curve.
+pub fn alt_bn128_g1_sum(items: &[(bool, G1Point)]) -> G1Point {
+    let data = serialize_alt_bn128_input(items);
+    assert_supported_by_mock("alt_bn128_g1_sum");
+    unsafe { sys::alt_bn128_g1_sum(data.len() as _, data.as_ptr() as _, ATOMIC_OP_REGISTER) };
+    deserialize_alt_bn128_point(expect_register(read_register(ATOMIC_OP_REGISTER)))
+}
+
+/// Returns `true` if the product of the pairings of the G1 and G2 points is one, as checked when
+/// verifying zk-SNARK proofs such as Groth16.
+///
+/// Panics if a point is not on the curve or a G2 point is not in the subgroup.
+pub fn alt_bn128_pairing_check(items: &[(G1Point, G2Point)]) -> bool {
+    let data = serialize_alt_bn128_input(items);
+    assert_supported_by_mock("alt_bn128_pairing_check");
+    unsafe { sys::alt_bn128_pairing_check(data.len() as _, data.as_ptr() as _) == 1 }
+}
+
+/// Computes the sum of the G1 points on the BLS12-381 curve, subtracting the points for which the
+/// sign is `true`, `sum_i((-1)^sign_i * point_i)`.
+///
+/// Panics if a point is not on the curve.
+pub fn bls12381_p1_sum(items: &[(bool, bls12_381::G1Point)]) -> bls12_381::G1Point {
+    let data = bls12381_input(items.iter().map(|(sign, p)| (bls12381_sign(*sign), p)));
+    assert_supported_by_mock("bls12381_p1_sum");
+    let status =
+        unsafe { sys::bls12381_p1_sum(data.len() as _, data.as_ptr() as _, ATOMIC_OP_REGISTER) };
+    bls12_381::G1Point(bls12381_output(status))
+}
+
+/// Computes the sum of the G2 points on the BLS12-381 curve, subtracting the points for which the
+/// sign is `true`, `sum_i((-1)^sign_i * point_i)`.
+///
+/// Panics if a point is not on the curve.
+pub fn bls12381_p2_sum(items: &[(bool, bls12_381::G2Point)]) -> bls12_381::G2Point {
+    let data = bls12381_input(items.iter().map(|(sign, p)| (bls12381_sign(*sign), p)));
+    assert_supported_by_mock("bls12381_p2_sum");
+    let status =
+        unsafe { sys::bls12381_p2_sum(data.len() as _, data.as_ptr() as _, ATOMIC_OP_REGISTER) };
+    bls12_381::G2Point(bls12381_output(status))
+}
+
+/// Computes the sum of the G1 points multiplied by their scalars on the BLS12-381 curve,
+/// `sum_i(scalar_i * point_i)`.
+///
+/// Panics if a point is not in the G1 group.
+pub fn bls12381_g1_multiexp(
+    items: &[(bls12_381::G1Point, bls12_381::Scalar)],
+) -> bls12_381::G1Point {
+    let data = bls12381_input(items.iter().map(|(p, s)| (p, s)));
+    assert_supported_by_mock("bls12381_g1_multiexp");
+    let status = unsafe {
+        sys::bls12381_g1_multiexp(data.len() as _, data.as_ptr() as _, ATOMIC_OP_REGISTER)
+    };
+    bls12_381::G1Point(bls12381_output(status))
+}
+
+/// Computes the sum of the G2 points multiplied by their scalars on the BLS12-381 curve,
+/// `sum_i(scalar_i * point_i)`.
+///
+/// Panics if a point is not in the G2 group.
+pub fn bls12381_g2_multiexp(
+    items: &[(bls12_381::G2Point, bls12_381::Scalar)],
+) -> bls12_381::G2Point {
+    let data = bls12381_input(items.iter().map(|(p, s)| (p, s)));
+    assert_supported_by_mock("bls12381_g2_multiexp");
+    let status = unsafe {
+        sys::bls12381_g2_multiexp(data.len() as _, data.as_ptr() as _, ATOMIC_OP_REGISTER)
+    };
+    bls12_381::G2Point(bls12381_output(status))
+}
+
+/// Maps each base field element to a point of the G1 group, as used to hash messages to the
+/// curve.
+///
+/// Panics if an element is not in the base field.
+pub fn bls12381_map_fp_to_g1(elements: &[bls12_381::Fp]) -> Vec<bls12_381::G1Point> {
+    let data: Vec<u8> = elements.iter().flat_map(|e| e.0).collect();
+    assert_supported_by_mock("bls12381_map_fp_to_g1");
+    let status = unsafe {
+        sys::bls12381_map_fp_to_g1(data.len() as _, data.as_ptr() as _, ATOMIC_OP_REGISTER)
+    };
+    bls12381_points(status, bls12_381::G1Point)
+}
+
+/// Maps each element of the quadratic extension field to a point of the G2 group, as used to
+/// hash messages to the curve.
+///
+/// Panics if an element is not in the field.
+pub fn bls12381_map_fp2_to_g2(elements: &[bls12_381::Fp2]) -> Vec<bls12_381::G2Point> {
+    let data: Vec<u8> = elements.iter().flat_map(|e| e.0).collect();
+    assert_supported_by_mock("bls12381_map_fp2_to_g2");
+    let status = unsafe {
+        sys::bls12381_map_fp2_to_g2(data.len() as _, data.as_ptr() as _, ATOMIC_OP_REGISTER)
+    };
+    bls12381_points(status, bls12_381::G2Point)
+}
+
+/// Returns `true` if the product of the pairings of the G1 and G2 points is one, as checked when
+/// verifying BLS signatures.
+///
+/// Panics if a point is not in its group.
+pub fn bls12381_pairing_check(items: &[(bls12_381::G1Point, bls12_381::G2Point)]) -> bool {
+    let data = bls12381_input(items.iter().map(|(p1, p2)| (p1, p2)));
+    assert_supported_by_mock("bls12381_pairing_check");
+    match unsafe { sys::bls12381_pairing_check(data.len() as _, data.as_ptr() as _) } {
+        0 => true,
+        2 => false,
+        _ => panic_str(BLS12381_INVALID_INPUT_ERR),
+    }
+}
+
+const BLS12381_INVALID_INPUT_ERR: &str = "Invalid bls12-381 input";
+
+fn bls12381_sign(sign: bool) -> [u8; 1] {
+    [sign as u8]
+}
+
+fn bls12381_input<A, B>(items: impl Iterator<Item = (A, B)>) -> Vec<u8>
+where
+    A: AsRef<[u8]>,
+    B: AsRef<[u8]>,
+{
+    let mut data = Vec::new();
+    for (a, b) in items {
+        data.extend_from_slice(a.as_ref());
+        data.extend_from_slice(b.as_ref());
+    }
+    data
+}
+
+fn bls12381_output<const N: usize>(status: u64) -> [u8; N] {
+    if status != 0 {
+        panic_str(BLS12381_INVALID_INPUT_ERR);
+    }
+    let mut point = [0; N];
+    read_register_into(ATOMIC_OP_REGISTER, &mut point);
+    point
+}
+
+fn bls12381_points<const N: usize, P>(status: u64, point: fn([u8; N]) -> P) -> Vec<P> {
+    if status != 0 {
+        panic_str(BLS12381_INVALID_INPUT_ERR);
+    }
+    let bytes = expect_register(read_register(ATOMIC_OP_REGISTER));
+    bytes
+        .chunks_exact(N)
+        .map(|chunk| {
+            let mut bytes = [0; N];
+            bytes.copy_from_slice(chunk);
+            point(bytes)
+        })
+        .collect()
+}
+
+fn serialize_alt_bn128_input<T: BorshSerialize>(items: &[T]) -> Vec<u8> {
+    items.try_to_vec().unwrap_or_else(|_| panic_str("Cannot serialize alt_bn128 input"))
+}
+
+fn deserialize_alt_bn128_point(bytes: Vec<u8>) -> G1Point {
+    G1Point::try_from_slice(&bytes).unwrap_or_else(|_| abort())
+}
+
 // ################
 // # Promises API #
 // ################
@@ -246,6 +634,7 @@ pub fn promise_create(
     amount: Balance,
     gas: Gas,
 ) -> PromiseIndex {
+    assert_not_view("promise_create");
     let account_id = account_id.as_bytes();
     unsafe {
         sys::promise_create(
@@ -270,6 +659,7 @@ pub fn promise_then(
     amount: Balance,
     gas: Gas,
 ) -> PromiseIndex {
+    assert_not_view("promise_then");
     let account_id = account_id.as_bytes();
     unsafe {
         sys::promise_then(
@@ -288,6 +678,7 @@ pub fn promise_then(
 
 /// Creates a new promise which completes when time all promises passed as arguments complete.
 pub fn promise_and(promise_indices: &[PromiseIndex]) -> PromiseIndex {
+    assert_not_view("promise_and");
     let mut data = vec![0u8; promise_indices.len() * size_of::<PromiseIndex>()];
     for i in 0..promise_indices.len() {
         data[i * size_of::<PromiseIndex>()..(i + 1) * size_of::<PromiseIndex>()]
@@ -297,11 +688,13 @@ pub fn promise_and(promise_indices: &[PromiseIndex]) -> PromiseIndex {
 }
 
 pub fn promise_batch_create(account_id: &AccountId) -> PromiseIndex {
+    assert_not_view("promise_batch_create");
     let account_id = account_id.as_ref();
     unsafe { sys::promise_batch_create(account_id.len() as _, account_id.as_ptr() as _) }
 }
 
 pub fn promise_batch_then(promise_index: PromiseIndex, account_id: &AccountId) -> PromiseIndex {
+    assert_not_view("promise_batch_then");
     let account_id: &str = account_id.as_ref();
     unsafe {
         sys::promise_batch_then(promise_index, account_id.len() as _, account_id.as_ptr() as _)
@@ -309,10 +702,12 @@ pub fn promise_batch_then(promise_index: PromiseIndex, account_id: &AccountId) -
 }
 
 pub fn promise_batch_action_create_account(promise_index: PromiseIndex) {
+    assert_not_view("promise_batch_action_create_account");
     unsafe { sys::promise_batch_action_create_account(promise_index) }
 }
 
 pub fn promise_batch_action_deploy_contract(promise_index: u64, code: &[u8]) {
+    assert_not_view("promise_batch_action_deploy_contract");
     unsafe {
         sys::promise_batch_action_deploy_contract(
             promise_index,
@@ -329,6 +724,7 @@ pub fn promise_batch_action_function_call(
     amount: Balance,
     gas: Gas,
 ) {
+    assert_not_view("promise_batch_action_function_call");
     unsafe {
         sys::promise_batch_action_function_call(
             promise_index,
@@ -343,6 +739,7 @@ pub fn promise_batch_action_function_call(
 }
 
 pub fn promise_batch_action_transfer(promise_index: PromiseIndex, amount: Balance) {
+    assert_not_view("promise_batch_action_transfer");
     unsafe { sys::promise_batch_action_transfer(promise_index, &amount as *const Balance as _) }
 }
 
@@ -351,6 +748,7 @@ pub fn promise_batch_action_stake(
     amount: Balance,
     public_key: &PublicKey,
 ) {
+    assert_not_view("promise_batch_action_stake");
     unsafe {
         sys::promise_batch_action_stake(
             promise_index,
@@ -365,6 +763,7 @@ pub fn promise_batch_action_add_key_with_full_access(
     public_key: &PublicKey,
     nonce: u64,
 ) {
+    assert_not_view("promise_batch_action_add_key_with_full_access");
     unsafe {
         sys::promise_batch_action_add_key_with_full_access(
             promise_index,
@@ -382,6 +781,7 @@ pub fn promise_batch_action_add_key_with_function_call(
     receiver_id: &AccountId,
     method_names: &str,
 ) {
+    assert_not_view("promise_batch_action_add_key_with_function_call");
     let receiver_id: &str = receiver_id.as_ref();
     unsafe {
         sys::promise_batch_action_add_key_with_function_call(
@@ -398,6 +798,7 @@ pub fn promise_batch_action_add_key_with_function_call(
     }
 }
 pub fn promise_batch_action_delete_key(promise_index: PromiseIndex, public_key: &PublicKey) {
+    assert_not_view("promise_batch_action_delete_key");
     unsafe {
         sys::promise_batch_action_delete_key(
             promise_index,
@@ -407,10 +808,38 @@ pub fn promise_batch_action_delete_key(promise_index: PromiseIndex, public_key:
     }
 }
 
+/// Appends an action on an access key of the account to the batch of actions for the given
+/// promise pointed by `promise_index`.
+pub fn promise_batch_action_access_key(promise_index: PromiseIndex, action: &AccessKeyAction) {
+    match action {
+        AccessKeyAction::AddFullAccessKey { public_key, nonce } => {
+            promise_batch_action_add_key_with_full_access(promise_index, public_key, *nonce)
+        }
+        AccessKeyAction::AddFunctionCallKey {
+            public_key,
+            nonce,
+            allowance,
+            receiver_id,
+            method_names,
+        } => promise_batch_action_add_key_with_function_call(
+            promise_index,
+            public_key,
+            *nonce,
+            *allowance,
+            receiver_id,
+            &method_names.join(","),
+        ),
+        AccessKeyAction::DeleteKey { public_key } => {
+            promise_batch_action_delete_key(promise_index, public_key)
+        }
+    }
+}
+
 pub fn promise_batch_action_delete_account(
     promise_index: PromiseIndex,
     beneficiary_id: &AccountId,
 ) {
+    assert_not_view("promise_batch_action_delete_account");
     let beneficiary_id: &str = beneficiary_id.as_ref();
     unsafe {
         sys::promise_batch_action_delete_account(
@@ -425,11 +854,13 @@ pub fn promise_batch_action_delete_account(
 /// promises that caused the callback. This function returns the number of complete and
 /// incomplete callbacks.
 pub fn promise_results_count() -> u64 {
+    assert_not_view("promise_results_count");
     unsafe { sys::promise_results_count() }
 }
 /// If the current function is invoked by a callback we can access the execution results of the
 /// promises that caused the callback.
 pub fn promise_result(result_idx: u64) -> PromiseResult {
+    assert_not_view("promise_result");
     match unsafe { sys::promise_result(result_idx, ATOMIC_OP_REGISTER) } {
         0 => PromiseResult::NotReady,
         1 => {
@@ -443,28 +874,78 @@ pub fn promise_result(result_idx: u64) -> PromiseResult {
 /// Consider the execution result of promise under `promise_idx` as execution result of this
 /// function.
 pub fn promise_return(promise_idx: PromiseIndex) {
+    assert_not_view("promise_return");
     unsafe { sys::promise_return(promise_idx) }
 }
 
+/// Creates a promise which calls `function_name` on the current account with the `arguments`
+/// once it is resumed with [`promise_yield_resume`], or after a timeout of a number of blocks set
+/// by the protocol. The function receives the payload of the resumption as the promise result,
+/// or a failed result on timeout.
+///
+/// `gas_weight` is the share of the unused gas of the call which is attached to the function call
+/// in addition to `gas`, and can be 0.
+///
+/// Returns the index of the promise, and the data id which is used to resume it.
+pub fn promise_yield_create(
+    function_name: &str,
+    arguments: &[u8],
+    gas: Gas,
+    gas_weight: u64,
+) -> (PromiseIndex, CryptoHash) {
+    assert_not_view("promise_yield_create");
+    assert_supported_by_mock("promise_yield_create");
+    let promise_index = unsafe {
+        sys::promise_yield_create(
+            function_name.len() as _,
+            function_name.as_ptr() as _,
+            arguments.len() as _,
+            arguments.as_ptr() as _,
+            gas.0,
+            gas_weight,
+            ATOMIC_OP_REGISTER,
+        )
+    };
+    let mut data_id = [0; 32];
+    expect_register(read_register_into(ATOMIC_OP_REGISTER, &mut data_id));
+    (promise_index, data_id)
+}
+
+/// Resumes the promise created with [`promise_yield_create`] which has the `data_id`, passing the
+/// `payload` to its function. This can be called from any function call of the current account,
+/// and returns `false` if the promise was already resumed or timed out.
+pub fn promise_yield_resume(data_id: &CryptoHash, payload: &[u8]) -> bool {
+    assert_not_view("promise_yield_resume");
+    assert_supported_by_mock("promise_yield_resume");
+    unsafe {
+        sys::promise_yield_resume(
+            data_id.len() as _,
+            data_id.as_ptr() as _,
+            payload.len() as _,
+            payload.as_ptr() as _,
+        ) == 1
+    }
+}
+
 // ###############
 // # Validator API #
 // ###############
 
 /// For a given account return its current stake. If the account is not a validator, returns 0.
-pub fn validator_stake(account_id: &AccountId) -> Balance {
+pub fn validator_stake(account_id: &AccountId) -> NearToken {
     let account_id: &str = account_id.as_ref();
     let data = [0u8; size_of::<Balance>()];
     unsafe {
         sys::validator_stake(account_id.len() as _, account_id.as_ptr() as _, data.as_ptr() as u64)
     };
-    Balance::from_le_bytes(data)
+    NearToken::from_yoctonear(Balance::from_le_bytes(data))
 }
 
 /// Returns the total stake of validators in the current epoch.
-pub fn validator_total_stake() -> Balance {
+pub fn validator_total_stake() -> NearToken {
     let data = [0u8; size_of::<Balance>()];
     unsafe { sys::validator_total_stake(data.as_ptr() as u64) };
-    Balance::from_le_bytes(data)
+    NearToken::from_yoctonear(Balance::from_le_bytes(data))
 }
 
 // #####################
@@ -474,16 +955,57 @@ pub fn validator_total_stake() -> Balance {
 pub fn value_return(value: &[u8]) {
     unsafe { sys::value_return(value.len() as _, value.as_ptr() as _) }
 }
+
+/// Sets the value serialized as JSON as the return value of the contract, as `#[near_bindgen]`
+/// methods return their result by default. With the `canonical-json` feature, the value is
+/// serialized as [canonical](crate::json_types::canonical) JSON.
+pub fn value_return_json<T: serde::Serialize + ?Sized>(value: &T) {
+    let data = crate::json_types::canonical::result_to_vec(value)
+        .unwrap_or_else(|_| panic_str("Failed to serialize the return value using JSON."));
+    value_return(&data)
+}
+
+/// Sets the value serialized with Borsh as the return value of the contract.
+pub fn value_return_borsh<T: BorshSerialize + ?Sized>(value: &T) {
+    let data = value
+        .try_to_vec()
+        .unwrap_or_else(|_| panic_str("Failed to serialize the return value using Borsh."));
+    value_return(&data)
+}
+
 /// Terminates the execution of the program with the UTF-8 encoded message.
 /// [`panic_str`] should be used as the bytes are required to be UTF-8
 #[deprecated(since = "4.0.0", note = "Use env::panic_str to panic with a message.")]
 pub fn panic(message: &[u8]) -> ! {
-    unsafe { sys::panic_utf8(message.len() as _, message.as_ptr() as _) }
+    #[cfg(target_arch = "wasm32")]
+    unsafe {
+        sys::panic_utf8(message.len() as _, message.as_ptr() as _)
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    panic_str(&String::from_utf8_lossy(message))
 }
 
 /// Terminates the execution of the program with the UTF-8 encoded message.
 pub fn panic_str(message: &str) -> ! {
-    unsafe { sys::panic_utf8(message.len() as _, message.as_ptr() as _) }
+    #[cfg(target_arch = "wasm32")]
+    unsafe {
+        sys::panic_utf8(message.len() as _, message.as_ptr() as _)
+    }
+    // The panic is raised here rather than by the mocked host function, so that unit tests catch
+    // it with the message alone.
+    #[cfg(not(target_arch = "wasm32"))]
+    panic!("{}", message)
+}
+
+/// Terminates the execution of the program with the displayed value as the message.
+pub fn panic_display(message: impl std::fmt::Display) -> ! {
+    panic_str(&message.to_string())
+}
+
+/// Terminates the execution of the program with the error serialized as the message, which is
+/// JSON by default, so that the reason of the failure can be parsed. See [`FunctionError`].
+pub fn panic_err<E: FunctionError>(error: E) -> ! {
+    panic_str(&error.panic_message())
 }
 
 /// Aborts the current contract execution without a custom message.
@@ -495,9 +1017,7 @@ pub fn abort() -> ! {
         core::arch::wasm32::unreachable()
     }
     #[cfg(not(target_arch = "wasm32"))]
-    unsafe {
-        sys::panic()
-    }
+    panic_str(ABORT_MSG)
 }
 
 /// Logs the string message message. This message is stored on chain.
@@ -523,6 +1043,7 @@ pub fn log(message: &[u8]) {
 /// Writes key-value into storage.
 /// If another key-value existed in the storage with the same key it returns `true`, otherwise `false`.
 pub fn storage_write(key: &[u8], value: &[u8]) -> bool {
+    assert_not_view("storage_write");
     match unsafe {
         sys::storage_write(
             key.len() as _,
@@ -537,6 +1058,29 @@ pub fn storage_write(key: &[u8], value: &[u8]) -> bool {
         _ => abort(),
     }
 }
+/// Writes each key-value pair into storage, in order, and returns the number of keys which
+/// already had a value. The pairs are written directly from the given slices, so the keys and
+/// values can be built in a single buffer which is reused for the whole batch.
+pub fn storage_write_batch<'a, I>(entries: I) -> u64
+where
+    I: IntoIterator<Item = (&'a [u8], &'a [u8])>,
+{
+    entries.into_iter().filter(|(key, value)| storage_write(key, value)).count() as u64
+}
+/// Writes the key-value pairs as [`storage_write_batch`], calling `f` after each write with the
+/// pair and whether it replaced a value. The length of the replaced value can be read with
+/// [`storage_evicted_len`] until the next write.
+#[cfg(feature = "unstable")]
+pub(crate) fn storage_write_batch_with<'a, I, F>(entries: I, mut f: F)
+where
+    I: IntoIterator<Item = (&'a [u8], &'a [u8])>,
+    F: FnMut(&[u8], &[u8], bool),
+{
+    for (key, value) in entries {
+        let replaced = storage_write(key, value);
+        f(key, value, replaced);
+    }
+}
 /// Reads the value stored under the given key.
 pub fn storage_read(key: &[u8]) -> Option<Vec<u8>> {
     match unsafe { sys::storage_read(key.len() as _, key.as_ptr() as _, ATOMIC_OP_REGISTER) } {
@@ -545,9 +1089,35 @@ pub fn storage_read(key: &[u8]) -> Option<Vec<u8>> {
         _ => abort(),
     }
 }
+/// Reads the value stored under the given key into the `register_id`, without copying it into
+/// the contract's memory. Returns `false` if there is no value for the key.
+///
+/// The value can then be read with [`read_register_into`] into an existing buffer, after its
+/// length is checked with [`register_len`].
+pub fn storage_read_to_register(key: &[u8], register_id: u64) -> bool {
+    match unsafe { sys::storage_read(key.len() as _, key.as_ptr() as _, register_id) } {
+        0 => false,
+        1 => true,
+        _ => abort(),
+    }
+}
+/// Reads the value stored under the given key into `buffer`, replacing its contents. The buffer
+/// only allocates if its capacity is smaller than the value. Returns `false`, leaving the buffer
+/// unchanged, if there is no value for the key.
+#[cfg(feature = "unstable")]
+pub(crate) fn storage_read_into(key: &[u8], buffer: &mut Vec<u8>) -> bool {
+    if !storage_read_to_register(key, ATOMIC_OP_REGISTER) {
+        return false;
+    }
+    buffer.clear();
+    buffer.resize(expect_register(register_len(ATOMIC_OP_REGISTER)) as usize, 0);
+    read_register_into(ATOMIC_OP_REGISTER, buffer);
+    true
+}
 /// Removes the value stored under the given key.
 /// If key-value existed returns `true`, otherwise `false`.
 pub fn storage_remove(key: &[u8]) -> bool {
+    assert_not_view("storage_remove");
     match unsafe { sys::storage_remove(key.len() as _, key.as_ptr() as _, EVICTED_REGISTER) } {
         0 => false,
         1 => true,
@@ -558,6 +1128,12 @@ pub fn storage_remove(key: &[u8]) -> bool {
 pub fn storage_get_evicted() -> Option<Vec<u8>> {
     read_register(EVICTED_REGISTER)
 }
+/// Returns the length of the most recent value that was evicted with `storage_write` or
+/// `storage_remove` command, without reading the value.
+#[cfg(feature = "unstable")]
+pub(crate) fn storage_evicted_len() -> Option<u64> {
+    register_len(EVICTED_REGISTER)
+}
 /// Checks if there is a key-value in the storage.
 pub fn storage_has_key(key: &[u8]) -> bool {
     match unsafe { sys::storage_has_key(key.len() as _, key.as_ptr() as _) } {
@@ -581,6 +1157,46 @@ pub fn state_write<T: borsh::BorshSerialize>(state: &T) {
     storage_write(STATE_KEY, &data);
 }
 
+/// Load the state of the given object, which was written by [`state_write_versioned`].
+///
+/// Returns `Ok(None)` if there is no state, and an error if the state was written with a
+/// version other than `version`. Migration code can use the error to pick the older schema to
+/// read the state with, before writing it back with the current version.
+///
+/// # Examples
+/// ```
+/// use near_sdk::env;
+///
+/// env::state_write_versioned(1, &10u32);
+/// assert_eq!(env::state_read_versioned::<u32>(1), Ok(Some(10)));
+///
+/// let err = env::state_read_versioned::<u64>(2).unwrap_err();
+/// assert_eq!(err.found, 1);
+/// ```
+pub fn state_read_versioned<T: borsh::BorshDeserialize>(
+    version: u8,
+) -> Result<Option<T>, StateVersionMismatch> {
+    let data = match storage_read(STATE_KEY) {
+        Some(data) => data,
+        None => return Ok(None),
+    };
+    match data.split_first() {
+        Some((&found, state)) if found == version => {
+            Ok(Some(T::try_from_slice(state).expect("Cannot deserialize the contract state.")))
+        }
+        Some((&found, _)) => Err(StateVersionMismatch { expected: version, found }),
+        None => panic_str("Cannot deserialize the contract state."),
+    }
+}
+
+/// Write the state of the given object, prefixed with the schema `version`, so that it can be
+/// read with [`state_read_versioned`].
+pub fn state_write_versioned<T: borsh::BorshSerialize>(version: u8, state: &T) {
+    let mut data = vec![version];
+    state.serialize(&mut data).expect("Cannot serialize the contract state.");
+    storage_write(STATE_KEY, &data);
+}
+
 /// Returns `true` if the contract state exists and `false` otherwise.
 pub fn state_exists() -> bool {
     storage_has_key(STATE_KEY)
@@ -594,8 +1210,23 @@ pub fn state_exists() -> bool {
 /// TODO: will be using the host function when it will be available.
 pub const STORAGE_PRICE_PER_BYTE: Balance = 10_000_000_000_000_000_000;
 
+/// Returns the cost of a byte of storage. In unit tests, it can be changed with
+/// [`mock::set_storage_byte_cost`](crate::mock::set_storage_byte_cost).
 pub fn storage_byte_cost() -> Balance {
-    STORAGE_PRICE_PER_BYTE
+    #[cfg(target_arch = "wasm32")]
+    {
+        STORAGE_PRICE_PER_BYTE
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    crate::mock::storage_byte_cost()
+}
+
+/// Returns the deposit required to cover the storage of the given number of bytes, at the
+/// [`storage_byte_cost`]. Panics if the deposit does not fit in a [`Balance`].
+pub fn required_storage_deposit(bytes: StorageUsage) -> Balance {
+    Balance::from(bytes)
+        .checked_mul(storage_byte_cost())
+        .unwrap_or_else(|| panic_str(STORAGE_DEPOSIT_OVERFLOW_ERR))
 }
 
 // ##################
@@ -719,4 +1350,237 @@ mod tests {
         assert!(!is_valid_account_id(&[0, 1, 2]));
         assert!(is_valid_account_id(b"near"));
     }
+
+    #[test]
+    fn test_storage_usage_across_calls() {
+        let context = crate::test_utils::VMContextBuilder::new().build();
+        let base = context.storage_usage;
+        crate::testing_env!(context);
+        super::storage_write(b"key", b"value");
+        assert_eq!(super::storage_usage(), base + 3 + 5 + 40);
+
+        // The storage is carried over, and its usage is kept by setting it in the context.
+        crate::testing_env!(crate::test_utils::VMContextBuilder::new()
+            .storage_usage(super::storage_usage())
+            .build());
+        assert_eq!(super::storage_usage(), base + 3 + 5 + 40);
+        super::storage_remove(b"key");
+        assert_eq!(super::storage_usage(), base);
+    }
+
+    #[test]
+    fn test_protocol_config() {
+        use crate::test_utils::{VMConfigBuilder, VMContextBuilder};
+
+        crate::mock::set_storage_byte_cost(10u128.pow(18));
+        let config = VMConfigBuilder::new().max_gas_burnt(Gas::from_tgas(100)).build();
+        assert_eq!(config.limit_config.max_gas_burnt, Gas::from_tgas(100).0);
+        crate::testing_env!(VMContextBuilder::new().build(), config, Default::default());
+        assert_eq!(storage_byte_cost(), 10u128.pow(18));
+        assert_eq!(required_storage_deposit(100), 10u128.pow(20));
+    }
+
+    #[test]
+    #[should_panic(expected = "The required storage deposit overflows the balance")]
+    fn test_required_storage_deposit_overflow() {
+        crate::mock::set_storage_byte_cost(Balance::MAX / 2);
+        required_storage_deposit(3);
+    }
+
+    #[test]
+    #[should_panic(expected = "storage_write is not allowed in view calls")]
+    fn test_view_call_storage_write() {
+        crate::testing_env!(crate::test_utils::VMContextBuilder::new().view_call().build());
+        assert_eq!(storage_read(b"key"), None);
+        storage_write(b"key", b"value");
+    }
+
+    #[test]
+    #[should_panic(expected = "The random seed is not set")]
+    fn test_random_seed_not_set() {
+        crate::testing_env!(crate::test_utils::VMContextBuilder::new().build());
+        random_seed();
+    }
+
+    #[test]
+    #[should_panic(expected = "The random seed is not set")]
+    fn test_rng_seed_not_set() {
+        crate::testing_env!(crate::test_utils::VMContextBuilder::new().build());
+        rng();
+    }
+
+    #[test]
+    fn test_random_seeds() {
+        use crate::test_utils::{random_seeds, VMContextBuilder};
+
+        let mut seeds = Vec::new();
+        for seed in random_seeds(4) {
+            crate::testing_env!(VMContextBuilder::new().random_seed_array(seed).build());
+            assert_eq!(random_seed(), seed.to_vec());
+            seeds.push(seed);
+        }
+        seeds.dedup();
+        assert_eq!(seeds.len(), 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "attached_deposit is not allowed in view calls")]
+    fn test_view_call_attached_deposit() {
+        crate::testing_env!(crate::test_utils::VMContextBuilder::new().view_call().build());
+        attached_deposit();
+    }
+
+    #[test]
+    #[should_panic(expected = "ecrecover is not supported by the mocked blockchain")]
+    fn test_unsupported_ecrecover() {
+        ecrecover(&[0; 32], &Secp256k1Signature([0; 64]), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "ed25519_verify is not supported by the mocked blockchain")]
+    fn test_unsupported_ed25519_verify() {
+        ed25519_verify(&[0; 64], b"message", &[0; 32]);
+    }
+
+    #[test]
+    #[should_panic(expected = "alt_bn128_g1_multiexp is not supported by the mocked blockchain")]
+    fn test_unsupported_alt_bn128_g1_multiexp() {
+        alt_bn128_g1_multiexp(&[(G1Point::default(), Scalar::default())]);
+    }
+
+    #[test]
+    #[should_panic(expected = "alt_bn128_g1_sum is not supported by the mocked blockchain")]
+    fn test_unsupported_alt_bn128_g1_sum() {
+        alt_bn128_g1_sum(&[(false, G1Point::default())]);
+    }
+
+    #[test]
+    #[should_panic(expected = "alt_bn128_pairing_check is not supported by the mocked blockchain")]
+    fn test_unsupported_alt_bn128_pairing_check() {
+        alt_bn128_pairing_check(&[(G1Point::default(), G2Point::default())]);
+    }
+
+    #[test]
+    #[should_panic(expected = "bls12381_p1_sum is not supported by the mocked blockchain")]
+    fn test_unsupported_bls12381_p1_sum() {
+        bls12381_p1_sum(&[(false, bls12_381::G1Point([0; 96]))]);
+    }
+
+    #[test]
+    #[should_panic(expected = "bls12381_p2_sum is not supported by the mocked blockchain")]
+    fn test_unsupported_bls12381_p2_sum() {
+        bls12381_p2_sum(&[(false, bls12_381::G2Point([0; 192]))]);
+    }
+
+    #[test]
+    #[should_panic(expected = "bls12381_g1_multiexp is not supported by the mocked blockchain")]
+    fn test_unsupported_bls12381_g1_multiexp() {
+        bls12381_g1_multiexp(&[(bls12_381::G1Point([0; 96]), bls12_381::Scalar::default())]);
+    }
+
+    #[test]
+    #[should_panic(expected = "bls12381_g2_multiexp is not supported by the mocked blockchain")]
+    fn test_unsupported_bls12381_g2_multiexp() {
+        bls12381_g2_multiexp(&[(bls12_381::G2Point([0; 192]), bls12_381::Scalar::default())]);
+    }
+
+    #[test]
+    #[should_panic(expected = "bls12381_map_fp_to_g1 is not supported by the mocked blockchain")]
+    fn test_unsupported_bls12381_map_fp_to_g1() {
+        bls12381_map_fp_to_g1(&[bls12_381::Fp([0; 48])]);
+    }
+
+    #[test]
+    #[should_panic(expected = "bls12381_map_fp2_to_g2 is not supported by the mocked blockchain")]
+    fn test_unsupported_bls12381_map_fp2_to_g2() {
+        bls12381_map_fp2_to_g2(&[bls12_381::Fp2([0; 96])]);
+    }
+
+    #[test]
+    #[should_panic(expected = "bls12381_pairing_check is not supported by the mocked blockchain")]
+    fn test_unsupported_bls12381_pairing_check() {
+        bls12381_pairing_check(&[(bls12_381::G1Point([0; 96]), bls12_381::G2Point([0; 192]))]);
+    }
+
+    #[test]
+    #[should_panic(expected = "promise_yield_create is not supported by the mocked blockchain")]
+    fn test_unsupported_promise_yield_create() {
+        promise_yield_create("resume", b"{}", Gas(0), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "promise_yield_resume is not supported by the mocked blockchain")]
+    fn test_unsupported_promise_yield_resume() {
+        promise_yield_resume(&[0; 32], b"payload");
+    }
+
+    #[test]
+    #[should_panic(expected = "explicit guest panic")]
+    fn test_abort() {
+        abort();
+    }
+
+    #[test]
+    #[should_panic(expected = "deprecated panic")]
+    #[allow(deprecated)]
+    fn test_deprecated_panic() {
+        panic(b"deprecated panic");
+    }
+
+    #[test]
+    #[should_panic(expected = "InvalidPromiseIndex")]
+    fn test_mocked_host_function_error() {
+        promise_return(7);
+    }
+
+    #[test]
+    fn test_validator_stake() {
+        let mut validators = std::collections::HashMap::new();
+        validators.insert("alice.near".to_string(), 100);
+        validators.insert("bob.near".to_string(), 200);
+        crate::testing_env!(
+            crate::test_utils::VMContextBuilder::new().build(),
+            Default::default(),
+            Default::default(),
+            validators
+        );
+        assert_eq!(validator_stake(&"alice.near".parse().unwrap()), NearToken::from_yoctonear(100));
+        assert_eq!(validator_stake(&"carol.near".parse().unwrap()), NearToken::from_yoctonear(0));
+        assert_eq!(validator_total_stake(), NearToken::from_yoctonear(300));
+    }
+
+    fn returned_value() -> Vec<u8> {
+        match crate::mock::with_mocked_blockchain(|b| b.outcome().return_data) {
+            near_vm_logic::types::ReturnData::Value(value) => value,
+            other => panic!("expected a returned value, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_value_return_json() {
+        #[derive(serde::Serialize)]
+        struct Outcome {
+            memo: &'static str,
+            total: u64,
+        }
+
+        value_return_json(&Outcome { memo: "ok", total: 5 });
+        assert_eq!(returned_value(), br#"{"memo":"ok","total":5}"#.to_vec());
+    }
+
+    #[test]
+    fn test_value_return_borsh() {
+        value_return_borsh(&(5u32, "ok".to_string()));
+        assert_eq!(returned_value(), (5u32, "ok".to_string()).try_to_vec().unwrap());
+    }
+
+    #[test]
+    fn test_storage_write_batch() {
+        storage_write(b"a", b"0");
+        let buffer = b"a1b2";
+        let entries = buffer.chunks(2).map(|entry| entry.split_at(1));
+        assert_eq!(storage_write_batch(entries), 1);
+        assert_eq!(storage_read(b"a"), Some(b"1".to_vec()));
+        assert_eq!(storage_read(b"b"), Some(b"2".to_vec()));
+    }
 }
@@ -1,6 +1,9 @@
+use std::convert::TryInto;
 use std::mem::MaybeUninit;
 
-use crate::sys;
+use crate::{env, sys};
+
+const ERR_IDENTITY_LENGTH: &str = "Identity hasher input must be exactly 32 bytes";
 
 const ATOMIC_OP_REGISTER: u64 = u64::MAX - 2;
 
@@ -8,12 +11,34 @@ fn read_register_fixed(register_id: u64, buf: &mut [MaybeUninit<u8>]) {
     unsafe { sys::read_register(register_id, buf.as_ptr() as _) }
 }
 
+/// Pure Rust implementations of the hashes, which are used instead of the host functions with
+/// the `software-hash` feature, so that the hashers can be used off-chain without the mocked
+/// blockchain.
+#[cfg(all(feature = "software-hash", not(target_arch = "wasm32")))]
+mod software {
+    use sha3::Digest;
+
+    pub(super) fn hash<D, const N: usize>(ingest: &[u8]) -> [u8; N]
+    where
+        D: Digest,
+    {
+        let mut hash = [0u8; N];
+        hash.copy_from_slice(&D::digest(ingest));
+        hash
+    }
+}
+
 mod private {
     /// Seal `CryptoHasher` implementations to limit usage to the builtin implementations
     pub trait Sealed {}
 
     impl Sealed for super::Sha256 {}
     impl Sealed for super::Keccak256 {}
+    impl Sealed for super::Keccak512 {}
+    impl Sealed for super::Ripemd160 {}
+    #[cfg(feature = "sha3-hash")]
+    impl Sealed for super::Sha3_256 {}
+    impl Sealed for super::Identity {}
 }
 
 /// Cryptographic hashes that can be used within the SDK as a hashing function.
@@ -25,14 +50,20 @@ pub trait CryptoHasher: self::private::Sealed {
     fn hash(ingest: &[u8]) -> Self::Digest;
 }
 
-/// Sha256 hash helper which hashes through a syscall. This type satisfies the [`CryptoHasher`]
-/// trait.
+/// Sha256 hash helper which hashes through a syscall, or in Rust with the `software-hash`
+/// feature outside of Wasm. This type satisfies the [`CryptoHasher`] trait.
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum Sha256 {}
 
 impl CryptoHasher for Sha256 {
     type Digest = [u8; 32];
 
+    #[cfg(all(feature = "software-hash", not(target_arch = "wasm32")))]
+    fn hash(ingest: &[u8]) -> Self::Digest {
+        software::hash::<sha2::Sha256, 32>(ingest)
+    }
+
+    #[cfg(not(all(feature = "software-hash", not(target_arch = "wasm32"))))]
     fn hash(ingest: &[u8]) -> Self::Digest {
         unsafe { sys::sha256(ingest.len() as _, ingest.as_ptr() as _, ATOMIC_OP_REGISTER) };
 
@@ -42,14 +73,20 @@ impl CryptoHasher for Sha256 {
     }
 }
 
-/// Keccak256 hash helper which hashes through a syscall. This type satisfies the [`CryptoHasher`]
-/// trait.
+/// Keccak256 hash helper which hashes through a syscall, or in Rust with the `software-hash`
+/// feature outside of Wasm. This type satisfies the [`CryptoHasher`] trait.
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum Keccak256 {}
 
 impl CryptoHasher for Keccak256 {
     type Digest = [u8; 32];
 
+    #[cfg(all(feature = "software-hash", not(target_arch = "wasm32")))]
+    fn hash(ingest: &[u8]) -> Self::Digest {
+        software::hash::<sha3::Keccak256, 32>(ingest)
+    }
+
+    #[cfg(not(all(feature = "software-hash", not(target_arch = "wasm32"))))]
     fn hash(ingest: &[u8]) -> Self::Digest {
         unsafe { sys::keccak256(ingest.len() as _, ingest.as_ptr() as _, ATOMIC_OP_REGISTER) };
 
@@ -58,3 +95,121 @@ impl CryptoHasher for Keccak256 {
         unsafe { std::mem::transmute(hash) }
     }
 }
+
+/// Keccak512 hash helper which hashes through a syscall, or in Rust with the `software-hash`
+/// feature outside of Wasm. This type satisfies the [`CryptoHasher`] trait.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Keccak512 {}
+
+impl CryptoHasher for Keccak512 {
+    type Digest = [u8; 64];
+
+    #[cfg(all(feature = "software-hash", not(target_arch = "wasm32")))]
+    fn hash(ingest: &[u8]) -> Self::Digest {
+        software::hash::<sha3::Keccak512, 64>(ingest)
+    }
+
+    #[cfg(not(all(feature = "software-hash", not(target_arch = "wasm32"))))]
+    fn hash(ingest: &[u8]) -> Self::Digest {
+        unsafe { sys::keccak512(ingest.len() as _, ingest.as_ptr() as _, ATOMIC_OP_REGISTER) };
+
+        let mut hash = [MaybeUninit::uninit(); 64];
+        read_register_fixed(ATOMIC_OP_REGISTER, &mut hash);
+        unsafe { std::mem::transmute(hash) }
+    }
+}
+
+/// Ripemd160 hash helper which hashes through a syscall. This type satisfies the
+/// [`CryptoHasher`] trait.
+///
+/// The digest is 20 bytes long, as used for Bitcoin addresses, so this cannot be used as the
+/// hasher of collections, which need a 32 byte digest.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Ripemd160 {}
+
+impl CryptoHasher for Ripemd160 {
+    type Digest = [u8; 20];
+
+    fn hash(ingest: &[u8]) -> Self::Digest {
+        env::assert_supported_by_mock("ripemd160");
+        unsafe { sys::ripemd160(ingest.len() as _, ingest.as_ptr() as _, ATOMIC_OP_REGISTER) };
+
+        let mut hash = [MaybeUninit::uninit(); 20];
+        read_register_fixed(ATOMIC_OP_REGISTER, &mut hash);
+        unsafe { std::mem::transmute(hash) }
+    }
+}
+
+/// SHA3-256 hash helper. This type satisfies the [`CryptoHasher`] trait.
+///
+/// There is no host function for SHA3, so this hashes within the contract, which costs more gas
+/// and adds to the contract size. This is only available with the `sha3-hash` feature.
+#[cfg(feature = "sha3-hash")]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Sha3_256 {}
+
+#[cfg(feature = "sha3-hash")]
+impl CryptoHasher for Sha3_256 {
+    type Digest = [u8; 32];
+
+    fn hash(ingest: &[u8]) -> Self::Digest {
+        use sha3::Digest;
+
+        sha3::Sha3_256::digest(ingest).into()
+    }
+}
+
+/// Identity hash helper which passes 32 bytes of input through without hashing. This type
+/// satisfies the [`CryptoHasher`] trait.
+///
+/// This can be used for collections where the prefix and encoded key are already a 32 byte
+/// hash, to avoid paying the gas cost of hashing them again. The input length is checked when
+/// hashing, and any input which is not 32 bytes long will panic. To have the key length checked
+/// at compile time, use `LookupMap::prehashed` from the `store` module.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Identity {}
+
+impl CryptoHasher for Identity {
+    type Digest = [u8; 32];
+
+    fn hash(ingest: &[u8]) -> Self::Digest {
+        ingest.try_into().unwrap_or_else(|_| env::panic_str(ERR_IDENTITY_LENGTH))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keccak512_matches_env() {
+        assert_eq!(Keccak512::hash(b"near").to_vec(), env::keccak512(b"near"));
+    }
+
+    #[test]
+    #[should_panic(expected = "ripemd160 is not supported by the mocked blockchain")]
+    fn ripemd160_is_not_mocked() {
+        Ripemd160::hash(b"near");
+    }
+
+    #[cfg(feature = "software-hash")]
+    #[test]
+    fn software_hashes_match_host() {
+        assert_eq!(Sha256::hash(b"near").to_vec(), env::sha256(b"near"));
+        assert_eq!(Keccak256::hash(b"near").to_vec(), env::keccak256(b"near"));
+    }
+
+    #[cfg(feature = "sha3-hash")]
+    #[test]
+    fn sha3_256() {
+        assert_eq!(
+            Sha3_256::hash(b"abc"),
+            [
+                0x3a, 0x98, 0x5d, 0xa7, 0x4f, 0xe2, 0x25, 0xb2, 0x04, 0x5c, 0x17, 0x2d, 0x6b, 0xd3,
+                0x90, 0xbd, 0x85, 0x5f, 0x08, 0x6e, 0x3e, 0x9d, 0x52, 0x5b, 0x46, 0xbf, 0xe2, 0x45,
+                0x11, 0x43, 0x15, 0x32
+            ]
+        );
+    }
+}
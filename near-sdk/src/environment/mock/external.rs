@@ -15,6 +15,8 @@ pub(crate) struct SdkExternal {
     pub fake_trie: HashMap<Vec<u8>, Vec<u8>>,
     pub receipts: Vec<Receipt>,
     pub validators: HashMap<String, Balance>,
+    /// Current account, when the balances are tracked in the ledger.
+    pub ledger_account: Option<String>,
 }
 
 pub struct MockedValuePtr {
@@ -35,6 +37,17 @@ impl SdkExternal {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Moves the deposit of an action from the current account to the receiver of the receipt in
+    /// the ledger. The runtime checks the balance of the current account, which is the one in the
+    /// ledger, before the action is added.
+    fn move_deposit(&self, receipt_index: u64, deposit: Balance) {
+        if let Some(account_id) = &self.ledger_account {
+            let receiver_id = &self.receipts[receipt_index as usize].receiver_id;
+            super::ledger::transfer(account_id, receiver_id.as_str(), deposit)
+                .expect("deposit exceeds the balance in the ledger");
+        }
+    }
 }
 
 impl External for SdkExternal {
@@ -44,10 +57,8 @@ impl External for SdkExternal {
     }
 
     fn storage_get(&self, key: &[u8]) -> Result<Option<Box<dyn ValuePtr>>> {
-        Ok(self
-            .fake_trie
-            .get(key)
-            .map(|value| Box::new(MockedValuePtr { value: value.clone() }) as Box<_>))
+        Ok(super::faults::read(key, self.fake_trie.get(key))
+            .map(|value| Box::new(MockedValuePtr { value }) as Box<_>))
     }
 
     fn storage_remove(&mut self, key: &[u8]) -> Result<()> {
@@ -61,7 +72,7 @@ impl External for SdkExternal {
     }
 
     fn storage_has_key(&mut self, key: &[u8]) -> Result<bool> {
-        Ok(self.fake_trie.contains_key(key))
+        Ok(super::faults::read(key, self.fake_trie.get(key)).is_some())
     }
 
     fn create_receipt(&mut self, receipt_indices: Vec<u64>, receiver_id: String) -> Result<u64> {
@@ -103,6 +114,7 @@ impl External for SdkExternal {
         attached_deposit: u128,
         prepaid_gas: u64,
     ) -> Result<()> {
+        self.move_deposit(receipt_index, attached_deposit);
         self.receipts.get_mut(receipt_index as usize).unwrap().actions.push(
             VmAction::FunctionCall {
                 method_name: String::from_utf8(method_name)
@@ -117,6 +129,7 @@ impl External for SdkExternal {
     }
 
     fn append_action_transfer(&mut self, receipt_index: u64, amount: u128) -> Result<()> {
+        self.move_deposit(receipt_index, amount);
         self.receipts
             .get_mut(receipt_index as usize)
             .unwrap()
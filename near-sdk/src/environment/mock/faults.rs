@@ -0,0 +1,99 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use super::with_mocked_blockchain;
+
+/// Failure of the storage of the mocked blockchain, injected for a key with
+/// [`inject_storage_fault`] to exercise the code handling invalid or outdated state.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StorageFault {
+    /// Reads return the value with all of its bits flipped, which fails to deserialize for most
+    /// types.
+    Corrupt,
+    /// Reads behave as if the key was not in the storage.
+    Drop,
+    /// Reads return the value the key had when the fault was injected, ignoring later writes and
+    /// removals.
+    Stale,
+}
+
+/// Faults injected for the keys, with the value of the key when the fault was injected.
+type Faults = HashMap<Vec<u8>, (StorageFault, Option<Vec<u8>>)>;
+
+thread_local! {
+    static FAULTS: RefCell<Faults> = RefCell::new(HashMap::new());
+}
+
+/// Injects the fault for the key in the storage of the mocked blockchain. The fault applies to
+/// every read of the key, including the values returned by `env::storage_write` and
+/// `env::storage_remove`, while writes still change the storage. Like the ledger, the faults are
+/// kept when the blockchain is set up again with [`testing_env!`](crate::testing_env) until they
+/// are cleared with [`clear_storage_faults`]. This can only be used inside tests.
+///
+/// # Examples
+/// ```
+/// use near_sdk::env;
+/// use near_sdk::mock::{inject_storage_fault, StorageFault};
+///
+/// env::storage_write(b"key", b"old");
+/// inject_storage_fault(b"key", StorageFault::Stale);
+/// env::storage_write(b"key", b"new");
+/// assert_eq!(env::storage_read(b"key"), Some(b"old".to_vec()));
+/// ```
+pub fn inject_storage_fault(key: impl AsRef<[u8]>, fault: StorageFault) {
+    let key = key.as_ref();
+    let value = with_mocked_blockchain(|b| b.storage_value(key));
+    FAULTS.with(|f| f.borrow_mut().insert(key.to_vec(), (fault, value)));
+}
+
+/// Clears the faults injected with [`inject_storage_fault`]. This can only be used inside tests.
+pub fn clear_storage_faults() {
+    FAULTS.with(|f| f.borrow_mut().clear())
+}
+
+/// Returns the value read for the key, given the value in the storage.
+pub(crate) fn read(key: &[u8], value: Option<&Vec<u8>>) -> Option<Vec<u8>> {
+    FAULTS.with(|f| match f.borrow().get(key) {
+        None => value.cloned(),
+        Some((StorageFault::Corrupt, _)) => value.map(|value| value.iter().map(|b| !b).collect()),
+        Some((StorageFault::Drop, _)) => None,
+        Some((StorageFault::Stale, stale)) => stale.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::VMContextBuilder;
+    use crate::{assert_contract_panics, env, testing_env};
+
+    #[test]
+    fn corrupt_value() {
+        env::state_write(&true);
+        inject_storage_fault(b"STATE", StorageFault::Corrupt);
+        assert_eq!(env::storage_read(b"STATE"), Some(vec![0xfe]));
+        assert_contract_panics!(env::state_read::<bool>(), "Cannot deserialize the contract state");
+    }
+
+    #[test]
+    fn dropped_key() {
+        env::storage_write(b"key", b"value");
+        inject_storage_fault(b"key", StorageFault::Drop);
+        assert!(!env::storage_has_key(b"key"));
+        assert_eq!(env::storage_read(b"key"), None);
+
+        // Faults are kept for the next call, until they are cleared.
+        testing_env!(VMContextBuilder::new().build());
+        assert_eq!(env::storage_read(b"key"), None);
+        clear_storage_faults();
+        assert_eq!(env::storage_read(b"key"), Some(b"value".to_vec()));
+    }
+
+    #[test]
+    fn stale_value() {
+        inject_storage_fault(b"key", StorageFault::Stale);
+        env::storage_write(b"key", b"new");
+        assert_eq!(env::storage_read(b"key"), None);
+        assert!(!env::storage_has_key(b"key"));
+    }
+}
@@ -0,0 +1,118 @@
+use near_primitives_core::config::ExtCosts;
+use near_primitives_core::profile::ProfileData;
+
+use crate::Gas;
+
+const STORAGE_READ_COSTS: &[ExtCosts] = &[
+    ExtCosts::storage_read_base,
+    ExtCosts::storage_read_key_byte,
+    ExtCosts::storage_read_value_byte,
+    ExtCosts::storage_has_key_base,
+    ExtCosts::storage_has_key_byte,
+    ExtCosts::storage_iter_create_prefix_base,
+    ExtCosts::storage_iter_create_prefix_byte,
+    ExtCosts::storage_iter_create_range_base,
+    ExtCosts::storage_iter_create_from_byte,
+    ExtCosts::storage_iter_create_to_byte,
+    ExtCosts::storage_iter_next_base,
+    ExtCosts::storage_iter_next_key_byte,
+    ExtCosts::storage_iter_next_value_byte,
+];
+
+const STORAGE_WRITE_COSTS: &[ExtCosts] = &[
+    ExtCosts::storage_write_base,
+    ExtCosts::storage_write_key_byte,
+    ExtCosts::storage_write_value_byte,
+    ExtCosts::storage_write_evicted_byte,
+    ExtCosts::storage_remove_base,
+    ExtCosts::storage_remove_key_byte,
+    ExtCosts::storage_remove_ret_value_byte,
+];
+
+const HASHING_COSTS: &[ExtCosts] = &[
+    ExtCosts::sha256_base,
+    ExtCosts::sha256_byte,
+    ExtCosts::keccak256_base,
+    ExtCosts::keccak256_byte,
+    ExtCosts::keccak512_base,
+    ExtCosts::keccak512_byte,
+];
+
+const LOG_COSTS: &[ExtCosts] = &[ExtCosts::log_base, ExtCosts::log_byte];
+
+/// Gas burnt in the mocked blockchain, by category of host function, as returned by
+/// [`gas_used`](super::gas_used).
+///
+/// The categories do not cover every cost, such as the ones of reading registers or touching trie
+/// nodes, which are counted in `other`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GasProfile {
+    /// Total gas burnt.
+    pub total: Gas,
+    /// Gas burnt reading storage, including checking keys and iterating.
+    pub storage_read: Gas,
+    /// Gas burnt writing and removing storage values.
+    pub storage_write: Gas,
+    /// Gas burnt by the hash functions.
+    pub hashing: Gas,
+    /// Gas burnt by logs.
+    pub logs: Gas,
+    /// Gas burnt by the actions of the created promises.
+    pub actions: Gas,
+    /// Gas burnt by the other host functions.
+    pub other: Gas,
+}
+
+impl GasProfile {
+    pub(crate) fn new(profile: &ProfileData, total: u64) -> Self {
+        let sum = |costs: &[ExtCosts]| -> u64 {
+            costs.iter().map(|&cost| profile.get_ext_cost(cost as usize)).sum()
+        };
+        let storage_read = sum(STORAGE_READ_COSTS);
+        let storage_write = sum(STORAGE_WRITE_COSTS);
+        let hashing = sum(HASHING_COSTS);
+        let logs = sum(LOG_COSTS);
+        let actions = profile.action_gas();
+        let categorized = storage_read + storage_write + hashing + logs + actions;
+        Self {
+            total: Gas(total),
+            storage_read: Gas(storage_read),
+            storage_write: Gas(storage_write),
+            hashing: Gas(hashing),
+            logs: Gas(logs),
+            actions: Gas(actions),
+            other: Gas(total.saturating_sub(categorized)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mock::gas_used;
+    use crate::test_utils::VMContextBuilder;
+    use crate::{assert_gas_le, env, testing_env, Gas};
+
+    #[test]
+    fn gas_by_category() {
+        testing_env!(VMContextBuilder::new().build());
+        env::storage_write(b"key", b"value");
+        let after_write = gas_used();
+        assert!(after_write.storage_write > Gas(0));
+        assert_eq!(after_write.hashing, Gas(0));
+
+        assert_gas_le!(env::sha256(b"value"), Gas::from_tgas(1));
+        env::log_str("hashed");
+        let profile = gas_used();
+        assert!(profile.hashing > Gas(0));
+        assert!(profile.logs > Gas(0));
+        assert_eq!(profile.storage_write, after_write.storage_write);
+        assert!(profile.total >= profile.storage_write + profile.hashing + profile.logs);
+    }
+
+    #[test]
+    #[should_panic(expected = "more than the limit")]
+    fn gas_limit_exceeded() {
+        testing_env!(VMContextBuilder::new().build());
+        assert_gas_le!(env::storage_write(b"key", b"value"), Gas(1));
+    }
+}
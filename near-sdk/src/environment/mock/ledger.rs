@@ -0,0 +1,112 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use crate::Balance;
+
+thread_local! {
+    /// Balances of the accounts in tests, which are only tracked once one of them is set.
+    static BALANCES: RefCell<HashMap<String, Balance>> = RefCell::new(HashMap::new());
+}
+
+/// Sets the balance of the account in the ledger of the mocked blockchain, which tracks the
+/// balances of all accounts once one of them is set. From then on:
+/// - each call set up with [`testing_env!`](crate::testing_env) moves its attached deposit from the
+///   predecessor to the current account, and the balance of the current account in the context
+///   is the one in the ledger, if it was tracked before;
+/// - the transfers and the deposits of function calls in the receipts created by the contract are
+///   moved from the current account to the receivers of the receipts.
+///
+/// The ledger is kept for the rest of the test, unlike the rest of the blockchain, and it is not
+/// used by the [`MockedRuntime`](super::MockedRuntime), which keeps its own balances. This can
+/// only be used inside tests.
+pub fn set_balance(account_id: impl AsRef<str>, amount: Balance) {
+    BALANCES.with(|b| b.borrow_mut().insert(account_id.as_ref().to_string(), amount));
+}
+
+/// Returns the balance of the account in the ledger set up with [`set_balance`], or zero if the
+/// account is not tracked. This can only be used inside tests.
+pub fn balance_of(account_id: impl AsRef<str>) -> Balance {
+    BALANCES.with(|b| b.borrow().get(account_id.as_ref()).copied().unwrap_or_default())
+}
+
+/// Returns `true` if the balances are tracked.
+pub(crate) fn is_enabled() -> bool {
+    BALANCES.with(|b| !b.borrow().is_empty())
+}
+
+/// Returns the balance of the account, which starts to be tracked with `balance` if it was not.
+pub(crate) fn track(account_id: &str, balance: Balance) -> Balance {
+    BALANCES.with(|b| *b.borrow_mut().entry(account_id.to_string()).or_insert(balance))
+}
+
+/// Moves `amount` from one account to the other, or returns the balance of the sender if it is
+/// not enough.
+pub(crate) fn transfer(from: &str, to: &str, amount: Balance) -> Result<(), Balance> {
+    BALANCES.with(|b| {
+        let mut balances = b.borrow_mut();
+        let sender = balances.entry(from.to_string()).or_default();
+        *sender = sender.checked_sub(amount).ok_or(*sender)?;
+        *balances.entry(to.to_string()).or_default() += amount;
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::VMContextBuilder;
+    use crate::{env, testing_env, AccountId, Promise};
+
+    #[test]
+    fn deposits_and_transfers() {
+        let alice: AccountId = "alice.near".parse().unwrap();
+        let contract: AccountId = "contract.near".parse().unwrap();
+        set_balance(&alice, 100);
+        set_balance(&contract, 1_000);
+
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(contract.clone())
+            .predecessor_account_id(alice.clone())
+            .attached_deposit(40)
+            .build());
+        assert_eq!(env::account_balance(), 1_040);
+        Promise::new("bob.near".parse().unwrap()).transfer(300);
+
+        assert_eq!(balance_of(&alice), 60);
+        assert_eq!(balance_of(&contract), 740);
+        assert_eq!(balance_of("bob.near"), 300);
+    }
+
+    #[test]
+    fn deposit_is_moved_once() {
+        let alice: AccountId = "alice.near".parse().unwrap();
+        let contract: AccountId = "contract.near".parse().unwrap();
+        set_balance(&alice, 100);
+        set_balance(&contract, 1_000);
+
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(contract.clone())
+            .predecessor_account_id(alice.clone())
+            .attached_deposit(10)
+            .build());
+        assert_eq!(balance_of(&alice), 90);
+
+        crate::mock::advance_block(1);
+        crate::mock::advance_time(crate::mock::BLOCK_TIME);
+        crate::mock::set_epoch(3);
+        assert_eq!(balance_of(&alice), 90);
+        assert_eq!(balance_of(&contract), 1_010);
+        assert_eq!(env::attached_deposit(), 0);
+        assert_eq!(env::account_balance(), 1_010);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot attach 40, it has a balance of 10")]
+    fn insufficient_deposit() {
+        set_balance("alice.near", 10);
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id("alice.near".parse().unwrap())
+            .attached_deposit(40)
+            .build());
+    }
+}
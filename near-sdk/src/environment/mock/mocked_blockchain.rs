@@ -1,7 +1,8 @@
-use super::{Receipt, SdkExternal};
+use super::{ledger, GasProfile, Receipt, SdkExternal, Snapshot};
 use crate::test_utils::VMContextBuilder;
-use crate::types::{Balance, PromiseResult};
+use crate::types::{Balance, PromiseResult, StorageUsage};
 use crate::RuntimeFeesConfig;
+use near_primitives_core::profile::ProfileData;
 use near_vm_logic::mocks::mock_memory::MockedMemory;
 use near_vm_logic::types::PromiseResult as VmPromiseResult;
 use near_vm_logic::{External, MemoryLike, VMConfig, VMContext, VMLogic, VMOutcome};
@@ -18,6 +19,18 @@ pub struct MockedBlockchain {
     // We keep ownership over logic fixture so that references in `VMLogic` are valid.
     #[allow(dead_code)]
     logic_fixture: LogicFixture,
+    /// Gas spent by the host functions, shared with `VMLogic`.
+    profile: ProfileData,
+    /// Difference between the storage usage of the account and the usage tracked by `VMLogic`,
+    /// which changes when a snapshot of the storage is restored.
+    storage_usage_offset: i64,
+    /// Context the blockchain was set up with, before the usage of the storage is added to it.
+    context: VMContext,
+    /// Whether the context is a view call, in which the mutating host functions are not allowed.
+    is_view: bool,
+    /// Storage mutations performed by the `store` collections.
+    #[cfg(feature = "store-journal")]
+    store_journal: Vec<crate::store::journal::StorageMutation>,
 }
 
 impl Default for MockedBlockchain {
@@ -45,7 +58,7 @@ struct LogicFixture {
 
 impl MockedBlockchain {
     pub fn new(
-        context: VMContext,
+        mut context: VMContext,
         config: VMConfig,
         fees_config: RuntimeFeesConfig,
         promise_results: Vec<PromiseResult>,
@@ -53,7 +66,24 @@ impl MockedBlockchain {
         validators: HashMap<String, Balance>,
         memory_opt: Option<Box<dyn MemoryLike>>,
     ) -> Self {
+        let initial_context = context.clone();
+        let is_view = context.is_view;
         let mut ext = Box::new(SdkExternal::new());
+        if ledger::is_enabled() && !is_view {
+            let account_id = context.current_account_id.clone();
+            context.account_balance = ledger::track(&account_id, context.account_balance);
+            if let Err(balance) = ledger::transfer(
+                &context.predecessor_account_id,
+                &account_id,
+                context.attached_deposit,
+            ) {
+                panic!(
+                    "{} cannot attach {}, it has a balance of {}",
+                    context.predecessor_account_id, context.attached_deposit, balance
+                );
+            }
+            ext.ledger_account = Some(account_id);
+        }
         ext.fake_trie = storage;
         ext.validators = validators;
         let memory = memory_opt.unwrap_or_else(|| Box::new(MockedMemory {}));
@@ -62,6 +92,7 @@ impl MockedBlockchain {
         let fees_config = Box::new(fees_config);
 
         let mut logic_fixture = LogicFixture { ext, memory, promise_results, config, fees_config };
+        let profile = ProfileData::new_enabled();
 
         let logic = unsafe {
             VMLogic::new_with_protocol_version(
@@ -71,19 +102,81 @@ impl MockedBlockchain {
                 &*(logic_fixture.fees_config.as_mut() as *const RuntimeFeesConfig),
                 &*(logic_fixture.promise_results.as_ref().as_slice() as *const [VmPromiseResult]),
                 &mut *(logic_fixture.memory.as_mut() as *mut dyn MemoryLike),
-                Default::default(),
+                profile.clone(),
                 u32::MAX,
             )
         };
 
         let logic = RefCell::new(logic);
-        Self { logic, logic_fixture }
+        Self {
+            logic,
+            logic_fixture,
+            profile,
+            storage_usage_offset: 0,
+            context: initial_context,
+            is_view,
+            #[cfg(feature = "store-journal")]
+            store_journal: Vec::new(),
+        }
     }
 
     pub fn take_storage(&mut self) -> HashMap<Vec<u8>, Vec<u8>> {
         std::mem::take(&mut self.logic_fixture.ext.fake_trie)
     }
 
+    /// Returns the value of the key in the storage, without the injected faults.
+    pub(crate) fn storage_value(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.logic_fixture.ext.fake_trie.get(key).cloned()
+    }
+
+    /// Returns a copy of the storage, along with the storage usage of the account.
+    pub fn storage_snapshot(&self) -> Snapshot {
+        Snapshot {
+            storage: self.logic_fixture.ext.fake_trie.clone(),
+            storage_usage: self.storage_usage(),
+        }
+    }
+
+    /// Replaces the storage, and the storage usage of the account, with the `snapshot`.
+    pub fn restore(&mut self, snapshot: Snapshot) {
+        self.storage_usage_offset += snapshot.storage_usage as i64 - self.storage_usage() as i64;
+        self.logic_fixture.ext.fake_trie = snapshot.storage;
+    }
+
+    fn storage_usage(&self) -> StorageUsage {
+        let usage = self.logic.borrow_mut().storage_usage().unwrap();
+        (usage as i64 + self.storage_usage_offset) as StorageUsage
+    }
+
+    /// Sets up the blockchain for the next call, with the context updated by `f`. Like
+    /// [`testing_env!`](crate::testing_env), it keeps the storage, the configuration and the
+    /// validators, and starts with no logs, receipts or promise results. The storage usage and the
+    /// balance of the context are the ones at the end of the previous call, and no deposit is
+    /// attached, so that the deposit of the previous call is not moved again in the ledger.
+    pub(crate) fn next_call(&mut self, f: impl FnOnce(&mut VMContext)) {
+        let mut context = self.context.clone();
+        context.storage_usage = self.storage_usage();
+        context.account_balance = self.logic.borrow().clone_outcome().balance;
+        context.attached_deposit = 0;
+        f(&mut context);
+        let storage = self.take_storage();
+        let validators = std::mem::take(&mut self.logic_fixture.ext.validators);
+        *self = Self::new(
+            context,
+            (*self.logic_fixture.config).clone(),
+            (*self.logic_fixture.fees_config).clone(),
+            vec![],
+            storage,
+            validators,
+            None,
+        );
+    }
+
+    /// Returns `true` if the blockchain is set up for a view call.
+    pub fn is_view(&self) -> bool {
+        self.is_view
+    }
+
     pub fn created_receipts(&self) -> &Vec<Receipt> {
         &self.logic_fixture.ext.receipts
     }
@@ -91,6 +184,11 @@ impl MockedBlockchain {
         self.logic.borrow().clone_outcome()
     }
 
+    /// Returns the gas used since the blockchain was set up, by category.
+    pub fn gas_profile(&self) -> GasProfile {
+        GasProfile::new(&self.profile, self.logic.borrow().clone_outcome().burnt_gas)
+    }
+
     pub fn gas(&mut self, gas_amount: u32) {
         self.logic.borrow_mut().gas(gas_amount).unwrap()
     }
@@ -98,12 +196,29 @@ impl MockedBlockchain {
     pub fn logs(&self) -> Vec<String> {
         self.logic.borrow().clone_outcome().logs
     }
+
+    /// Returns the storage mutations performed by the `store` collections since the blockchain
+    /// was set up, and clears them.
+    #[cfg(feature = "store-journal")]
+    pub fn take_store_journal(&mut self) -> Vec<crate::store::journal::StorageMutation> {
+        std::mem::take(&mut self.store_journal)
+    }
+
+    #[cfg(feature = "store-journal")]
+    pub(crate) fn record_store_mutation(
+        &mut self,
+        mutation: crate::store::journal::StorageMutation,
+    ) {
+        self.store_journal.push(mutation);
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
 mod mock_chain {
     use near_vm_logic::{VMLogic, VMLogicError};
 
+    // The mocked host functions unwind with the errors of `VMLogic`, such as exceeding the gas
+    // limit, so that unit tests can catch them instead of the process aborting.
     fn with_mock_interface<F, R>(f: F) -> R
     where
         F: FnOnce(&mut VMLogic) -> Result<R, VMLogicError>,
@@ -112,109 +227,231 @@ mod mock_chain {
     }
 
     #[no_mangle]
-    extern "C" fn read_register(register_id: u64, ptr: u64) {
+    extern "C-unwind" fn read_register(register_id: u64, ptr: u64) {
         with_mock_interface(|b| b.read_register(register_id, ptr))
     }
     #[no_mangle]
-    extern "C" fn register_len(register_id: u64) -> u64 {
+    extern "C-unwind" fn register_len(register_id: u64) -> u64 {
         with_mock_interface(|b| b.register_len(register_id))
     }
     #[no_mangle]
-    extern "C" fn current_account_id(register_id: u64) {
+    extern "C-unwind" fn write_register(register_id: u64, data_len: u64, data_ptr: u64) {
+        with_mock_interface(|b| b.write_register(register_id, data_len, data_ptr))
+    }
+    #[no_mangle]
+    extern "C-unwind" fn current_account_id(register_id: u64) {
         with_mock_interface(|b| b.current_account_id(register_id))
     }
     #[no_mangle]
-    extern "C" fn signer_account_id(register_id: u64) {
+    extern "C-unwind" fn signer_account_id(register_id: u64) {
         with_mock_interface(|b| b.signer_account_id(register_id))
     }
     #[no_mangle]
-    extern "C" fn signer_account_pk(register_id: u64) {
+    extern "C-unwind" fn signer_account_pk(register_id: u64) {
         with_mock_interface(|b| b.signer_account_pk(register_id))
     }
     #[no_mangle]
-    extern "C" fn predecessor_account_id(register_id: u64) {
+    extern "C-unwind" fn predecessor_account_id(register_id: u64) {
         with_mock_interface(|b| b.predecessor_account_id(register_id))
     }
     #[no_mangle]
-    extern "C" fn input(register_id: u64) {
+    extern "C-unwind" fn input(register_id: u64) {
         with_mock_interface(|b| b.input(register_id))
     }
     #[no_mangle]
-    extern "C" fn block_index() -> u64 {
+    extern "C-unwind" fn block_index() -> u64 {
         with_mock_interface(|b| b.block_index())
     }
     #[no_mangle]
-    extern "C" fn block_timestamp() -> u64 {
+    extern "C-unwind" fn block_timestamp() -> u64 {
         with_mock_interface(|b| b.block_timestamp())
     }
     #[no_mangle]
-    extern "C" fn epoch_height() -> u64 {
+    extern "C-unwind" fn epoch_height() -> u64 {
         with_mock_interface(|b| b.epoch_height())
     }
     #[no_mangle]
-    extern "C" fn storage_usage() -> u64 {
-        with_mock_interface(|b| b.storage_usage())
+    extern "C-unwind" fn storage_usage() -> u64 {
+        crate::mock::with_mocked_blockchain(|b| b.storage_usage())
     }
     #[no_mangle]
-    extern "C" fn account_balance(balance_ptr: u64) {
+    extern "C-unwind" fn account_balance(balance_ptr: u64) {
         with_mock_interface(|b| b.account_balance(balance_ptr))
     }
     #[no_mangle]
-    extern "C" fn account_locked_balance(balance_ptr: u64) {
+    extern "C-unwind" fn account_locked_balance(balance_ptr: u64) {
         with_mock_interface(|b| b.account_locked_balance(balance_ptr))
     }
     #[no_mangle]
-    extern "C" fn attached_deposit(balance_ptr: u64) {
+    extern "C-unwind" fn attached_deposit(balance_ptr: u64) {
         with_mock_interface(|b| b.attached_deposit(balance_ptr))
     }
     #[no_mangle]
-    extern "C" fn prepaid_gas() -> u64 {
+    extern "C-unwind" fn prepaid_gas() -> u64 {
         with_mock_interface(|b| b.prepaid_gas())
     }
     #[no_mangle]
-    extern "C" fn used_gas() -> u64 {
+    extern "C-unwind" fn used_gas() -> u64 {
         with_mock_interface(|b| b.used_gas())
     }
     #[no_mangle]
-    extern "C" fn random_seed(register_id: u64) {
+    extern "C-unwind" fn random_seed(register_id: u64) {
         with_mock_interface(|b| b.random_seed(register_id))
     }
     #[no_mangle]
-    extern "C" fn sha256(value_len: u64, value_ptr: u64, register_id: u64) {
+    extern "C-unwind" fn sha256(value_len: u64, value_ptr: u64, register_id: u64) {
         with_mock_interface(|b| b.sha256(value_len, value_ptr, register_id))
     }
     #[no_mangle]
-    extern "C" fn keccak256(value_len: u64, value_ptr: u64, register_id: u64) {
+    extern "C-unwind" fn keccak256(value_len: u64, value_ptr: u64, register_id: u64) {
         with_mock_interface(|b| b.keccak256(value_len, value_ptr, register_id))
     }
     #[no_mangle]
-    extern "C" fn keccak512(value_len: u64, value_ptr: u64, register_id: u64) {
+    extern "C-unwind" fn keccak512(value_len: u64, value_ptr: u64, register_id: u64) {
         with_mock_interface(|b| b.keccak512(value_len, value_ptr, register_id))
     }
+    // The following functions are not supported by the version of the VM logic used for mocking.
+    // The `env` functions calling them panic before, with the name of the host function.
+    #[no_mangle]
+    extern "C-unwind" fn ecrecover(
+        _hash_len: u64,
+        _hash_ptr: u64,
+        _sig_len: u64,
+        _sig_ptr: u64,
+        _v: u64,
+        _malleability_flag: u64,
+        _register_id: u64,
+    ) -> u64 {
+        panic!("ecrecover is not supported by the mocked blockchain")
+    }
+    #[no_mangle]
+    extern "C-unwind" fn bls12381_p1_sum(
+        _value_len: u64,
+        _value_ptr: u64,
+        _register_id: u64,
+    ) -> u64 {
+        panic!("bls12381_p1_sum is not supported by the mocked blockchain")
+    }
+    #[no_mangle]
+    extern "C-unwind" fn bls12381_p2_sum(
+        _value_len: u64,
+        _value_ptr: u64,
+        _register_id: u64,
+    ) -> u64 {
+        panic!("bls12381_p2_sum is not supported by the mocked blockchain")
+    }
+    #[no_mangle]
+    extern "C-unwind" fn bls12381_g1_multiexp(
+        _value_len: u64,
+        _value_ptr: u64,
+        _register_id: u64,
+    ) -> u64 {
+        panic!("bls12381_g1_multiexp is not supported by the mocked blockchain")
+    }
+    #[no_mangle]
+    extern "C-unwind" fn bls12381_g2_multiexp(
+        _value_len: u64,
+        _value_ptr: u64,
+        _register_id: u64,
+    ) -> u64 {
+        panic!("bls12381_g2_multiexp is not supported by the mocked blockchain")
+    }
+    #[no_mangle]
+    extern "C-unwind" fn bls12381_map_fp_to_g1(
+        _value_len: u64,
+        _value_ptr: u64,
+        _register_id: u64,
+    ) -> u64 {
+        panic!("bls12381_map_fp_to_g1 is not supported by the mocked blockchain")
+    }
+    #[no_mangle]
+    extern "C-unwind" fn bls12381_map_fp2_to_g2(
+        _value_len: u64,
+        _value_ptr: u64,
+        _register_id: u64,
+    ) -> u64 {
+        panic!("bls12381_map_fp2_to_g2 is not supported by the mocked blockchain")
+    }
+    #[no_mangle]
+    extern "C-unwind" fn bls12381_pairing_check(_value_len: u64, _value_ptr: u64) -> u64 {
+        panic!("bls12381_pairing_check is not supported by the mocked blockchain")
+    }
+    #[no_mangle]
+    extern "C-unwind" fn promise_yield_create(
+        _function_name_len: u64,
+        _function_name_ptr: u64,
+        _arguments_len: u64,
+        _arguments_ptr: u64,
+        _gas: u64,
+        _gas_weight: u64,
+        _register_id: u64,
+    ) -> u64 {
+        panic!("promise_yield_create is not supported by the mocked blockchain")
+    }
+    #[no_mangle]
+    extern "C-unwind" fn promise_yield_resume(
+        _data_id_len: u64,
+        _data_id_ptr: u64,
+        _payload_len: u64,
+        _payload_ptr: u64,
+    ) -> u32 {
+        panic!("promise_yield_resume is not supported by the mocked blockchain")
+    }
+    #[no_mangle]
+    extern "C-unwind" fn ripemd160(_value_len: u64, _value_ptr: u64, _register_id: u64) {
+        panic!("ripemd160 is not supported by the mocked blockchain")
+    }
+    #[no_mangle]
+    extern "C-unwind" fn ed25519_verify(
+        _sig_len: u64,
+        _sig_ptr: u64,
+        _msg_len: u64,
+        _msg_ptr: u64,
+        _pub_key_len: u64,
+        _pub_key_ptr: u64,
+    ) -> u64 {
+        panic!("ed25519_verify is not supported by the mocked blockchain")
+    }
+    #[no_mangle]
+    extern "C-unwind" fn alt_bn128_g1_multiexp(
+        _value_len: u64,
+        _value_ptr: u64,
+        _register_id: u64,
+    ) {
+        panic!("alt_bn128_g1_multiexp is not supported by the mocked blockchain")
+    }
+    #[no_mangle]
+    extern "C-unwind" fn alt_bn128_g1_sum(_value_len: u64, _value_ptr: u64, _register_id: u64) {
+        panic!("alt_bn128_g1_sum is not supported by the mocked blockchain")
+    }
+    #[no_mangle]
+    extern "C-unwind" fn alt_bn128_pairing_check(_value_len: u64, _value_ptr: u64) -> u64 {
+        panic!("alt_bn128_pairing_check is not supported by the mocked blockchain")
+    }
     #[no_mangle]
-    extern "C" fn value_return(value_len: u64, value_ptr: u64) {
+    extern "C-unwind" fn value_return(value_len: u64, value_ptr: u64) {
         with_mock_interface(|b| b.value_return(value_len, value_ptr))
     }
     #[no_mangle]
-    extern "C" fn panic() -> ! {
+    extern "C-unwind" fn panic() -> ! {
         with_mock_interface(|b| b.panic());
         unreachable!()
     }
     #[no_mangle]
-    extern "C" fn panic_utf8(len: u64, ptr: u64) -> ! {
+    extern "C-unwind" fn panic_utf8(len: u64, ptr: u64) -> ! {
         with_mock_interface(|b| b.panic_utf8(len, ptr));
         unreachable!()
     }
     #[no_mangle]
-    extern "C" fn log_utf8(len: u64, ptr: u64) {
+    extern "C-unwind" fn log_utf8(len: u64, ptr: u64) {
         with_mock_interface(|b| b.log_utf8(len, ptr))
     }
     #[no_mangle]
-    extern "C" fn log_utf16(len: u64, ptr: u64) {
+    extern "C-unwind" fn log_utf16(len: u64, ptr: u64) {
         with_mock_interface(|b| b.log_utf16(len, ptr))
     }
     #[no_mangle]
-    extern "C" fn promise_create(
+    extern "C-unwind" fn promise_create(
         account_id_len: u64,
         account_id_ptr: u64,
         method_name_len: u64,
@@ -238,7 +475,7 @@ mod mock_chain {
         })
     }
     #[no_mangle]
-    extern "C" fn promise_then(
+    extern "C-unwind" fn promise_then(
         promise_index: u64,
         account_id_len: u64,
         account_id_ptr: u64,
@@ -264,15 +501,15 @@ mod mock_chain {
         })
     }
     #[no_mangle]
-    extern "C" fn promise_and(promise_idx_ptr: u64, promise_idx_count: u64) -> u64 {
+    extern "C-unwind" fn promise_and(promise_idx_ptr: u64, promise_idx_count: u64) -> u64 {
         with_mock_interface(|b| b.promise_and(promise_idx_ptr, promise_idx_count))
     }
     #[no_mangle]
-    extern "C" fn promise_batch_create(account_id_len: u64, account_id_ptr: u64) -> u64 {
+    extern "C-unwind" fn promise_batch_create(account_id_len: u64, account_id_ptr: u64) -> u64 {
         with_mock_interface(|b| b.promise_batch_create(account_id_len, account_id_ptr))
     }
     #[no_mangle]
-    extern "C" fn promise_batch_then(
+    extern "C-unwind" fn promise_batch_then(
         promise_index: u64,
         account_id_len: u64,
         account_id_ptr: u64,
@@ -280,11 +517,11 @@ mod mock_chain {
         with_mock_interface(|b| b.promise_batch_then(promise_index, account_id_len, account_id_ptr))
     }
     #[no_mangle]
-    extern "C" fn promise_batch_action_create_account(promise_index: u64) {
+    extern "C-unwind" fn promise_batch_action_create_account(promise_index: u64) {
         with_mock_interface(|b| b.promise_batch_action_create_account(promise_index))
     }
     #[no_mangle]
-    extern "C" fn promise_batch_action_deploy_contract(
+    extern "C-unwind" fn promise_batch_action_deploy_contract(
         promise_index: u64,
         code_len: u64,
         code_ptr: u64,
@@ -294,7 +531,7 @@ mod mock_chain {
         })
     }
     #[no_mangle]
-    extern "C" fn promise_batch_action_function_call(
+    extern "C-unwind" fn promise_batch_action_function_call(
         promise_index: u64,
         method_name_len: u64,
         method_name_ptr: u64,
@@ -316,11 +553,11 @@ mod mock_chain {
         })
     }
     #[no_mangle]
-    extern "C" fn promise_batch_action_transfer(promise_index: u64, amount_ptr: u64) {
+    extern "C-unwind" fn promise_batch_action_transfer(promise_index: u64, amount_ptr: u64) {
         with_mock_interface(|b| b.promise_batch_action_transfer(promise_index, amount_ptr))
     }
     #[no_mangle]
-    extern "C" fn promise_batch_action_stake(
+    extern "C-unwind" fn promise_batch_action_stake(
         promise_index: u64,
         amount_ptr: u64,
         public_key_len: u64,
@@ -331,7 +568,7 @@ mod mock_chain {
         })
     }
     #[no_mangle]
-    extern "C" fn promise_batch_action_add_key_with_full_access(
+    extern "C-unwind" fn promise_batch_action_add_key_with_full_access(
         promise_index: u64,
         public_key_len: u64,
         public_key_ptr: u64,
@@ -347,7 +584,7 @@ mod mock_chain {
         })
     }
     #[no_mangle]
-    extern "C" fn promise_batch_action_add_key_with_function_call(
+    extern "C-unwind" fn promise_batch_action_add_key_with_function_call(
         promise_index: u64,
         public_key_len: u64,
         public_key_ptr: u64,
@@ -373,7 +610,7 @@ mod mock_chain {
         })
     }
     #[no_mangle]
-    extern "C" fn promise_batch_action_delete_key(
+    extern "C-unwind" fn promise_batch_action_delete_key(
         promise_index: u64,
         public_key_len: u64,
         public_key_ptr: u64,
@@ -383,7 +620,7 @@ mod mock_chain {
         })
     }
     #[no_mangle]
-    extern "C" fn promise_batch_action_delete_account(
+    extern "C-unwind" fn promise_batch_action_delete_account(
         promise_index: u64,
         beneficiary_id_len: u64,
         beneficiary_id_ptr: u64,
@@ -397,19 +634,19 @@ mod mock_chain {
         })
     }
     #[no_mangle]
-    extern "C" fn promise_results_count() -> u64 {
+    extern "C-unwind" fn promise_results_count() -> u64 {
         with_mock_interface(|b| b.promise_results_count())
     }
     #[no_mangle]
-    extern "C" fn promise_result(result_idx: u64, register_id: u64) -> u64 {
+    extern "C-unwind" fn promise_result(result_idx: u64, register_id: u64) -> u64 {
         with_mock_interface(|b| b.promise_result(result_idx, register_id))
     }
     #[no_mangle]
-    extern "C" fn promise_return(promise_id: u64) {
+    extern "C-unwind" fn promise_return(promise_id: u64) {
         with_mock_interface(|b| b.promise_return(promise_id))
     }
     #[no_mangle]
-    extern "C" fn storage_write(
+    extern "C-unwind" fn storage_write(
         key_len: u64,
         key_ptr: u64,
         value_len: u64,
@@ -421,23 +658,23 @@ mod mock_chain {
         })
     }
     #[no_mangle]
-    extern "C" fn storage_read(key_len: u64, key_ptr: u64, register_id: u64) -> u64 {
+    extern "C-unwind" fn storage_read(key_len: u64, key_ptr: u64, register_id: u64) -> u64 {
         with_mock_interface(|b| b.storage_read(key_len, key_ptr, register_id))
     }
     #[no_mangle]
-    extern "C" fn storage_remove(key_len: u64, key_ptr: u64, register_id: u64) -> u64 {
+    extern "C-unwind" fn storage_remove(key_len: u64, key_ptr: u64, register_id: u64) -> u64 {
         with_mock_interface(|b| b.storage_remove(key_len, key_ptr, register_id))
     }
     #[no_mangle]
-    extern "C" fn storage_has_key(key_len: u64, key_ptr: u64) -> u64 {
+    extern "C-unwind" fn storage_has_key(key_len: u64, key_ptr: u64) -> u64 {
         with_mock_interface(|b| b.storage_has_key(key_len, key_ptr))
     }
     #[no_mangle]
-    extern "C" fn validator_stake(account_id_len: u64, account_id_ptr: u64, stake_ptr: u64) {
+    extern "C-unwind" fn validator_stake(account_id_len: u64, account_id_ptr: u64, stake_ptr: u64) {
         with_mock_interface(|b| b.validator_stake(account_id_len, account_id_ptr, stake_ptr))
     }
     #[no_mangle]
-    extern "C" fn validator_total_stake(stake_ptr: u64) {
+    extern "C-unwind" fn validator_total_stake(stake_ptr: u64) {
         with_mock_interface(|b| b.validator_total_stake(stake_ptr))
     }
 }
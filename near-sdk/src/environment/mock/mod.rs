@@ -1,17 +1,38 @@
 mod external;
+mod faults;
+mod gas_profile;
+mod ledger;
 mod mocked_blockchain;
 mod receipt;
+mod runtime;
+mod snapshot;
 
 pub(crate) use self::external::SdkExternal;
+pub use self::faults::{clear_storage_faults, inject_storage_fault, StorageFault};
+pub use self::gas_profile::GasProfile;
+pub use self::ledger::{balance_of, set_balance};
 pub use self::mocked_blockchain::MockedBlockchain;
 pub use self::receipt::{Receipt, VmAction};
-use core::cell::RefCell;
+pub use self::runtime::{CallOutcome, ExecutionOutcome, MockedRuntime, ReceiptOrder};
+pub use self::snapshot::Snapshot;
+use crate::{Balance, EpochHeight};
+use core::cell::{Cell, RefCell};
+use core::time::Duration;
+
+/// Time between blocks used by [`advance_block`] and [`advance_time`], which is about the time
+/// between blocks on mainnet.
+pub const BLOCK_TIME: Duration = Duration::from_secs(1);
+/// Number of blocks in an epoch used by [`advance_block`] and [`advance_time`], as on mainnet.
+pub const EPOCH_LENGTH: u64 = 43_200;
 
 thread_local! {
     /// Low-level blockchain interface wrapped by the environment. Prefer using `env::*` and
     /// `testing_env` for interacting with the real and fake blockchains.
     static BLOCKCHAIN_INTERFACE: RefCell<MockedBlockchain>
          = RefCell::new(MockedBlockchain::default());
+    /// Cost of a byte of storage returned by `env::storage_byte_cost` in tests.
+    static STORAGE_BYTE_COST: Cell<Balance>
+        = const { Cell::new(crate::env::STORAGE_PRICE_PER_BYTE) };
 }
 
 /// Perform function on a mutable reference to the [`MockedBlockchain`]. This can only be used
@@ -22,3 +43,106 @@ where
 {
     BLOCKCHAIN_INTERFACE.with(|b| f(&mut b.borrow_mut()))
 }
+
+/// Returns the gas burnt in the [`MockedBlockchain`] since it was set up with
+/// [`testing_env!`](crate::testing_env), by category of host function. This can only be used
+/// inside tests.
+pub fn gas_used() -> GasProfile {
+    with_mocked_blockchain(|b| b.gas_profile())
+}
+
+/// Returns the receipts created by the contract in the [`MockedBlockchain`] since it was set up
+/// with [`testing_env!`](crate::testing_env), in the order they were created. This can only be
+/// used inside tests.
+pub fn created_receipts() -> Vec<Receipt> {
+    with_mocked_blockchain(|b| b.created_receipts().clone())
+}
+
+/// Returns a copy of the contract storage in the [`MockedBlockchain`], which can be brought back
+/// with [`restore`]. This can only be used inside tests.
+pub fn storage_snapshot() -> Snapshot {
+    with_mocked_blockchain(|b| b.storage_snapshot())
+}
+
+/// Replaces the contract storage in the [`MockedBlockchain`] with the `snapshot`, along with the
+/// storage usage of the account. The rest of the environment, such as the context, the logs and
+/// the gas used, is left as it is. This can only be used inside tests.
+pub fn restore(snapshot: Snapshot) {
+    with_mocked_blockchain(|b| b.restore(snapshot))
+}
+
+/// Sets the cost of a byte of storage returned by [`env::storage_byte_cost`] for the rest of the
+/// test, to test the contract against another protocol configuration. Unlike the rest of the
+/// configuration, it is not reset by [`testing_env!`](crate::testing_env). This can only be used
+/// inside tests.
+///
+/// [`env::storage_byte_cost`]: crate::env::storage_byte_cost
+pub fn set_storage_byte_cost(cost: Balance) {
+    STORAGE_BYTE_COST.with(|c| c.set(cost))
+}
+
+pub(crate) fn storage_byte_cost() -> Balance {
+    STORAGE_BYTE_COST.with(|c| c.get())
+}
+
+/// Moves the [`MockedBlockchain`] `blocks` blocks forward, along with the block timestamp, by
+/// [`BLOCK_TIME`] per block, and the epoch height, by one every [`EPOCH_LENGTH`] blocks. Like
+/// [`testing_env!`](crate::testing_env), this starts a new call: the storage and the balance are
+/// kept, while the logs, receipts, promise results and attached deposit are cleared. This can
+/// only be used inside tests.
+pub fn advance_block(blocks: u64) {
+    with_mocked_blockchain(|b| {
+        b.next_call(|context| {
+            let epochs = (context.block_index % EPOCH_LENGTH + blocks) / EPOCH_LENGTH;
+            context.block_index += blocks;
+            context.block_timestamp += blocks * BLOCK_TIME.as_nanos() as u64;
+            context.epoch_height += epochs;
+        })
+    })
+}
+
+/// Moves the timestamp of the [`MockedBlockchain`] forward by `duration`, along with the block
+/// height, by a block every [`BLOCK_TIME`], and the epoch height, by one every [`EPOCH_LENGTH`]
+/// blocks. This starts a new call like [`advance_block`]. This can only be used inside tests.
+pub fn advance_time(duration: Duration) {
+    with_mocked_blockchain(|b| {
+        b.next_call(|context| {
+            let blocks = (duration.as_nanos() / BLOCK_TIME.as_nanos()) as u64;
+            let epochs = (context.block_index % EPOCH_LENGTH + blocks) / EPOCH_LENGTH;
+            context.block_index += blocks;
+            context.block_timestamp += duration.as_nanos() as u64;
+            context.epoch_height += epochs;
+        })
+    })
+}
+
+/// Sets the epoch height of the [`MockedBlockchain`], leaving the block height and timestamp as
+/// they are. This starts a new call like [`advance_block`]. This can only be used inside tests.
+pub fn set_epoch(epoch_height: EpochHeight) {
+    with_mocked_blockchain(|b| b.next_call(|context| context.epoch_height = epoch_height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::env;
+
+    #[test]
+    fn time_progression() {
+        env::storage_write(b"key", b"value");
+        let start = env::block_timestamp();
+
+        advance_block(EPOCH_LENGTH - 1);
+        assert_eq!(env::block_height(), EPOCH_LENGTH - 1);
+        assert_eq!(env::epoch_height(), 0);
+        advance_time(BLOCK_TIME * 2);
+        assert_eq!(env::block_height(), EPOCH_LENGTH + 1);
+        assert_eq!(env::block_timestamp() - start, (EPOCH_LENGTH + 1) * 1_000_000_000);
+        assert_eq!(env::epoch_height(), 1);
+
+        set_epoch(10);
+        assert_eq!(env::epoch_height(), 10);
+        assert_eq!(env::block_height(), EPOCH_LENGTH + 1);
+        assert_eq!(env::storage_read(b"key"), Some(b"value".to_vec()));
+    }
+}
@@ -1,12 +1,31 @@
 use crate::{AccountId, Balance, Gas, PublicKey};
 
+/// Receipt created by a contract in the mocked blockchain, as returned by
+/// [`created_receipts`](super::created_receipts).
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Receipt {
+    /// Indices of the receipts, in the order they were created, which have to be executed before
+    /// this receipt, such as the promises this receipt is a callback of.
     pub receipt_indices: Vec<u64>,
     pub receiver_id: AccountId,
     pub actions: Vec<VmAction>,
 }
 
+impl Receipt {
+    /// Returns the names of the methods called by the function call actions of the receipt.
+    pub fn method_names(&self) -> Vec<&str> {
+        self.actions
+            .iter()
+            .filter_map(|action| match action {
+                VmAction::FunctionCall { method_name, .. } => Some(method_name.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Action of a [`Receipt`].
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[non_exhaustive]
 pub enum VmAction {
@@ -45,3 +64,74 @@ pub enum VmAction {
         beneficiary_id: AccountId,
     },
 }
+
+#[cfg(test)]
+mod tests {
+    use super::VmAction;
+    use crate::mock::created_receipts;
+    use crate::test_utils::VMContextBuilder;
+    use crate::{testing_env, AccessKeyAction, Gas, Promise, PublicKey};
+
+    #[test]
+    fn inspect_created_receipts() {
+        testing_env!(VMContextBuilder::new().build());
+        Promise::new("bob.near".parse().unwrap())
+            .transfer(1)
+            .function_call("ping".to_string(), b"{}".to_vec(), 2, Gas::from_tgas(5))
+            .then(Promise::new("alice.near".parse().unwrap()).function_call(
+                "on_ping".to_string(),
+                vec![],
+                0,
+                Gas::from_tgas(5),
+            ));
+
+        let receipts = created_receipts();
+        assert_eq!(receipts.len(), 2);
+        assert_eq!(receipts[0].receiver_id.as_str(), "bob.near");
+        assert_eq!(receipts[0].method_names(), ["ping"]);
+        assert_eq!(receipts[0].actions[0], VmAction::Transfer { deposit: 1 });
+        assert_eq!(
+            receipts[0].actions[1],
+            VmAction::FunctionCall {
+                method_name: "ping".to_string(),
+                args: b"{}".to_vec(),
+                gas: Gas::from_tgas(5),
+                deposit: 2
+            }
+        );
+        assert_eq!(receipts[1].receipt_indices, [0]);
+        assert_eq!(receipts[1].method_names(), ["on_ping"]);
+    }
+
+    #[test]
+    fn access_key_actions() {
+        testing_env!(VMContextBuilder::new().build());
+        let key: PublicKey =
+            "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp".parse().unwrap();
+        let add =
+            AccessKeyAction::add_function_call_key(key.clone(), "alice.near".parse().unwrap())
+                .with_nonce(3)
+                .with_allowance(250)
+                .with_method_names(["vote", "claim"]);
+        Promise::new("alice.near".parse().unwrap())
+            .access_key_action(AccessKeyAction::delete_key(key.clone()))
+            .access_key_action(add)
+            .access_key_action(AccessKeyAction::add_full_access_key(key.clone()));
+
+        let receipts = created_receipts();
+        assert_eq!(
+            receipts[0].actions,
+            [
+                VmAction::DeleteKey { public_key: key.clone() },
+                VmAction::AddKeyWithFunctionCall {
+                    public_key: key.clone(),
+                    nonce: 3,
+                    allowance: Some(250),
+                    receiver_id: "alice.near".parse().unwrap(),
+                    method_names: vec!["vote".to_string(), "claim".to_string()],
+                },
+                VmAction::AddKeyWithFullAccess { public_key: key, nonce: 0 },
+            ]
+        );
+    }
+}
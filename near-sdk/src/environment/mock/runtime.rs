@@ -0,0 +1,671 @@
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+
+use borsh::BorshDeserialize;
+use near_vm_logic::types::ReturnData;
+
+use super::{with_mocked_blockchain, MockedBlockchain, VmAction};
+use crate::test_utils::VMContextBuilder;
+use crate::{env, AccountId, Balance, Gas, PromiseResult, StorageUsage};
+
+/// Gas attached to the calls made with [`MockedRuntime::call`].
+const CALL_GAS: Gas = Gas::from_tgas(300);
+
+/// Handler of the function calls to a contract, which is called with the method name and behaves
+/// like the methods exported by `#[near_bindgen]`: it reads the arguments from [`env::input`],
+/// the state from [`env::state_read`] and returns the result with [`env::value_return`].
+type ContractHandler = Box<dyn FnMut(&str)>;
+
+/// Mocked runtime which executes cross-contract calls between several contracts in unit tests.
+///
+/// Each contract is registered under its account ID with a handler which dispatches the method
+/// calls to the contract. When a method creates promises, the runtime executes the resulting
+/// receipts in order, passing the results of promises to their callbacks, until none are left.
+/// Each account has its own storage and balance. The deposit of a call is taken from the signer,
+/// and the actions of a receipt are applied together: when one of them fails, the changes of the
+/// receipt are discarded and its deposits are refunded to the predecessor.
+///
+/// The receipts ready to be executed are picked in the order they were created by default, which
+/// can be changed with [`set_receipt_order`](Self::set_receipt_order) to test the assumptions of
+/// the contract about the order of the receipts, and calls by third parties can be interleaved
+/// with the receipts with [`schedule_call`](Self::schedule_call).
+///
+/// Only function calls and transfers are simulated. The other actions are ignored, and gas is
+/// not accounted for. The runtime sets up the [`MockedBlockchain`] for each function call, so
+/// it replaces the one set up by [`testing_env!`](crate::testing_env).
+///
+/// # Examples
+/// ```
+/// use near_sdk::mock::MockedRuntime;
+/// use near_sdk::{env, AccountId, Gas, Promise, PromiseResult};
+///
+/// let mut runtime = MockedRuntime::new();
+/// let echo: AccountId = "echo.near".parse().unwrap();
+/// let caller: AccountId = "caller.near".parse().unwrap();
+/// runtime.add_contract(echo.clone(), |_method| {
+///     env::value_return(&env::input().unwrap());
+/// });
+/// runtime.add_contract(caller.clone(), |method| match method {
+///     "call" => {
+///         let echo: AccountId = "echo.near".parse().unwrap();
+///         Promise::new(echo)
+///             .function_call("echo".to_string(), b"hi".to_vec(), 0, Gas::from_tgas(10))
+///             .then(Promise::new(env::current_account_id()).function_call(
+///                 "callback".to_string(),
+///                 vec![],
+///                 0,
+///                 Gas::from_tgas(10),
+///             ))
+///             .as_return();
+///     }
+///     "callback" => match env::promise_result(0) {
+///         PromiseResult::Successful(value) => env::value_return(&value),
+///         _ => env::panic_str("echo failed"),
+///     },
+///     _ => env::panic_str("unknown method"),
+/// });
+///
+/// let outcome = runtime.call(&caller, &caller, "call", vec![], 0);
+/// assert_eq!(outcome.result, PromiseResult::Successful(b"hi".to_vec()));
+/// assert_eq!(outcome.executions.len(), 3);
+/// ```
+pub struct MockedRuntime {
+    contracts: HashMap<AccountId, ContractHandler>,
+    accounts: HashMap<AccountId, MockedAccount>,
+    block_height: u64,
+    receipt_order: ReceiptOrder,
+    /// State of the generator picking the receipts in the [`ReceiptOrder::Shuffled`] order.
+    shuffle_state: u64,
+    /// Calls by third parties to execute along with the receipts of the next call.
+    scheduled: Vec<PendingReceipt>,
+}
+
+/// Order in which the [`MockedRuntime`] executes the receipts which are ready, that is whose
+/// dependencies have been executed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReceiptOrder {
+    /// The receipts are executed in the order they were created.
+    #[default]
+    Fifo,
+    /// The receipts created last are executed first.
+    Lifo,
+    /// The callbacks, which are the receipts depending on other receipts, are executed once no
+    /// other receipt is ready.
+    DelayCallbacks,
+    /// The receipts are picked at random with the seed, in the same order in every run.
+    Shuffled(u64),
+}
+
+/// Balance and storage of an account of the [`MockedRuntime`].
+#[derive(Clone, Default)]
+struct MockedAccount {
+    balance: Balance,
+    storage_usage: StorageUsage,
+    storage: HashMap<Vec<u8>, Vec<u8>>,
+}
+
+/// Outcome of a call executed by the [`MockedRuntime`], including all the receipts it created.
+#[derive(Debug, PartialEq)]
+pub struct CallOutcome {
+    /// Result of the call, which is the result of the promise it returned, if any.
+    pub result: PromiseResult,
+    /// Executions of the call and the receipts it created, in the order they were executed.
+    pub executions: Vec<ExecutionOutcome>,
+}
+
+/// Outcome of the execution of a single receipt by the [`MockedRuntime`].
+#[derive(Debug, PartialEq)]
+pub struct ExecutionOutcome {
+    pub predecessor_id: AccountId,
+    pub receiver_id: AccountId,
+    /// Result of the receipt, which is the result of the promise it returned, if any.
+    pub result: PromiseResult,
+    pub logs: Vec<String>,
+}
+
+/// Receipt waiting for the receipts it depends on to be executed.
+struct PendingReceipt {
+    id: usize,
+    predecessor_id: AccountId,
+    signer_id: AccountId,
+    receiver_id: AccountId,
+    actions: Vec<VmAction>,
+    dependencies: Vec<usize>,
+}
+
+enum Resolution {
+    Done(PromiseResult),
+    /// The result is the one of another receipt, which the method returned as a promise.
+    Forward(usize),
+}
+
+impl Default for MockedRuntime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockedRuntime {
+    pub fn new() -> Self {
+        Self {
+            contracts: HashMap::new(),
+            accounts: HashMap::new(),
+            block_height: 0,
+            receipt_order: ReceiptOrder::default(),
+            shuffle_state: 0,
+            scheduled: Vec::new(),
+        }
+    }
+
+    /// Sets the order in which the receipts which are ready are executed.
+    pub fn set_receipt_order(&mut self, order: ReceiptOrder) -> &mut Self {
+        if let ReceiptOrder::Shuffled(seed) = order {
+            self.shuffle_state = seed;
+        }
+        self.receipt_order = order;
+        self
+    }
+
+    /// Schedules a call to the method of the contract by a third party, which is executed along
+    /// with the receipts of the next [`call`](Self::call), as if it was submitted in the meantime.
+    /// It is ready as soon as the call starts, and its execution is part of the outcome of the
+    /// call.
+    pub fn schedule_call(
+        &mut self,
+        signer_id: &AccountId,
+        receiver_id: &AccountId,
+        method_name: &str,
+        args: Vec<u8>,
+        deposit: Balance,
+    ) -> &mut Self {
+        self.scheduled.push(function_call_receipt(
+            signer_id,
+            receiver_id,
+            method_name,
+            args,
+            deposit,
+        ));
+        self
+    }
+
+    /// Registers the contract deployed to the account, with the handler of its function calls.
+    /// The account starts with the balance of [`VMContextBuilder::new`].
+    pub fn add_contract<F>(&mut self, account_id: AccountId, handler: F) -> &mut Self
+    where
+        F: FnMut(&str) + 'static,
+    {
+        let context = VMContextBuilder::new().build();
+        self.accounts.entry(account_id.clone()).or_insert_with(|| MockedAccount {
+            balance: context.account_balance,
+            storage_usage: context.storage_usage,
+            storage: HashMap::new(),
+        });
+        self.contracts.insert(account_id, Box::new(handler));
+        self
+    }
+
+    /// Sets the balance of the account, which is created if it does not exist.
+    pub fn set_balance(&mut self, account_id: &AccountId, balance: Balance) -> &mut Self {
+        self.accounts.entry(account_id.clone()).or_default().balance = balance;
+        self
+    }
+
+    /// Returns the balance of the account, or zero if the account does not exist.
+    pub fn balance(&self, account_id: &AccountId) -> Balance {
+        self.accounts.get(account_id).map_or(0, |account| account.balance)
+    }
+
+    /// Reads the state of the contract deployed to the account, as [`env::state_read`] would.
+    pub fn view_state<T: BorshDeserialize>(&self, account_id: &AccountId) -> Option<T> {
+        let data = self.accounts.get(account_id)?.storage.get(env::STATE_KEY)?;
+        T::try_from_slice(data).ok()
+    }
+
+    /// Calls the method of the contract as the signer, then executes the receipts created by the
+    /// call until none are left. The deposits of the call and of the scheduled calls are taken
+    /// from their signers, which panics if their balance is not enough.
+    pub fn call(
+        &mut self,
+        signer_id: &AccountId,
+        receiver_id: &AccountId,
+        method_name: &str,
+        args: Vec<u8>,
+        deposit: Balance,
+    ) -> CallOutcome {
+        let mut pending =
+            vec![function_call_receipt(signer_id, receiver_id, method_name, args, deposit)];
+        let mut resolutions: Vec<Option<Resolution>> = vec![None];
+        for mut receipt in std::mem::take(&mut self.scheduled) {
+            receipt.id = resolutions.len();
+            resolutions.push(None);
+            pending.push(receipt);
+        }
+        for receipt in &pending {
+            let signer = self.accounts.entry(receipt.signer_id.clone()).or_default();
+            let deposit = receipt_deposit(receipt);
+            signer.balance = signer.balance.checked_sub(deposit).unwrap_or_else(|| {
+                panic!(
+                    "{} cannot attach {}, it has a balance of {}",
+                    receipt.signer_id, deposit, signer.balance
+                )
+            });
+        }
+        let mut executions = Vec::new();
+
+        while let Some(position) = self.next_receipt(&pending, &resolutions) {
+            let receipt = pending.remove(position);
+            let promise_results = receipt
+                .dependencies
+                .iter()
+                .map(|&id| resolve(&resolutions, id).unwrap_or(PromiseResult::NotReady))
+                .collect();
+            let (resolution, logs, created) = self.execute(&receipt, promise_results);
+            let next_id = resolutions.len();
+            let resolution = match resolution {
+                Resolution::Forward(index) => Resolution::Forward(next_id + index),
+                done => done,
+            };
+            resolutions[receipt.id] = Some(resolution);
+            for (index, created) in created.into_iter().enumerate() {
+                resolutions.push(None);
+                pending.push(PendingReceipt {
+                    id: next_id + index,
+                    predecessor_id: receipt.receiver_id.clone(),
+                    signer_id: receipt.signer_id.clone(),
+                    receiver_id: created.receiver_id,
+                    actions: created.actions,
+                    dependencies: created
+                        .receipt_indices
+                        .into_iter()
+                        .map(|index| next_id + index as usize)
+                        .collect(),
+                });
+            }
+            executions.push((receipt.id, receipt.predecessor_id, receipt.receiver_id, logs));
+        }
+
+        let executions = executions
+            .into_iter()
+            .map(|(id, predecessor_id, receiver_id, logs)| ExecutionOutcome {
+                predecessor_id,
+                receiver_id,
+                result: resolve(&resolutions, id).unwrap_or(PromiseResult::NotReady),
+                logs,
+            })
+            .collect();
+        CallOutcome {
+            result: resolve(&resolutions, 0).unwrap_or(PromiseResult::NotReady),
+            executions,
+        }
+    }
+
+    /// Returns the position of the next receipt to execute, among the pending receipts which are
+    /// ready, or `None` if none are.
+    fn next_receipt(
+        &mut self,
+        pending: &[PendingReceipt],
+        resolutions: &[Option<Resolution>],
+    ) -> Option<usize> {
+        let ready: Vec<usize> = (0..pending.len())
+            .filter(|&position| {
+                pending[position].dependencies.iter().all(|&id| resolve(resolutions, id).is_some())
+            })
+            .collect();
+        match self.receipt_order {
+            ReceiptOrder::Fifo => ready.first().copied(),
+            ReceiptOrder::Lifo => ready.last().copied(),
+            ReceiptOrder::DelayCallbacks => ready
+                .iter()
+                .copied()
+                .find(|&position| pending[position].dependencies.is_empty())
+                .or_else(|| ready.first().copied()),
+            ReceiptOrder::Shuffled(_) if ready.is_empty() => None,
+            ReceiptOrder::Shuffled(_) => {
+                // SplitMix64, which is enough to pick the receipts.
+                self.shuffle_state = self.shuffle_state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+                let mut z = self.shuffle_state;
+                z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+                z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+                z ^= z >> 31;
+                Some(ready[(z % ready.len() as u64) as usize])
+            }
+        }
+    }
+
+    /// Executes the actions of the receipt, and returns its result, logs and the receipts it
+    /// created, whose indices are relative to each other. The deposits of the actions are
+    /// credited to the receiver, and if an action fails, the receiver is restored to its state
+    /// before the receipt and the deposits are refunded to the predecessor.
+    fn execute(
+        &mut self,
+        receipt: &PendingReceipt,
+        promise_results: Vec<PromiseResult>,
+    ) -> (Resolution, Vec<String>, Vec<super::Receipt>) {
+        self.block_height += 1;
+        let receiver_before = self.accounts.get(&receipt.receiver_id).cloned();
+        let mut resolution = Resolution::Done(PromiseResult::Successful(vec![]));
+        let mut logs = Vec::new();
+        let mut created = Vec::new();
+        let mut promise_results = Some(promise_results);
+        let mut failed = false;
+        for action in &receipt.actions {
+            match action {
+                VmAction::Transfer { deposit } => {
+                    self.accounts.entry(receipt.receiver_id.clone()).or_default().balance +=
+                        deposit;
+                }
+                VmAction::FunctionCall { method_name, args, gas, deposit } => {
+                    let handler = match self.contracts.get_mut(&receipt.receiver_id) {
+                        Some(handler) => handler,
+                        None => {
+                            failed = true;
+                            break;
+                        }
+                    };
+                    let account = self.accounts.entry(receipt.receiver_id.clone()).or_default();
+                    account.balance += deposit;
+                    // As on chain, the calls executed in the same block share the random seed.
+                    let mut random_seed = [0; 32];
+                    random_seed[..8].copy_from_slice(&self.block_height.to_le_bytes());
+                    let mut context = VMContextBuilder::new();
+                    context
+                        .current_account_id(receipt.receiver_id.clone())
+                        .signer_account_id(receipt.signer_id.clone())
+                        .predecessor_account_id(receipt.predecessor_id.clone())
+                        .block_index(self.block_height)
+                        .random_seed_array(random_seed)
+                        // `VMLogic` adds the attached deposit to the balance of the context.
+                        .account_balance(account.balance - deposit)
+                        .storage_usage(account.storage_usage)
+                        .attached_deposit(*deposit)
+                        .prepaid_gas(*gas);
+                    context.context.input = args.clone();
+                    env::set_blockchain_interface(MockedBlockchain::new(
+                        context.build(),
+                        Default::default(),
+                        Default::default(),
+                        promise_results.take().unwrap_or_default(),
+                        account.storage.clone(),
+                        Default::default(),
+                        None,
+                    ));
+
+                    let succeeded =
+                        panic::catch_unwind(AssertUnwindSafe(|| handler(method_name))).is_ok();
+                    let (outcome, receipts, storage) = with_mocked_blockchain(|b| {
+                        (b.outcome(), b.created_receipts().clone(), b.take_storage())
+                    });
+                    logs.extend(outcome.logs);
+                    if !succeeded {
+                        failed = true;
+                        break;
+                    }
+
+                    account.balance = outcome.balance;
+                    account.storage_usage = outcome.storage_usage;
+                    account.storage = storage;
+                    resolution = match outcome.return_data {
+                        ReturnData::Value(value) => {
+                            Resolution::Done(PromiseResult::Successful(value))
+                        }
+                        ReturnData::None => Resolution::Done(PromiseResult::Successful(vec![])),
+                        ReturnData::ReceiptIndex(index) => {
+                            Resolution::Forward(created.len() + index as usize)
+                        }
+                    };
+                    let offset = created.len() as u64;
+                    created.extend(receipts.into_iter().map(|mut receipt| {
+                        receipt.receipt_indices.iter_mut().for_each(|index| *index += offset);
+                        receipt
+                    }));
+                }
+                // Other actions are not simulated.
+                _ => {}
+            }
+        }
+
+        if failed {
+            match receiver_before {
+                Some(account) => self.accounts.insert(receipt.receiver_id.clone(), account),
+                None => self.accounts.remove(&receipt.receiver_id),
+            };
+            self.accounts.entry(receipt.predecessor_id.clone()).or_default().balance +=
+                receipt_deposit(receipt);
+            resolution = Resolution::Done(PromiseResult::Failed);
+            created.clear();
+        }
+        (resolution, logs, created)
+    }
+}
+
+/// Returns the sum of the deposits of the actions of the receipt.
+fn receipt_deposit(receipt: &PendingReceipt) -> Balance {
+    receipt
+        .actions
+        .iter()
+        .map(|action| match action {
+            VmAction::Transfer { deposit } | VmAction::FunctionCall { deposit, .. } => *deposit,
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Returns the receipt of a function call submitted by the signer, whose ID is the first one.
+fn function_call_receipt(
+    signer_id: &AccountId,
+    receiver_id: &AccountId,
+    method_name: &str,
+    args: Vec<u8>,
+    deposit: Balance,
+) -> PendingReceipt {
+    PendingReceipt {
+        id: 0,
+        predecessor_id: signer_id.clone(),
+        signer_id: signer_id.clone(),
+        receiver_id: receiver_id.clone(),
+        actions: vec![VmAction::FunctionCall {
+            method_name: method_name.to_string(),
+            args,
+            gas: CALL_GAS,
+            deposit,
+        }],
+        dependencies: vec![],
+    }
+}
+
+/// Returns the result of the receipt, following the receipts it returned, or `None` if it is not
+/// known yet.
+fn resolve(resolutions: &[Option<Resolution>], mut id: usize) -> Option<PromiseResult> {
+    loop {
+        match resolutions.get(id)?.as_ref()? {
+            Resolution::Done(result) => return Some(result.clone()),
+            Resolution::Forward(next) => id = *next,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Promise;
+
+    fn account(id: &str) -> AccountId {
+        id.parse().unwrap()
+    }
+
+    #[test]
+    fn failed_call_is_reverted() {
+        let mut runtime = MockedRuntime::new();
+        let counter = account("counter.near");
+        let alice = account("alice.near");
+        runtime.add_contract(counter.clone(), |method| {
+            let count: u32 = env::state_read().unwrap_or_default();
+            env::state_write(&(count + 1));
+            if method == "fail" {
+                env::panic_str("failed");
+            }
+        });
+        runtime.set_balance(&alice, 100);
+
+        let initial_balance = runtime.balance(&counter);
+        assert_eq!(
+            runtime.call(&alice, &counter, "increment", vec![], 10).result,
+            PromiseResult::Successful(vec![])
+        );
+        assert_eq!(
+            runtime.call(&alice, &counter, "fail", vec![], 10).result,
+            PromiseResult::Failed
+        );
+        assert_eq!(runtime.view_state::<u32>(&counter), Some(1));
+        assert_eq!(runtime.balance(&counter), initial_balance + 10);
+        assert_eq!(runtime.balance(&alice), 90);
+    }
+
+    fn total_balance(runtime: &MockedRuntime) -> Balance {
+        runtime.accounts.values().map(|account| account.balance).sum()
+    }
+
+    #[test]
+    fn balances_are_conserved() {
+        let mut runtime = MockedRuntime::new();
+        let contract = account("contract.near");
+        let alice = account("alice.near");
+        let bob = account("bob.near");
+        runtime.add_contract(contract.clone(), |method| match method {
+            "pay" => {}
+            "fail" => env::panic_str("failed"),
+            "forward" => {
+                // The transfer is undone when the function call of the same receipt fails.
+                Promise::new(env::current_account_id()).transfer(5).function_call(
+                    "fail".to_string(),
+                    vec![],
+                    7,
+                    Gas::from_tgas(10),
+                );
+                Promise::new(account("missing.near")).function_call(
+                    "method".to_string(),
+                    vec![],
+                    11,
+                    Gas::from_tgas(10),
+                );
+                Promise::new(account("bob.near")).transfer(13);
+            }
+            _ => env::panic_str("unknown method"),
+        });
+        runtime.set_balance(&alice, 100);
+        let contract_balance = runtime.balance(&contract);
+        let total = total_balance(&runtime);
+
+        runtime.call(&alice, &contract, "fail", vec![], 10);
+        assert_eq!(total_balance(&runtime), total);
+        assert_eq!(runtime.balance(&alice), 100);
+        assert_eq!(runtime.balance(&contract), contract_balance);
+
+        runtime.call(&alice, &contract, "pay", vec![], 10);
+        assert_eq!(total_balance(&runtime), total);
+        assert_eq!(runtime.balance(&alice), 90);
+        assert_eq!(runtime.balance(&contract), contract_balance + 10);
+
+        let outcome = runtime.call(&alice, &contract, "forward", vec![], 0);
+        assert_eq!(outcome.executions.len(), 4);
+        assert_eq!(total_balance(&runtime), total);
+        assert_eq!(runtime.balance(&contract), contract_balance + 10 - 13);
+        assert_eq!(runtime.balance(&bob), 13);
+        assert_eq!(runtime.balance(&account("missing.near")), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "alice.near cannot attach 10, it has a balance of 0")]
+    fn deposit_exceeds_balance() {
+        let mut runtime = MockedRuntime::new();
+        let contract = account("contract.near");
+        runtime.add_contract(contract.clone(), |_method| {});
+        runtime.call(&account("alice.near"), &contract, "pay", vec![], 10);
+    }
+
+    #[test]
+    fn callback_receives_results() {
+        let mut runtime = MockedRuntime::new();
+        let caller = account("caller.near");
+        runtime.add_contract(caller.clone(), |method| match method {
+            "call" => {
+                Promise::new(account("missing.near"))
+                    .function_call("method".to_string(), vec![], 0, Gas::from_tgas(10))
+                    .and(Promise::new(account("bob.near")).transfer(5))
+                    .then(Promise::new(env::current_account_id()).function_call(
+                        "callback".to_string(),
+                        vec![],
+                        0,
+                        Gas::from_tgas(10),
+                    ));
+            }
+            "callback" => {
+                assert_eq!(env::predecessor_account_id(), env::current_account_id());
+                let results: Vec<_> =
+                    (0..env::promise_results_count()).map(env::promise_result).collect();
+                assert_eq!(results, [PromiseResult::Failed, PromiseResult::Successful(vec![])]);
+                env::log_str("callback");
+            }
+            _ => env::panic_str("unknown method"),
+        });
+
+        let outcome = runtime.call(&caller, &caller, "call", vec![], 0);
+        assert_eq!(outcome.executions.len(), 4);
+        assert_eq!(outcome.executions[3].result, PromiseResult::Successful(vec![]));
+        assert_eq!(outcome.executions[3].logs, ["callback"]);
+        assert_eq!(runtime.balance(&account("bob.near")), 5);
+    }
+
+    /// Returns the logs of the executions, which are the methods executed in order.
+    fn order(runtime: &mut MockedRuntime, caller: &AccountId) -> Vec<String> {
+        let outcome = runtime.call(caller, caller, "call", vec![], 0);
+        outcome.executions.into_iter().flat_map(|execution| execution.logs).collect()
+    }
+
+    #[test]
+    fn receipt_orders() {
+        let mut runtime = MockedRuntime::new();
+        let caller = account("caller.near");
+        let other = account("other.near");
+        runtime.add_contract(other.clone(), |method| env::log_str(method));
+        runtime.add_contract(caller.clone(), |method| {
+            env::log_str(method);
+            if method == "call" {
+                let call = |method: &str, account: &str| {
+                    Promise::new(account.parse().unwrap()).function_call(
+                        method.to_string(),
+                        vec![],
+                        0,
+                        Gas::from_tgas(10),
+                    )
+                };
+                call("first", "other.near").then(call("callback", "caller.near"));
+                call("second", "other.near");
+            }
+        });
+
+        assert_eq!(order(&mut runtime, &caller), ["call", "first", "callback", "second"]);
+        runtime.set_receipt_order(ReceiptOrder::Lifo);
+        assert_eq!(order(&mut runtime, &caller), ["call", "second", "first", "callback"]);
+        runtime.set_receipt_order(ReceiptOrder::DelayCallbacks);
+        assert_eq!(order(&mut runtime, &caller), ["call", "first", "second", "callback"]);
+
+        runtime.set_receipt_order(ReceiptOrder::DelayCallbacks).schedule_call(
+            &other,
+            &other,
+            "third_party",
+            vec![],
+            0,
+        );
+        assert_eq!(
+            order(&mut runtime, &caller),
+            ["call", "third_party", "first", "second", "callback"]
+        );
+
+        let shuffled = order(runtime.set_receipt_order(ReceiptOrder::Shuffled(7)), &caller);
+        assert_eq!(order(runtime.set_receipt_order(ReceiptOrder::Shuffled(7)), &caller), shuffled);
+        let position = |method| shuffled.iter().position(|m| m == method).unwrap();
+        assert_eq!(shuffled.len(), 4);
+        assert!(position("first") < position("callback"));
+    }
+}
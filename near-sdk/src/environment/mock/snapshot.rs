@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+
+use crate::StorageUsage;
+
+/// Contract storage of the [`MockedBlockchain`](super::MockedBlockchain) at some point of a
+/// test, taken with [`storage_snapshot`](super::storage_snapshot) and brought back with
+/// [`restore`](super::restore).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Snapshot {
+    pub(crate) storage: HashMap<Vec<u8>, Vec<u8>>,
+    pub(crate) storage_usage: StorageUsage,
+}
+
+impl Snapshot {
+    /// Returns the key-value pairs of the storage.
+    pub fn storage(&self) -> &HashMap<Vec<u8>, Vec<u8>> {
+        &self.storage
+    }
+
+    /// Returns the storage usage of the account when the snapshot was taken.
+    pub fn storage_usage(&self) -> StorageUsage {
+        self.storage_usage
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{env, mock};
+
+    #[test]
+    fn restore_storage() {
+        let initial_usage = env::storage_usage();
+        env::storage_write(b"kept", b"1");
+        env::storage_write(b"changed", b"1");
+        let snapshot = mock::storage_snapshot();
+        let usage = env::storage_usage();
+
+        env::storage_write(b"changed", b"22");
+        env::storage_write(b"added", b"3");
+        env::storage_remove(b"kept");
+        assert_ne!(env::storage_usage(), usage);
+
+        mock::restore(snapshot.clone());
+        assert_eq!(mock::storage_snapshot(), snapshot);
+        assert_eq!(env::storage_read(b"changed"), Some(b"1".to_vec()));
+        assert!(!env::storage_has_key(b"added"));
+        assert_eq!(env::storage_usage(), usage);
+
+        env::storage_remove(b"changed");
+        env::storage_remove(b"kept");
+        assert_eq!(env::storage_usage(), initial_usage);
+    }
+}
@@ -1,9 +1,18 @@
 pub mod env;
 
+pub(crate) mod rng;
+
 #[cfg(feature = "unstable")]
 pub mod hash;
 
+#[cfg(feature = "unstable-sys")]
+pub mod sys;
+
 #[cfg(not(target_arch = "wasm32"))]
 /// Mock blockchain utilities. These can only be used inside tests and are not available for
 /// a wasm32 target.
 pub mod mock;
+
+#[cfg(all(feature = "test-runtime", not(target_arch = "wasm32")))]
+/// Execution of the compiled contract in unit tests. This is not available for a wasm32 target.
+pub mod test_runtime;
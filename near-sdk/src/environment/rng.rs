@@ -0,0 +1,131 @@
+use std::cell::Cell;
+use std::convert::TryInto;
+use std::ops::Range;
+
+use rand_core::{impls, Error, RngCore};
+
+use crate::env;
+
+thread_local! {
+    /// Number of generators created during the current function call.
+    static GENERATORS: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Starts counting the generators of a new function call, which in unit tests begins when the
+/// mocked blockchain is replaced.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn reset() {
+    GENERATORS.with(|count| count.set(0));
+}
+
+/// xoshiro256** generator, seeded from the block's random seed.
+pub(crate) struct SeedRng {
+    state: [u64; 4],
+}
+
+impl SeedRng {
+    /// Creates a generator from the hash of the random seed and the number of generators created
+    /// before it, so that each generator of a function call produces a different sequence.
+    pub(crate) fn new() -> Self {
+        let index = GENERATORS.with(|count| count.replace(count.get() + 1));
+        let seed = env::sha256(&[env::random_seed().as_slice(), &index.to_le_bytes()].concat());
+        let mut state = [0; 4];
+        for (word, bytes) in state.iter_mut().zip(seed.chunks_exact(8)) {
+            *word = u64::from_le_bytes(bytes.try_into().unwrap_or_else(|_| env::abort()));
+        }
+        if state == [0; 4] {
+            // The all-zero state is the only state which the generator cannot leave.
+            state[0] = 1;
+        }
+        Self { state }
+    }
+
+    /// Returns a uniformly distributed number in `0..bound`, without the bias of `next % bound`.
+    pub(crate) fn below(&mut self, bound: u64) -> u64 {
+        // Reject the values of the last incomplete multiple of `bound` in the `u64` range.
+        let zone = u64::MAX - (u64::MAX - bound + 1) % bound;
+        loop {
+            let value = self.next_u64();
+            if value <= zone {
+                return value % bound;
+            }
+        }
+    }
+}
+
+impl RngCore for SeedRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let s = &mut self.state;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        result
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        impls::fill_bytes_via_next(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// Returns a uniformly distributed number in `range`.
+pub(crate) fn random_in_range(rng: &mut SeedRng, range: Range<u64>) -> u64 {
+    if range.start >= range.end {
+        env::panic_str("Cannot sample from an empty range");
+    }
+    range.start + rng.below(range.end - range.start)
+}
+
+/// Shuffles the slice with a Fisher-Yates shuffle.
+pub(crate) fn shuffle<T>(rng: &mut SeedRng, slice: &mut [T]) {
+    for i in (1..slice.len()).rev() {
+        let j = rng.below(i as u64 + 1) as usize;
+        slice.swap(i, j);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{random_in_range, shuffle, SeedRng};
+    use crate::test_utils::VMContextBuilder;
+    use crate::testing_env;
+    use rand_core::RngCore;
+
+    #[test]
+    fn generators_are_deterministic() {
+        testing_env!(VMContextBuilder::new().random_seed_array([7; 32]).build());
+        let first: Vec<u64> = (0..4).map(|_| SeedRng::new().next_u64()).collect();
+        // Each generator of a call has its own sequence.
+        assert!(first.windows(2).all(|w| w[0] != w[1]));
+
+        let mut rng = SeedRng::new();
+        assert!((0..100).map(|_| random_in_range(&mut rng, 10..13)).all(|x| (10..13).contains(&x)));
+        let mut values: Vec<u32> = (0..20).collect();
+        shuffle(&mut rng, &mut values);
+        assert_ne!(values, (0..20).collect::<Vec<_>>());
+        values.sort_unstable();
+        assert_eq!(values, (0..20).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn generators_restart_with_the_context() {
+        testing_env!(VMContextBuilder::new().random_seed_array([7; 32]).build());
+        let first: Vec<u64> = (0..4).map(|_| SeedRng::new().next_u64()).collect();
+        testing_env!(VMContextBuilder::new().random_seed_array([7; 32]).build());
+        let second: Vec<u64> = (0..4).map(|_| SeedRng::new().next_u64()).collect();
+        assert_eq!(first, second);
+    }
+}
@@ -0,0 +1,146 @@
+//! Raw host functions of the NEAR runtime, for calling host functions which do not have a
+//! wrapper in [`env`](crate::env) yet.
+//!
+//! All the `extern` declarations of [`near_sys`] are re-exported as is, and calling them is
+//! `unsafe`. The [`safe`] module contains typed helpers for the parts of a host function call
+//! which are easy to get wrong: passing slices as length and pointer pairs, reading registers
+//! and reading `u128` values written to memory.
+//!
+//! This module is only available with the `unstable-sys` feature, and its contents follow the
+//! host functions of the runtime, so they may change between releases.
+//!
+//! # Examples
+//! ```
+//! # near_sdk::testing_env!(near_sdk::test_utils::VMContextBuilder::new().build());
+//! use near_sdk::sys::{self, safe::{self, Slice}};
+//!
+//! let value = Slice::new(b"hello");
+//! let hash = safe::call_with_register(|register_id| unsafe {
+//!     sys::sha256(value.len(), value.ptr(), register_id)
+//! });
+//! assert_eq!(hash, near_sdk::env::sha256(b"hello"));
+//! ```
+
+pub use near_sys::*;
+
+/// Typed, safe helpers for calling the raw host functions.
+pub mod safe {
+    use std::mem::size_of;
+
+    use crate::env;
+
+    /// Register written by [`call_with_register`], which is not used by the [`env`] functions.
+    const CALL_REGISTER: u64 = u64::MAX - 3;
+
+    /// A byte slice in the form host functions take it, a length and a pointer.
+    #[derive(Debug, Clone, Copy)]
+    pub struct Slice<'a> {
+        bytes: &'a [u8],
+    }
+
+    impl<'a> Slice<'a> {
+        /// Wraps the bytes to pass to a host function.
+        pub fn new(bytes: &'a [u8]) -> Self {
+            Self { bytes }
+        }
+
+        /// Returns the length of the slice.
+        pub fn len(&self) -> u64 {
+            self.bytes.len() as u64
+        }
+
+        /// Returns `true` if the slice is empty.
+        pub fn is_empty(&self) -> bool {
+            self.bytes.is_empty()
+        }
+
+        /// Returns the pointer to the start of the slice.
+        pub fn ptr(&self) -> u64 {
+            self.bytes.as_ptr() as u64
+        }
+    }
+
+    impl<'a> From<&'a [u8]> for Slice<'a> {
+        fn from(bytes: &'a [u8]) -> Self {
+            Self::new(bytes)
+        }
+    }
+
+    impl<'a> From<&'a str> for Slice<'a> {
+        fn from(s: &'a str) -> Self {
+            Self::new(s.as_bytes())
+        }
+    }
+
+    /// Returns the size of the register, or `None` if the register is not used.
+    pub fn register_len(register_id: u64) -> Option<u64> {
+        env::register_len(register_id)
+    }
+
+    /// Reads the content of the register, or returns `None` if the register is not used.
+    pub fn read_register(register_id: u64) -> Option<Vec<u8>> {
+        env::read_register(register_id)
+    }
+
+    /// Reads the content of the register into the start of `buffer` and returns the number of
+    /// bytes read. Returns `None` if the register is not used or if `buffer` is smaller than the
+    /// register, instead of panicking like [`env::read_register_into`].
+    pub fn read_register_into(register_id: u64, buffer: &mut [u8]) -> Option<usize> {
+        match register_len(register_id) {
+            Some(len) if len as usize <= buffer.len() => {
+                env::read_register_into(register_id, buffer)
+            }
+            _ => None,
+        }
+    }
+
+    /// Writes `data` into the register.
+    pub fn write_register(register_id: u64, data: &[u8]) {
+        let data = Slice::new(data);
+        unsafe { super::write_register(register_id, data.len(), data.ptr()) }
+    }
+
+    /// Calls `f` with the id of a register for the host function to write its result to, and
+    /// returns the content of that register.
+    ///
+    /// The register is cleared before the call, so if the host function does not write its
+    /// result, the returned value is empty.
+    pub fn call_with_register(f: impl FnOnce(u64)) -> Vec<u8> {
+        write_register(CALL_REGISTER, &[]);
+        f(CALL_REGISTER);
+        read_register(CALL_REGISTER).unwrap_or_default()
+    }
+
+    /// Calls `f` with a pointer to 16 bytes of memory for the host function to write a `u128`
+    /// value to, such as a balance, and returns that value.
+    pub fn call_with_u128(f: impl FnOnce(u64)) -> u128 {
+        let mut data = [0u8; size_of::<u128>()];
+        f(data.as_mut_ptr() as u64);
+        u128::from_le_bytes(data)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::safe::{self, Slice};
+    use crate::env;
+
+    #[test]
+    fn typed_host_calls() {
+        let value = Slice::from("hello");
+        let hash = safe::call_with_register(|register_id| unsafe {
+            super::keccak256(value.len(), value.ptr(), register_id)
+        });
+        assert_eq!(hash, env::keccak256(b"hello"));
+
+        let balance = safe::call_with_u128(|ptr| unsafe { super::account_balance(ptr) });
+        assert_eq!(balance, env::account_balance());
+
+        let mut buffer = [0u8; 4];
+        safe::write_register(7, b"bytes");
+        assert_eq!(safe::read_register_into(7, &mut buffer), None);
+        assert_eq!(safe::read_register_into(7, &mut [0u8; 8]), Some(5));
+        assert_eq!(safe::read_register_into(8, &mut buffer), None);
+    }
+}
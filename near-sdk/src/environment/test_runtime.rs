@@ -0,0 +1,556 @@
+//! Execution of the compiled contract in unit tests.
+//!
+//! The [`MockedBlockchain`](crate::mock::MockedBlockchain) runs the contract as native code, so
+//! it does not catch the issues which only show up once the contract is compiled to wasm, such as
+//! the gas used by the code of the contract or a missing export. [`TestRuntime`] loads the
+//! compiled contract and executes its methods with an interpreter, with the host functions backed
+//! by the same VM logic as the mocked blockchain. This sits between the unit tests and the
+//! integration tests which deploy the contract to a sandbox node.
+
+use crate::mock::{Receipt, SdkExternal};
+use crate::{Balance, Gas, RuntimeFeesConfig, StorageUsage};
+use near_vm_logic::types::PromiseResult as VmPromiseResult;
+use near_vm_logic::{External, MemoryLike, VMConfig, VMContext, VMLogic, VMLogicError};
+use serde::de::DeserializeOwned;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::path::Path;
+use std::ptr;
+use std::rc::Rc;
+use wasmi::core::Trap;
+use wasmi::{AsContextMut, Caller, Config, Engine, Extern, Linker, Module, Store};
+
+pub use near_vm_logic::types::ReturnData;
+
+/// Runtime which executes the methods of a compiled contract, with the gas and the limits of the
+/// [`VMConfig`].
+///
+/// Each call is executed against the storage of the contract, which is kept between the calls,
+/// and the changes of a failed call are discarded. The wasm instructions executed by a call are
+/// charged the `regular_op_cost` of the config each, in addition to the gas of the host
+/// functions. Contracts importing host functions which the mocked blockchain does not support,
+/// such as `ecrecover`, cannot be loaded.
+///
+/// # Examples
+/// ```no_run
+/// use near_sdk::test_runtime::TestRuntime;
+/// use near_sdk::test_utils::VMContextBuilder;
+///
+/// let mut runtime =
+///     TestRuntime::from_file("target/wasm32-unknown-unknown/release/status_message.wasm");
+/// let context = VMContextBuilder::new().build();
+/// runtime.call(context.clone(), "set_status", br#"{"message":"hello"}"#).unwrap();
+/// let outcome = runtime.call(context, "get_status", br#"{"account_id":"bob"}"#);
+/// assert_eq!(outcome.unwrap_json::<Option<String>>(), Some("hello".to_string()));
+/// ```
+pub struct TestRuntime {
+    engine: Engine,
+    module: Module,
+    code_len: u64,
+    storage: HashMap<Vec<u8>, Vec<u8>>,
+    config: VMConfig,
+    fees_config: RuntimeFeesConfig,
+}
+
+/// Outcome of a call executed by the [`TestRuntime`].
+#[derive(Debug)]
+pub struct WasmOutcome {
+    /// Data returned by the method, or the error it failed with.
+    pub result: Result<ReturnData, String>,
+    pub logs: Vec<String>,
+    pub burnt_gas: Gas,
+    pub used_gas: Gas,
+    /// Receipts created by the method, which are not executed.
+    pub receipts: Vec<Receipt>,
+    pub balance: Balance,
+    pub storage_usage: StorageUsage,
+}
+
+impl WasmOutcome {
+    /// Returns the value returned by the method, if any.
+    ///
+    /// # Panics
+    ///
+    /// If the call failed or the method returned a promise.
+    pub fn unwrap(&self) -> Option<Vec<u8>> {
+        match &self.result {
+            Ok(ReturnData::Value(value)) => Some(value.clone()),
+            Ok(ReturnData::None) => None,
+            Ok(ReturnData::ReceiptIndex(_)) => panic!("The method returned a promise"),
+            Err(error) => panic!("The call failed: {}", error),
+        }
+    }
+
+    /// Deserializes the JSON value returned by the method.
+    ///
+    /// # Panics
+    ///
+    /// If the call failed or did not return a value of type `T`.
+    pub fn unwrap_json<T: DeserializeOwned>(&self) -> T {
+        let value = self.unwrap().expect("The method did not return a value");
+        serde_json::from_slice(&value).expect("The method did not return a value of this type")
+    }
+
+    /// Returns the error the call failed with.
+    ///
+    /// # Panics
+    ///
+    /// If the call succeeded.
+    pub fn unwrap_err(&self) -> &str {
+        match &self.result {
+            Ok(_) => panic!("The call succeeded"),
+            Err(error) => error,
+        }
+    }
+}
+
+impl TestRuntime {
+    /// Loads the contract from its wasm code.
+    ///
+    /// # Panics
+    ///
+    /// If the code is not a valid wasm module.
+    pub fn new(code: impl AsRef<[u8]>) -> Self {
+        let code = code.as_ref();
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, code)
+            .unwrap_or_else(|error| panic!("Invalid contract code: {}", error));
+        Self {
+            engine,
+            module,
+            code_len: code.len() as u64,
+            storage: HashMap::new(),
+            config: VMConfig::default(),
+            fees_config: RuntimeFeesConfig::default(),
+        }
+    }
+
+    /// Loads the contract from the wasm file, which is usually in
+    /// `target/wasm32-unknown-unknown/release`.
+    ///
+    /// # Panics
+    ///
+    /// If the file cannot be read or is not a valid wasm module.
+    pub fn from_file(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        let code = std::fs::read(path)
+            .unwrap_or_else(|error| panic!("Cannot read {}: {}", path.display(), error));
+        Self::new(code)
+    }
+
+    /// Sets the configuration of the VM, with its gas costs and limits.
+    pub fn set_config(&mut self, config: VMConfig) -> &mut Self {
+        self.config = config;
+        self
+    }
+
+    /// Sets the configuration of the fees of the runtime.
+    pub fn set_fees_config(&mut self, fees_config: RuntimeFeesConfig) -> &mut Self {
+        self.fees_config = fees_config;
+        self
+    }
+
+    /// Returns the storage of the contract.
+    pub fn storage(&self) -> &HashMap<Vec<u8>, Vec<u8>> {
+        &self.storage
+    }
+
+    /// Returns the storage of the contract, which can be changed between calls.
+    pub fn storage_mut(&mut self) -> &mut HashMap<Vec<u8>, Vec<u8>> {
+        &mut self.storage
+    }
+
+    /// Calls the method of the contract with the arguments, which replace the input of the
+    /// context. As with the [`MockedBlockchain`](crate::mock::MockedBlockchain), the storage
+    /// usage of the context is the usage of the account without the storage.
+    pub fn call(&mut self, mut context: VMContext, method: &str, args: &[u8]) -> WasmOutcome {
+        let record_bytes = self.fees_config.storage_usage_config.num_extra_bytes_record;
+        context.storage_usage += self
+            .storage
+            .iter()
+            .map(|(key, value)| key.len() as u64 + value.len() as u64 + record_bytes)
+            .sum::<u64>();
+        context.input = args.to_vec();
+        // The prepaid gas does not limit view calls.
+        let gas_limit = if context.is_view {
+            self.config.limit_config.max_gas_burnt_view
+        } else {
+            self.config.limit_config.max_gas_burnt.min(context.prepaid_gas)
+        };
+        // The fuel only stops the execution, the gas is charged by the VM logic.
+        let fuel = match self.config.regular_op_cost {
+            0 => u64::MAX,
+            op_cost => gas_limit / op_cost as u64 + 1,
+        };
+
+        let mut ext = Box::new(SdkExternal::new());
+        ext.fake_trie = self.storage.clone();
+        let view =
+            Rc::new(Cell::new(ptr::slice_from_raw_parts_mut(ptr::NonNull::dangling().as_ptr(), 0)));
+        let mut fixture = LogicFixture {
+            ext,
+            memory: Box::new(WasmMemory { view: Rc::clone(&view) }),
+            promise_results: Box::new(Vec::new()),
+            config: Box::new(self.config.clone()),
+            fees_config: Box::new(self.fees_config.clone()),
+        };
+        let logic = unsafe {
+            VMLogic::new_with_protocol_version(
+                &mut *(fixture.ext.as_mut() as *mut dyn External),
+                context,
+                &*(fixture.config.as_ref() as *const VMConfig),
+                &*(fixture.fees_config.as_ref() as *const RuntimeFeesConfig),
+                &*(fixture.promise_results.as_slice() as *const [VmPromiseResult]),
+                &mut *(fixture.memory.as_mut() as *mut dyn MemoryLike),
+                Default::default(),
+                u32::MAX,
+            )
+        };
+
+        let mut store = Store::new(&self.engine, HostState { logic, view, fuel_charged: 0 });
+        store.add_fuel(fuel).expect("fuel metering is enabled");
+        let result = self.execute(&mut store, method);
+        let fuel_consumed = store.fuel_consumed().unwrap_or_default();
+        let mut state = store.into_data();
+        let result = match state.charge_wasm_gas(fuel_consumed) {
+            // Running out of gas is reported in place of the trap it caused.
+            Err(error) => Err(error_message(&error)),
+            Ok(()) => result,
+        };
+        // The logic is consumed before the fixture it references is taken apart.
+        let outcome = state.logic.outcome();
+        let return_data = outcome.return_data;
+
+        if result.is_ok() {
+            self.storage = std::mem::take(&mut fixture.ext.fake_trie);
+        }
+        WasmOutcome {
+            result: result.map(|()| return_data),
+            logs: outcome.logs,
+            burnt_gas: Gas(outcome.burnt_gas),
+            used_gas: Gas(outcome.used_gas),
+            receipts: std::mem::take(&mut fixture.ext.receipts),
+            balance: outcome.balance,
+            storage_usage: outcome.storage_usage,
+        }
+    }
+
+    fn execute(&self, store: &mut Store<HostState>, method: &str) -> Result<(), String> {
+        store
+            .data_mut()
+            .logic
+            .add_contract_compile_fee(self.code_len)
+            .map_err(|error| error_message(&error))?;
+        let mut linker = Linker::new(&self.engine);
+        link_host_functions(&mut linker);
+        let instance = linker
+            .instantiate(&mut *store, &self.module)
+            .and_then(|instance| instance.start(&mut *store))
+            .map_err(|error| format!("Cannot instantiate the contract: {}", error))?;
+        let function = instance
+            .get_typed_func::<(), ()>(&*store, method)
+            .map_err(|_| format!("Method {} is not exported by the contract", method))?;
+        function.call(&mut *store, ()).map_err(|error| error.to_string())
+    }
+}
+
+/// Objects referenced by the [`VMLogic`] of a call.
+struct LogicFixture {
+    ext: Box<SdkExternal>,
+    memory: Box<WasmMemory>,
+    #[allow(clippy::box_collection)]
+    promise_results: Box<Vec<VmPromiseResult>>,
+    config: Box<VMConfig>,
+    fees_config: Box<RuntimeFeesConfig>,
+}
+
+/// Memory of the instance, as seen by the [`VMLogic`]. The view is set before each host function
+/// call, as the memory can be moved when it grows.
+struct WasmMemory {
+    view: Rc<Cell<*mut [u8]>>,
+}
+
+impl WasmMemory {
+    fn data(&self) -> &[u8] {
+        unsafe { &*self.view.get() }
+    }
+
+    fn data_mut(&mut self) -> &mut [u8] {
+        unsafe { &mut *self.view.get() }
+    }
+}
+
+impl MemoryLike for WasmMemory {
+    fn fits_memory(&self, offset: u64, len: u64) -> bool {
+        matches!(offset.checked_add(len), Some(end) if end <= self.data().len() as u64)
+    }
+
+    fn read_memory(&self, offset: u64, buffer: &mut [u8]) {
+        let offset = offset as usize;
+        buffer.copy_from_slice(&self.data()[offset..offset + buffer.len()]);
+    }
+
+    fn read_memory_u8(&self, offset: u64) -> u8 {
+        self.data()[offset as usize]
+    }
+
+    fn write_memory(&mut self, offset: u64, buffer: &[u8]) {
+        let offset = offset as usize;
+        self.data_mut()[offset..offset + buffer.len()].copy_from_slice(buffer);
+    }
+}
+
+struct HostState {
+    logic: VMLogic<'static>,
+    view: Rc<Cell<*mut [u8]>>,
+    /// Fuel consumed by the instance which was already charged as gas.
+    fuel_charged: u64,
+}
+
+impl HostState {
+    /// Charges the wasm instructions executed since the last host function call.
+    fn charge_wasm_gas(&mut self, fuel_consumed: u64) -> Result<(), VMLogicError> {
+        let mut ops = fuel_consumed - self.fuel_charged;
+        self.fuel_charged = fuel_consumed;
+        while ops > 0 {
+            let chunk = ops.min(u32::MAX as u64);
+            self.logic.gas(chunk as u32)?;
+            ops -= chunk;
+        }
+        Ok(())
+    }
+}
+
+/// Calls the host function on the [`VMLogic`], with the memory of the instance, after charging the
+/// gas of the instructions executed so far.
+fn call_host<R>(
+    caller: &mut Caller<'_, HostState>,
+    f: impl FnOnce(&mut VMLogic<'static>) -> Result<R, VMLogicError>,
+) -> Result<R, Trap> {
+    let fuel_consumed = caller.fuel_consumed().unwrap_or_default();
+    let memory = caller.get_export("memory").and_then(Extern::into_memory);
+    let state = match memory {
+        Some(memory) => {
+            let (data, state) = memory.data_and_store_mut(caller.as_context_mut());
+            state.view.set(data);
+            state
+        }
+        None => caller.data_mut(),
+    };
+    state
+        .charge_wasm_gas(fuel_consumed)
+        .and_then(|()| f(&mut state.logic))
+        .map_err(|error| Trap::new(error_message(&error)))
+}
+
+/// Returns the message of the error, as the runtime reports it.
+fn error_message(error: &VMLogicError) -> String {
+    match error {
+        VMLogicError::HostError(error) => error.to_string(),
+        error => error.to_string(),
+    }
+}
+
+macro_rules! host_functions {
+    ($linker:expr, $($name:ident($($arg:ident),*);)*) => {
+        $(
+            $linker
+                .func_wrap(
+                    "env",
+                    stringify!($name),
+                    |mut caller: Caller<'_, HostState>, $($arg: u64),*| {
+                        call_host(&mut caller, |logic| logic.$name($($arg),*))
+                    },
+                )
+                .expect("host functions are defined once");
+        )*
+    };
+}
+
+fn link_host_functions(linker: &mut Linker<HostState>) {
+    host_functions!(linker,
+        read_register(register_id, ptr);
+        register_len(register_id);
+        write_register(register_id, data_len, data_ptr);
+        current_account_id(register_id);
+        signer_account_id(register_id);
+        signer_account_pk(register_id);
+        predecessor_account_id(register_id);
+        input(register_id);
+        block_index();
+        block_timestamp();
+        epoch_height();
+        storage_usage();
+        account_balance(balance_ptr);
+        account_locked_balance(balance_ptr);
+        attached_deposit(balance_ptr);
+        prepaid_gas();
+        used_gas();
+        random_seed(register_id);
+        sha256(value_len, value_ptr, register_id);
+        keccak256(value_len, value_ptr, register_id);
+        keccak512(value_len, value_ptr, register_id);
+        value_return(value_len, value_ptr);
+        panic();
+        panic_utf8(len, ptr);
+        log_utf8(len, ptr);
+        log_utf16(len, ptr);
+        promise_create(
+            account_id_len,
+            account_id_ptr,
+            method_name_len,
+            method_name_ptr,
+            arguments_len,
+            arguments_ptr,
+            amount_ptr,
+            gas
+        );
+        promise_then(
+            promise_index,
+            account_id_len,
+            account_id_ptr,
+            method_name_len,
+            method_name_ptr,
+            arguments_len,
+            arguments_ptr,
+            amount_ptr,
+            gas
+        );
+        promise_and(promise_idx_ptr, promise_idx_count);
+        promise_batch_create(account_id_len, account_id_ptr);
+        promise_batch_then(promise_index, account_id_len, account_id_ptr);
+        promise_batch_action_create_account(promise_index);
+        promise_batch_action_deploy_contract(promise_index, code_len, code_ptr);
+        promise_batch_action_function_call(
+            promise_index,
+            method_name_len,
+            method_name_ptr,
+            arguments_len,
+            arguments_ptr,
+            amount_ptr,
+            gas
+        );
+        promise_batch_action_transfer(promise_index, amount_ptr);
+        promise_batch_action_stake(promise_index, amount_ptr, public_key_len, public_key_ptr);
+        promise_batch_action_add_key_with_full_access(
+            promise_index,
+            public_key_len,
+            public_key_ptr,
+            nonce
+        );
+        promise_batch_action_add_key_with_function_call(
+            promise_index,
+            public_key_len,
+            public_key_ptr,
+            nonce,
+            allowance_ptr,
+            receiver_id_len,
+            receiver_id_ptr,
+            method_names_len,
+            method_names_ptr
+        );
+        promise_batch_action_delete_key(promise_index, public_key_len, public_key_ptr);
+        promise_batch_action_delete_account(promise_index, beneficiary_id_len, beneficiary_id_ptr);
+        promise_results_count();
+        promise_result(result_idx, register_id);
+        promise_return(promise_id);
+        storage_write(key_len, key_ptr, value_len, value_ptr, register_id);
+        storage_read(key_len, key_ptr, register_id);
+        storage_remove(key_len, key_ptr, register_id);
+        storage_has_key(key_len, key_ptr);
+        validator_stake(account_id_len, account_id_ptr, stake_ptr);
+        validator_total_stake(stake_ptr);
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{VMConfigBuilder, VMContextBuilder};
+
+    const CONTRACT: &str = r#"
+        (module
+          (import "env" "input" (func $input (param i64)))
+          (import "env" "register_len" (func $register_len (param i64) (result i64)))
+          (import "env" "read_register" (func $read_register (param i64 i64)))
+          (import "env" "storage_write"
+            (func $storage_write (param i64 i64 i64 i64 i64) (result i64)))
+          (import "env" "storage_read" (func $storage_read (param i64 i64 i64) (result i64)))
+          (import "env" "value_return" (func $value_return (param i64 i64)))
+          (import "env" "log_utf8" (func $log_utf8 (param i64 i64)))
+          (import "env" "panic_utf8" (func $panic_utf8 (param i64 i64)))
+          (memory (export "memory") 1)
+          (data (i32.const 0) "key")
+          (data (i32.const 8) "stored")
+          (data (i32.const 16) "not stored")
+          ;; Stores the input under "key".
+          (func $set (export "set")
+            (call $input (i64.const 0))
+            (call $read_register (i64.const 0) (i64.const 64))
+            (drop (call $storage_write
+              (i64.const 3) (i64.const 0)
+              (call $register_len (i64.const 0)) (i64.const 64)
+              (i64.const 1)))
+            (call $log_utf8 (i64.const 6) (i64.const 8)))
+          ;; Returns the value stored under "key".
+          (func (export "get")
+            (if (i64.eqz (call $storage_read (i64.const 3) (i64.const 0) (i64.const 0)))
+              (then (call $panic_utf8 (i64.const 10) (i64.const 16))))
+            (call $read_register (i64.const 0) (i64.const 64))
+            (call $value_return (call $register_len (i64.const 0)) (i64.const 64)))
+          (func (export "set_and_panic")
+            (call $set)
+            (call $panic_utf8 (i64.const 10) (i64.const 16)))
+          (func (export "spin")
+            (loop $spin (br $spin))))
+    "#;
+
+    fn runtime() -> TestRuntime {
+        TestRuntime::new(wat::parse_str(CONTRACT).unwrap())
+    }
+
+    #[test]
+    fn call_methods() {
+        let mut runtime = runtime();
+        let context = VMContextBuilder::new().build();
+        let outcome = runtime.call(context.clone(), "set", b"\"value\"");
+        assert_eq!(outcome.unwrap(), None);
+        assert_eq!(outcome.logs, vec!["stored".to_string()]);
+        assert!(outcome.burnt_gas.0 > 0);
+        assert_eq!(runtime.storage().get(&b"key"[..]), Some(&b"\"value\"".to_vec()));
+
+        let outcome = runtime.call(context, "get", &[]);
+        assert_eq!(outcome.unwrap_json::<String>(), "value");
+    }
+
+    #[test]
+    fn failed_call_discards_changes() {
+        let mut runtime = runtime();
+        let context = VMContextBuilder::new().build();
+        let outcome = runtime.call(context.clone(), "set_and_panic", b"value");
+        assert_eq!(outcome.unwrap_err(), "Smart contract panicked: not stored");
+        assert!(runtime.storage().is_empty());
+
+        let outcome = runtime.call(context, "missing", &[]);
+        assert_eq!(outcome.unwrap_err(), "Method missing is not exported by the contract");
+    }
+
+    #[test]
+    fn exceeded_gas() {
+        let mut runtime = runtime();
+        let context = VMContextBuilder::new().prepaid_gas(Gas::from_tgas(5)).build();
+        let outcome = runtime.call(context, "spin", &[]);
+        assert_eq!(outcome.unwrap_err(), "Exceeded the prepaid gas.");
+        assert_eq!(outcome.burnt_gas, Gas::from_tgas(5));
+
+        runtime.set_config(VMConfigBuilder::new().max_gas_burnt(Gas::from_tgas(1)).build());
+        let context = VMContextBuilder::new().build();
+        let outcome = runtime.call(context, "spin", &[]);
+        assert_eq!(
+            outcome.unwrap_err(),
+            "Exceeded the maximum amount of gas allowed to burn per contract."
+        );
+    }
+}
@@ -0,0 +1,117 @@
+//! Canonical JSON serialization, which produces the same bytes for the same value regardless of
+//! the order of map entries or the version of the SDK, so that hashes and signatures over JSON
+//! can be reproduced.
+//!
+//! The canonical form is compact JSON, with the keys of every object sorted by their UTF-8
+//! bytes. Floating point numbers are rejected, since their textual representation is not fixed;
+//! integers wider than 64 bits are rejected too, and should be serialized as strings, like
+//! [`U128`](super::U128).
+//!
+//! With the `canonical-json` feature, the results of `#[near_bindgen]` methods and
+//! [`env::value_return_json`](crate::env::value_return_json) are serialized canonically.
+//!
+//! # Examples
+//! ```
+//! use near_sdk::json_types::canonical;
+//! use std::collections::HashMap;
+//!
+//! let mut balances = HashMap::new();
+//! balances.insert("carol", 3);
+//! balances.insert("alice", 1);
+//! balances.insert("bob", 2);
+//! assert_eq!(canonical::to_string(&balances).unwrap(), r#"{"alice":1,"bob":2,"carol":3}"#);
+//! assert!(canonical::to_string(&1.5).is_err());
+//! ```
+
+use serde::ser::Error as _;
+use serde::Serialize;
+use serde_json::{Error, Value};
+
+const ERR_FLOAT: &str = "Floating point numbers are not supported in canonical JSON";
+
+/// Serializes the value as canonical JSON bytes.
+pub fn to_vec<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, Error> {
+    let mut out = Vec::new();
+    write_value(&mut out, &serde_json::to_value(value)?)?;
+    Ok(out)
+}
+
+/// Serializes the value as a canonical JSON string.
+pub fn to_string<T: Serialize + ?Sized>(value: &T) -> Result<String, Error> {
+    // Only valid UTF-8 is written, as the strings are written by `serde_json`.
+    to_vec(value).map(|bytes| String::from_utf8(bytes).unwrap_or_else(|_| crate::env::abort()))
+}
+
+/// Serializes the result of a contract method as JSON, canonically with the `canonical-json`
+/// feature.
+#[doc(hidden)]
+pub fn result_to_vec<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, Error> {
+    if cfg!(feature = "canonical-json") {
+        to_vec(value)
+    } else {
+        serde_json::to_vec(value)
+    }
+}
+
+fn write_value(out: &mut Vec<u8>, value: &Value) -> Result<(), Error> {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_unstable_by_key(|(key, _)| *key);
+            out.push(b'{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                serde_json::to_writer(&mut *out, key)?;
+                out.push(b':');
+                write_value(out, value)?;
+            }
+            out.push(b'}');
+        }
+        Value::Array(items) => {
+            out.push(b'[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                write_value(out, item)?;
+            }
+            out.push(b']');
+        }
+        Value::Number(number) if number.is_f64() => return Err(Error::custom(ERR_FLOAT)),
+        _ => serde_json::to_writer(&mut *out, value)?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json_types::U128;
+    use std::collections::HashMap;
+
+    #[derive(Serialize)]
+    struct Event {
+        standard: &'static str,
+        data: Vec<HashMap<String, U128>>,
+    }
+
+    #[test]
+    fn sorts_nested_keys() {
+        let mut amounts = HashMap::new();
+        amounts.insert("zed".to_string(), U128(u128::MAX));
+        amounts.insert("a\"b".to_string(), U128(0));
+        let event = Event { standard: "nep141", data: vec![amounts] };
+        assert_eq!(
+            to_string(&event).unwrap(),
+            format!(r#"{{"data":[{{"a\"b":"0","zed":"{}"}}],"standard":"nep141"}}"#, u128::MAX)
+        );
+    }
+
+    #[test]
+    fn rejects_floats() {
+        assert!(to_vec(&vec![1.0f64]).is_err());
+        assert_eq!(to_vec(&vec![-1i64, 2]).unwrap(), b"[-1,2]");
+    }
+}
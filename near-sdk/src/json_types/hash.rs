@@ -30,6 +30,17 @@ impl ser::Serialize for Base58CryptoHash {
     }
 }
 
+#[cfg(feature = "abi")]
+impl schemars::JsonSchema for Base58CryptoHash {
+    fn schema_name() -> String {
+        "Base58CryptoHash".to_string()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        <String as schemars::JsonSchema>::json_schema(gen)
+    }
+}
+
 impl<'de> de::Deserialize<'de> for Base58CryptoHash {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -23,6 +23,24 @@ macro_rules! impl_str_type {
             }
         }
 
+        #[cfg(feature = "test-utils")]
+        impl<'a> arbitrary::Arbitrary<'a> for $iden {
+            fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+                Ok(Self(u.arbitrary()?))
+            }
+        }
+
+        #[cfg(feature = "abi")]
+        impl schemars::JsonSchema for $iden {
+            fn schema_name() -> String {
+                stringify!($iden).to_string()
+            }
+
+            fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+                <String as schemars::JsonSchema>::json_schema(gen)
+            }
+        }
+
         impl Serialize for $iden {
             fn serialize<S>(
                 &self,
@@ -1,5 +1,6 @@
 //! Helper types for JSON serialization.
 
+pub mod canonical;
 mod hash;
 mod integers;
 mod vector;
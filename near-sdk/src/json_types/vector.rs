@@ -50,6 +50,24 @@ mod base64_bytes {
     }
 }
 
+#[cfg(feature = "abi")]
+impl schemars::JsonSchema for Base64VecU8 {
+    fn schema_name() -> String {
+        "Base64VecU8".to_string()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        <String as schemars::JsonSchema>::json_schema(gen)
+    }
+}
+
+#[cfg(feature = "test-utils")]
+impl<'a> arbitrary::Arbitrary<'a> for Base64VecU8 {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self(u.arbitrary()?))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2,10 +2,15 @@
 extern crate quickcheck;
 
 pub use near_sdk_macros::{
-    callback, callback_vec, ext_contract, init, metadata, near_bindgen, result_serializer,
-    serializer, BorshStorageKey, PanicOnDefault,
+    access_control, callback, callback_vec, event, ext_contract, init, metadata, near_bindgen,
+    pausable, result_serializer, serializer, BorshStorageKey, FunctionError, NearEvent, Ownable,
+    PanicOnDefault,
 };
 
+// The generated code implements a trait of `store`.
+#[cfg(feature = "unstable")]
+pub use near_sdk_macros::VersionedState;
+
 #[cfg(feature = "unstable")]
 pub mod store;
 
@@ -16,17 +21,28 @@ pub mod collections;
 mod environment;
 pub use environment::env;
 
-#[cfg(feature = "unstable")]
+#[cfg(feature = "unstable-sys")]
+pub use environment::sys;
+#[cfg(all(feature = "unstable", not(feature = "unstable-sys")))]
 pub use near_sys as sys;
 
 mod promise;
-pub use promise::{Promise, PromiseOrValue};
+pub use promise::{AccessKeyAction, Promise, PromiseOrValue, YieldedPromise};
 
 mod metadata;
 pub use metadata::{Metadata, MethodMetadata};
 
+#[cfg(feature = "abi")]
+pub mod abi;
+
+pub mod access_control;
+
 pub mod json_types;
 
+pub mod ownable;
+
+pub mod pausable;
+
 mod types;
 pub use crate::types::*;
 
@@ -35,6 +51,8 @@ pub use environment::mock;
 #[cfg(not(target_arch = "wasm32"))]
 // Re-export to avoid breakages
 pub use environment::mock::MockedBlockchain;
+#[cfg(all(feature = "test-runtime", not(target_arch = "wasm32")))]
+pub use environment::test_runtime;
 #[cfg(not(target_arch = "wasm32"))]
 pub use near_vm_logic::VMConfig;
 #[cfg(not(target_arch = "wasm32"))]
@@ -68,3 +86,10 @@ pub use serde;
 
 #[doc(hidden)]
 pub use serde_json;
+
+#[cfg(feature = "abi")]
+pub use schemars;
+
+#[cfg(feature = "test-utils")]
+#[doc(hidden)]
+pub use arbitrary;
@@ -0,0 +1,119 @@
+//! Owner of the contract, kept in the storage of the contract, and the guard of the methods which
+//! only the owner can call.
+//!
+//! The contract gets the methods managing its owner with `#[derive(Ownable)]` on its struct, and
+//! the methods of its `#[near_bindgen]` sections are restricted to the owner with `#[only_owner]`:
+//!
+//! ```
+//! use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+//! use near_sdk::{near_bindgen, ownable, AccountId, Ownable, PanicOnDefault};
+//!
+//! #[near_bindgen]
+//! #[derive(BorshDeserialize, BorshSerialize, Ownable, PanicOnDefault)]
+//! pub struct Contract {
+//!     fee: u64,
+//! }
+//!
+//! #[near_bindgen]
+//! impl Contract {
+//!     #[init]
+//!     pub fn new(owner_id: AccountId) -> Self {
+//!         ownable::set_owner(&owner_id);
+//!         Self { fee: 0 }
+//!     }
+//!
+//!     #[only_owner]
+//!     pub fn set_fee(&mut self, fee: u64) {
+//!         self.fee = fee;
+//!     }
+//! }
+//! ```
+//!
+//! The ownership is transferred in two steps: the owner proposes the new owner with the
+//! `transfer_ownership` method, which becomes the owner when it calls `accept_ownership`. The
+//! contract also gets the `owner` and `pending_owner` view methods.
+
+use crate::{env, AccountId};
+
+/// Storage key of the owner.
+const OWNER_KEY: &[u8] = b"__owner";
+
+/// Storage key of the proposed owner, until it accepts the ownership.
+const PENDING_OWNER_KEY: &[u8] = b"__pending_owner";
+
+fn read_account(key: &[u8]) -> Option<AccountId> {
+    env::storage_read(key).map(|value| {
+        let account_id = String::from_utf8(value).unwrap_or_else(|_| env::abort());
+        AccountId::new_unchecked(account_id)
+    })
+}
+
+/// Returns the owner of the contract.
+pub fn owner() -> Option<AccountId> {
+    read_account(OWNER_KEY)
+}
+
+/// Sets the owner of the contract, which is usually called in the initialization method.
+pub fn set_owner(owner_id: &AccountId) {
+    env::storage_write(OWNER_KEY, owner_id.as_bytes());
+}
+
+/// Returns the proposed owner of the contract, until it accepts the ownership.
+pub fn pending_owner() -> Option<AccountId> {
+    read_account(PENDING_OWNER_KEY)
+}
+
+/// Proposes the new owner of the contract, replacing the previous proposal.
+pub fn propose_owner(owner_id: &AccountId) {
+    env::storage_write(PENDING_OWNER_KEY, owner_id.as_bytes());
+}
+
+/// Makes the predecessor the owner of the contract, and panics unless it is the proposed owner.
+pub fn accept_ownership() {
+    let predecessor = env::predecessor_account_id();
+    if pending_owner().as_ref() != Some(&predecessor) {
+        env::panic_str("Only the proposed owner can accept the ownership")
+    }
+    env::storage_remove(PENDING_OWNER_KEY);
+    set_owner(&predecessor);
+}
+
+/// Panics unless the predecessor is the owner, which is the guard of the methods marked with
+/// `#[only_owner]`.
+pub fn require_owner(method: &str) {
+    if owner() != Some(env::predecessor_account_id()) {
+        env::panic_str(&format!("Method {} can only be called by the owner", method))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{accounts, VMContextBuilder};
+    use crate::{assert_contract_panics, testing_env};
+
+    #[test]
+    fn transfer_ownership() {
+        testing_env!(VMContextBuilder::new().predecessor_account_id(accounts(1)).build());
+        assert_contract_panics!(
+            require_owner("set_fee"),
+            "Method set_fee can only be called by the owner"
+        );
+        set_owner(&accounts(1));
+        assert_eq!(owner(), Some(accounts(1)));
+        require_owner("set_fee");
+
+        propose_owner(&accounts(2));
+        assert_eq!(pending_owner(), Some(accounts(2)));
+        assert_contract_panics!(
+            accept_ownership(),
+            "Only the proposed owner can accept the ownership"
+        );
+
+        testing_env!(VMContextBuilder::new().predecessor_account_id(accounts(2)).build());
+        accept_ownership();
+        assert_eq!(owner(), Some(accounts(2)));
+        assert_eq!(pending_owner(), None);
+        require_owner("set_fee");
+    }
+}
@@ -0,0 +1,108 @@
+//! Features of the contract which can be paused in an emergency, kept in the storage of the
+//! contract, and the guard of the methods which cannot be called while their feature is paused.
+//!
+//! The contract gets the methods pausing its features with `#[pausable]` on its struct, and the
+//! methods of its `#[near_bindgen]` sections are guarded with `#[when_not_paused]`:
+//!
+//! ```
+//! use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+//! use near_sdk::{access_control, near_bindgen, pausable, AccountId, PanicOnDefault};
+//!
+//! #[near_bindgen]
+//! #[access_control(roles(Admin))]
+//! #[pausable(manager_roles(Admin))]
+//! #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+//! pub struct Contract {
+//!     total: u64,
+//! }
+//!
+//! #[near_bindgen]
+//! impl Contract {
+//!     #[init]
+//!     pub fn new(admin_id: AccountId) -> Self {
+//!         access_control::grant_role("Admin", &admin_id);
+//!         Self { total: 0 }
+//!     }
+//!
+//!     // Paused with the `deposit` feature, or with all the features.
+//!     #[when_not_paused]
+//!     pub fn deposit(&mut self, amount: u64) {
+//!         self.total += amount;
+//!     }
+//!
+//!     // Paused with the `transfers` feature, or with all the features.
+//!     #[when_not_paused("transfers")]
+//!     pub fn withdraw(&mut self, amount: u64) {
+//!         self.total -= amount;
+//!     }
+//! }
+//! ```
+//!
+//! The feature of a method is its name, unless it is given to `#[when_not_paused(..)]`, and pausing
+//! the [`ALL_FEATURES`] feature pauses all the methods. The `pa_pause_feature` and
+//! `pa_unpause_feature` methods can be called by the contract itself and by the accounts with the
+//! roles given as `manager_roles(..)`, which are declared with `#[access_control]`.
+
+use crate::env;
+use borsh::BorshSerialize;
+
+/// Feature which pauses all the methods guarded with `#[when_not_paused]`.
+pub const ALL_FEATURES: &str = "ALL";
+
+/// Prefix of the storage keys of the paused features.
+const PAUSED_PREFIX: &[u8] = b"__paused";
+
+fn feature_key(feature: &str) -> Vec<u8> {
+    let mut key = PAUSED_PREFIX.to_vec();
+    feature.serialize(&mut key).unwrap_or_else(|_| env::abort());
+    key
+}
+
+/// Returns whether the feature is paused, or all the features are.
+pub fn is_paused(feature: &str) -> bool {
+    env::storage_has_key(&feature_key(feature)) || env::storage_has_key(&feature_key(ALL_FEATURES))
+}
+
+/// Pauses the feature, and returns whether it was not paused.
+pub fn pause(feature: &str) -> bool {
+    !env::storage_write(&feature_key(feature), &[])
+}
+
+/// Unpauses the feature, and returns whether it was paused.
+pub fn unpause(feature: &str) -> bool {
+    env::storage_remove(&feature_key(feature))
+}
+
+/// Panics if the feature is paused, which is the guard of the methods marked with
+/// `#[when_not_paused]`.
+pub fn require_not_paused(feature: &str, method: &str) {
+    if is_paused(feature) {
+        env::panic_str(&format!("Method {} is paused", method))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_contract_panics;
+
+    #[test]
+    fn pause_and_unpause() {
+        assert!(pause("transfers"));
+        assert!(!pause("transfers"));
+        assert!(is_paused("transfers"));
+        assert!(!is_paused("deposit"));
+        assert_contract_panics!(
+            require_not_paused("transfers", "withdraw"),
+            "Method withdraw is paused"
+        );
+        require_not_paused("deposit", "deposit");
+
+        pause(ALL_FEATURES);
+        assert!(is_paused("deposit"));
+        assert!(unpause(ALL_FEATURES));
+        assert!(unpause("transfers"));
+        assert!(!unpause("transfers"));
+        assert!(!is_paused("transfers"));
+    }
+}
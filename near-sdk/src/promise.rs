@@ -1,10 +1,12 @@
-use borsh::BorshSchema;
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::fmt;
 use std::io::{Error, Write};
+use std::marker::PhantomData;
 use std::rc::Rc;
 
-use crate::{AccountId, Balance, Gas, PromiseIndex, PublicKey};
+use crate::{AccountId, Balance, CryptoHash, Gas, PromiseIndex, PublicKey};
 
 pub enum PromiseAction {
     CreateAccount,
@@ -93,6 +95,137 @@ impl PromiseAction {
     }
 }
 
+/// An action on an access key of an account, which can be added to a [`Promise`] with
+/// [`Promise::access_key_action`] or to a promise batch with
+/// [`env::promise_batch_action_access_key`](crate::env::promise_batch_action_access_key).
+///
+/// # Examples
+/// ```no_run
+/// use near_sdk::{env, AccessKeyAction, Promise};
+///
+/// let key = env::signer_account_pk();
+/// let add = AccessKeyAction::add_function_call_key(key.clone(), env::current_account_id())
+///     .with_allowance(250_000_000_000_000_000_000_000)
+///     .with_method_names(["vote", "claim"]);
+/// Promise::new(env::current_account_id())
+///     .access_key_action(AccessKeyAction::delete_key(key))
+///     .access_key_action(add);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum AccessKeyAction {
+    /// Adds a key with full access to the account.
+    AddFullAccessKey { public_key: PublicKey, nonce: u64 },
+    /// Adds a key which can only call the methods `method_names` on `receiver_id`, or any method
+    /// if `method_names` is empty, spending at most `allowance` on gas. An allowance of zero is
+    /// unlimited.
+    AddFunctionCallKey {
+        public_key: PublicKey,
+        nonce: u64,
+        allowance: Balance,
+        receiver_id: AccountId,
+        method_names: Vec<String>,
+    },
+    /// Deletes a key from the account.
+    DeleteKey { public_key: PublicKey },
+}
+
+impl AccessKeyAction {
+    /// Adds a key with full access to the account, with a nonce of zero.
+    pub fn add_full_access_key(public_key: PublicKey) -> Self {
+        Self::AddFullAccessKey { public_key, nonce: 0 }
+    }
+
+    /// Adds a key which can call any method on `receiver_id`, with an unlimited allowance and a
+    /// nonce of zero.
+    pub fn add_function_call_key(public_key: PublicKey, receiver_id: AccountId) -> Self {
+        Self::AddFunctionCallKey {
+            public_key,
+            nonce: 0,
+            allowance: 0,
+            receiver_id,
+            method_names: Vec::new(),
+        }
+    }
+
+    /// Deletes a key from the account.
+    pub fn delete_key(public_key: PublicKey) -> Self {
+        Self::DeleteKey { public_key }
+    }
+
+    /// Sets the nonce of the key which is added.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the action deletes a key.
+    pub fn with_nonce(mut self, nonce: u64) -> Self {
+        match &mut self {
+            Self::AddFullAccessKey { nonce: n, .. } | Self::AddFunctionCallKey { nonce: n, .. } => {
+                *n = nonce
+            }
+            Self::DeleteKey { .. } => {
+                crate::env::panic_str("Cannot set the nonce of a deleted key")
+            }
+        }
+        self
+    }
+
+    /// Sets the allowance of the function call key which is added.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the action does not add a function call key.
+    pub fn with_allowance(mut self, allowance: Balance) -> Self {
+        match &mut self {
+            Self::AddFunctionCallKey { allowance: a, .. } => *a = allowance,
+            _ => crate::env::panic_str("Only function call keys have an allowance"),
+        }
+        self
+    }
+
+    /// Restricts the function call key which is added to the given methods.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the action does not add a function call key.
+    pub fn with_method_names<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        match &mut self {
+            Self::AddFunctionCallKey { method_names, .. } => {
+                method_names.extend(names.into_iter().map(Into::into))
+            }
+            _ => crate::env::panic_str("Only function call keys are restricted to methods"),
+        }
+        self
+    }
+}
+
+impl From<AccessKeyAction> for PromiseAction {
+    fn from(action: AccessKeyAction) -> Self {
+        match action {
+            AccessKeyAction::AddFullAccessKey { public_key, nonce } => {
+                PromiseAction::AddFullAccessKey { public_key, nonce }
+            }
+            AccessKeyAction::AddFunctionCallKey {
+                public_key,
+                nonce,
+                allowance,
+                receiver_id,
+                method_names,
+            } => PromiseAction::AddAccessKey {
+                public_key,
+                allowance,
+                receiver_id,
+                method_names: method_names.join(","),
+                nonce,
+            },
+            AccessKeyAction::DeleteKey { public_key } => PromiseAction::DeleteKey { public_key },
+        }
+    }
+}
+
 pub struct PromiseSingle {
     pub account_id: AccountId,
     pub actions: RefCell<Vec<PromiseAction>>,
@@ -309,6 +442,11 @@ impl Promise {
         self.add_action(PromiseAction::DeleteKey { public_key })
     }
 
+    /// Add an action on an access key of the given account.
+    pub fn access_key_action(self, action: AccessKeyAction) -> Self {
+        self.add_action(action.into())
+    }
+
     /// Delete the given account.
     pub fn delete_account(self, beneficiary_id: AccountId) -> Self {
         self.add_action(PromiseAction::DeleteAccount { beneficiary_id })
@@ -452,6 +590,38 @@ where
     }
 }
 
+/// The result of a promise is the result of the last method it calls, which can be any value.
+#[cfg(feature = "abi")]
+impl schemars::JsonSchema for Promise {
+    fn schema_name() -> String {
+        "Promise".to_string()
+    }
+
+    fn json_schema(_gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        schemars::schema::Schema::Bool(true)
+    }
+
+    fn is_referenceable() -> bool {
+        false
+    }
+}
+
+/// Like [`BorshSchema`], the schema is the schema of the value, which the promise should return.
+#[cfg(feature = "abi")]
+impl<T: schemars::JsonSchema> schemars::JsonSchema for PromiseOrValue<T> {
+    fn schema_name() -> String {
+        T::schema_name()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        gen.subschema_for::<T>()
+    }
+
+    fn is_referenceable() -> bool {
+        false
+    }
+}
+
 impl<T> From<Promise> for PromiseOrValue<T> {
     fn from(promise: Promise) -> Self {
         PromiseOrValue::Promise(promise)
@@ -468,3 +638,79 @@ impl<T: borsh::BorshSerialize> borsh::BorshSerialize for PromiseOrValue<T> {
         }
     }
 }
+
+/// A handle to a promise created with [`env::promise_yield_create`], which waits for a value of
+/// type `T` from outside the contract, such as an oracle or an MPC signer.
+///
+/// The handle can be stored in the contract state, and the value is passed to the callback when a
+/// later function call resumes the promise with [`resume`]. The value is serialized as JSON, so
+/// the callback can receive it as a `#[callback_result]` argument of type `T`. The callback
+/// receives a failed result instead if the promise is not resumed before it times out.
+///
+/// # Examples
+/// ```no_run
+/// use near_sdk::{env, Gas, YieldedPromise};
+///
+/// let (promise_index, request) =
+///     YieldedPromise::<String>::new("on_signature", b"{}", Gas::from_tgas(10));
+/// env::promise_return(promise_index);
+///
+/// // Later, in the function call which receives the signature.
+/// request.resume(&"signature".to_string());
+/// ```
+///
+/// [`env::promise_yield_create`]: crate::env::promise_yield_create
+/// [`resume`]: Self::resume
+pub struct YieldedPromise<T> {
+    data_id: CryptoHash,
+    payload: PhantomData<fn(T)>,
+}
+
+impl<T> YieldedPromise<T>
+where
+    T: serde::Serialize,
+{
+    /// Creates a promise which calls `function_name` on the current account with the `arguments`
+    /// once it is resumed, attaching `gas` to the call.
+    ///
+    /// Returns the index of the promise, which can be returned from the function call with
+    /// [`env::promise_return`](crate::env::promise_return), and the handle to resume it.
+    pub fn new(function_name: &str, arguments: &[u8], gas: Gas) -> (PromiseIndex, Self) {
+        let (promise_index, data_id) =
+            crate::env::promise_yield_create(function_name, arguments, gas, 0);
+        (promise_index, Self { data_id, payload: PhantomData })
+    }
+
+    /// Returns the data id which identifies the promise.
+    pub fn data_id(&self) -> &CryptoHash {
+        &self.data_id
+    }
+
+    /// Resumes the promise, passing the value to the callback. Returns `false` if the promise
+    /// was already resumed or timed out.
+    pub fn resume(&self, value: &T) -> bool {
+        let payload = serde_json::to_vec(value)
+            .unwrap_or_else(|_| crate::env::panic_str("Failed to serialize the resumed value."));
+        crate::env::promise_yield_resume(&self.data_id, &payload)
+    }
+}
+
+//? Manual implementations needed only because borsh derive is leaking field types
+// https://github.com/near/borsh-rs/issues/41
+impl<T> BorshSerialize for YieldedPromise<T> {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        BorshSerialize::serialize(&self.data_id, writer)
+    }
+}
+
+impl<T> BorshDeserialize for YieldedPromise<T> {
+    fn deserialize(buf: &mut &[u8]) -> Result<Self, Error> {
+        Ok(Self { data_id: BorshDeserialize::deserialize(buf)?, payload: PhantomData })
+    }
+}
+
+impl<T> fmt::Debug for YieldedPromise<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("YieldedPromise").field("data_id", &self.data_id).finish()
+    }
+}
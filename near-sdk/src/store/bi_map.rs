@@ -0,0 +1,311 @@
+use std::borrow::Borrow;
+use std::fmt;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::key::ToKey;
+use super::{LookupMap, ERR_INCONSISTENT_STATE};
+use crate::crypto_hash::Sha256;
+use crate::{env, IntoStorageKey};
+
+/// A non-iterable, lazily loaded storage map which keeps a one-to-one mapping between left and
+/// right values, and can be looked up in both directions.
+///
+/// The mapping is stored in two [`LookupMap`]s, one for each direction, which are always updated
+/// together. Inserting a pair removes any existing pairs which contain either of its values, so
+/// each left value maps to at most one right value and vice versa.
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::BiMap;
+///
+/// let mut tokens = BiMap::new(b"t");
+/// tokens.insert("alice".to_string(), 1u64);
+/// tokens.insert("bob".to_string(), 2);
+///
+/// assert_eq!(tokens.get_by_left("alice"), Some(&1));
+/// assert_eq!(tokens.get_by_right(&2).map(String::as_str), Some("bob"));
+///
+/// // Inserting a pair replaces the existing pairs of both values.
+/// tokens.insert("alice".to_string(), 2);
+/// assert_eq!(tokens.get_by_right(&1), None);
+/// assert_eq!(tokens.get_by_left("bob"), None);
+///
+/// assert_eq!(tokens.remove_by_right(&2), Some("alice".to_string()));
+/// assert!(!tokens.contains_left("alice"));
+/// ```
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct BiMap<L, R, H = Sha256>
+where
+    L: BorshSerialize + Ord,
+    R: BorshSerialize + Ord,
+    H: ToKey,
+{
+    left_to_right: LookupMap<L, R, H>,
+    right_to_left: LookupMap<R, L, H>,
+}
+
+impl<L, R, H> fmt::Debug for BiMap<L, R, H>
+where
+    L: BorshSerialize + Ord,
+    R: BorshSerialize + Ord,
+    H: ToKey,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BiMap")
+            .field("left_to_right", &self.left_to_right)
+            .field("right_to_left", &self.right_to_left)
+            .finish()
+    }
+}
+
+impl<L, R> BiMap<L, R, Sha256>
+where
+    L: BorshSerialize + Ord,
+    R: BorshSerialize + Ord,
+{
+    #[inline]
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self::with_hasher(prefix)
+    }
+}
+
+impl<L, R, H> BiMap<L, R, H>
+where
+    L: BorshSerialize + Ord,
+    R: BorshSerialize + Ord,
+    H: ToKey,
+{
+    /// Initialize a [`BiMap`] with a custom hash function.
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::crypto_hash::Keccak256;
+    /// use near_sdk::store::BiMap;
+    ///
+    /// let map = BiMap::<String, u64, Keccak256>::with_hasher(b"m");
+    /// ```
+    pub fn with_hasher<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        let mut left_key = prefix.into_storage_key();
+        let right_key = [left_key.as_slice(), b"r"].concat();
+        left_key.push(b'l');
+        Self {
+            left_to_right: LookupMap::with_hasher(left_key),
+            right_to_left: LookupMap::with_hasher(right_key),
+        }
+    }
+
+    /// Flushes the intermediate values of the map before this is called when the structure is
+    /// [`Drop`]ed. This will write all modified values to storage but keep all cached values
+    /// in memory.
+    pub fn flush(&mut self) {
+        self.left_to_right.flush();
+        self.right_to_left.flush();
+    }
+}
+
+impl<L, R, H> BiMap<L, R, H>
+where
+    L: BorshSerialize + BorshDeserialize + Ord + Clone,
+    R: BorshSerialize + BorshDeserialize + Ord + Clone,
+    H: ToKey,
+{
+    /// Returns the right value associated with the left value.
+    ///
+    /// The value may be any borrowed form of the left type, but [`BorshSerialize`] and
+    /// [`ToOwned<Owned = L>`](ToOwned) on the borrowed form *must* match those for the left
+    /// type.
+    pub fn get_by_left<Q: ?Sized>(&self, left: &Q) -> Option<&R>
+    where
+        L: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = L>,
+    {
+        self.left_to_right.get(left)
+    }
+
+    /// Returns the left value associated with the right value.
+    ///
+    /// The value may be any borrowed form of the right type, but [`BorshSerialize`] and
+    /// [`ToOwned<Owned = R>`](ToOwned) on the borrowed form *must* match those for the right
+    /// type.
+    pub fn get_by_right<Q: ?Sized>(&self, right: &Q) -> Option<&L>
+    where
+        R: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = R>,
+    {
+        self.right_to_left.get(right)
+    }
+
+    /// Returns `true` if the map contains a pair with the left value.
+    pub fn contains_left<Q: ?Sized>(&self, left: &Q) -> bool
+    where
+        L: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = L> + Ord,
+    {
+        self.left_to_right.contains_key(left)
+    }
+
+    /// Returns `true` if the map contains a pair with the right value.
+    pub fn contains_right<Q: ?Sized>(&self, right: &Q) -> bool
+    where
+        R: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = R> + Ord,
+    {
+        self.right_to_left.contains_key(right)
+    }
+
+    /// Inserts a pair into the map. Any existing pairs which contain either the left or the right
+    /// value are removed first, and are returned as `(left_pair, right_pair)`.
+    ///
+    /// # Examples
+    /// ```
+    /// use near_sdk::store::BiMap;
+    ///
+    /// let mut map = BiMap::new(b"m");
+    /// assert_eq!(map.insert(1u8, 10u8), (None, None));
+    /// assert_eq!(map.insert(2, 20), (None, None));
+    /// assert_eq!(map.insert(1, 20), (Some((1, 10)), Some((2, 20))));
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn insert(&mut self, left: L, right: R) -> (Option<(L, R)>, Option<(L, R)>) {
+        let left_pair = self.remove_by_left(&left).map(|r| (left.clone(), r));
+        let right_pair = self.remove_by_right(&right).map(|l| (l, right.clone()));
+        self.left_to_right.set(left.clone(), Some(right.clone()));
+        self.right_to_left.set(right, Some(left));
+        (left_pair, right_pair)
+    }
+
+    /// Removes the pair with the left value from the map, returning the right value of the pair.
+    pub fn remove_by_left<Q: ?Sized>(&mut self, left: &Q) -> Option<R>
+    where
+        L: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = L>,
+    {
+        let right = self.left_to_right.remove(left)?;
+        self.right_to_left.remove(&right).unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE));
+        Some(right)
+    }
+
+    /// Removes the pair with the right value from the map, returning the left value of the pair.
+    pub fn remove_by_right<Q: ?Sized>(&mut self, right: &Q) -> Option<L>
+    where
+        R: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = R>,
+    {
+        let left = self.right_to_left.remove(right)?;
+        self.left_to_right.remove(&left).unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE));
+        Some(left)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::BiMap;
+    use crate::test_utils::test_env::setup_free;
+    use arbitrary::{Arbitrary, Unstructured};
+    use rand::RngCore;
+    use rand::SeedableRng;
+    use std::collections::HashMap;
+
+    #[test]
+    fn insert_replaces_both_directions() {
+        let mut map = BiMap::new(b"b");
+        map.insert(1u32, 10u32);
+        map.insert(2, 20);
+        assert_eq!(map.insert(1, 20), (Some((1, 10)), Some((2, 20))));
+        assert_eq!(map.get_by_left(&1), Some(&20));
+        assert_eq!(map.get_by_right(&20), Some(&1));
+        assert!(!map.contains_left(&2));
+        assert!(!map.contains_right(&10));
+        map.flush();
+
+        let mut map = BiMap::<u32, u32>::new(b"b");
+        assert_eq!(map.get_by_right(&20), Some(&1));
+        assert_eq!(map.remove_by_left(&1), Some(20));
+        assert_eq!(map.remove_by_right(&20), None);
+        map.flush();
+        assert!(crate::mock::with_mocked_blockchain(|b| b.take_storage()).is_empty());
+    }
+
+    #[derive(Arbitrary, Debug)]
+    enum Op {
+        Insert(u8, u8),
+        RemoveLeft(u8),
+        RemoveRight(u8),
+        GetLeft(u8),
+        GetRight(u8),
+        Flush,
+        Restore,
+    }
+
+    #[test]
+    fn arbitrary() {
+        setup_free();
+
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(0);
+        let mut buf = vec![0; 4096];
+        for _ in 0..512 {
+            // Clear storage in-between runs
+            crate::mock::with_mocked_blockchain(|b| b.take_storage());
+            rng.fill_bytes(&mut buf);
+
+            let mut bm = BiMap::new(b"b");
+            let mut lr = HashMap::new();
+            let mut rl = HashMap::new();
+            let u = Unstructured::new(&buf);
+            if let Ok(ops) = Vec::<Op>::arbitrary_take_rest(u) {
+                for op in ops {
+                    match op {
+                        Op::Insert(l, r) => {
+                            let left_pair = lr.remove(&l).map(|r| {
+                                rl.remove(&r);
+                                (l, r)
+                            });
+                            let right_pair = rl.remove(&r).map(|l| {
+                                lr.remove(&l);
+                                (l, r)
+                            });
+                            lr.insert(l, r);
+                            rl.insert(r, l);
+                            assert_eq!(bm.insert(l, r), (left_pair, right_pair));
+                        }
+                        Op::RemoveLeft(l) => {
+                            let expected = lr.remove(&l);
+                            if let Some(r) = expected {
+                                rl.remove(&r);
+                            }
+                            assert_eq!(bm.remove_by_left(&l), expected);
+                        }
+                        Op::RemoveRight(r) => {
+                            let expected = rl.remove(&r);
+                            if let Some(l) = expected {
+                                lr.remove(&l);
+                            }
+                            assert_eq!(bm.remove_by_right(&r), expected);
+                        }
+                        Op::GetLeft(l) => {
+                            assert_eq!(bm.get_by_left(&l), lr.get(&l));
+                        }
+                        Op::GetRight(r) => {
+                            assert_eq!(bm.get_by_right(&r), rl.get(&r));
+                        }
+                        Op::Flush => {
+                            bm.flush();
+                        }
+                        Op::Restore => {
+                            bm.flush();
+                            bm = BiMap::new(b"b");
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,355 @@
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::vec::Iter;
+use super::{Vector, ERR_INCONSISTENT_STATE};
+use crate::{env, IntoStorageKey};
+
+/// A priority queue implemented with a binary heap that stores its content on the trie. This is a
+/// max-heap: [`pop`](Self::pop) and [`peek`](Self::peek) return the greatest element.
+///
+/// The heap is stored as a [`Vector`], so each element is stored in its own storage slot and only
+/// the elements on the path between the root and a leaf are loaded and written when sifting.
+/// [`push`](Self::push) and [`pop`](Self::pop) touch O(log(N)) storage slots, and
+/// [`peek`](Self::peek) only loads the root.
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::BinaryHeap;
+///
+/// let mut bids = BinaryHeap::new(b"h");
+/// bids.push(10u128);
+/// bids.push(50);
+/// bids.push(20);
+///
+/// assert_eq!(bids.peek(), Some(&50));
+/// assert_eq!(bids.pop(), Some(50));
+/// assert_eq!(bids.pop(), Some(20));
+/// assert_eq!(bids.len(), 1);
+/// ```
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct BinaryHeap<T>
+where
+    T: BorshSerialize,
+{
+    data: Vector<T>,
+}
+
+impl<T> BinaryHeap<T>
+where
+    T: BorshSerialize,
+{
+    /// Create new heap with zero elements. Prefixes storage accesss with the prefix provided.
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { data: Vector::new(prefix) }
+    }
+
+    /// Returns the number of elements in the heap.
+    pub fn len(&self) -> u32 {
+        self.data.len()
+    }
+
+    /// Returns `true` if the heap contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Removes all elements from the heap.
+    pub fn clear(&mut self) {
+        self.data.clear()
+    }
+
+    /// Removes up to `limit` of the elements stored last in the heap without loading them. The
+    /// removed elements are leaves of the heap, so the remaining elements are still a valid heap.
+    /// Returns `true` if there are elements left to remove.
+    pub fn clear_chunk(&mut self, limit: u32) -> bool {
+        self.data.clear_chunk(limit)
+    }
+
+    /// Flushes the cache and writes all modified values to storage.
+    pub fn flush(&mut self) {
+        self.data.flush()
+    }
+
+    /// Returns the number of storage bytes added by flushes of this heap since it was created
+    /// or loaded from storage.
+    pub fn bytes_written(&self) -> u64 {
+        self.data.bytes_written()
+    }
+
+    /// Returns the number of storage bytes released by flushes of this heap since it was
+    /// created or loaded from storage.
+    pub fn bytes_freed(&self) -> u64 {
+        self.data.bytes_freed()
+    }
+}
+
+impl<T> BinaryHeap<T>
+where
+    T: BorshSerialize + BorshDeserialize + Ord,
+{
+    /// Returns the greatest element of the heap, or `None` if it is empty.
+    pub fn peek(&self) -> Option<&T> {
+        self.data.get(0)
+    }
+
+    /// Returns a mutable reference to the greatest element of the heap, or `None` if it is empty.
+    /// If the element is modified, the heap is restored when the returned [`PeekMut`] is dropped.
+    ///
+    /// # Examples
+    /// ```
+    /// use near_sdk::store::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::new(b"h");
+    /// heap.extend([1, 5, 2]);
+    ///
+    /// if let Some(mut top) = heap.peek_mut() {
+    ///     *top = 0;
+    /// }
+    /// assert_eq!(heap.peek(), Some(&2));
+    /// ```
+    pub fn peek_mut(&mut self) -> Option<PeekMut<'_, T>> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(PeekMut { heap: self, modified: false })
+        }
+    }
+
+    /// Pushes an element onto the heap.
+    pub fn push(&mut self, element: T) {
+        self.data.push(element);
+        self.sift_up(self.len() - 1);
+    }
+
+    /// Removes the greatest element from the heap and returns it, or `None` if it is empty.
+    pub fn pop(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+        let top = self.data.swap_remove(0);
+        self.sift_down(0);
+        Some(top)
+    }
+
+    /// Returns an iterator visiting all elements of the heap in arbitrary order. This iterator
+    /// will lazily load any values iterated over from storage.
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.data.iter()
+    }
+
+    fn element(&self, index: u32) -> &T {
+        self.data.get(index).unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE))
+    }
+
+    fn sift_up(&mut self, mut index: u32) {
+        while index > 0 {
+            let parent = (index - 1) / 2;
+            if self.element(index) <= self.element(parent) {
+                break;
+            }
+            self.data.swap(index, parent);
+            index = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut index: u32) {
+        let len = self.len();
+        loop {
+            let mut largest = index;
+            for child in [2 * index + 1, 2 * index + 2] {
+                if child < len && self.element(child) > self.element(largest) {
+                    largest = child;
+                }
+            }
+            if largest == index {
+                break;
+            }
+            self.data.swap(index, largest);
+            index = largest;
+        }
+    }
+}
+
+/// Structure wrapping a mutable reference to the greatest element of a [`BinaryHeap`].
+///
+/// This `struct` is created by the `peek_mut` method on [`BinaryHeap`].
+pub struct PeekMut<'a, T>
+where
+    T: BorshSerialize + BorshDeserialize + Ord,
+{
+    heap: &'a mut BinaryHeap<T>,
+    /// Whether the element may have been modified and the heap needs to be restored on drop.
+    modified: bool,
+}
+
+impl<'a, T> PeekMut<'a, T>
+where
+    T: BorshSerialize + BorshDeserialize + Ord,
+{
+    /// Removes the peeked value from the heap and returns it.
+    pub fn pop(mut this: PeekMut<'a, T>) -> T {
+        this.modified = false;
+        this.heap.pop().unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE))
+    }
+}
+
+impl<'a, T> Deref for PeekMut<'a, T>
+where
+    T: BorshSerialize + BorshDeserialize + Ord,
+{
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.heap.element(0)
+    }
+}
+
+impl<'a, T> DerefMut for PeekMut<'a, T>
+where
+    T: BorshSerialize + BorshDeserialize + Ord,
+{
+    fn deref_mut(&mut self) -> &mut T {
+        self.modified = true;
+        self.heap.data.get_mut(0).unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE))
+    }
+}
+
+impl<'a, T> Drop for PeekMut<'a, T>
+where
+    T: BorshSerialize + BorshDeserialize + Ord,
+{
+    fn drop(&mut self) {
+        if self.modified {
+            self.heap.sift_down(0);
+        }
+    }
+}
+
+impl<T> Extend<T> for BinaryHeap<T>
+where
+    T: BorshSerialize + BorshDeserialize + Ord,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iter {
+            self.push(item)
+        }
+    }
+}
+
+impl<T> fmt::Debug for BinaryHeap<T>
+where
+    T: BorshSerialize + BorshDeserialize + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BinaryHeap").field("data", &self.data).finish()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::{BinaryHeap, PeekMut};
+    use crate::test_utils::test_env::setup_free;
+    use arbitrary::{Arbitrary, Unstructured};
+    use borsh::{BorshDeserialize, BorshSerialize};
+    use rand::RngCore;
+    use rand::SeedableRng;
+
+    #[test]
+    fn pops_in_order() {
+        let mut heap = BinaryHeap::new(b"h");
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(2);
+        let mut baseline: Vec<u32> = (0..100).map(|_| rng.next_u32() % 50).collect();
+        heap.extend(baseline.iter().copied());
+        heap.flush();
+
+        let serialized = heap.try_to_vec().unwrap();
+        drop(heap);
+        let mut heap = BinaryHeap::<u32>::try_from_slice(&serialized).unwrap();
+        baseline.sort_unstable();
+        while let Some(top) = heap.pop() {
+            assert_eq!(Some(top), baseline.pop());
+        }
+        assert!(baseline.is_empty());
+    }
+
+    #[test]
+    fn peek_mut() {
+        let mut heap = BinaryHeap::new(b"h");
+        heap.extend([3u8, 8, 5]);
+        {
+            let top = heap.peek_mut().unwrap();
+            assert_eq!(*top, 8);
+        }
+        *heap.peek_mut().unwrap() = 1;
+        assert_eq!(heap.peek(), Some(&5));
+        assert_eq!(PeekMut::pop(heap.peek_mut().unwrap()), 5);
+        assert_eq!(heap.pop(), Some(3));
+        assert_eq!(heap.pop(), Some(1));
+        assert!(heap.peek_mut().is_none());
+    }
+
+    #[derive(Arbitrary, Debug)]
+    enum Op {
+        Push(u8),
+        Pop,
+        Peek,
+        Flush,
+        Reset,
+        Clear,
+    }
+
+    #[test]
+    fn arbitrary() {
+        setup_free();
+
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(0);
+        let mut buf = vec![0; 4096];
+        for _ in 0..512 {
+            // Clear storage in-between runs
+            crate::mock::with_mocked_blockchain(|b| b.take_storage());
+            rng.fill_bytes(&mut buf);
+
+            let mut sh = BinaryHeap::new(b"h");
+            let mut bh = std::collections::BinaryHeap::new();
+            let u = Unstructured::new(&buf);
+            if let Ok(ops) = Vec::<Op>::arbitrary_take_rest(u) {
+                for op in ops {
+                    match op {
+                        Op::Push(v) => {
+                            sh.push(v);
+                            bh.push(v);
+                        }
+                        Op::Pop => {
+                            assert_eq!(sh.pop(), bh.pop());
+                        }
+                        Op::Peek => {
+                            assert_eq!(sh.peek(), bh.peek());
+                        }
+                        Op::Flush => {
+                            sh.flush();
+                        }
+                        Op::Reset => {
+                            let serialized = sh.try_to_vec().unwrap();
+                            sh = BinaryHeap::try_from_slice(&serialized).unwrap();
+                        }
+                        Op::Clear => {
+                            sh.clear();
+                            bh.clear();
+                        }
+                    }
+                }
+            }
+            assert_eq!(sh.len() as usize, bh.len());
+        }
+    }
+}
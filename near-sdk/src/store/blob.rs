@@ -0,0 +1,246 @@
+use std::fmt;
+use std::ops::{Bound, RangeBounds};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::journal;
+use crate::{env, IntoStorageKey};
+
+const ERR_OUT_OF_BOUNDS: &str = "Blob range out of bounds";
+const ERR_INVALID_CHUNK_SIZE: &str = "Blob chunk size must be greater than zero";
+
+/// Default number of bytes stored under each storage key of a [`Blob`].
+pub const DEFAULT_CHUNK_SIZE: u32 = 16 * 1024;
+
+/// A byte buffer which is split into fixed-size chunks, each stored under its own storage key.
+///
+/// This allows storing values which are too large to be read or written in a single storage
+/// operation. Appending only writes the last chunk and the new chunks, and reading a range only
+/// reads the chunks which overlap with it. Unlike the other collections, chunks are not cached
+/// and are written to storage immediately.
+///
+/// The length of the blob is stored with the structure itself, so the blob must be stored in
+/// the contract state for its contents to be accessible.
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::Blob;
+///
+/// let mut wasm = Blob::with_chunk_size(b"w", 4);
+/// wasm.append(b"\0asm");
+/// wasm.append(&[1, 0, 0, 0]);
+///
+/// assert_eq!(wasm.len(), 8);
+/// assert_eq!(wasm.read_range(2..6), b"sm\x01\0");
+/// assert_eq!(wasm.to_vec(), b"\0asm\x01\0\0\0");
+/// ```
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Blob {
+    len: u64,
+    chunk_size: u32,
+    prefix: Box<[u8]>,
+}
+
+impl Blob {
+    /// Create a new, empty blob with chunks of [`DEFAULT_CHUNK_SIZE`] bytes. Prefixes storage
+    /// accesses with the prefix provided.
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self::with_chunk_size(prefix, DEFAULT_CHUNK_SIZE)
+    }
+
+    /// Create a new, empty blob which stores at most `chunk_size` bytes under each storage key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is zero.
+    pub fn with_chunk_size<S>(prefix: S, chunk_size: u32) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        if chunk_size == 0 {
+            env::panic_str(ERR_INVALID_CHUNK_SIZE);
+        }
+        Self { len: 0, chunk_size, prefix: prefix.into_storage_key().into_boxed_slice() }
+    }
+
+    /// Returns the number of bytes in the blob.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns `true` if the blob contains no bytes.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of bytes stored under each storage key.
+    pub fn chunk_size(&self) -> u32 {
+        self.chunk_size
+    }
+
+    fn chunk_key(&self, chunk: u64) -> Vec<u8> {
+        let mut key = Vec::with_capacity(self.prefix.len() + 8);
+        key.extend_from_slice(&self.prefix);
+        key.extend_from_slice(&chunk.to_le_bytes());
+        key
+    }
+
+    /// Returns the number of chunks needed to store `len` bytes.
+    fn chunks_for(&self, len: u64) -> u64 {
+        let chunk_size = self.chunk_size as u64;
+        let full = len / chunk_size;
+        if full * chunk_size < len {
+            full + 1
+        } else {
+            full
+        }
+    }
+
+    fn read_chunk(&self, chunk: u64) -> Vec<u8> {
+        env::storage_read(&self.chunk_key(chunk))
+            .unwrap_or_else(|| env::panic_str(super::ERR_INCONSISTENT_STATE))
+    }
+
+    /// Appends the bytes to the end of the blob.
+    pub fn append(&mut self, mut data: &[u8]) {
+        let chunk_size = self.chunk_size as u64;
+        let partial = (self.len % chunk_size) as usize;
+        if partial != 0 && !data.is_empty() {
+            // Fill up the last chunk before writing new ones.
+            let chunk = self.len / chunk_size;
+            let mut bytes = self.read_chunk(chunk);
+            let take = data.len().min(self.chunk_size as usize - partial);
+            bytes.extend_from_slice(&data[..take]);
+            journal::storage_write(&self.chunk_key(chunk), &bytes);
+            self.len += take as u64;
+            data = &data[take..];
+        }
+        for bytes in data.chunks(self.chunk_size as usize) {
+            journal::storage_write(&self.chunk_key(self.len / chunk_size), bytes);
+            self.len += bytes.len() as u64;
+        }
+    }
+
+    /// Reads the bytes within the range, only loading the chunks which overlap with it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds of the blob or its start is greater than its end.
+    pub fn read_range<R>(&self, range: R) -> Vec<u8>
+    where
+        R: RangeBounds<u64>,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(&i) => i,
+            Bound::Excluded(&i) => i.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&i) => i.saturating_add(1),
+            Bound::Excluded(&i) => i,
+            Bound::Unbounded => self.len,
+        };
+        if start > end || end > self.len {
+            env::panic_str(ERR_OUT_OF_BOUNDS);
+        }
+
+        let chunk_size = self.chunk_size as u64;
+        let mut result = Vec::with_capacity((end - start) as usize);
+        let mut pos = start;
+        while pos < end {
+            let chunk = pos / chunk_size;
+            let bytes = self.read_chunk(chunk);
+            let from = (pos - chunk * chunk_size) as usize;
+            let to = (end - chunk * chunk_size).min(chunk_size) as usize;
+            result.extend_from_slice(&bytes[from..to]);
+            pos = (chunk + 1) * chunk_size;
+        }
+        result
+    }
+
+    /// Reads the whole blob into memory.
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.read_range(..)
+    }
+
+    /// Shortens the blob to `len` bytes, removing the chunks past the new end from storage. Has
+    /// no effect if `len` is greater than the current length.
+    pub fn truncate(&mut self, len: u64) {
+        if len >= self.len {
+            return;
+        }
+        let chunk_size = self.chunk_size as u64;
+        for chunk in self.chunks_for(len)..self.chunks_for(self.len) {
+            journal::storage_remove(&self.chunk_key(chunk));
+        }
+        let partial = (len % chunk_size) as usize;
+        if partial != 0 {
+            let chunk = len / chunk_size;
+            let mut bytes = self.read_chunk(chunk);
+            bytes.truncate(partial);
+            journal::storage_write(&self.chunk_key(chunk), &bytes);
+        }
+        self.len = len;
+    }
+
+    /// Removes all bytes from the blob, removing every chunk from storage.
+    pub fn clear(&mut self) {
+        self.truncate(0)
+    }
+}
+
+impl fmt::Debug for Blob {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Blob")
+            .field("len", &self.len)
+            .field("chunk_size", &self.chunk_size)
+            .field("prefix", &self.prefix)
+            .finish()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::Blob;
+    use borsh::{BorshDeserialize, BorshSerialize};
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn matches_vec() {
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(5);
+        let mut blob = Blob::with_chunk_size(b"b", 7);
+        let mut baseline = Vec::new();
+        for _ in 0..50 {
+            let data: Vec<u8> = (0..rng.gen_range(0..20)).map(|_| rng.gen()).collect();
+            blob.append(&data);
+            baseline.extend_from_slice(&data);
+
+            let start = rng.gen_range(0..=baseline.len());
+            let end = rng.gen_range(start..=baseline.len());
+            assert_eq!(blob.read_range(start as u64..end as u64), baseline[start..end]);
+
+            if rng.gen_bool(0.1) {
+                let len = rng.gen_range(0..=baseline.len());
+                blob.truncate(len as u64);
+                baseline.truncate(len);
+            }
+        }
+
+        let blob = Blob::try_from_slice(&blob.try_to_vec().unwrap()).unwrap();
+        assert_eq!(blob.len(), baseline.len() as u64);
+        assert_eq!(blob.to_vec(), baseline);
+    }
+
+    #[test]
+    fn clear() {
+        let mut blob = Blob::with_chunk_size(b"b", 3);
+        blob.append(&[1; 10]);
+        blob.clear();
+        assert!(blob.is_empty());
+        assert!(crate::mock::with_mocked_blockchain(|b| b.take_storage()).is_empty());
+    }
+}
@@ -0,0 +1,254 @@
+use std::convert::TryInto;
+use std::fmt;
+use std::marker::PhantomData;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::IndexMap;
+use crate::crypto_hash::{CryptoHasher, Sha256};
+use crate::{env, IntoStorageKey};
+
+const ERR_ELEMENT_SERIALIZATION: &str = "Cannot serialize element";
+const ERR_INVALID_PARAMETERS: &str = "Bloom filter must have at least one bit and one hash";
+
+/// A probabilistic set which stores its bits on the trie. [`maybe_contains`] never returns
+/// `false` for an item which was inserted, but can return `true` for an item which was not.
+///
+/// The filter is configured with the number of bits and the number of hashes computed for each
+/// item. The bits are stored in 64 bit words, each in its own storage slot, so inserting or
+/// checking an item loads and writes at most `num_hashes` words. Words which have never been set
+/// are not stored at all.
+///
+/// For `n` expected items and a target false positive rate `p`, a good choice is
+/// `num_bits = -n * ln(p) / ln(2)^2` and `num_hashes = num_bits / n * ln(2)`.
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::BloomFilter;
+///
+/// let mut seen = BloomFilter::new(b"b", 1024, 3);
+/// seen.insert("alice");
+///
+/// assert!(seen.maybe_contains("alice"));
+/// if !seen.maybe_contains("bob") {
+///     // "bob" has definitely not been inserted, skip the expensive lookup.
+/// }
+/// ```
+///
+/// [`maybe_contains`]: Self::maybe_contains
+pub struct BloomFilter<T: ?Sized, H = Sha256>
+where
+    T: BorshSerialize,
+    H: CryptoHasher<Digest = [u8; 32]>,
+{
+    num_bits: u32,
+    num_hashes: u32,
+    words: IndexMap<u64>,
+    item: PhantomData<fn(&T)>,
+    hasher: PhantomData<H>,
+}
+
+//? Manual implementations needed only because borsh derive is leaking field types
+// https://github.com/near/borsh-rs/issues/41
+impl<T: ?Sized, H> BorshSerialize for BloomFilter<T, H>
+where
+    T: BorshSerialize,
+    H: CryptoHasher<Digest = [u8; 32]>,
+{
+    fn serialize<W: borsh::maybestd::io::Write>(
+        &self,
+        writer: &mut W,
+    ) -> Result<(), borsh::maybestd::io::Error> {
+        BorshSerialize::serialize(&self.num_bits, writer)?;
+        BorshSerialize::serialize(&self.num_hashes, writer)?;
+        BorshSerialize::serialize(&self.words, writer)?;
+        Ok(())
+    }
+}
+
+impl<T: ?Sized, H> BorshDeserialize for BloomFilter<T, H>
+where
+    T: BorshSerialize,
+    H: CryptoHasher<Digest = [u8; 32]>,
+{
+    fn deserialize(buf: &mut &[u8]) -> Result<Self, borsh::maybestd::io::Error> {
+        Ok(Self {
+            num_bits: BorshDeserialize::deserialize(buf)?,
+            num_hashes: BorshDeserialize::deserialize(buf)?,
+            words: BorshDeserialize::deserialize(buf)?,
+            item: PhantomData,
+            hasher: PhantomData,
+        })
+    }
+}
+
+impl<T: ?Sized, H> Drop for BloomFilter<T, H>
+where
+    T: BorshSerialize,
+    H: CryptoHasher<Digest = [u8; 32]>,
+{
+    fn drop(&mut self) {
+        self.flush()
+    }
+}
+
+impl<T: ?Sized, H> fmt::Debug for BloomFilter<T, H>
+where
+    T: BorshSerialize,
+    H: CryptoHasher<Digest = [u8; 32]>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BloomFilter")
+            .field("num_bits", &self.num_bits)
+            .field("num_hashes", &self.num_hashes)
+            .field("prefix", &self.words.prefix)
+            .finish()
+    }
+}
+
+impl<T: ?Sized> BloomFilter<T, Sha256>
+where
+    T: BorshSerialize,
+{
+    /// Create a new, empty filter with `num_bits` bits which sets `num_hashes` bits per item.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_bits` or `num_hashes` is zero.
+    #[inline]
+    pub fn new<S>(prefix: S, num_bits: u32, num_hashes: u32) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self::with_hasher(prefix, num_bits, num_hashes)
+    }
+}
+
+impl<T: ?Sized, H> BloomFilter<T, H>
+where
+    T: BorshSerialize,
+    H: CryptoHasher<Digest = [u8; 32]>,
+{
+    /// Initialize a [`BloomFilter`] with a custom hash function.
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::crypto_hash::Keccak256;
+    /// use near_sdk::store::BloomFilter;
+    ///
+    /// let filter = BloomFilter::<str, Keccak256>::with_hasher(b"b", 1024, 3);
+    /// ```
+    pub fn with_hasher<S>(prefix: S, num_bits: u32, num_hashes: u32) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        if num_bits == 0 || num_hashes == 0 {
+            env::panic_str(ERR_INVALID_PARAMETERS);
+        }
+        Self {
+            num_bits,
+            num_hashes,
+            words: IndexMap::new(prefix),
+            item: PhantomData,
+            hasher: PhantomData,
+        }
+    }
+
+    /// Returns the number of bits of the filter.
+    pub fn num_bits(&self) -> u32 {
+        self.num_bits
+    }
+
+    /// Returns the number of bits set for each item.
+    pub fn num_hashes(&self) -> u32 {
+        self.num_hashes
+    }
+
+    /// Flushes the cache and writes all modified words to storage.
+    pub fn flush(&mut self) {
+        self.words.flush()
+    }
+
+    /// Resets all bits of the filter. This removes every word of the filter from storage.
+    pub fn clear(&mut self) {
+        for word in 0..Self::num_words(self.num_bits) {
+            self.words.set(word, None);
+        }
+    }
+
+    fn num_words(num_bits: u32) -> u32 {
+        (num_bits - 1) / 64 + 1
+    }
+
+    /// Returns the bit positions of the item, using double hashing of the item's digest.
+    fn bits(&self, item: &T) -> impl Iterator<Item = u32> {
+        let bytes = item.try_to_vec().unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_SERIALIZATION));
+        let digest = H::hash(&bytes);
+        let h1 = u64::from_le_bytes(digest[..8].try_into().unwrap());
+        let h2 = u64::from_le_bytes(digest[8..16].try_into().unwrap());
+        let num_bits = self.num_bits as u64;
+        (0..self.num_hashes as u64)
+            .map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % num_bits) as u32)
+    }
+
+    /// Adds the item to the filter. Returns `true` if any bit of the filter changed, which
+    /// means that the item was definitely not in the filter before.
+    pub fn insert(&mut self, item: &T) -> bool {
+        let mut changed = false;
+        for bit in self.bits(item) {
+            let mask = 1 << (bit % 64);
+            let word = self.words.get(bit / 64).copied().unwrap_or(0);
+            if word & mask == 0 {
+                self.words.set(bit / 64, Some(word | mask));
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Returns `false` if the item has definitely not been inserted, and `true` if it may have
+    /// been inserted.
+    pub fn maybe_contains(&self, item: &T) -> bool {
+        self.bits(item).all(|bit| {
+            let word = self.words.get(bit / 64).copied().unwrap_or(0);
+            word & (1 << (bit % 64)) != 0
+        })
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::BloomFilter;
+    use borsh::{BorshDeserialize, BorshSerialize};
+
+    #[test]
+    fn no_false_negatives() {
+        let mut filter = BloomFilter::new(b"b", 2048, 4);
+        for i in 0..100u32 {
+            filter.insert(&i);
+            assert!(!filter.insert(&i));
+        }
+        filter.flush();
+
+        let serialized = filter.try_to_vec().unwrap();
+        drop(filter);
+        let filter = BloomFilter::<u32>::try_from_slice(&serialized).unwrap();
+        assert!((0..100u32).all(|i| filter.maybe_contains(&i)));
+
+        // With ~14 bits per item and 4 hashes, the false positive rate is below 1%.
+        let false_positives = (100..10_100u32).filter(|i| filter.maybe_contains(i)).count();
+        assert!(false_positives < 100, "{} false positives", false_positives);
+    }
+
+    #[test]
+    fn clear() {
+        let mut filter = BloomFilter::<str>::new(b"b", 100, 2);
+        filter.insert("a");
+        filter.flush();
+        filter.clear();
+        filter.flush();
+        assert!(!filter.maybe_contains("a"));
+        assert!(crate::mock::with_mocked_blockchain(|b| b.take_storage()).is_empty());
+    }
+}
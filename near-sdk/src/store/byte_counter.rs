@@ -0,0 +1,39 @@
+use crate::env;
+
+/// Number of bytes the protocol charges for each storage record, on top of the lengths of its
+/// key and value.
+pub(crate) const STORAGE_RECORD_BYTES: u64 = 40;
+
+/// Running count of the storage bytes written and freed when flushing a collection.
+#[derive(Default, Debug, Clone, Copy)]
+pub(crate) struct ByteCounter {
+    pub(crate) written: u64,
+    pub(crate) freed: u64,
+}
+
+impl ByteCounter {
+    /// Records a write of a value of `value_len` bytes under a key of `key_len` bytes, where
+    /// `replaced` is the result of the [`env::storage_write`] call.
+    pub(crate) fn record_write(&mut self, key_len: usize, value_len: usize, replaced: bool) {
+        let value_len = value_len as u64;
+        if replaced {
+            let old_len = env::storage_evicted_len().unwrap_or_default();
+            if value_len >= old_len {
+                self.written += value_len - old_len;
+            } else {
+                self.freed += old_len - value_len;
+            }
+        } else {
+            self.written += key_len as u64 + value_len + STORAGE_RECORD_BYTES;
+        }
+    }
+
+    /// Records the removal of a key of `key_len` bytes, where `removed` is the result of the
+    /// [`env::storage_remove`] call.
+    pub(crate) fn record_remove(&mut self, key_len: usize, removed: bool) {
+        if removed {
+            let old_len = env::storage_evicted_len().unwrap_or_default();
+            self.freed += key_len as u64 + old_len + STORAGE_RECORD_BYTES;
+        }
+    }
+}
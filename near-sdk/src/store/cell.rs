@@ -0,0 +1,218 @@
+use std::fmt;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use once_cell::unsync::OnceCell;
+
+use super::journal;
+use crate::{env, IntoStorageKey};
+
+const ERR_VALUE_SERIALIZATION: &str = "Cannot serialize value with Borsh";
+const ERR_VALUE_DESERIALIZATION: &str = "Cannot deserialize value with Borsh";
+const ERR_NOT_FOUND: &str = "No value found for the given key";
+
+struct CellEntry<T> {
+    value: T,
+    /// Serialized value currently in storage, [`None`] if the value has not been written yet.
+    stored: Option<Vec<u8>>,
+    /// Whether the value was mutably accessed since it was loaded or last flushed.
+    touched: bool,
+}
+
+/// A persistent value which is stored under its own key, and is only written back to storage if
+/// its serialized value actually changed.
+///
+/// Like [`Lazy`](super::Lazy), a large field of the contract state can be wrapped in a
+/// [`Cell`] so that it is only loaded when accessed and is not rewritten along with the rest of
+/// the state. In addition, mutable access alone does not cause a write: when the cell is
+/// flushed, the value is only written if it serializes differently from the value loaded from
+/// storage. Use [`is_modified`](Self::is_modified) to check whether a write is pending.
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::Cell;
+///
+/// let mut config = Cell::new(b"c", vec![1u8, 2, 3]);
+/// config.flush();
+/// assert!(!config.is_modified());
+///
+/// // Mutable access which leaves the value unchanged does not need a write.
+/// config.sort();
+/// assert!(!config.is_modified());
+///
+/// config.push(4);
+/// assert!(config.is_modified());
+/// ```
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Cell<T>
+where
+    T: BorshSerialize,
+{
+    /// Key bytes to index the contract's storage.
+    storage_key: Box<[u8]>,
+    /// Cached value which is lazily loaded and deserialized from storage.
+    #[borsh_skip]
+    cache: OnceCell<CellEntry<T>>,
+}
+
+impl<T> Cell<T>
+where
+    T: BorshSerialize,
+{
+    /// Create a new cell with the given `storage_key` and initial value. The value is written to
+    /// storage when the cell is flushed.
+    pub fn new<S>(storage_key: S, value: T) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self {
+            storage_key: storage_key.into_storage_key().into_boxed_slice(),
+            cache: OnceCell::from(CellEntry { value, stored: None, touched: true }),
+        }
+    }
+
+    fn serialize(value: &T) -> Vec<u8> {
+        value.try_to_vec().unwrap_or_else(|_| env::panic_str(ERR_VALUE_SERIALIZATION))
+    }
+
+    /// Returns `true` if the value differs from the value in storage, and so will be written
+    /// when the cell is flushed. This serializes the value if it was mutably accessed.
+    pub fn is_modified(&self) -> bool {
+        match self.cache.get() {
+            Some(entry) if entry.touched => {
+                entry.stored.as_deref() != Some(Self::serialize(&entry.value).as_slice())
+            }
+            _ => false,
+        }
+    }
+
+    /// Replaces the value, without loading the current value from storage.
+    pub fn set(&mut self, value: T) {
+        match self.cache.get_mut() {
+            Some(entry) => {
+                entry.value = value;
+                entry.touched = true;
+            }
+            None => {
+                let stored = env::storage_read(&self.storage_key);
+                let _ = self.cache.set(CellEntry { value, stored, touched: true });
+            }
+        }
+    }
+
+    /// Writes the value to storage if it changed. This will automatically be done when the cell
+    /// is dropped through [`Drop`] so this should only be used when the changes need to be
+    /// reflected in the underlying storage before then.
+    pub fn flush(&mut self) {
+        if let Some(entry) = self.cache.get_mut() {
+            if entry.touched {
+                let serialized = Self::serialize(&entry.value);
+                if entry.stored.as_ref() != Some(&serialized) {
+                    journal::storage_write(&self.storage_key, &serialized);
+                    entry.stored = Some(serialized);
+                }
+                entry.touched = false;
+            }
+        }
+    }
+}
+
+impl<T> Cell<T>
+where
+    T: BorshSerialize + BorshDeserialize,
+{
+    fn load(storage_key: &[u8]) -> CellEntry<T> {
+        let stored =
+            env::storage_read(storage_key).unwrap_or_else(|| env::panic_str(ERR_NOT_FOUND));
+        let value = T::try_from_slice(&stored)
+            .unwrap_or_else(|_| env::panic_str(ERR_VALUE_DESERIALIZATION));
+        CellEntry { value, stored: Some(stored), touched: false }
+    }
+
+    /// Returns a reference to the value, loading it from storage if it was not loaded yet.
+    ///
+    /// This function will panic if the value is not loaded and does not exist in storage.
+    pub fn get(&self) -> &T {
+        &self.cache.get_or_init(|| Self::load(&self.storage_key)).value
+    }
+
+    /// Returns a mutable reference to the value, loading it from storage if it was not loaded
+    /// yet. The value will be compared with the stored value when the cell is flushed.
+    ///
+    /// This function will panic if the value is not loaded and does not exist in storage.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.cache.get_or_init(|| Self::load(&self.storage_key));
+        let entry = self.cache.get_mut().unwrap_or_else(|| env::abort());
+        entry.touched = true;
+        &mut entry.value
+    }
+}
+
+impl<T> Drop for Cell<T>
+where
+    T: BorshSerialize,
+{
+    fn drop(&mut self) {
+        self.flush()
+    }
+}
+
+impl<T> core::ops::Deref for Cell<T>
+where
+    T: BorshSerialize + BorshDeserialize,
+{
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        Self::get(self)
+    }
+}
+
+impl<T> core::ops::DerefMut for Cell<T>
+where
+    T: BorshSerialize + BorshDeserialize,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        Self::get_mut(self)
+    }
+}
+
+impl<T> fmt::Debug for Cell<T>
+where
+    T: BorshSerialize + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cell")
+            .field("storage_key", &self.storage_key)
+            .field("value", &self.cache.get().map(|entry| &entry.value))
+            .finish()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::Cell;
+    use crate::env;
+    use borsh::{BorshDeserialize, BorshSerialize};
+
+    #[test]
+    fn writes_only_changed_values() {
+        let mut cell = Cell::new(b"c", 5u32);
+        assert!(cell.is_modified());
+        let serialized = cell.try_to_vec().unwrap();
+        drop(cell);
+        assert_eq!(env::storage_read(b"c"), Some(5u32.try_to_vec().unwrap()));
+
+        let mut cell = Cell::<u32>::try_from_slice(&serialized).unwrap();
+        assert_eq!(*cell, 5);
+        // Overwrite storage behind the cell's back to observe whether it writes.
+        env::storage_write(b"c", &7u32.try_to_vec().unwrap());
+        *cell += 0;
+        cell.flush();
+        assert_eq!(env::storage_read(b"c"), Some(7u32.try_to_vec().unwrap()));
+
+        *cell += 1;
+        drop(cell);
+        assert_eq!(env::storage_read(b"c"), Some(6u32.try_to_vec().unwrap()));
+    }
+}
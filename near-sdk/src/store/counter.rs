@@ -0,0 +1,222 @@
+use std::fmt;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use once_cell::unsync::OnceCell;
+
+use crate::env;
+use crate::store::lazy::{load_optional_and_deserialize, serialize_and_store};
+use crate::utils::{CacheEntry, EntryState};
+use crate::IntoStorageKey;
+
+const ERR_OVERFLOW: &str = "Counter overflowed";
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// Unsigned integer types which can be stored in a [`Counter`].
+///
+/// This trait is sealed, and is implemented for [`u64`] and [`u128`].
+pub trait CounterValue:
+    private::Sealed + BorshSerialize + BorshDeserialize + Copy + Default + PartialEq + fmt::Debug
+{
+    #[doc(hidden)]
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    #[doc(hidden)]
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    #[doc(hidden)]
+    fn one() -> Self;
+}
+
+macro_rules! impl_counter_value {
+    ($($ty:ty),*) => {
+        $(
+            impl private::Sealed for $ty {}
+
+            impl CounterValue for $ty {
+                fn checked_add(self, rhs: Self) -> Option<Self> {
+                    <$ty>::checked_add(self, rhs)
+                }
+
+                fn checked_sub(self, rhs: Self) -> Option<Self> {
+                    <$ty>::checked_sub(self, rhs)
+                }
+
+                fn one() -> Self {
+                    1
+                }
+            }
+        )*
+    };
+}
+
+impl_counter_value!(u64, u128);
+
+/// A number stored under its own storage key, for values such as a total supply or a nonce
+/// which are updated often but should not be rewritten with the rest of the contract state.
+///
+/// The value is read from storage on first access, and a counter with no value in storage is
+/// zero. Changes are kept in memory and only written when the counter is flushed or dropped.
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::Counter;
+///
+/// let mut supply: Counter<u128> = Counter::new(b"s");
+/// supply.increment_by(100);
+/// assert_eq!(supply.decrement_checked(30), Some(70));
+/// assert_eq!(supply.decrement_checked(80), None);
+/// assert_eq!(supply.get(), 70);
+///
+/// let mut nonce: Counter = Counter::new(b"n");
+/// assert_eq!(nonce.increment(), 1);
+/// ```
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Counter<T = u64>
+where
+    T: CounterValue,
+{
+    /// Key bytes to index the contract's storage.
+    storage_key: Box<[u8]>,
+    /// Cached value which is lazily loaded from storage.
+    #[borsh_skip]
+    cache: OnceCell<CacheEntry<T>>,
+}
+
+impl<T> Counter<T>
+where
+    T: CounterValue,
+{
+    /// Create a counter stored under the given `storage_key`. The counter starts from the value
+    /// in storage, or zero if there is none.
+    pub fn new<S>(storage_key: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self {
+            storage_key: storage_key.into_storage_key().into_boxed_slice(),
+            cache: OnceCell::new(),
+        }
+    }
+
+    fn entry_mut(&mut self) -> &mut CacheEntry<T> {
+        let storage_key = &self.storage_key;
+        self.cache.get_or_init(|| load_optional_and_deserialize(storage_key));
+        self.cache.get_mut().unwrap_or_else(|| env::abort())
+    }
+
+    fn update(&mut self, value: T) {
+        let entry = self.entry_mut();
+        if *entry.value() != Some(value) {
+            *entry.value_mut() = Some(value);
+        }
+    }
+
+    /// Returns the current value of the counter.
+    pub fn get(&self) -> T {
+        let entry = self.cache.get_or_init(|| load_optional_and_deserialize(&self.storage_key));
+        entry.value().unwrap_or_default()
+    }
+
+    /// Sets the value of the counter.
+    pub fn set(&mut self, value: T) {
+        match self.cache.get_mut() {
+            Some(entry) => *entry.value_mut() = Some(value),
+            None => {
+                let _ = self.cache.set(CacheEntry::new_modified(Some(value)));
+            }
+        }
+    }
+
+    /// Adds one to the counter and returns the new value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the counter overflows.
+    pub fn increment(&mut self) -> T {
+        self.increment_by(T::one())
+    }
+
+    /// Adds `amount` to the counter and returns the new value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the counter overflows.
+    pub fn increment_by(&mut self, amount: T) -> T {
+        let value = self.get().checked_add(amount).unwrap_or_else(|| env::panic_str(ERR_OVERFLOW));
+        self.update(value);
+        value
+    }
+
+    /// Subtracts `amount` from the counter and returns the new value, or returns [`None`] and
+    /// leaves the counter unchanged if `amount` is greater than the value.
+    pub fn decrement_checked(&mut self, amount: T) -> Option<T> {
+        let value = self.get().checked_sub(amount)?;
+        self.update(value);
+        Some(value)
+    }
+
+    /// Writes the value to storage if it changed. This will automatically be done when the
+    /// counter is dropped through [`Drop`] so this should only be used when the changes need to
+    /// be reflected in the underlying storage before then.
+    pub fn flush(&mut self) {
+        if let Some(entry) = self.cache.get_mut() {
+            if entry.is_modified() {
+                serialize_and_store(&self.storage_key, &entry.value().unwrap_or_default());
+                entry.replace_state(EntryState::Cached);
+            }
+        }
+    }
+}
+
+impl<T> Drop for Counter<T>
+where
+    T: CounterValue,
+{
+    fn drop(&mut self) {
+        self.flush()
+    }
+}
+
+impl<T> fmt::Debug for Counter<T>
+where
+    T: CounterValue,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Counter")
+            .field("storage_key", &self.storage_key)
+            .field("value", &self.cache.get().map(|entry| entry.value()))
+            .finish()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::Counter;
+    use crate::env;
+    use borsh::{BorshDeserialize, BorshSerialize};
+
+    #[test]
+    fn lazy_write_back() {
+        let mut counter: Counter = Counter::new(b"c");
+        assert_eq!(counter.get(), 0);
+        counter.increment_by(5);
+        assert!(!env::storage_has_key(b"c"));
+        let serialized = counter.try_to_vec().unwrap();
+        drop(counter);
+        assert_eq!(env::storage_read(b"c"), Some(5u64.try_to_vec().unwrap()));
+
+        let mut counter = Counter::<u64>::try_from_slice(&serialized).unwrap();
+        // The value is only read on first access.
+        env::storage_write(b"c", &7u64.try_to_vec().unwrap());
+        assert_eq!(counter.get(), 7);
+        // A failed decrement leaves nothing to write, which is observed by writing behind the
+        // counter's back.
+        assert_eq!(counter.decrement_checked(8), None);
+        counter.flush();
+        env::storage_write(b"c", &9u64.try_to_vec().unwrap());
+        drop(counter);
+        assert_eq!(env::storage_read(b"c"), Some(9u64.try_to_vec().unwrap()));
+    }
+}
@@ -0,0 +1,533 @@
+mod iter;
+
+use std::fmt;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+pub use self::iter::Iter;
+use super::{IndexMap, ERR_INCONSISTENT_STATE};
+use crate::{env, IntoStorageKey};
+
+const ERR_INDEX_OUT_OF_BOUNDS: &str = "Index out of bounds";
+const ERR_CAPACITY_EXCEEDED: &str = "Deque is at capacity";
+
+fn expect_consistent_state<T>(val: Option<T>) -> T {
+    val.unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE))
+}
+
+/// A double-ended queue that stores its content on the trie. This implementation will load and
+/// store values in the underlying storage lazily.
+///
+/// Elements are stored under the index of their slot, and the deque keeps track of the index of
+/// the first element and its length. Pushing and popping at either end only touches the storage
+/// slot of that element, so none of the other elements are moved or loaded.
+///
+/// A deque can optionally be created with a maximum capacity through [`Deque::bounded`], in which
+/// case pushing onto a full deque will panic, and the `try_push_*` methods will return the value
+/// back instead.
+///
+/// As with [`Vector`](crate::store::Vector), all changes are cached and written to storage when
+/// the deque is flushed or [`Drop`]ed.
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::Deque;
+///
+/// let mut deque = Deque::new(b"d");
+/// deque.push_back(2);
+/// deque.push_back(3);
+/// deque.push_front(1);
+///
+/// assert_eq!(deque.front(), Some(&1));
+/// assert_eq!(deque.back(), Some(&3));
+/// assert_eq!(deque.len(), 3);
+///
+/// assert_eq!(deque.pop_front(), Some(1));
+/// assert_eq!(deque.pop_back(), Some(3));
+/// assert!(Iterator::eq(deque.iter(), [2].iter()));
+/// ```
+pub struct Deque<T>
+where
+    T: BorshSerialize,
+{
+    /// Index of the slot of the first element.
+    head: u32,
+    len: u32,
+    capacity: Option<u32>,
+    values: IndexMap<T>,
+}
+
+//? Manual implementations needed only because borsh derive is leaking field types
+// https://github.com/near/borsh-rs/issues/41
+impl<T> BorshSerialize for Deque<T>
+where
+    T: BorshSerialize,
+{
+    fn serialize<W: borsh::maybestd::io::Write>(
+        &self,
+        writer: &mut W,
+    ) -> Result<(), borsh::maybestd::io::Error> {
+        BorshSerialize::serialize(&self.head, writer)?;
+        BorshSerialize::serialize(&self.len, writer)?;
+        BorshSerialize::serialize(&self.capacity, writer)?;
+        BorshSerialize::serialize(&self.values, writer)?;
+        Ok(())
+    }
+}
+
+impl<T> BorshDeserialize for Deque<T>
+where
+    T: BorshSerialize,
+{
+    fn deserialize(buf: &mut &[u8]) -> Result<Self, borsh::maybestd::io::Error> {
+        Ok(Self {
+            head: BorshDeserialize::deserialize(buf)?,
+            len: BorshDeserialize::deserialize(buf)?,
+            capacity: BorshDeserialize::deserialize(buf)?,
+            values: BorshDeserialize::deserialize(buf)?,
+        })
+    }
+}
+
+impl<T> Drop for Deque<T>
+where
+    T: BorshSerialize,
+{
+    fn drop(&mut self) {
+        self.flush()
+    }
+}
+
+impl<T> Deque<T>
+where
+    T: BorshSerialize,
+{
+    /// Create new deque with zero elements. Prefixes storage accesss with the prefix provided.
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { head: 0, len: 0, capacity: None, values: IndexMap::new(prefix) }
+    }
+
+    /// Create new deque which can hold at most `capacity` elements.
+    ///
+    /// # Examples
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut deque = Deque::bounded(b"d", 2);
+    /// deque.push_back(1);
+    /// deque.push_back(2);
+    /// assert!(deque.is_full());
+    /// assert_eq!(deque.try_push_back(3), Err(3));
+    ///
+    /// deque.pop_front();
+    /// assert_eq!(deque.try_push_back(3), Ok(()));
+    /// ```
+    pub fn bounded<S>(prefix: S, capacity: u32) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { head: 0, len: 0, capacity: Some(capacity), values: IndexMap::new(prefix) }
+    }
+
+    /// Returns the number of elements in the deque.
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    /// Returns `true` if the deque contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the maximum number of elements of a bounded deque, or [`None`] if the deque is not
+    /// bounded.
+    pub fn capacity(&self) -> Option<u32> {
+        self.capacity
+    }
+
+    /// Returns `true` if no more elements can be pushed onto the deque.
+    pub fn is_full(&self) -> bool {
+        self.len == self.capacity.unwrap_or(u32::MAX)
+    }
+
+    /// Converts an index of the deque into the index of the slot it is stored in.
+    fn slot(&self, index: u32) -> u32 {
+        self.head.wrapping_add(index)
+    }
+
+    /// Removes all elements from the deque. This will remove all storage values for the length of
+    /// the [`Deque`].
+    pub fn clear(&mut self) {
+        for i in 0..self.len {
+            self.values.set(self.slot(i), None);
+        }
+        self.head = 0;
+        self.len = 0;
+    }
+
+    /// Removes up to `limit` elements from the back of the deque without loading them. Returns
+    /// `true` if there are elements left to remove.
+    ///
+    /// This can be used to clear a deque which is too large to [`clear`](Self::clear) within the
+    /// gas limit of a single function call.
+    pub fn clear_chunk(&mut self, limit: u32) -> bool {
+        let new_len = self.len.saturating_sub(limit);
+        for i in new_len..self.len {
+            self.values.set(self.slot(i), None);
+        }
+        self.len = new_len;
+        if new_len == 0 {
+            self.head = 0;
+        }
+        !self.is_empty()
+    }
+
+    /// Flushes the cache and writes all modified values to storage.
+    pub fn flush(&mut self) {
+        self.values.flush();
+    }
+
+    /// Returns the number of storage bytes added by flushes of this deque since it was created
+    /// or loaded from storage.
+    pub fn bytes_written(&self) -> u64 {
+        self.values.bytes.written
+    }
+
+    /// Returns the number of storage bytes released by flushes of this deque since it was
+    /// created or loaded from storage.
+    pub fn bytes_freed(&self) -> u64 {
+        self.values.bytes.freed
+    }
+
+    /// Appends an element to the back of the deque.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the deque is full.
+    pub fn push_back(&mut self, element: T) {
+        if self.try_push_back(element).is_err() {
+            env::panic_str(ERR_CAPACITY_EXCEEDED);
+        }
+    }
+
+    /// Appends an element to the back of the deque, returning the element back if the deque is
+    /// full.
+    pub fn try_push_back(&mut self, element: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(element);
+        }
+        self.values.set(self.slot(self.len), Some(element));
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Prepends an element to the front of the deque.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the deque is full.
+    pub fn push_front(&mut self, element: T) {
+        if self.try_push_front(element).is_err() {
+            env::panic_str(ERR_CAPACITY_EXCEEDED);
+        }
+    }
+
+    /// Prepends an element to the front of the deque, returning the element back if the deque is
+    /// full.
+    pub fn try_push_front(&mut self, element: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(element);
+        }
+        self.head = self.head.wrapping_sub(1);
+        self.values.set(self.head, Some(element));
+        self.len += 1;
+        Ok(())
+    }
+}
+
+impl<T> Deque<T>
+where
+    T: BorshSerialize + BorshDeserialize,
+{
+    /// Returns the element at the index from the front of the deque, or `None` if the index is
+    /// out of bounds.
+    pub fn get(&self, index: u32) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        self.values.get(self.slot(index))
+    }
+
+    /// Returns a mutable reference to the element at the index from the front of the deque, or
+    /// `None` if the index is out of bounds.
+    pub fn get_mut(&mut self, index: u32) -> Option<&mut T> {
+        if index >= self.len {
+            return None;
+        }
+        self.values.get_mut(self.slot(index))
+    }
+
+    /// Returns a reference to the front element, or `None` if the deque is empty.
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    /// Returns a mutable reference to the front element, or `None` if the deque is empty.
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(0)
+    }
+
+    /// Returns a reference to the back element, or `None` if the deque is empty.
+    pub fn back(&self) -> Option<&T> {
+        self.get(self.len.checked_sub(1)?)
+    }
+
+    /// Returns a mutable reference to the back element, or `None` if the deque is empty.
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(self.len.checked_sub(1)?)
+    }
+
+    /// Removes the first element and returns it, or `None` if the deque is empty.
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+        let prev = self.values.get_mut_inner(self.head).replace(None);
+        self.head = self.head.wrapping_add(1);
+        self.len -= 1;
+        Some(expect_consistent_state(prev))
+    }
+
+    /// Removes the last element and returns it, or `None` if the deque is empty.
+    pub fn pop_back(&mut self) -> Option<T> {
+        let new_len = self.len.checked_sub(1)?;
+        let prev = self.values.get_mut_inner(self.slot(new_len)).replace(None);
+        self.len = new_len;
+        Some(expect_consistent_state(prev))
+    }
+
+    /// Returns a front-to-back iterator over the deque. This iterator will lazily load any
+    /// values iterated over from storage.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter::new(self)
+    }
+}
+
+impl<T> Extend<T> for Deque<T>
+where
+    T: BorshSerialize + BorshDeserialize,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iter {
+            self.push_back(item)
+        }
+    }
+}
+
+impl<T> core::ops::Index<u32> for Deque<T>
+where
+    T: BorshSerialize + BorshDeserialize,
+{
+    type Output = T;
+
+    fn index(&self, index: u32) -> &Self::Output {
+        self.get(index).unwrap_or_else(|| env::panic_str(ERR_INDEX_OUT_OF_BOUNDS))
+    }
+}
+
+impl<T> core::ops::IndexMut<u32> for Deque<T>
+where
+    T: BorshSerialize + BorshDeserialize,
+{
+    fn index_mut(&mut self, index: u32) -> &mut Self::Output {
+        self.get_mut(index).unwrap_or_else(|| env::panic_str(ERR_INDEX_OUT_OF_BOUNDS))
+    }
+}
+
+impl<T> serde::Serialize for Deque<T>
+where
+    T: BorshSerialize + BorshDeserialize + serde::Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+/// Compares the elements with a [`VecDeque`](std::collections::VecDeque), loading all elements
+/// from storage. Only available outside of Wasm, to assert on the contents of the deque in unit
+/// tests.
+#[cfg(not(target_arch = "wasm32"))]
+impl<T> PartialEq<std::collections::VecDeque<T>> for Deque<T>
+where
+    T: PartialEq + BorshSerialize + BorshDeserialize,
+{
+    fn eq(&self, other: &std::collections::VecDeque<T>) -> bool {
+        self.len() as usize == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T> fmt::Debug for Deque<T>
+where
+    T: BorshSerialize + BorshDeserialize + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if cfg!(feature = "expensive-debug") {
+            fmt::Debug::fmt(&self.iter().collect::<Vec<_>>(), f)
+        } else {
+            f.debug_struct("Deque")
+                .field("head", &self.head)
+                .field("len", &self.len)
+                .field("capacity", &self.capacity)
+                .field("prefix", &self.values.prefix)
+                .finish()
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use arbitrary::{Arbitrary, Unstructured};
+    use borsh::{BorshDeserialize, BorshSerialize};
+    use rand::{RngCore, SeedableRng};
+    use std::collections::VecDeque;
+
+    use super::Deque;
+    use crate::test_utils::test_env::setup_free;
+
+    #[test]
+    fn push_pop_both_ends() {
+        let mut deque = Deque::new(b"d");
+        assert_eq!(deque.pop_front(), None);
+        assert_eq!(deque.pop_back(), None);
+
+        // Pushing to the front wraps the head index around.
+        deque.push_front(1u8);
+        deque.push_front(0);
+        deque.push_back(2);
+        assert_eq!(deque.len(), 3);
+        assert!(Iterator::eq(deque.iter(), [0, 1, 2].iter()));
+        assert!(Iterator::eq(deque.iter().rev(), [2, 1, 0].iter()));
+
+        *deque.back_mut().unwrap() = 3;
+        deque[0] = 4;
+        assert_eq!(deque.front(), Some(&4));
+        assert_eq!(deque.back(), Some(&3));
+        assert_eq!(deque.get(3), None);
+
+        let serialized = deque.try_to_vec().unwrap();
+        drop(deque);
+        let mut deque = Deque::<u8>::try_from_slice(&serialized).unwrap();
+        assert_eq!(deque.pop_back(), Some(3));
+        assert_eq!(deque.pop_front(), Some(4));
+        assert_eq!(deque.pop_front(), Some(1));
+        assert!(deque.is_empty());
+    }
+
+    #[test]
+    fn bounded() {
+        let mut deque = Deque::bounded(b"d", 2);
+        assert_eq!(deque.capacity(), Some(2));
+        deque.push_back(1u8);
+        deque.push_front(0);
+        assert!(deque.is_full());
+        assert_eq!(deque.try_push_back(2), Err(2));
+        assert_eq!(deque.try_push_front(2), Err(2));
+
+        deque.pop_back();
+        assert!(!deque.is_full());
+        assert_eq!(deque.try_push_back(2), Ok(()));
+    }
+
+    #[test]
+    fn clear() {
+        let mut deque = Deque::new(b"d");
+        deque.extend(0..5u8);
+        deque.push_front(9);
+        deque.flush();
+        deque.clear();
+        deque.flush();
+        assert!(deque.is_empty());
+        assert_eq!(crate::mock::with_mocked_blockchain(|b| b.take_storage()).len(), 0);
+    }
+
+    #[derive(Arbitrary, Debug)]
+    enum Op {
+        PushBack(u8),
+        PushFront(u8),
+        PopBack,
+        PopFront,
+        Set(u32, u8),
+        Get(u32),
+        Flush,
+        Reset,
+        Clear,
+    }
+
+    #[test]
+    fn arbitrary() {
+        setup_free();
+
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(0);
+        let mut buf = vec![0; 4096];
+        for _ in 0..512 {
+            // Clear storage in-between runs
+            crate::mock::with_mocked_blockchain(|b| b.take_storage());
+            rng.fill_bytes(&mut buf);
+
+            let mut dq = Deque::new(b"d");
+            let mut vd = VecDeque::new();
+            let u = Unstructured::new(&buf);
+            if let Ok(ops) = Vec::<Op>::arbitrary_take_rest(u) {
+                for op in ops {
+                    match op {
+                        Op::PushBack(v) => {
+                            dq.push_back(v);
+                            vd.push_back(v);
+                        }
+                        Op::PushFront(v) => {
+                            dq.push_front(v);
+                            vd.push_front(v);
+                        }
+                        Op::PopBack => {
+                            assert_eq!(dq.pop_back(), vd.pop_back());
+                        }
+                        Op::PopFront => {
+                            assert_eq!(dq.pop_front(), vd.pop_front());
+                        }
+                        Op::Set(k, v) => {
+                            if let Some(e) = dq.get_mut(k) {
+                                *e = v;
+                                vd[k as usize] = v;
+                            }
+                        }
+                        Op::Get(k) => {
+                            assert_eq!(dq.get(k), vd.get(k as usize));
+                        }
+                        Op::Flush => {
+                            dq.flush();
+                        }
+                        Op::Reset => {
+                            let serialized = dq.try_to_vec().unwrap();
+                            dq = Deque::try_from_slice(&serialized).unwrap();
+                        }
+                        Op::Clear => {
+                            dq.clear();
+                            vd.clear();
+                        }
+                    }
+                }
+            }
+            assert_eq!(dq, vd);
+        }
+    }
+}
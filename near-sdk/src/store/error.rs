@@ -0,0 +1,45 @@
+use std::fmt;
+use std::io;
+
+/// Error returned by the fallible (`try_*`) methods of the store collections.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum StoreError {
+    /// A value could not be serialized to be written to storage.
+    SerializationError(io::Error),
+    /// A value read from storage could not be deserialized.
+    DeserializationError(io::Error),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SerializationError(e) => write!(f, "cannot serialize element: {}", e),
+            Self::DeserializationError(e) => write!(f, "cannot deserialize element: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::SerializationError(e) | Self::DeserializationError(e) => Some(e),
+        }
+    }
+}
+
+/// Error returned by `insert_new` on the store maps when the key already has a value. The map is
+/// not modified and the value which was not inserted is given back.
+#[derive(Debug, PartialEq, Eq)]
+pub struct OccupiedError<V> {
+    /// The value which was not inserted.
+    pub value: V,
+}
+
+impl<V: fmt::Debug> fmt::Display for OccupiedError<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "key already exists, value {:?} was not inserted", self.value)
+    }
+}
+
+impl<V: fmt::Debug> std::error::Error for OccupiedError<V> {}
@@ -0,0 +1,306 @@
+use std::borrow::Borrow;
+use std::fmt;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::key::ToKey;
+use super::{LookupMap, TreeMap, ERR_INCONSISTENT_STATE};
+use crate::crypto_hash::Sha256;
+use crate::{env, IntoStorageKey};
+
+/// The clock which the expiry of the entries of an [`ExpiringLookupMap`] is measured in.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExpiryClock {
+    /// Entries expire at a block height, as returned by [`env::block_height`].
+    BlockHeight,
+    /// Entries expire at a block timestamp in nanoseconds, as returned by
+    /// [`env::block_timestamp`].
+    Timestamp,
+}
+
+impl ExpiryClock {
+    fn now(self) -> u64 {
+        match self {
+            Self::BlockHeight => env::block_height(),
+            Self::Timestamp => env::block_timestamp(),
+        }
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+struct ValueAndExpiry<V> {
+    value: V,
+    expires_at: u64,
+}
+
+/// A non-iterable, lazily loaded storage map whose entries expire at a given block height or
+/// timestamp, after which they behave as if they were not in the map.
+///
+/// Expired entries are not removed from storage when they are read. They are removed when they
+/// are overwritten or removed, or in bounded batches by [`purge_expired`], which finds them
+/// through an index ordered by expiry. An entry expires once the current value of the map's
+/// [`ExpiryClock`] is greater than or equal to its expiry.
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::{ExpiringLookupMap, ExpiryClock};
+/// use near_sdk::test_utils::VMContextBuilder;
+/// use near_sdk::testing_env;
+///
+/// let mut nonces = ExpiringLookupMap::new(b"n", ExpiryClock::BlockHeight);
+/// testing_env!(VMContextBuilder::new().block_index(100).build());
+/// nonces.insert("alice".to_string(), 7u64, 110);
+/// assert_eq!(nonces.get("alice"), Some(&7));
+///
+/// testing_env!(VMContextBuilder::new().block_index(110).build());
+/// assert_eq!(nonces.get("alice"), None);
+/// assert_eq!(nonces.purge_expired(10), 1);
+/// ```
+///
+/// [`purge_expired`]: Self::purge_expired
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ExpiringLookupMap<K, V, H = Sha256>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    clock: ExpiryClock,
+    entries: LookupMap<K, ValueAndExpiry<V>, H>,
+    expiries: TreeMap<(u64, K), (), H>,
+}
+
+impl<K, V, H> fmt::Debug for ExpiringLookupMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExpiringLookupMap")
+            .field("clock", &self.clock)
+            .field("entries", &self.entries)
+            .finish()
+    }
+}
+
+impl<K, V> ExpiringLookupMap<K, V, Sha256>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+{
+    /// Create a new, empty map whose entries expire according to the given clock.
+    #[inline]
+    pub fn new<S>(prefix: S, clock: ExpiryClock) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self::with_hasher(prefix, clock)
+    }
+}
+
+impl<K, V, H> ExpiringLookupMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    /// Initialize an [`ExpiringLookupMap`] with a custom hash function.
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::crypto_hash::Keccak256;
+    /// use near_sdk::store::{ExpiringLookupMap, ExpiryClock};
+    ///
+    /// let map = ExpiringLookupMap::<String, u64, Keccak256>::with_hasher(
+    ///     b"m",
+    ///     ExpiryClock::Timestamp,
+    /// );
+    /// ```
+    pub fn with_hasher<S>(prefix: S, clock: ExpiryClock) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        let mut entries_key = prefix.into_storage_key();
+        let expiries_key = [entries_key.as_slice(), b"x"].concat();
+        entries_key.push(b'e');
+        Self {
+            clock,
+            entries: LookupMap::with_hasher(entries_key),
+            expiries: TreeMap::with_hasher(expiries_key),
+        }
+    }
+
+    /// Returns the clock which the expiry of the entries is measured in.
+    pub fn clock(&self) -> ExpiryClock {
+        self.clock
+    }
+
+    /// Flushes the intermediate values of the map before this is called when the structure is
+    /// [`Drop`]ed. This will write all modified values to storage but keep all cached values
+    /// in memory.
+    pub fn flush(&mut self) {
+        self.entries.flush();
+        self.expiries.flush();
+    }
+}
+
+impl<K, V, H> ExpiringLookupMap<K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+    /// Returns a reference to the value corresponding to the key, or `None` if the key is not in
+    /// the map or its entry has expired.
+    ///
+    /// The key may be any borrowed form of the map's key type, but [`BorshSerialize`] and
+    /// [`ToOwned<Owned = K>`](ToOwned) on the borrowed form *must* match those for the key type.
+    pub fn get<Q: ?Sized>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+    {
+        let now = self.clock.now();
+        self.entries.get(k).filter(|e| e.expires_at > now).map(|e| &e.value)
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key, or `None` if the key
+    /// is not in the map or its entry has expired.
+    pub fn get_mut<Q: ?Sized>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+    {
+        let now = self.clock.now();
+        self.entries.get_mut(k).filter(|e| e.expires_at > now).map(|e| &mut e.value)
+    }
+
+    /// Returns the expiry of the entry for the key, or `None` if the key is not in the map or its
+    /// entry has expired.
+    pub fn expires_at<Q: ?Sized>(&self, k: &Q) -> Option<u64>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+    {
+        let now = self.clock.now();
+        self.entries.get(k).map(|e| e.expires_at).filter(|&expires_at| expires_at > now)
+    }
+
+    /// Returns `true` if the map contains an entry for the key which has not expired.
+    pub fn contains_key<Q: ?Sized>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+    {
+        self.expires_at(k).is_some()
+    }
+
+    /// Inserts a key-value pair which expires at `expires_at`, measured in the map's
+    /// [`ExpiryClock`].
+    ///
+    /// If the map had an entry for the key which has not expired, its value is returned.
+    pub fn insert(&mut self, k: K, value: V, expires_at: u64) -> Option<V> {
+        let now = self.clock.now();
+        let prev = self.entries.insert(k.clone(), ValueAndExpiry { value, expires_at });
+        match prev {
+            // The entry is already in the expiry index at the same position.
+            Some(prev) if prev.expires_at == expires_at => Self::unexpired(prev, now),
+            prev => {
+                if let Some(prev) = &prev {
+                    self.remove_expiry(prev.expires_at, k.clone());
+                }
+                self.expiries.insert((expires_at, k), ());
+                prev.and_then(|prev| Self::unexpired(prev, now))
+            }
+        }
+    }
+
+    /// Removes a key from the map, returning its value if its entry had not expired.
+    pub fn remove(&mut self, k: &K) -> Option<V> {
+        let now = self.clock.now();
+        let prev = self.entries.remove(k)?;
+        self.remove_expiry(prev.expires_at, k.clone());
+        Self::unexpired(prev, now)
+    }
+
+    fn unexpired(entry: ValueAndExpiry<V>, now: u64) -> Option<V> {
+        if entry.expires_at > now {
+            Some(entry.value)
+        } else {
+            None
+        }
+    }
+
+    fn remove_expiry(&mut self, expires_at: u64, k: K) {
+        self.expiries
+            .remove(&(expires_at, k))
+            .unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE));
+    }
+
+    /// Removes up to `limit` expired entries from storage, starting with the entries which
+    /// expired first. Returns the number of entries removed.
+    ///
+    /// Expired entries already behave as missing, so this only needs to be called to reclaim
+    /// their storage, for example from a maintenance method which anyone can call.
+    pub fn purge_expired(&mut self, limit: u32) -> u32 {
+        let now = self.clock.now();
+        let mut removed = 0;
+        while removed < limit {
+            let (expires_at, k) = match self.expiries.min() {
+                Some((expires_at, k)) if *expires_at <= now => (*expires_at, k.clone()),
+                _ => break,
+            };
+            self.remove_expiry(expires_at, k.clone());
+            // Set instead of remove to avoid loading the expired value from storage.
+            self.entries.set(k, None);
+            removed += 1;
+        }
+        removed
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::{ExpiringLookupMap, ExpiryClock};
+    use crate::test_utils::VMContextBuilder;
+    use crate::testing_env;
+    use borsh::{BorshDeserialize, BorshSerialize};
+
+    fn set_timestamp(ns: u64) {
+        testing_env!(VMContextBuilder::new().block_timestamp(ns).build());
+    }
+
+    #[test]
+    fn expiry_and_purge() {
+        set_timestamp(0);
+        let mut map = ExpiringLookupMap::new(b"m", ExpiryClock::Timestamp);
+        for i in 0..10u32 {
+            map.insert(i, i, 100 + u64::from(i) * 10);
+        }
+        // Extending an entry moves it in the expiry index.
+        map.insert(0, 0, 1000);
+        map.flush();
+
+        let serialized = map.try_to_vec().unwrap();
+        drop(map);
+        set_timestamp(150);
+        let mut map = ExpiringLookupMap::<u32, u32>::try_from_slice(&serialized).unwrap();
+
+        assert_eq!(map.get(&0), Some(&0));
+        assert_eq!(map.get(&4), None);
+        assert_eq!(map.expires_at(&5), None);
+        assert_eq!(map.expires_at(&6), Some(160));
+        // Overwriting an expired entry does not return its value.
+        assert_eq!(map.insert(1, 11, 2000), None);
+        assert_eq!(map.remove(&2), None);
+
+        assert_eq!(map.purge_expired(2), 2);
+        assert_eq!(map.purge_expired(10), 1);
+        assert_eq!(map.purge_expired(10), 0);
+        assert!(!map.contains_key(&3));
+        assert!(map.contains_key(&1));
+        assert!((6..10).all(|i| map.get(&i) == Some(&i)));
+    }
+}
@@ -1,4 +1,5 @@
 use std::iter::FusedIterator;
+use std::mem;
 
 use borsh::{BorshDeserialize, BorshSerialize};
 
@@ -200,3 +201,90 @@ where
         }
     }
 }
+
+/// A draining iterator for [`FreeList<T>`]. This only yields the occupied entries, and removes
+/// all cells from the bucket, including the ones not yielded when the iterator is dropped.
+pub struct Drain<'a, T>
+where
+    T: BorshDeserialize + BorshSerialize,
+{
+    /// Draining iterator over the empty and filled cells.
+    values: vec::Drain<'a, Slot<T>>,
+    /// Amount of valid elements left to iterate.
+    elements_left: u32,
+}
+
+impl<'a, T> Drain<'a, T>
+where
+    T: BorshDeserialize + BorshSerialize,
+{
+    pub(super) fn new(bucket: &'a mut FreeList<T>) -> Self {
+        let elements_left = mem::take(&mut bucket.occupied_count);
+        bucket.first_free = None;
+        Self { values: bucket.elements.drain(..), elements_left }
+    }
+}
+
+impl<'a, T> Iterator for Drain<'a, T>
+where
+    T: BorshDeserialize + BorshSerialize,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.elements_left == 0 {
+            return None;
+        }
+        loop {
+            match self.values.next() {
+                Some(Slot::Empty { .. }) => continue,
+                Some(Slot::Occupied(value)) => {
+                    decrement_count(&mut self.elements_left);
+                    return Some(value);
+                }
+                None => {
+                    // This should never be hit, because if 0 occupied elements, should have
+                    // returned before the loop
+                    env::panic_str(ERR_INCONSISTENT_STATE)
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let elements_left = self.elements_left as usize;
+        (elements_left, Some(elements_left))
+    }
+
+    fn count(self) -> usize {
+        self.elements_left as usize
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Drain<'a, T> where T: BorshSerialize + BorshDeserialize {}
+impl<'a, T> FusedIterator for Drain<'a, T> where T: BorshSerialize + BorshDeserialize {}
+
+impl<'a, T> DoubleEndedIterator for Drain<'a, T>
+where
+    T: BorshSerialize + BorshDeserialize,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.elements_left == 0 {
+            return None;
+        }
+        loop {
+            match self.values.next_back() {
+                Some(Slot::Empty { .. }) => continue,
+                Some(Slot::Occupied(value)) => {
+                    decrement_count(&mut self.elements_left);
+                    return Some(value);
+                }
+                None => {
+                    // This should never be hit, because if 0 occupied elements, should have
+                    // returned before the loop
+                    env::panic_str(ERR_INCONSISTENT_STATE)
+                }
+            }
+        }
+    }
+}
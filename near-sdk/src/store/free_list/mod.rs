@@ -1,20 +1,44 @@
 mod iter;
-pub use self::iter::{Iter, IterMut};
+pub use self::iter::{Drain, Iter, IterMut};
 
-use super::{Vector, ERR_INCONSISTENT_STATE};
+use super::sample::{sample_indices, SeedRng};
+use super::transaction::{Rollback, StorageSnapshot};
+use super::{StoreError, Vector, ERR_INCONSISTENT_STATE};
 use crate::{env, IntoStorageKey};
 
 use borsh::{BorshDeserialize, BorshSerialize};
 
+const ERR_ELEMENT_SERIALIZATION: &str = "Cannot serialize element";
+
 use std::{fmt, mem};
 
-/// Index for value within a bucket.
+/// Index for value within a bucket. Indices stay valid until the value at the index is removed,
+/// so they can be stored and used as stable handles to the values.
 #[derive(BorshSerialize, BorshDeserialize, Debug, Hash, PartialEq, Eq, Clone, Copy)]
 pub struct FreeListIndex(u32);
 
 /// Unordered container of values. This is similar to [`Vector`] except that values are not
 /// re-arranged on removal, keeping the indices consistent. When an element is removed, it will
 /// be replaced with an empty cell which will be populated on the next insertion.
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::FreeList;
+///
+/// let mut registry = FreeList::new(b"r");
+/// let alice = registry.insert("alice".to_string());
+/// let bob = registry.insert("bob".to_string());
+///
+/// // Removing a value does not change the indices of other values.
+/// assert_eq!(registry.remove(alice), Some("alice".to_string()));
+/// assert_eq!(registry.get(bob).map(String::as_str), Some("bob"));
+/// assert_eq!(registry.get(alice), None);
+///
+/// // The slot of the removed value is reused by the next insertion.
+/// let carol = registry.insert("carol".to_string());
+/// assert_eq!(carol, alice);
+/// assert_eq!(registry.iter().count(), 2);
+/// ```
 pub struct FreeList<T>
 where
     T: BorshSerialize,
@@ -104,6 +128,7 @@ impl<T> FreeList<T>
 where
     T: BorshSerialize,
 {
+    /// Create new bucket with zero elements. Prefixes storage accesss with the prefix provided.
     pub fn new<S: IntoStorageKey>(prefix: S) -> Self {
         Self { first_free: None, occupied_count: 0, elements: Vector::new(prefix) }
     }
@@ -121,6 +146,35 @@ where
         self.elements.flush()
     }
 
+    /// Flushes cached changes to storage, as with [`flush`](Self::flush), but returns a
+    /// [`StoreError`] if a value cannot be serialized.
+    pub fn try_flush(&mut self) -> Result<(), StoreError> {
+        self.elements.try_flush()
+    }
+
+    /// Returns the number of storage bytes added by flushes of this bucket since it was created
+    /// or loaded from storage.
+    pub fn bytes_written(&self) -> u64 {
+        self.elements.bytes_written()
+    }
+
+    /// Returns the number of storage bytes released by flushes of this bucket since it was
+    /// created or loaded from storage.
+    pub fn bytes_freed(&self) -> u64 {
+        self.elements.bytes_freed()
+    }
+
+    /// Returns the number of storage bytes used by a new occupied slot holding `value`.
+    pub(crate) fn estimated_slot_bytes(&self, value: &T) -> u64
+    where
+        T: BorshSerialize,
+    {
+        let value_len =
+            value.try_to_vec().unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_SERIALIZATION)).len();
+        // Occupied slots are prefixed by the enum variant byte.
+        self.elements.estimated_slot_bytes(1 + value_len)
+    }
+
     /// Clears the bucket, removing all values (including removed entries).
     pub fn clear(&mut self) {
         self.elements.clear();
@@ -200,6 +254,33 @@ where
         prev.into_value()
     }
 
+    /// Returns `n` distinct values chosen uniformly at random, in random order, or all values if
+    /// the bucket holds fewer than `n`.
+    pub(crate) fn sample(&self, n: u32, rng: &mut SeedRng) -> Vec<&T> {
+        let n = n.min(self.len());
+        if n.saturating_mul(2) >= self.len() {
+            // Most values are needed, so load all of them instead of probing for occupied slots.
+            let values: Vec<&T> = self.iter().collect();
+            return sample_indices(values.len() as u32, n, rng)
+                .into_iter()
+                .map(|i| values[i as usize])
+                .collect();
+        }
+        // Probe random slots, skipping removed ones. At least half of the remaining values are
+        // not yet chosen, so this terminates quickly unless most slots have been removed.
+        let mut chosen = std::collections::HashSet::new();
+        let mut sample = Vec::with_capacity(n as usize);
+        while sample.len() < n as usize {
+            let slot = rng.below(self.elements.len() as u64) as u32;
+            if let Some(value) = self.get(FreeListIndex(slot)) {
+                if chosen.insert(slot) {
+                    sample.push(value);
+                }
+            }
+        }
+        sample
+    }
+
     /// Generates iterator for shared references to each value in the bucket.
     pub fn iter(&self) -> Iter<T> {
         Iter::new(self)
@@ -209,6 +290,84 @@ where
     pub fn iter_mut(&mut self) -> IterMut<T> {
         IterMut::new(self)
     }
+
+    /// Retains only the values specified by the predicate. Removed slots are reused by later
+    /// insertions, and the indices of retained values do not change.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        for i in 0..self.elements.len() {
+            let keep = match self.elements.get(i) {
+                Some(Slot::Occupied(value)) => f(value),
+                Some(Slot::Empty { .. }) => continue,
+                None => env::panic_str(ERR_INCONSISTENT_STATE),
+            };
+            if !keep {
+                self.remove(FreeListIndex(i));
+            }
+        }
+    }
+
+    /// Removes up to `limit` cells, including the ones of removed values, from the end of the
+    /// bucket. Returns `true` if there are cells left to remove.
+    ///
+    /// This can be used to clear a bucket which is too large to [`clear`](Self::clear) within the
+    /// gas limit of a single function call. Empty cells which are not removed yet are not reused
+    /// by later insertions.
+    pub fn clear_chunk(&mut self, limit: u32) -> bool {
+        self.clear_chunk_with(limit, drop)
+    }
+
+    /// Same as [`clear_chunk`](Self::clear_chunk), but passes each removed value to `f`.
+    pub(crate) fn clear_chunk_with<F>(&mut self, limit: u32, mut f: F) -> bool
+    where
+        F: FnMut(T),
+    {
+        for _ in 0..limit {
+            match self.elements.pop() {
+                Some(Slot::Occupied(value)) => {
+                    self.occupied_count -= 1;
+                    f(value);
+                }
+                Some(Slot::Empty { .. }) => {
+                    // The removed cell could be linked from any other empty cell, so stop reusing
+                    // empty cells instead of searching for it.
+                    self.first_free = None;
+                }
+                None => break,
+            }
+        }
+        !self.elements.is_empty()
+    }
+
+    /// Creates a draining iterator that removes all values from the bucket and yields the
+    /// removed values. All values are removed when the iterator is dropped, even if it was not
+    /// fully consumed.
+    pub fn drain(&mut self) -> Drain<'_, T> {
+        Drain::new(self)
+    }
+}
+
+impl<T> Rollback for FreeList<T>
+where
+    T: BorshSerialize,
+{
+    type Snapshot = (Option<FreeListIndex>, u32, (u32, StorageSnapshot));
+
+    fn snapshot(&mut self) -> Self::Snapshot {
+        (self.first_free, self.occupied_count, self.elements.snapshot())
+    }
+
+    fn rollback(&mut self, (first_free, occupied_count, elements): Self::Snapshot) {
+        self.first_free = first_free;
+        self.occupied_count = occupied_count;
+        self.elements.rollback(elements);
+    }
+
+    fn commit(&mut self, (_, _, elements): Self::Snapshot) {
+        self.elements.commit(elements);
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -238,6 +397,27 @@ mod tests {
         assert_eq!(bucket.get(i3), Some(&4));
     }
 
+    #[test]
+    fn drain() {
+        let mut bucket = FreeList::new(b"b");
+        let indices: Vec<_> = (0..8u8).map(|i| bucket.insert(i)).collect();
+        bucket.remove(indices[2]);
+        bucket.remove(indices[5]);
+
+        let mut drain = bucket.drain();
+        assert_eq!(drain.len(), 6);
+        assert_eq!(drain.next(), Some(0));
+        assert_eq!(drain.next_back(), Some(7));
+        assert_eq!(drain.len(), 4);
+        drop(drain);
+
+        assert!(bucket.is_empty());
+        assert_eq!(bucket.iter().count(), 0);
+        let i = bucket.insert(9);
+        assert_eq!(i, FreeListIndex(0));
+        assert_eq!(bucket.iter().collect::<Vec<_>>(), [&9]);
+    }
+
     #[test]
     fn bucket_iterator() {
         let mut bucket = FreeList::new(b"b");
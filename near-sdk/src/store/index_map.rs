@@ -1,8 +1,12 @@
+use std::cell::RefCell;
 use std::fmt;
 
 use borsh::{BorshDeserialize, BorshSerialize};
 use once_cell::unsync::OnceCell;
 
+use super::byte_counter::STORAGE_RECORD_BYTES;
+use super::transaction::{Rollback, StorageSnapshot, UndoLog};
+use super::{journal, read_buffer, ByteCounter, StoreError};
 use crate::utils::StableMap;
 use crate::{env, CacheEntry, EntryState, IntoStorageKey};
 
@@ -23,6 +27,15 @@ where
     /// Some functionality would be different from tests to Wasm if exceeding 32-bit length.
     #[borsh_skip]
     pub(crate) cache: StableMap<u32, OnceCell<CacheEntry<T>>>,
+    /// Storage bytes written and freed by flushes since the map was created or loaded.
+    #[borsh_skip]
+    pub(crate) bytes: ByteCounter,
+    /// Buffer reused to build the storage keys of the elements which are read.
+    #[borsh_skip]
+    key_buffer: RefCell<Vec<u8>>,
+    /// Previous values of the keys written during a transaction.
+    #[borsh_skip]
+    undo: UndoLog,
 }
 
 impl<T> IndexMap<T>
@@ -34,7 +47,13 @@ where
     where
         S: IntoStorageKey,
     {
-        Self { prefix: prefix.into_storage_key().into_boxed_slice(), cache: Default::default() }
+        Self {
+            prefix: prefix.into_storage_key().into_boxed_slice(),
+            cache: Default::default(),
+            bytes: Default::default(),
+            key_buffer: Default::default(),
+            undo: Default::default(),
+        }
     }
 
     fn index_to_lookup_key(prefix: &[u8], index: u32, buf: &mut Vec<u8>) {
@@ -42,8 +61,20 @@ where
         buf.extend_from_slice(&index.to_le_bytes());
     }
 
+    /// Returns the number of storage bytes used by a slot holding a serialized value of
+    /// `value_len` bytes.
+    pub(crate) fn estimated_slot_bytes(&self, value_len: usize) -> u64 {
+        (self.prefix.len() + 4 + value_len) as u64 + STORAGE_RECORD_BYTES
+    }
+
     /// Flushes the cache and writes all modified values to storage.
     pub fn flush(&mut self) {
+        self.try_flush().unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_SERIALIZATION))
+    }
+
+    /// Flushes the cache, as with [`flush`](Self::flush), but returns a [`StoreError`] if a value
+    /// cannot be serialized.
+    pub fn try_flush(&mut self) -> Result<(), StoreError> {
         let mut buf = Vec::new();
         // Capacity is prefix length plus bytes needed for u32 bytes (4*u8)
         let mut key_buf = Vec::with_capacity(self.prefix.len() + 4);
@@ -56,12 +87,16 @@ where
                         Some(modified) => {
                             buf.clear();
                             BorshSerialize::serialize(modified, &mut buf)
-                                .unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_SERIALIZATION));
-                            env::storage_write(&key_buf, &buf);
+                                .map_err(StoreError::SerializationError)?;
+                            let replaced = journal::storage_write(&key_buf, &buf);
+                            self.bytes.record_write(key_buf.len(), buf.len(), replaced);
+                            self.undo.record(&key_buf, replaced);
                         }
                         None => {
                             // Element was removed, clear the storage for the value
-                            env::storage_remove(&key_buf);
+                            let removed = journal::storage_remove(&key_buf);
+                            self.bytes.record_remove(key_buf.len(), removed);
+                            self.undo.record(&key_buf, removed);
                         }
                     }
 
@@ -71,6 +106,7 @@ where
                 }
             }
         }
+        Ok(())
     }
 
     /// Sets a value at a given index to the value provided. If none is provided, this index will
@@ -86,38 +122,72 @@ where
     }
 }
 
+impl<T> Rollback for IndexMap<T>
+where
+    T: BorshSerialize,
+{
+    type Snapshot = StorageSnapshot;
+
+    fn snapshot(&mut self) -> Self::Snapshot {
+        self.flush();
+        (self.bytes, self.undo.begin())
+    }
+
+    fn rollback(&mut self, (bytes, undo): Self::Snapshot) {
+        self.cache.inner().clear();
+        self.undo.rollback(undo);
+        self.bytes = bytes;
+    }
+
+    fn commit(&mut self, (_, undo): Self::Snapshot) {
+        self.undo.commit(undo);
+    }
+}
+
 impl<T> IndexMap<T>
 where
     T: BorshSerialize + BorshDeserialize,
 {
-    fn deserialize_element(raw_element: &[u8]) -> T {
-        T::try_from_slice(raw_element)
-            .unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_DESERIALIZATION))
+    fn deserialize_element(raw_element: &[u8]) -> Result<T, StoreError> {
+        T::try_from_slice(raw_element).map_err(StoreError::DeserializationError)
+    }
+
+    /// Reads the element at the index from storage, building its key in the buffer.
+    fn load_element(
+        prefix: &[u8],
+        buffer: &RefCell<Vec<u8>>,
+        index: u32,
+    ) -> Result<CacheEntry<T>, StoreError> {
+        let mut key = buffer.borrow_mut();
+        key.clear();
+        Self::index_to_lookup_key(prefix, index, &mut key);
+        let value = read_buffer::with_storage_value(&key, Self::deserialize_element).transpose()?;
+        Ok(CacheEntry::new_cached(value))
     }
 
     /// Returns the element by index or `None` if it is not present.
     pub fn get(&self, index: u32) -> Option<&T> {
-        let entry = self.cache.get(index).get_or_init(|| {
-            let mut buf = Vec::with_capacity(self.prefix.len() + 4);
-            Self::index_to_lookup_key(&self.prefix, index, &mut buf);
-            let storage_bytes = env::storage_read(&buf);
-            let value = storage_bytes.as_deref().map(Self::deserialize_element);
-            CacheEntry::new_cached(value)
-        });
-        entry.value().as_ref()
+        self.try_get(index).unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_DESERIALIZATION))
+    }
+
+    /// Returns the element by index, or a [`StoreError`] if it cannot be deserialized. A failed
+    /// load is not cached.
+    pub fn try_get(&self, index: u32) -> Result<Option<&T>, StoreError> {
+        let entry = self
+            .cache
+            .get(index)
+            .get_or_try_init(|| Self::load_element(&self.prefix, &self.key_buffer, index))?;
+        Ok(entry.value().as_ref())
     }
 
     /// Returns a mutable reference to the element at the `index` provided.
     pub(crate) fn get_mut_inner(&mut self, index: u32) -> &mut CacheEntry<T> {
         let prefix = &self.prefix;
+        let key_buffer = &self.key_buffer;
         let entry = self.cache.get_mut(index);
-        entry.get_or_init(|| {
-            let mut key = Vec::with_capacity(prefix.len() + 4);
-            Self::index_to_lookup_key(prefix, index, &mut key);
-            let storage_bytes = env::storage_read(&key);
-            let value = storage_bytes.as_deref().map(Self::deserialize_element);
-            CacheEntry::new_cached(value)
-        });
+        entry
+            .get_or_try_init(|| Self::load_element(prefix, key_buffer, index))
+            .unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_DESERIALIZATION));
         let entry = entry.get_mut().unwrap();
         entry
     }
@@ -0,0 +1,361 @@
+use std::borrow::Borrow;
+use std::fmt;
+use std::ops::Bound;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::key::ToKey;
+use super::{LookupMap, TreeMap, ERR_INCONSISTENT_STATE};
+use crate::crypto_hash::Sha256;
+use crate::{env, IntoStorageKey};
+
+const ERR_ELEMENT_SERIALIZATION: &str = "Cannot serialize element";
+const ERR_INDICES_NOT_REGISTERED: &str =
+    "IndexedMap indices must be registered with `with_index` before the map is modified";
+
+type Extractor<V> = Box<dyn Fn(&V) -> Vec<u8>>;
+
+/// Key of an entry of a secondary index: the index number, the serialized index key and the
+/// serialized primary key.
+type IndexEntry = (u32, Vec<u8>, Vec<u8>);
+
+/// A non-iterable, lazily loaded storage map which keeps secondary indices over its values in
+/// sync with the map.
+///
+/// Each index is declared with a closure extracting an index key from a value, and maps each
+/// index key to the keys of all entries whose values have that index key. Inserting, updating
+/// or removing an entry updates every index, so entries can be looked up by index key with
+/// [`get_by_index`] without maintaining a second map by hand.
+///
+/// The closures are not stored in the contract state, so the indices must be declared again, in
+/// the same order, every time the map is loaded. Modifying the map before all of its indices
+/// have been declared panics. Indices should be declared before any entry is inserted, as
+/// existing entries are not added to an index declared later.
+///
+/// # Examples
+/// ```
+/// use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+/// use near_sdk::store::IndexedMap;
+///
+/// #[derive(BorshSerialize, BorshDeserialize)]
+/// struct Listing {
+///     seller: String,
+///     price: u128,
+/// }
+///
+/// const BY_SELLER: u32 = 0;
+///
+/// let mut listings = IndexedMap::new(b"l").with_index(|l: &Listing| l.seller.clone());
+/// listings.insert(1u64, Listing { seller: "alice".to_string(), price: 10 });
+/// listings.insert(2, Listing { seller: "bob".to_string(), price: 20 });
+/// listings.insert(3, Listing { seller: "alice".to_string(), price: 30 });
+///
+/// let alice: Vec<u64> =
+///     listings.get_by_index(BY_SELLER, &"alice".to_string()).copied().collect();
+/// assert_eq!(alice, [1, 3]);
+///
+/// listings.update(&3, |l| l.seller = "bob".to_string());
+/// assert_eq!(listings.get_by_index(BY_SELLER, &"bob".to_string()).count(), 2);
+/// ```
+///
+/// [`get_by_index`]: Self::get_by_index
+pub struct IndexedMap<K, V, H = Sha256>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    primary: LookupMap<K, V, H>,
+    indices: TreeMap<IndexEntry, K, H>,
+    num_indices: u32,
+    extractors: Vec<Extractor<V>>,
+}
+
+//? Manual implementations needed only because borsh derive is leaking field types
+// https://github.com/near/borsh-rs/issues/41
+impl<K, V, H> BorshSerialize for IndexedMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    fn serialize<W: borsh::maybestd::io::Write>(
+        &self,
+        writer: &mut W,
+    ) -> Result<(), borsh::maybestd::io::Error> {
+        BorshSerialize::serialize(&self.primary, writer)?;
+        BorshSerialize::serialize(&self.indices, writer)?;
+        BorshSerialize::serialize(&self.num_indices, writer)?;
+        Ok(())
+    }
+}
+
+impl<K, V, H> BorshDeserialize for IndexedMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    fn deserialize(buf: &mut &[u8]) -> Result<Self, borsh::maybestd::io::Error> {
+        Ok(Self {
+            primary: BorshDeserialize::deserialize(buf)?,
+            indices: BorshDeserialize::deserialize(buf)?,
+            num_indices: BorshDeserialize::deserialize(buf)?,
+            extractors: Vec::new(),
+        })
+    }
+}
+
+impl<K, V, H> fmt::Debug for IndexedMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IndexedMap")
+            .field("primary", &self.primary)
+            .field("num_indices", &self.num_indices)
+            .finish()
+    }
+}
+
+impl<K, V> IndexedMap<K, V, Sha256>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+{
+    /// Create a new map without any indices. Declare the indices with
+    /// [`with_index`](Self::with_index).
+    #[inline]
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self::with_hasher(prefix)
+    }
+}
+
+impl<K, V, H> IndexedMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    /// Initialize an [`IndexedMap`] with a custom hash function.
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::crypto_hash::Keccak256;
+    /// use near_sdk::store::IndexedMap;
+    ///
+    /// let map = IndexedMap::<u64, String, Keccak256>::with_hasher(b"m");
+    /// ```
+    pub fn with_hasher<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        let mut primary_key = prefix.into_storage_key();
+        let indices_key = [primary_key.as_slice(), b"i"].concat();
+        primary_key.push(b'p');
+        Self {
+            primary: LookupMap::with_hasher(primary_key),
+            indices: TreeMap::with_hasher(indices_key),
+            num_indices: 0,
+            extractors: Vec::new(),
+        }
+    }
+
+    /// Declares the next secondary index, which maps the key returned by `extract` for each
+    /// value to the keys of the entries with that value. Indices are numbered in the order they
+    /// are declared, starting from zero.
+    pub fn with_index<I, F>(mut self, extract: F) -> Self
+    where
+        I: BorshSerialize,
+        F: Fn(&V) -> I + 'static,
+    {
+        self.extractors.push(Box::new(move |value| {
+            extract(value)
+                .try_to_vec()
+                .unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_SERIALIZATION))
+        }));
+        self.num_indices = self.num_indices.max(self.extractors.len() as u32);
+        self
+    }
+
+    /// Flushes the intermediate values of the map before this is called when the structure is
+    /// [`Drop`]ed. This will write all modified values to storage but keep all cached values
+    /// in memory.
+    pub fn flush(&mut self) {
+        self.primary.flush();
+        self.indices.flush();
+    }
+}
+
+impl<K, V, H> IndexedMap<K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+    /// Returns a reference to the value corresponding to the key.
+    ///
+    /// The key may be any borrowed form of the map's key type, but [`BorshSerialize`] and
+    /// [`ToOwned<Owned = K>`](ToOwned) on the borrowed form *must* match those for the key type.
+    pub fn get<Q: ?Sized>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+    {
+        self.primary.get(k)
+    }
+
+    /// Returns `true` if the map contains a value for the specified key.
+    pub fn contains_key<Q: ?Sized>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K> + Ord,
+    {
+        self.primary.contains_key(k)
+    }
+
+    /// Returns an iterator over the keys of the entries whose values have the given key in the
+    /// index, in order of their serialized keys. The values are not loaded from storage.
+    pub fn get_by_index<I>(&self, index: u32, index_key: &I) -> impl Iterator<Item = &K>
+    where
+        I: BorshSerialize,
+    {
+        let index_key =
+            index_key.try_to_vec().unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_SERIALIZATION));
+        // The empty primary key is the smallest entry for the index key.
+        let start = (index, index_key, Vec::new());
+        self.indices
+            .range::<IndexEntry, _>((Bound::Included(&start), Bound::Unbounded))
+            .take_while(move |((i, key, _), _)| *i == start.0 && *key == start.1)
+            .map(|(_, k)| k)
+    }
+
+    fn check_indices(&self) {
+        if self.extractors.len() as u32 != self.num_indices {
+            env::panic_str(ERR_INDICES_NOT_REGISTERED);
+        }
+    }
+
+    fn index_keys(extractors: &[Extractor<V>], value: &V) -> Vec<Vec<u8>> {
+        extractors.iter().map(|extract| extract(value)).collect()
+    }
+
+    /// Moves the key between index entries for the index keys of its old and new values.
+    fn reindex(&mut self, k: &K, old_keys: Option<Vec<Vec<u8>>>, new_keys: Option<Vec<Vec<u8>>>) {
+        let encoded = k.try_to_vec().unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_SERIALIZATION));
+        let mut old_keys = old_keys.map(Vec::into_iter);
+        let mut new_keys = new_keys.map(Vec::into_iter);
+        for index in 0..self.num_indices {
+            let old_key = old_keys.as_mut().and_then(Iterator::next);
+            let new_key = new_keys.as_mut().and_then(Iterator::next);
+            if old_key == new_key {
+                continue;
+            }
+            if let Some(old_key) = old_key {
+                self.indices
+                    .remove(&(index, old_key, encoded.clone()))
+                    .unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE));
+            }
+            if let Some(new_key) = new_key {
+                self.indices.insert((index, new_key, encoded.clone()), k.clone());
+            }
+        }
+    }
+
+    /// Inserts a key-value pair into the map, updating every index.
+    ///
+    /// If the map did have this key present, the value is updated, and the old value is
+    /// returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the indices of the map have not all been declared.
+    pub fn insert(&mut self, k: K, value: V) -> Option<V> {
+        self.check_indices();
+        let new_keys = Self::index_keys(&self.extractors, &value);
+        let prev = self.primary.insert(k.clone(), value);
+        let old_keys = prev.as_ref().map(|prev| Self::index_keys(&self.extractors, prev));
+        self.reindex(&k, old_keys, Some(new_keys));
+        prev
+    }
+
+    /// Modifies the value of the key with the function, updating every index with the new value.
+    /// Returns `false` if the map does not contain the key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the indices of the map have not all been declared.
+    pub fn update<F>(&mut self, k: &K, f: F) -> bool
+    where
+        F: FnOnce(&mut V),
+    {
+        self.check_indices();
+        let old_keys = match self.primary.get(k) {
+            Some(value) => Self::index_keys(&self.extractors, value),
+            None => return false,
+        };
+        let value =
+            self.primary.get_mut(k).unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE));
+        f(value);
+        let new_keys = Self::index_keys(&self.extractors, value);
+        self.reindex(k, Some(old_keys), Some(new_keys));
+        true
+    }
+
+    /// Removes a key from the map and from every index, returning the value at the key if the
+    /// key was previously in the map.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the indices of the map have not all been declared.
+    pub fn remove(&mut self, k: &K) -> Option<V> {
+        self.check_indices();
+        let prev = self.primary.remove(k)?;
+        let old_keys = Self::index_keys(&self.extractors, &prev);
+        self.reindex(k, Some(old_keys), None);
+        Some(prev)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::IndexedMap;
+    use borsh::{BorshDeserialize, BorshSerialize};
+
+    fn by_parity_and_tens(map: IndexedMap<u32, u32>) -> IndexedMap<u32, u32> {
+        map.with_index(|v: &u32| v % 2 == 0).with_index(|v: &u32| v / 10)
+    }
+
+    #[test]
+    fn indices_follow_values() {
+        let mut map = by_parity_and_tens(IndexedMap::new(b"m"));
+        for k in 0..30u32 {
+            map.insert(k, k);
+        }
+        map.insert(4, 15);
+        assert!(map.update(&5, |v| *v = 26));
+        assert!(!map.update(&100, |_| unreachable!()));
+        assert_eq!(map.remove(&6), Some(6));
+        map.flush();
+
+        let serialized = map.try_to_vec().unwrap();
+        drop(map);
+        let map = by_parity_and_tens(IndexedMap::try_from_slice(&serialized).unwrap());
+
+        let evens: Vec<u32> = map.get_by_index(0, &true).copied().collect();
+        assert_eq!(evens.len(), 14);
+        assert!(evens.iter().all(|k| map.get(k).unwrap() % 2 == 0));
+        let mut ones: Vec<u32> = map.get_by_index(1, &1u32).copied().collect();
+        ones.sort_unstable();
+        assert_eq!(ones, [4, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19]);
+        assert_eq!(map.get_by_index(1, &2u32).count(), 11);
+        assert_eq!(map.get_by_index(1, &0u32).count(), 7);
+        assert_eq!(map.get_by_index(1, &3u32).count(), 0);
+    }
+}
@@ -0,0 +1,104 @@
+//! Journal of the storage mutations performed by the collections in this module.
+//!
+//! With the `store-journal` feature enabled, every storage write and removal made by a collection
+//! is recorded in the [`MockedBlockchain`](crate::mock::MockedBlockchain), so that unit tests can
+//! assert exactly which storage keys a method touched:
+//!
+//! ```ignore
+//! use near_sdk::store::journal::StorageMutation;
+//! use near_sdk::store::Vector;
+//! use near_sdk::{mock, testing_env};
+//! use near_sdk::test_utils::VMContextBuilder;
+//!
+//! testing_env!(VMContextBuilder::new().build());
+//! let mut vec = Vector::new(b"v");
+//! vec.push(1u8);
+//! vec.flush();
+//!
+//! let journal = mock::with_mocked_blockchain(|b| b.take_store_journal());
+//! assert_eq!(
+//!     journal,
+//!     [StorageMutation::Write { key: b"v\0\0\0\0".to_vec(), value: vec![1] }]
+//! );
+//! ```
+//!
+//! The journal is reset when a new blockchain is set up through
+//! [`testing_env!`](crate::testing_env), so that it only contains the mutations of one call.
+//! Without the feature, or when compiled to Wasm, nothing is recorded.
+
+use crate::env;
+
+/// A storage mutation performed by a collection.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StorageMutation {
+    /// The value was written under the key.
+    Write { key: Vec<u8>, value: Vec<u8> },
+    /// The key was removed from storage.
+    Remove { key: Vec<u8> },
+}
+
+/// Writes the value to storage through [`env::storage_write`], recording the mutation.
+pub(crate) fn storage_write(key: &[u8], value: &[u8]) -> bool {
+    record(|| StorageMutation::Write { key: key.to_vec(), value: value.to_vec() });
+    env::storage_write(key, value)
+}
+
+/// Writes the key-value pairs to storage through [`env::storage_write_batch`], recording the
+/// mutations, and calls `f` after each write as [`env::storage_write_batch_with`] does.
+pub(crate) fn storage_write_batch<'a, I, F>(entries: I, f: F)
+where
+    I: IntoIterator<Item = (&'a [u8], &'a [u8])>,
+    F: FnMut(&[u8], &[u8], bool),
+{
+    let entries = entries.into_iter().inspect(|(key, value)| {
+        record(|| StorageMutation::Write { key: key.to_vec(), value: value.to_vec() })
+    });
+    env::storage_write_batch_with(entries, f)
+}
+
+/// Removes the key from storage through [`env::storage_remove`], recording the mutation.
+pub(crate) fn storage_remove(key: &[u8]) -> bool {
+    record(|| StorageMutation::Remove { key: key.to_vec() });
+    env::storage_remove(key)
+}
+
+#[cfg(all(feature = "store-journal", not(target_arch = "wasm32")))]
+fn record<F: FnOnce() -> StorageMutation>(mutation: F) {
+    crate::mock::with_mocked_blockchain(|b| b.record_store_mutation(mutation()));
+}
+
+#[cfg(not(all(feature = "store-journal", not(target_arch = "wasm32"))))]
+#[inline(always)]
+fn record<F: FnOnce() -> StorageMutation>(_mutation: F) {}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(all(test, feature = "store-journal"))]
+mod tests {
+    use super::StorageMutation;
+    use crate::store::LookupMap;
+    use crate::test_utils::VMContextBuilder;
+    use crate::{mock, testing_env};
+
+    #[test]
+    fn records_collection_mutations() {
+        let mut map = LookupMap::new(b"m");
+        map.insert(1u8, 2u8);
+        map.flush();
+        map.remove(&1);
+        map.flush();
+        // Mutations made directly through `env` are not recorded.
+        crate::env::storage_write(b"k", b"v");
+
+        let journal = mock::with_mocked_blockchain(|b| b.take_store_journal());
+        match &journal[..] {
+            [StorageMutation::Write { key, value }, StorageMutation::Remove { key: removed }] => {
+                assert_eq!(value, &[2]);
+                assert_eq!(key, removed);
+            }
+            journal => panic!("unexpected journal {:?}", journal),
+        }
+
+        testing_env!(VMContextBuilder::new().build());
+        assert!(mock::with_mocked_blockchain(|b| b.take_store_journal()).is_empty());
+    }
+}
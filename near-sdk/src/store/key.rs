@@ -0,0 +1,106 @@
+//! Encodings used to convert keys of storage collections into bytes before they are combined
+//! with the collection prefix to create the storage key.
+
+use borsh::BorshSerialize;
+
+use crate::crypto_hash::CryptoHasher;
+use crate::env;
+
+const ERR_ELEMENT_SERIALIZATION: &str = "Cannot serialize element";
+
+/// Encoding of a key of type `K` into bytes. The encoded bytes are appended to the collection
+/// prefix and then hashed (or used directly) as the key in storage.
+///
+/// Two keys that compare equal *must* produce the same encoding, and the encoding of a borrowed
+/// form of a key *must* match the encoding of the owned key.
+pub trait KeyEncoder<K: ?Sized> {
+    /// Appends the encoded bytes of `key` to `buffer`.
+    fn encode(key: &K, buffer: &mut Vec<u8>);
+}
+
+/// Encodes keys with their [`BorshSerialize`] implementation. This is the default encoding of
+/// storage collections.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Borsh {}
+
+impl<K> KeyEncoder<K> for Borsh
+where
+    K: BorshSerialize + ?Sized,
+{
+    fn encode(key: &K, buffer: &mut Vec<u8>) {
+        key.serialize(buffer).unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_SERIALIZATION));
+    }
+}
+
+/// Encodes byte and string keys as their raw bytes, without the length prefix added by
+/// [`Borsh`]. This allows the storage layout to match contracts which were not written with
+/// this SDK.
+///
+/// Note that without a length prefix, keys of one collection can only be distinguished from
+/// keys of another collection by using prefixes which are not prefixes of each other.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum RawBytes {}
+
+impl KeyEncoder<[u8]> for RawBytes {
+    fn encode(key: &[u8], buffer: &mut Vec<u8>) {
+        buffer.extend_from_slice(key);
+    }
+}
+
+impl KeyEncoder<Vec<u8>> for RawBytes {
+    fn encode(key: &Vec<u8>, buffer: &mut Vec<u8>) {
+        buffer.extend_from_slice(key);
+    }
+}
+
+impl KeyEncoder<str> for RawBytes {
+    fn encode(key: &str, buffer: &mut Vec<u8>) {
+        buffer.extend_from_slice(key.as_bytes());
+    }
+}
+
+impl KeyEncoder<String> for RawBytes {
+    fn encode(key: &String, buffer: &mut Vec<u8>) {
+        buffer.extend_from_slice(key.as_bytes());
+    }
+}
+
+/// Conversion of the collection prefix concatenated with an encoded key into the key used in
+/// storage. This is implemented for all 32 byte [`CryptoHasher`]s, which hash the bytes, and for
+/// [`UnhashedKeys`], which uses the bytes directly.
+pub trait ToKey {
+    /// Type of the key used in storage.
+    type KeyType: AsRef<[u8]>;
+
+    /// Converts the prefix and encoded key bytes into the storage key.
+    fn to_key(ingest: &[u8]) -> Self::KeyType;
+}
+
+impl<H> ToKey for H
+where
+    H: CryptoHasher<Digest = [u8; 32]>,
+{
+    type KeyType = [u8; 32];
+
+    fn to_key(ingest: &[u8]) -> Self::KeyType {
+        H::hash(ingest)
+    }
+}
+
+/// Stores values under the collection prefix concatenated with the encoded key, without hashing.
+/// This keeps the storage keys readable by state inspection tools, such as the `view_state`
+/// RPC method, at the cost of storage keys which grow with the size of the key.
+///
+/// This should only be used with short keys, as the runtime limits the length of storage keys
+/// and charges gas for each byte of the key. Keys of different collections are only distinct if
+/// the prefixes of the collections are not prefixes of each other.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum UnhashedKeys {}
+
+impl ToKey for UnhashedKeys {
+    type KeyType = Vec<u8>;
+
+    fn to_key(ingest: &[u8]) -> Self::KeyType {
+        ingest.to_vec()
+    }
+}
@@ -10,6 +10,7 @@ use borsh::{BorshDeserialize, BorshSerialize};
 use once_cell::unsync::OnceCell;
 
 use crate::env;
+use crate::store::{journal, read_buffer, StoreError};
 use crate::utils::{CacheEntry, EntryState};
 use crate::IntoStorageKey;
 
@@ -27,22 +28,56 @@ fn expect_consistent_state<T>(val: Option<T>) -> T {
     val.unwrap_or_else(|| env::panic_str(ERR_DELETED))
 }
 
+fn deserialize<T>(bytes: &[u8]) -> T
+where
+    T: BorshDeserialize,
+{
+    T::try_from_slice(bytes).unwrap_or_else(|_| env::panic_str(ERR_VALUE_DESERIALIZATION))
+}
+
 pub(crate) fn load_and_deserialize<T>(key: &[u8]) -> CacheEntry<T>
 where
     T: BorshDeserialize,
 {
-    let bytes = expect_key_exists(env::storage_read(key));
-    let val =
-        T::try_from_slice(&bytes).unwrap_or_else(|_| env::panic_str(ERR_VALUE_DESERIALIZATION));
-    CacheEntry::new_cached(Some(val))
+    let value = expect_key_exists(read_buffer::with_storage_value(key, deserialize));
+    CacheEntry::new_cached(Some(value))
+}
+
+/// Loads the value at the key, caching [`None`] if there is no value in storage.
+pub(crate) fn load_optional_and_deserialize<T>(key: &[u8]) -> CacheEntry<T>
+where
+    T: BorshDeserialize,
+{
+    CacheEntry::new_cached(read_buffer::with_storage_value(key, deserialize))
+}
+
+/// Loads the value at the key, as with [`load_optional_and_deserialize`], but returns a
+/// [`StoreError`] if the value cannot be deserialized.
+pub(crate) fn try_load_optional_and_deserialize<T>(key: &[u8]) -> Result<CacheEntry<T>, StoreError>
+where
+    T: BorshDeserialize,
+{
+    let value = read_buffer::with_storage_value(key, |bytes| {
+        T::try_from_slice(bytes).map_err(StoreError::DeserializationError)
+    });
+    Ok(CacheEntry::new_cached(value.transpose()?))
 }
 
 pub(crate) fn serialize_and_store<T>(key: &[u8], value: &T)
 where
     T: BorshSerialize,
 {
-    let serialized = value.try_to_vec().unwrap_or_else(|_| env::panic_str(ERR_VALUE_SERIALIZATION));
-    env::storage_write(key, &serialized);
+    try_serialize_and_store(key, value).unwrap_or_else(|_| env::panic_str(ERR_VALUE_SERIALIZATION))
+}
+
+/// Writes the value at the key, or returns a [`StoreError`] if it cannot be serialized.
+pub(crate) fn try_serialize_and_store<T>(key: &[u8], value: &T) -> Result<(), StoreError>
+where
+    T: BorshSerialize,
+{
+    let serialized = value.try_to_vec().map_err(StoreError::SerializationError)?;
+    journal::storage_write(key, &serialized);
+    Ok(())
 }
 
 /// An persistent lazily loaded value, that stores a value in the storage.
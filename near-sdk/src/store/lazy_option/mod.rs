@@ -4,10 +4,16 @@ use borsh::{BorshDeserialize, BorshSerialize};
 use once_cell::unsync::OnceCell;
 
 use crate::env;
-use crate::store::lazy::{load_and_deserialize, serialize_and_store};
+use crate::store::journal;
+use crate::store::lazy::{
+    load_optional_and_deserialize, try_load_optional_and_deserialize, try_serialize_and_store,
+};
+use crate::store::StoreError;
 use crate::utils::{CacheEntry, EntryState};
 use crate::IntoStorageKey;
 
+const ERR_VALUE_SERIALIZATION: &str = "Cannot serialize value with Borsh";
+
 /// An persistent lazily loaded option, that stores a `value` in the storage when `Some(value)`
 /// is set, and not when `None` is set. `LazyOption` also [`Deref`]s into [`Option`] so we get
 /// all its APIs for free.
@@ -79,15 +85,21 @@ where
     /// value is dropped through [`Drop`] so this should only be used when the changes need to be
     /// reflected in the underlying storage before then.
     pub fn flush(&mut self) {
+        self.try_flush().unwrap_or_else(|_| env::panic_str(ERR_VALUE_SERIALIZATION))
+    }
+
+    /// Writes any changes to the value to storage, as with [`flush`](Self::flush), but returns a
+    /// [`StoreError`] if the value cannot be serialized.
+    pub fn try_flush(&mut self) -> Result<(), StoreError> {
         if let Some(v) = self.cache.get_mut() {
             if !v.is_modified() {
-                return;
+                return Ok(());
             }
 
             match v.value().as_ref() {
-                Some(value) => serialize_and_store(&self.storage_key, value),
+                Some(value) => try_serialize_and_store(&self.storage_key, value)?,
                 None => {
-                    env::storage_remove(&self.storage_key);
+                    journal::storage_remove(&self.storage_key);
                 }
             }
 
@@ -95,6 +107,7 @@ where
             // stored value. This avoids writing the same value twice.
             v.replace_state(EntryState::Cached);
         }
+        Ok(())
     }
 }
 
@@ -106,15 +119,37 @@ where
     /// The load from storage only happens once, and if the value is already cached, it will not
     /// be reloaded.
     pub fn get(&self) -> &Option<T> {
-        let entry = self.cache.get_or_init(|| load_and_deserialize(&self.storage_key));
+        let entry = self.cache.get_or_init(|| load_optional_and_deserialize(&self.storage_key));
         entry.value()
     }
 
+    /// Returns a reference to the lazily loaded optional, as with [`get`](Self::get), but returns
+    /// a [`StoreError`] if the value in storage cannot be deserialized. A failed load is not
+    /// cached.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::borsh::BorshDeserialize;
+    /// use near_sdk::env;
+    /// use near_sdk::store::{LazyOption, StoreError};
+    ///
+    /// env::storage_write(b"a", &[1, 2]);
+    /// // A lazy option loaded with the state of the contract, whose value is read when needed.
+    /// let a = LazyOption::<u32>::try_from_slice(&[1, 0, 0, 0, b'a']).unwrap();
+    /// assert!(matches!(a.try_get(), Err(StoreError::DeserializationError(_))));
+    /// ```
+    pub fn try_get(&self) -> Result<&Option<T>, StoreError> {
+        let entry =
+            self.cache.get_or_try_init(|| try_load_optional_and_deserialize(&self.storage_key))?;
+        Ok(entry.value())
+    }
+
     /// Returns a reference to the lazily loaded optional.
     /// The load from storage only happens once, and if the value is already cached, it will not
     /// be reloaded.
     pub fn get_mut(&mut self) -> &mut Option<T> {
-        self.cache.get_or_init(|| load_and_deserialize(&self.storage_key));
+        self.cache.get_or_init(|| load_optional_and_deserialize(&self.storage_key));
         let entry = self.cache.get_mut().unwrap_or_else(|| env::abort());
         entry.value_mut()
     }
@@ -160,4 +195,21 @@ mod tests {
         drop(a);
         assert!(!env::storage_has_key(b"a"));
     }
+
+    #[test]
+    pub fn test_lazy_option_load_none() {
+        let a = LazyOption::<u32>::new(b"a", None);
+        let serialized = a.try_to_vec().unwrap();
+        drop(a);
+
+        // Loading a value which was never stored yields `None`.
+        let mut a = LazyOption::<u32>::try_from_slice(&serialized).unwrap();
+        assert!(a.cache.get().is_none());
+        assert_eq!(a.get(), &None);
+
+        a.set(Some(8));
+        drop(a);
+        let a = LazyOption::<u32>::try_from_slice(&serialized).unwrap();
+        assert_eq!(a.get(), &Some(8));
+    }
 }
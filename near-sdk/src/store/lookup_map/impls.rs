@@ -3,13 +3,15 @@ use std::borrow::Borrow;
 use borsh::{BorshDeserialize, BorshSerialize};
 
 use super::{LookupMap, ERR_NOT_EXIST};
-use crate::{crypto_hash::CryptoHasher, env};
+use crate::env;
+use crate::store::key::{KeyEncoder, ToKey};
 
-impl<K, V, H> Extend<(K, V)> for LookupMap<K, V, H>
+impl<K, V, H, E> Extend<(K, V)> for LookupMap<K, V, H, E>
 where
     K: BorshSerialize + Ord,
     V: BorshSerialize,
-    H: CryptoHasher<Digest = [u8; 32]>,
+    H: ToKey,
+    E: KeyEncoder<K>,
 {
     fn extend<I>(&mut self, iter: I)
     where
@@ -21,12 +23,14 @@ where
     }
 }
 
-impl<K, V, H, Q: ?Sized> core::ops::Index<&Q> for LookupMap<K, V, H>
+impl<K, V, H, E, Q: ?Sized> core::ops::Index<&Q> for LookupMap<K, V, H, E>
 where
     K: BorshSerialize + Ord + Clone + Borrow<Q>,
     V: BorshSerialize + BorshDeserialize,
-    H: CryptoHasher<Digest = [u8; 32]>,
-    Q: BorshSerialize + ToOwned<Owned = K>,
+    H: ToKey,
+    E: KeyEncoder<K>,
+    Q: ToOwned<Owned = K>,
+    E: KeyEncoder<Q>,
 {
     type Output = V;
 
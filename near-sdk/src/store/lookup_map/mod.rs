@@ -2,15 +2,22 @@ mod entry;
 mod impls;
 
 use std::borrow::Borrow;
+use std::cell::RefCell;
+use std::collections::btree_map;
 use std::fmt;
 use std::marker::PhantomData;
+use std::ops::Bound;
 
 use borsh::{BorshDeserialize, BorshSerialize};
 use once_cell::unsync::OnceCell;
 
-use crate::crypto_hash::{CryptoHasher, Sha256};
+use super::byte_counter::STORAGE_RECORD_BYTES;
+use super::key::{Borsh, KeyEncoder, ToKey};
+use super::transaction::{self, Rollback, StorageSnapshot, UndoLog};
+use super::{journal, read_buffer, ByteCounter, OccupiedError, StoreError};
+use crate::crypto_hash::{Identity, Sha256};
 use crate::utils::{EntryState, StableMap};
-use crate::{env, CacheEntry, IntoStorageKey};
+use crate::{env, CacheEntry, CryptoHash, IntoStorageKey};
 
 pub use entry::{Entry, OccupiedEntry, VacantEntry};
 
@@ -18,17 +25,20 @@ const ERR_ELEMENT_DESERIALIZATION: &str = "Cannot deserialize element";
 const ERR_ELEMENT_SERIALIZATION: &str = "Cannot serialize element";
 const ERR_NOT_EXIST: &str = "Key does not exist in map";
 
-type LookupKey = [u8; 32];
-
 /// A non-iterable, lazily loaded storage map that stores its content directly on the storage trie.
 ///
-/// This map stores the values under a hash of the map's `prefix` and [`BorshSerialize`] of the key
-/// using the map's [`CryptoHasher`] implementation.
+/// This map stores the values under a hash of the map's `prefix` and the encoded key
+/// using the map's [`CryptoHasher`](crate::crypto_hash::CryptoHasher) implementation.
 ///
 /// The default hash function for [`LookupMap`] is [`Sha256`] which uses a syscall
 /// (or host function) built into the NEAR runtime to hash the key. To use a custom function,
 /// use [`with_hasher`]. Alternative builtin hash functions can be found at
-/// [`near_sdk::crypto_hash`](crate::crypto_hash).
+/// [`near_sdk::crypto_hash`](crate::crypto_hash). To store values under the prefix and encoded
+/// key without hashing, use [`UnhashedKeys`](crate::store::key::UnhashedKeys).
+///
+/// Keys are encoded with [`BorshSerialize`] by default. The encoding can be changed through the
+/// [`KeyEncoder`] type parameter, for example to [`RawBytes`](crate::store::key::RawBytes) to
+/// store `Vec<u8>` or `String` keys without the Borsh length prefix.
 ///
 /// # Examples
 /// ```
@@ -78,57 +88,100 @@ type LookupKey = [u8; 32];
 ///
 /// [`with_hasher`]: Self::with_hasher
 #[derive(BorshSerialize, BorshDeserialize)]
-pub struct LookupMap<K, V, H = Sha256>
+pub struct LookupMap<K, V, H = Sha256, E = Borsh>
 where
     K: BorshSerialize + Ord,
     V: BorshSerialize,
-    H: CryptoHasher<Digest = [u8; 32]>,
+    H: ToKey,
+    E: KeyEncoder<K>,
 {
     prefix: Box<[u8]>,
     /// Cache for loads and intermediate changes to the underlying vector.
     /// The cached entries are wrapped in a [`Box`] to avoid existing pointers from being
     /// invalidated.
     #[borsh_skip]
-    cache: StableMap<K, EntryAndHash<V>>,
+    cache: StableMap<K, EntryAndHash<V, H::KeyType>>,
 
     #[borsh_skip]
     hasher: PhantomData<H>,
+
+    #[borsh_skip]
+    key_encoder: PhantomData<E>,
+
+    /// Maximum amount of entries to keep in the cache before unmodified entries are evicted.
+    /// `None` indicates that the cache is unbounded.
+    #[borsh_skip]
+    cache_capacity: Option<usize>,
+
+    /// Storage bytes written and freed by flushes since the map was created or loaded.
+    #[borsh_skip]
+    bytes: ByteCounter,
+
+    /// Buffer reused to build the prefixed key bytes which are hashed into storage keys.
+    #[borsh_skip]
+    key_buffer: RefCell<Vec<u8>>,
+
+    /// Previous values of the keys written during a [`transaction`](Self::transaction).
+    #[borsh_skip]
+    undo: UndoLog,
 }
 
 // #[derive(Default)]
-struct EntryAndHash<V> {
+struct EntryAndHash<V, T> {
     value: OnceCell<CacheEntry<V>>,
-    hash: OnceCell<[u8; 32]>,
+    hash: OnceCell<T>,
 }
 
-impl<V> Default for EntryAndHash<V> {
+impl<V, T> Default for EntryAndHash<V, T> {
     fn default() -> Self {
         Self { value: Default::default(), hash: Default::default() }
     }
 }
 
-impl<K, V, H> Drop for LookupMap<K, V, H>
+impl<K, V, H, E> Drop for LookupMap<K, V, H, E>
 where
     K: BorshSerialize + Ord,
     V: BorshSerialize,
-    H: CryptoHasher<Digest = [u8; 32]>,
+    H: ToKey,
+    E: KeyEncoder<K>,
 {
     fn drop(&mut self) {
         self.flush()
     }
 }
 
-impl<K, V, H> fmt::Debug for LookupMap<K, V, H>
+impl<K, V, H, E> fmt::Debug for LookupMap<K, V, H, E>
 where
     K: BorshSerialize + Ord,
     V: BorshSerialize,
-    H: CryptoHasher<Digest = [u8; 32]>,
+    H: ToKey,
+    E: KeyEncoder<K>,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("LookupMap").field("prefix", &self.prefix).finish()
     }
 }
 
+/// Generates a map under an arbitrary prefix, seeded with arbitrary entries which are not flushed
+/// to storage yet.
+#[cfg(feature = "test-utils")]
+impl<'a, K, V, H, E> arbitrary::Arbitrary<'a> for LookupMap<K, V, H, E>
+where
+    K: BorshSerialize + Ord + arbitrary::Arbitrary<'a>,
+    V: BorshSerialize + arbitrary::Arbitrary<'a>,
+    H: ToKey,
+    E: KeyEncoder<K>,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let mut map = Self::with_hasher(u.arbitrary::<Vec<u8>>()?);
+        for entry in u.arbitrary_iter::<(K, V)>()? {
+            let (key, value) = entry?;
+            map.set(key, Some(value));
+        }
+        Ok(map)
+    }
+}
+
 impl<K, V> LookupMap<K, V, Sha256>
 where
     K: BorshSerialize + Ord,
@@ -141,13 +194,74 @@ where
     {
         Self::with_hasher(prefix)
     }
+
+    /// Initialize a [`LookupMap`] with a bounded cache. Once the cache holds `capacity` entries,
+    /// entries which are not modified (or have already been flushed) are evicted from memory.
+    ///
+    /// Eviction only happens on operations that take `&mut self` and on [`flush`], so values
+    /// loaded with [`get`] will be retained until the next mutable operation.
+    ///
+    /// The capacity is not serialized with the map, so it has to be set again through
+    /// [`set_cache_capacity`] after the map is loaded from storage.
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::store::LookupMap;
+    ///
+    /// let mut map: LookupMap<u32, u32> = LookupMap::with_cache_capacity(b"m", 16);
+    /// for i in 0..100 {
+    ///     map.insert(i, i);
+    ///     map.flush();
+    /// }
+    /// assert_eq!(map[&5], 5);
+    /// ```
+    ///
+    /// [`flush`]: Self::flush
+    /// [`get`]: Self::get
+    /// [`set_cache_capacity`]: Self::set_cache_capacity
+    pub fn with_cache_capacity<S>(prefix: S, capacity: usize) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        let mut map = Self::with_hasher(prefix);
+        map.set_cache_capacity(Some(capacity));
+        map
+    }
+}
+
+impl<V> LookupMap<CryptoHash, V, Identity>
+where
+    V: BorshSerialize,
+{
+    /// Initialize a [`LookupMap`] with keys that are already 32 byte hashes, which are used
+    /// directly as the storage keys instead of being hashed again with the prefix.
+    ///
+    /// The map has an empty prefix so that the key type guarantees at compile time that
+    /// the storage key is 32 bytes. This means that the keys will not be distinct from keys of
+    /// other prehashed maps in the contract, so the keys should already be separated by domain
+    /// (for example by including a unique tag in the data that was hashed).
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::crypto_hash::{CryptoHasher, Sha256};
+    /// use near_sdk::store::LookupMap;
+    ///
+    /// let mut map: LookupMap<_, u32, _> = LookupMap::prehashed();
+    /// let key = Sha256::hash(b"account.near");
+    /// map.insert(key, 1);
+    /// assert_eq!(map[&key], 1);
+    /// ```
+    pub fn prehashed() -> Self {
+        Self::with_hasher(Vec::new())
+    }
 }
 
-impl<K, V, H> LookupMap<K, V, H>
+impl<K, V, H, E> LookupMap<K, V, H, E>
 where
     K: BorshSerialize + Ord,
     V: BorshSerialize,
-    H: CryptoHasher<Digest = [u8; 32]>,
+    H: ToKey,
+    E: KeyEncoder<K>,
 {
     /// Initialize a [`LookupMap`] with a custom hash function.
     ///
@@ -158,6 +272,17 @@ where
     ///
     /// let map = LookupMap::<String, String, Keccak256>::with_hasher(b"m");
     /// ```
+    ///
+    /// This is also used to initialize a map with a custom [`KeyEncoder`]:
+    /// ```
+    /// use near_sdk::crypto_hash::Sha256;
+    /// use near_sdk::store::key::RawBytes;
+    /// use near_sdk::store::LookupMap;
+    ///
+    /// let mut map = LookupMap::<String, u8, Sha256, RawBytes>::with_hasher(b"m");
+    /// map.insert("test".to_string(), 7);
+    /// assert_eq!(map["test"], 7);
+    /// ```
     pub fn with_hasher<S>(prefix: S) -> Self
     where
         S: IntoStorageKey,
@@ -166,6 +291,30 @@ where
             prefix: prefix.into_storage_key().into_boxed_slice(),
             cache: Default::default(),
             hasher: Default::default(),
+            key_encoder: Default::default(),
+            cache_capacity: None,
+            bytes: Default::default(),
+            key_buffer: Default::default(),
+            undo: Default::default(),
+        }
+    }
+
+    /// Sets the maximum amount of entries kept in the cache. When the capacity is reached,
+    /// entries that are not modified are evicted from memory. Passing `None` makes the cache
+    /// unbounded, which is the default.
+    pub fn set_cache_capacity(&mut self, capacity: Option<usize>) {
+        self.cache_capacity = capacity;
+        self.evict_if_full();
+    }
+
+    /// Removes all unmodified entries from the cache if the cache capacity has been reached.
+    /// Modified entries are kept, as they still need to be written to storage.
+    fn evict_if_full(&mut self) {
+        if let Some(capacity) = self.cache_capacity {
+            let cache = self.cache.inner();
+            if cache.len() >= capacity {
+                cache.retain(|_, v| v.value.get().map(|e| e.is_modified()).unwrap_or(false));
+            }
         }
     }
 
@@ -176,6 +325,7 @@ where
     ///
     /// Calling `set` with a `None` value will delete the entry from storage.
     pub fn set(&mut self, key: K, value: Option<V>) {
+        self.evict_if_full();
         let entry = self.cache.get_mut(key);
         match entry.value.get_mut() {
             Some(entry) => *entry.value_mut() = value,
@@ -185,89 +335,487 @@ where
         }
     }
 
-    fn lookup_key<Q: ?Sized>(prefix: &[u8], key: &Q, buffer: &mut Vec<u8>) -> LookupKey
+    /// Removes a key from the map without loading the existing value from storage.
+    ///
+    /// This is cheaper than [`remove`](Self::remove) when the previous value is not needed, as
+    /// the value is neither read nor deserialized. The value is removed from storage when the
+    /// map is flushed.
+    ///
+    /// The key may be any borrowed form of the map's key type, but
+    /// [`KeyEncoder`] and [`ToOwned<Owned = K>`](ToOwned) on the borrowed form *must* match those for
+    /// the key type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::LookupMap;
+    ///
+    /// let mut map: LookupMap<String, u32> = LookupMap::new(b"m");
+    /// map.insert("poneyland".to_string(), 12);
+    ///
+    /// map.remove_no_return("poneyland");
+    /// assert_eq!(map.get("poneyland"), None);
+    /// ```
+    pub fn remove_no_return<Q: ?Sized>(&mut self, k: &Q)
+    where
+        K: Borrow<Q>,
+        Q: ToOwned<Owned = K>,
+        E: KeyEncoder<Q>,
+    {
+        self.set(k.to_owned(), None);
+    }
+
+    /// Returns the serialized bytes of the value corresponding to the key, without deserializing
+    /// it. This can be used to migrate values whose schema has changed, where the stored bytes
+    /// can no longer be deserialized into `V`.
+    ///
+    /// If the value was modified in the cache and not yet flushed, the cached value is serialized
+    /// and returned, otherwise the bytes are read directly from storage.
+    ///
+    /// The key may be any borrowed form of the map's key type, but
+    /// [`KeyEncoder`] and [`Ord`] on the borrowed form *must* match those for the key type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::borsh::BorshSerialize;
+    /// use near_sdk::store::LookupMap;
+    ///
+    /// let mut map: LookupMap<String, u32> = LookupMap::new(b"m");
+    /// map.insert("poneyland".to_string(), 12);
+    ///
+    /// assert_eq!(map.get_raw("poneyland"), Some(12u32.try_to_vec().unwrap()));
+    /// assert_eq!(map.get_raw("missing"), None);
+    /// ```
+    pub fn get_raw<Q: ?Sized>(&self, k: &Q) -> Option<Vec<u8>>
+    where
+        K: Borrow<Q>,
+        Q: Ord,
+        E: KeyEncoder<Q>,
+    {
+        let cached = self.cache.map_value_ref(k, |v| {
+            v.value.get().filter(|entry| entry.is_modified()).map(|entry| {
+                entry.value().as_ref().map(|value| {
+                    value.try_to_vec().unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_SERIALIZATION))
+                })
+            })
+        });
+        if let Some(Some(modified)) = cached {
+            return modified;
+        }
+
+        env::storage_read(Self::storage_key(&self.prefix, &self.key_buffer, k).as_ref())
+    }
+
+    /// Writes the raw bytes as the value for the given key directly to storage, without
+    /// serializing a value. Any cached value for the key is discarded, so the next access will
+    /// load and deserialize the newly written bytes.
+    ///
+    /// The bytes written are expected to be a valid [`BorshSerialize`] encoding of `V`, otherwise
+    /// later reads of the key will fail to deserialize.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::borsh::BorshSerialize;
+    /// use near_sdk::store::LookupMap;
+    ///
+    /// let mut map: LookupMap<String, u32> = LookupMap::new(b"m");
+    /// map.insert("poneyland".to_string(), 12);
+    ///
+    /// map.insert_raw("poneyland", &7u32.try_to_vec().unwrap());
+    /// assert_eq!(map["poneyland"], 7);
+    /// ```
+    pub fn insert_raw<Q: ?Sized>(&mut self, k: &Q, value: &[u8])
+    where
+        K: Borrow<Q>,
+        Q: Ord,
+        E: KeyEncoder<Q>,
+    {
+        self.write_raw(k, Some(value));
+    }
+
+    /// Writes or removes the raw value for the given key in storage, discarding the cached value.
+    fn write_raw<Q: ?Sized>(&mut self, k: &Q, value: Option<&[u8]>)
+    where
+        K: Borrow<Q>,
+        Q: Ord,
+        E: KeyEncoder<Q>,
+    {
+        self.cache.inner().remove(k);
+        let key = Self::storage_key(&self.prefix, &self.key_buffer, k);
+        Self::write_entry(&mut self.bytes, &mut self.undo, key.as_ref(), value);
+    }
+
+    /// Swaps the values of two keys, by moving the serialized values in storage without
+    /// deserializing them. If only one of the keys has a value, the value is moved to the other
+    /// key.
+    ///
+    /// The key may be any borrowed form of the map's key type, but
+    /// [`KeyEncoder`] and [`Ord`] on the borrowed form *must* match those for the key type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::LookupMap;
+    ///
+    /// let mut map: LookupMap<String, u32> = LookupMap::new(b"m");
+    /// map.insert("a".to_string(), 1);
+    /// map.insert("b".to_string(), 2);
+    ///
+    /// map.swap("a", "b");
+    /// assert_eq!(map["a"], 2);
+    /// assert_eq!(map["b"], 1);
+    ///
+    /// map.swap("a", "c");
+    /// assert_eq!(map.get("a"), None);
+    /// assert_eq!(map["c"], 2);
+    /// ```
+    pub fn swap<Q: ?Sized>(&mut self, a: &Q, b: &Q)
+    where
+        K: Borrow<Q>,
+        Q: Ord,
+        E: KeyEncoder<Q>,
+    {
+        let a_value = self.get_raw(a);
+        let b_value = self.get_raw(b);
+        self.write_raw(a, b_value.as_deref());
+        self.write_raw(b, a_value.as_deref());
+    }
+
+    /// Moves the value of the key `from` to the key `to`, by moving the serialized value in
+    /// storage without deserializing it. Any value of `to` is overwritten. Returns `false`, without
+    /// modifying the map, if `from` has no value.
+    ///
+    /// The key may be any borrowed form of the map's key type, but
+    /// [`KeyEncoder`] and [`Ord`] on the borrowed form *must* match those for the key type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::LookupMap;
+    ///
+    /// let mut map: LookupMap<String, u32> = LookupMap::new(b"m");
+    /// map.insert("a".to_string(), 1);
+    ///
+    /// assert!(map.rename_key("a", "b".to_string()));
+    /// assert_eq!(map.get("a"), None);
+    /// assert_eq!(map["b"], 1);
+    ///
+    /// assert!(!map.rename_key("a", "b".to_string()));
+    /// assert_eq!(map["b"], 1);
+    /// ```
+    pub fn rename_key<Q: ?Sized>(&mut self, from: &Q, to: K) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord,
+        E: KeyEncoder<Q>,
+    {
+        match self.get_raw(from) {
+            Some(value) => {
+                self.write_raw(from, None);
+                self.write_raw::<K>(&to, Some(&value));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes the cached value for the given key, so the next access reads the value from
+    /// storage again. This should be used when the value in storage may have been changed
+    /// outside of this map, for example by another instance of the map during a callback.
+    ///
+    /// Any modification of the value which has not been flushed is discarded. Use
+    /// [`flush`](Self::flush) before invalidating to keep the modifications.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::LookupMap;
+    ///
+    /// let mut map: LookupMap<String, u32> = LookupMap::new(b"m");
+    /// map.insert("poneyland".to_string(), 12);
+    /// map.flush();
+    ///
+    /// // Modify the value through another instance of the map.
+    /// let mut other: LookupMap<String, u32> = LookupMap::new(b"m");
+    /// other.insert("poneyland".to_string(), 7);
+    /// other.flush();
+    ///
+    /// assert_eq!(map["poneyland"], 12);
+    /// map.invalidate("poneyland");
+    /// assert_eq!(map["poneyland"], 7);
+    /// ```
+    pub fn invalidate<Q: ?Sized>(&mut self, k: &Q)
+    where
+        K: Borrow<Q>,
+        Q: Ord,
+    {
+        self.cache.inner().remove(k);
+    }
+
+    /// Removes all cached values, so that every following access reads the value from storage
+    /// again. Any modification which has not been flushed is discarded.
+    ///
+    /// See [`invalidate`](Self::invalidate) to only remove the cached value of a single key.
+    pub fn invalidate_all(&mut self) {
+        self.cache.inner().clear();
+    }
+
+    /// Inserts all key-value pairs from the iterator, writing each value directly to storage.
+    ///
+    /// Unlike [`insert`](Self::insert) or [`extend`](Extend::extend), the values are not kept in
+    /// the cache until the map is flushed, and the buffers used to build the storage keys and
+    /// serialize the values are reused for all entries. This keeps memory usage constant when
+    /// inserting a large amount of entries. Previous values are not read, and any cached value
+    /// for the inserted keys is discarded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::LookupMap;
+    ///
+    /// let mut map: LookupMap<u32, u32> = LookupMap::new(b"m");
+    /// map.insert_many((0..1000).map(|i| (i, i * 2)));
+    ///
+    /// assert_eq!(map[&500], 1000);
+    /// ```
+    pub fn insert_many<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let mut key_buf = Vec::new();
+        let mut value_buf = Vec::new();
+        for (k, v) in iter {
+            self.cache.inner().remove(&k);
+            key_buf.clear();
+            let key = Self::lookup_key(&self.prefix, &k, &mut key_buf);
+            value_buf.clear();
+            v.serialize(&mut value_buf)
+                .unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_SERIALIZATION));
+            Self::write_entry(&mut self.bytes, &mut self.undo, key.as_ref(), Some(&value_buf));
+        }
+    }
+
+    fn lookup_key<Q: ?Sized>(prefix: &[u8], key: &Q, buffer: &mut Vec<u8>) -> H::KeyType
     where
-        Q: BorshSerialize,
         K: Borrow<Q>,
+        E: KeyEncoder<Q>,
     {
-        // Concat the prefix with serialized key and hash the bytes for the lookup key.
+        // Concat the prefix with encoded key and hash the bytes for the lookup key.
         buffer.extend(prefix);
-        key.serialize(buffer).unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_SERIALIZATION));
+        E::encode(key, buffer);
 
-        H::hash(buffer)
+        H::to_key(buffer)
+    }
+
+    /// Computes the storage key for the key, as with [`lookup_key`](Self::lookup_key), reusing
+    /// the buffer so that hashed keys are computed without allocating.
+    fn storage_key<Q: ?Sized>(prefix: &[u8], buffer: &RefCell<Vec<u8>>, key: &Q) -> H::KeyType
+    where
+        K: Borrow<Q>,
+        E: KeyEncoder<Q>,
+    {
+        let mut buffer = buffer.borrow_mut();
+        buffer.clear();
+        Self::lookup_key(prefix, key, &mut buffer)
     }
 }
 
-impl<K, V, H> LookupMap<K, V, H>
+impl<K, V, H, E> LookupMap<K, V, H, E>
 where
     K: BorshSerialize + Ord,
     V: BorshSerialize + BorshDeserialize,
-    H: CryptoHasher<Digest = [u8; 32]>,
+    H: ToKey,
+    E: KeyEncoder<K>,
 {
-    fn deserialize_element(bytes: &[u8]) -> V {
-        V::try_from_slice(bytes).unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_DESERIALIZATION))
+    fn deserialize_element(bytes: &[u8]) -> Result<V, StoreError> {
+        V::try_from_slice(bytes).map_err(StoreError::DeserializationError)
     }
 
-    fn load_element<Q: ?Sized>(prefix: &[u8], key: &Q) -> (LookupKey, Option<V>)
+    fn load_element<Q: ?Sized>(
+        prefix: &[u8],
+        buffer: &RefCell<Vec<u8>>,
+        key: &Q,
+    ) -> Result<(H::KeyType, Option<V>), StoreError>
     where
-        Q: BorshSerialize,
         K: Borrow<Q>,
+        E: KeyEncoder<Q>,
     {
-        let key = Self::lookup_key(prefix, key, &mut Vec::new());
-        let storage_bytes = env::storage_read(&key);
-        (key, storage_bytes.as_deref().map(Self::deserialize_element))
+        let key = Self::storage_key(prefix, buffer, key);
+        let element =
+            read_buffer::with_storage_value(key.as_ref(), Self::deserialize_element).transpose()?;
+        Ok((key, element))
     }
 
     /// Returns a reference to the value corresponding to the key.
     ///
     /// The key may be any borrowed form of the map's key type, but
-    /// [`BorshSerialize`] and [`ToOwned<Owned = K>`](ToOwned) on the borrowed form *must* match those for
+    /// [`KeyEncoder`] and [`ToOwned<Owned = K>`](ToOwned) on the borrowed form *must* match those for
     /// the key type.
     pub fn get<Q: ?Sized>(&self, k: &Q) -> Option<&V>
     where
         K: Borrow<Q>,
-        Q: BorshSerialize + ToOwned<Owned = K>,
+        Q: ToOwned<Owned = K>,
+        E: KeyEncoder<Q>,
+    {
+        self.try_get(k).unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_DESERIALIZATION))
+    }
+
+    /// Returns a reference to the value corresponding to the key, or a [`StoreError`] if the
+    /// value in storage cannot be deserialized.
+    ///
+    /// The key may be any borrowed form of the map's key type, but
+    /// [`KeyEncoder`] and [`ToOwned<Owned = K>`](ToOwned) on the borrowed form *must* match those for
+    /// the key type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::{LookupMap, StoreError};
+    ///
+    /// let mut map: LookupMap<String, u32> = LookupMap::new(b"m");
+    /// map.insert_raw("poneyland", &[1, 2]);
+    ///
+    /// assert!(matches!(map.try_get("poneyland"), Err(StoreError::DeserializationError(_))));
+    /// assert_eq!(map.try_get("missing").unwrap(), None);
+    /// ```
+    pub fn try_get<Q: ?Sized>(&self, k: &Q) -> Result<Option<&V>, StoreError>
+    where
+        K: Borrow<Q>,
+        Q: ToOwned<Owned = K>,
+        E: KeyEncoder<Q>,
     {
         //* ToOwned bound, which forces a clone, is required to be able to keep the key in the cache
         let cached = self.cache.get(k.to_owned());
-        let entry = cached.value.get_or_init(|| {
-            let (key, element) = Self::load_element(&self.prefix, k);
+        let entry = cached.value.get_or_try_init(|| {
+            let (key, element) = Self::load_element(&self.prefix, &self.key_buffer, k)?;
             let _ = cached.hash.set(key);
-            CacheEntry::new_cached(element)
-        });
-        entry.value().as_ref()
+            Ok(CacheEntry::new_cached(element))
+        })?;
+        Ok(entry.value().as_ref())
     }
 
     pub(crate) fn get_mut_inner<Q: ?Sized>(&mut self, k: &Q) -> &mut CacheEntry<V>
     where
         K: Borrow<Q>,
-        Q: BorshSerialize + ToOwned<Owned = K>,
+        Q: ToOwned<Owned = K>,
+        E: KeyEncoder<Q>,
     {
+        self.try_get_mut_inner(k).unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_DESERIALIZATION))
+    }
+
+    fn try_get_mut_inner<Q: ?Sized>(&mut self, k: &Q) -> Result<&mut CacheEntry<V>, StoreError>
+    where
+        K: Borrow<Q>,
+        Q: ToOwned<Owned = K>,
+        E: KeyEncoder<Q>,
+    {
+        self.evict_if_full();
         let prefix = &self.prefix;
+        let key_buffer = &self.key_buffer;
         //* ToOwned bound, which forces a clone, is required to be able to keep the key in the cache
         let entry = self.cache.get_mut(k.to_owned());
-        entry.value.get_or_init(|| {
-            let (key, value) = Self::load_element(prefix, k);
+        entry.value.get_or_try_init(|| {
+            let (key, value) = Self::load_element(prefix, key_buffer, k)?;
             let _ = entry.hash.set(key);
-            CacheEntry::new_cached(value)
-        });
+            Ok(CacheEntry::new_cached(value))
+        })?;
         let entry = entry.value.get_mut().unwrap_or_else(|| unreachable!());
-        entry
+        Ok(entry)
     }
 
     /// Returns a mutable reference to the value corresponding to the key.
     ///
     /// The key may be any borrowed form of the map's key type, but
-    /// [`BorshSerialize`] and [`ToOwned<Owned = K>`](ToOwned) on the borrowed form *must* match those for
+    /// [`KeyEncoder`] and [`ToOwned<Owned = K>`](ToOwned) on the borrowed form *must* match those for
     /// the key type.
     pub fn get_mut<Q: ?Sized>(&mut self, k: &Q) -> Option<&mut V>
     where
         K: Borrow<Q>,
-        Q: BorshSerialize + ToOwned<Owned = K>,
+        Q: ToOwned<Owned = K>,
+        E: KeyEncoder<Q>,
     {
         self.get_mut_inner(k).value_mut().as_mut()
     }
 
+    /// Same as [`get_mut_inner`](Self::get_mut_inner), but takes ownership of the key so that it
+    /// can be moved into the cache without being cloned.
+    fn get_mut_inner_owned(&mut self, k: K) -> &mut CacheEntry<V> {
+        self.evict_if_full();
+        let prefix = &self.prefix;
+        let key_buffer = &self.key_buffer;
+        let load = |key: &K| {
+            let (hash, value) = Self::load_element(prefix, key_buffer, key)
+                .unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_DESERIALIZATION));
+            EntryAndHash {
+                value: OnceCell::from(CacheEntry::new_cached(value)),
+                hash: OnceCell::from(hash),
+            }
+        };
+        let entry = match self.cache.inner().entry(k) {
+            btree_map::Entry::Occupied(mut occupied) => {
+                if occupied.get().value.get().is_none() {
+                    let loaded = load(occupied.key());
+                    **occupied.get_mut() = loaded;
+                }
+                occupied.into_mut()
+            }
+            btree_map::Entry::Vacant(vacant) => {
+                let loaded = load(vacant.key());
+                vacant.insert(Box::new(loaded))
+            }
+        };
+        entry.value.get_mut().unwrap_or_else(|| unreachable!())
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key, inserting the result
+    /// of `default` if the map does not contain the key.
+    ///
+    /// Unlike going through [`entry`](Self::entry), the key is only looked up once and is moved
+    /// into the cache without being cloned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::LookupMap;
+    ///
+    /// let mut balances: LookupMap<String, u64> = LookupMap::new(b"m");
+    ///
+    /// *balances.get_or_insert_with("alice".to_string(), || 10) += 5;
+    /// *balances.get_or_insert_with("alice".to_string(), || 10) += 5;
+    /// assert_eq!(balances["alice"], 20);
+    /// ```
+    pub fn get_or_insert_with<F>(&mut self, k: K, default: F) -> &mut V
+    where
+        F: FnOnce() -> V,
+    {
+        let entry = self.get_mut_inner_owned(k);
+        if entry.value().is_none() {
+            *entry.value_mut() = Some(default());
+        }
+        entry.value_mut().as_mut().unwrap_or_else(|| unreachable!())
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key, inserting the default
+    /// value if the map does not contain the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::LookupMap;
+    ///
+    /// let mut counts: LookupMap<u8, u32> = LookupMap::new(b"m");
+    /// *counts.get_or_default(3) += 1;
+    /// assert_eq!(counts[&3], 1);
+    /// ```
+    pub fn get_or_default(&mut self, k: K) -> &mut V
+    where
+        V: Default,
+    {
+        self.get_or_insert_with(k, Default::default)
+    }
+
     /// Inserts a key-value pair into the map.
     ///
     /// If the map did not have this key present, [`None`] is returned.
@@ -282,27 +830,64 @@ where
         self.get_mut_inner(&k).replace(Some(v))
     }
 
+    /// Inserts a key-value pair into the map, as with [`insert`](Self::insert), but returns a
+    /// [`StoreError`] if the previous value in storage cannot be deserialized. The map is not
+    /// modified if an error is returned.
+    pub fn try_insert(&mut self, k: K, v: V) -> Result<Option<V>, StoreError>
+    where
+        K: Clone,
+    {
+        Ok(self.try_get_mut_inner(&k)?.replace(Some(v)))
+    }
+
+    /// Inserts a key-value pair into the map only if the map does not have this key present.
+    ///
+    /// If the key is present, the map is not modified and an [`OccupiedError`] containing the
+    /// value is returned. This only reads the key from storage once, unlike checking
+    /// [`contains_key`](Self::contains_key) before inserting.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::{LookupMap, OccupiedError};
+    ///
+    /// let mut owners: LookupMap<String, String> = LookupMap::new(b"m");
+    /// assert!(owners.insert_new("token".to_string(), "alice".to_string()).is_ok());
+    ///
+    /// let err = owners.insert_new("token".to_string(), "bob".to_string()).unwrap_err();
+    /// assert_eq!(err, OccupiedError { value: "bob".to_string() });
+    /// assert_eq!(owners["token"], "alice");
+    /// ```
+    pub fn insert_new(&mut self, k: K, v: V) -> Result<(), OccupiedError<V>> {
+        let entry = self.get_mut_inner_owned(k);
+        if entry.value().is_some() {
+            return Err(OccupiedError { value: v });
+        }
+        *entry.value_mut() = Some(v);
+        Ok(())
+    }
+
     /// Returns `true` if the map contains a value for the specified key.
     ///
     /// The key may be any borrowed form of the map's key type, but
-    /// [`BorshSerialize`] and [`ToOwned<Owned = K>`](ToOwned) on the borrowed form *must* match those for
+    /// [`KeyEncoder`] and [`ToOwned<Owned = K>`](ToOwned) on the borrowed form *must* match those for
     /// the key type.
     pub fn contains_key<Q: ?Sized>(&self, k: &Q) -> bool
     where
         K: Borrow<Q>,
-        Q: BorshSerialize + ToOwned<Owned = K> + Ord,
+        Q: ToOwned<Owned = K> + Ord,
+        E: KeyEncoder<Q>,
     {
         // Check cache before checking storage
-        let contains = self
-            .cache
-            .map_value_ref(k, |v| v.value.get().and_then(|s| s.value().as_ref()).is_some());
+        let contains =
+            self.cache.map_value_ref(k, |v| v.value.get().map(|s| s.value().is_some())).flatten();
         if let Some(is_some) = contains {
             return is_some;
         }
 
         // Value is not in cache, check if storage has value for given key.
-        let storage_key = Self::lookup_key(&self.prefix, k, &mut Vec::new());
-        let contains = env::storage_has_key(&storage_key);
+        let storage_key = Self::storage_key(&self.prefix, &self.key_buffer, k);
+        let contains = env::storage_has_key(storage_key.as_ref());
 
         if !contains {
             // If value not in cache and not in storage, can set a cached `None`
@@ -313,20 +898,74 @@ where
         contains
     }
 
+    /// Returns `true` if the map contains a value for the specified key, without adding the key
+    /// to the cache.
+    ///
+    /// Unlike [`contains_key`](Self::contains_key), a key which is not in storage is not cached
+    /// as missing, so this avoids growing the cache when checking many keys which are likely
+    /// absent. Values that are already cached, including modified values not yet flushed, are
+    /// still taken into account.
+    ///
+    /// The key may be any borrowed form of the map's key type, but
+    /// [`KeyEncoder`] and [`Ord`] on the borrowed form *must* match those for the key type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::LookupMap;
+    ///
+    /// let mut map: LookupMap<String, u32> = LookupMap::new(b"m");
+    /// map.insert("poneyland".to_string(), 12);
+    ///
+    /// assert!(map.contains_key_uncached("poneyland"));
+    /// assert!(!map.contains_key_uncached("missing"));
+    /// ```
+    pub fn contains_key_uncached<Q: ?Sized>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord,
+        E: KeyEncoder<Q>,
+    {
+        let cached =
+            self.cache.map_value_ref(k, |v| v.value.get().map(|s| s.value().is_some())).flatten();
+        if let Some(is_some) = cached {
+            return is_some;
+        }
+
+        env::storage_has_key(Self::storage_key(&self.prefix, &self.key_buffer, k).as_ref())
+    }
+
     /// Removes a key from the map, returning the value at the key if the key
     /// was previously in the map.
     ///
     /// The key may be any borrowed form of the map's key type, but
-    /// [`BorshSerialize`] and [`ToOwned<Owned = K>`](ToOwned) on the borrowed form *must* match those for
+    /// [`KeyEncoder`] and [`ToOwned<Owned = K>`](ToOwned) on the borrowed form *must* match those for
     /// the key type.
     pub fn remove<Q: ?Sized>(&mut self, k: &Q) -> Option<V>
     where
         K: Borrow<Q>,
-        Q: BorshSerialize + ToOwned<Owned = K>,
+        Q: ToOwned<Owned = K>,
+        E: KeyEncoder<Q>,
     {
         self.get_mut_inner(k).replace(None)
     }
 
+    /// Removes a key from the map, as with [`remove`](Self::remove), but returns a [`StoreError`]
+    /// if the value in storage cannot be deserialized. The map is not modified if an error is
+    /// returned.
+    ///
+    /// The key may be any borrowed form of the map's key type, but
+    /// [`KeyEncoder`] and [`ToOwned<Owned = K>`](ToOwned) on the borrowed form *must* match those for
+    /// the key type.
+    pub fn try_remove<Q: ?Sized>(&mut self, k: &Q) -> Result<Option<V>, StoreError>
+    where
+        K: Borrow<Q>,
+        Q: ToOwned<Owned = K>,
+        E: KeyEncoder<Q>,
+    {
+        Ok(self.try_get_mut_inner(k)?.replace(None))
+    }
+
     /// Gets the given key's corresponding entry in the map for in-place manipulation.
     /// ```
     /// use near_sdk::store::LookupMap;
@@ -358,44 +997,286 @@ where
     }
 }
 
-impl<K, V, H> LookupMap<K, V, H>
+impl<K, V, H, E> LookupMap<K, V, H, E>
 where
     K: BorshSerialize + Ord,
     V: BorshSerialize,
-    H: CryptoHasher<Digest = [u8; 32]>,
+    H: ToKey,
+    E: KeyEncoder<K>,
 {
     /// Flushes the intermediate values of the map before this is called when the structure is
     /// [`Drop`]ed. This will write all modified values to storage but keep all cached values
-    /// in memory.
+    /// in memory, unless the map has a cache capacity which has been exceeded.
     pub fn flush(&mut self) {
+        self.try_flush().unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_SERIALIZATION))
+    }
+
+    /// Calls `f` with the map, and discards the changes it made to the map if it returns an
+    /// error. This allows an operation to fail part way through without panicking, which would
+    /// revert every change made by the function call.
+    ///
+    /// The collection is flushed before `f` is called. The values which `f` writes to storage,
+    /// such as with [`insert_many`](Self::insert_many) or by flushing the collection, are written
+    /// back to their previous values when the changes are discarded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::LookupMap;
+    ///
+    /// let mut balances: LookupMap<String, u64> = LookupMap::new(b"b");
+    /// balances.insert("alice".to_string(), 10);
+    ///
+    /// let result: Result<(), &str> = balances.transaction(|balances| {
+    ///     *balances.get_mut("alice").unwrap() -= 5;
+    ///     balances.insert("bob".to_string(), 5);
+    ///     Err("bob cannot receive tokens")
+    /// });
+    /// assert!(result.is_err());
+    /// assert_eq!(balances.get("alice"), Some(&10));
+    /// assert!(!balances.contains_key("bob"));
+    /// ```
+    pub fn transaction<F, T, Error>(&mut self, f: F) -> Result<T, Error>
+    where
+        F: FnOnce(&mut Self) -> Result<T, Error>,
+    {
+        transaction::transaction(self, f)
+    }
+
+    /// Flushes the intermediate values of the map, as with [`flush`](Self::flush), but returns a
+    /// [`StoreError`] if a value cannot be serialized. The values are all serialized before any
+    /// of them is written, so nothing is written when the error is returned.
+    pub fn try_flush(&mut self) -> Result<(), StoreError> {
+        // The modified values are serialized into a single buffer, which is then written with
+        // one batch borrowing the keys and values, rather than with a buffer per entry.
+        let prefix = &self.prefix;
+        let mut key_buf = Vec::new();
         let mut buf = Vec::new();
+        let mut ends = Vec::new();
+        for (k, v) in self.cache.inner().iter_mut() {
+            let val = match v.value.get_mut() {
+                Some(val) if val.is_modified() => val,
+                _ => continue,
+            };
+            v.hash.get_or_init(|| {
+                key_buf.clear();
+                Self::lookup_key(prefix, k, &mut key_buf)
+            });
+            if let Some(modified) = val.value().as_ref() {
+                BorshSerialize::serialize(modified, &mut buf)
+                    .map_err(StoreError::SerializationError)?;
+                ends.push(buf.len());
+            }
+        }
+
+        let keys = self.cache.inner().values().filter_map(|v| {
+            let val = v.value.get()?;
+            if val.is_modified() && val.value().is_some() {
+                v.hash.get().map(AsRef::as_ref)
+            } else {
+                None
+            }
+        });
+        let ranges = ends.iter().scan(0, |start, &end| Some(std::mem::replace(start, end)..end));
+        let (bytes, undo) = (&mut self.bytes, &mut self.undo);
+        journal::storage_write_batch(
+            keys.zip(ranges.map(|range| &buf[range])),
+            |key, value, replaced| {
+                bytes.record_write(key.len(), value.len(), replaced);
+                undo.record(key, replaced);
+            },
+        );
+
         for (k, v) in self.cache.inner().iter_mut() {
             if let Some(val) = v.value.get_mut() {
                 if val.is_modified() {
-                    let prefix = &self.prefix;
-                    let key = v.hash.get_or_init(|| {
-                        buf.clear();
-                        Self::lookup_key(prefix, k, &mut buf)
-                    });
-                    match val.value().as_ref() {
-                        Some(modified) => {
-                            buf.clear();
-                            BorshSerialize::serialize(modified, &mut buf)
-                                .unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_SERIALIZATION));
-                            env::storage_write(key, &buf);
-                        }
-                        None => {
-                            // Element was removed, clear the storage for the value
-                            env::storage_remove(key);
-                        }
+                    if val.value().is_none() {
+                        // Element was removed, clear the storage for the value
+                        let key = v.hash.get_or_init(|| {
+                            key_buf.clear();
+                            Self::lookup_key(prefix, k, &mut key_buf)
+                        });
+                        Self::write_entry(&mut self.bytes, &mut self.undo, key.as_ref(), None);
                     }
-
-                    // Update state of flushed state as cached, to avoid duplicate writes/removes
-                    // while also keeping the cached values in memory.
                     val.replace_state(EntryState::Cached);
                 }
             }
         }
+        self.evict_if_full();
+        Ok(())
+    }
+
+    /// Writes the cached value of a single key to storage if it was modified, while the other
+    /// modified values stay cached until the map is flushed. This can be used to make sure a
+    /// value is persisted before a cross-contract call is made, without writing the whole cache.
+    ///
+    /// Has no effect if the key is not cached or its value was not modified.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::LookupMap;
+    ///
+    /// let mut map: LookupMap<String, u32> = LookupMap::new(b"m");
+    /// map.insert("hot".to_string(), 1);
+    /// map.insert("cold".to_string(), 2);
+    ///
+    /// let written = map.bytes_written();
+    /// map.flush_key("hot");
+    /// assert!(map.bytes_written() > written);
+    /// ```
+    pub fn flush_key<Q: ?Sized>(&mut self, k: &Q)
+    where
+        K: Borrow<Q>,
+        Q: Ord,
+    {
+        // A range is used to get the stored key along with the mutable value.
+        let range = (Bound::Included(k), Bound::Included(k));
+        if let Some((k, v)) = self.cache.inner().range_mut::<Q, _>(range).next() {
+            Self::flush_entry(&self.prefix, &mut self.bytes, &mut self.undo, &mut Vec::new(), k, v)
+                .unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_SERIALIZATION));
+        }
+    }
+
+    /// Writes the cached values of the given keys to storage, in the order of the iterator, as
+    /// with [`flush_key`](Self::flush_key).
+    pub fn flush_keys<'a, I, Q>(&mut self, keys: I)
+    where
+        I: IntoIterator<Item = &'a Q>,
+        K: Borrow<Q>,
+        Q: Ord + ?Sized + 'a,
+    {
+        for k in keys {
+            self.flush_key(k);
+        }
+    }
+
+    fn flush_entry(
+        prefix: &[u8],
+        bytes: &mut ByteCounter,
+        undo: &mut UndoLog,
+        buf: &mut Vec<u8>,
+        k: &K,
+        v: &mut EntryAndHash<V, H::KeyType>,
+    ) -> Result<(), StoreError> {
+        if let Some(val) = v.value.get_mut() {
+            if val.is_modified() {
+                let key = v.hash.get_or_init(|| {
+                    buf.clear();
+                    Self::lookup_key(prefix, k, buf)
+                });
+                match val.value().as_ref() {
+                    Some(modified) => {
+                        buf.clear();
+                        BorshSerialize::serialize(modified, buf)
+                            .map_err(StoreError::SerializationError)?;
+                        Self::write_entry(bytes, undo, key.as_ref(), Some(buf));
+                    }
+                    None => {
+                        // Element was removed, clear the storage for the value
+                        Self::write_entry(bytes, undo, key.as_ref(), None);
+                    }
+                }
+
+                // Update state of flushed state as cached, to avoid duplicate writes/removes
+                // while also keeping the cached values in memory.
+                val.replace_state(EntryState::Cached);
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the value under the storage key, or removes the key if there is no value, and
+    /// records the storage bytes written or freed in `bytes` and the previous value in `undo`.
+    fn write_entry(bytes: &mut ByteCounter, undo: &mut UndoLog, key: &[u8], value: Option<&[u8]>) {
+        match value {
+            Some(value) => {
+                let replaced = journal::storage_write(key, value);
+                bytes.record_write(key.len(), value.len(), replaced);
+                undo.record(key, replaced);
+            }
+            None => {
+                let removed = journal::storage_remove(key);
+                bytes.record_remove(key.len(), removed);
+                undo.record(key, removed);
+            }
+        }
+    }
+
+    /// Flushes the intermediate values of the map, as with [`flush`](Self::flush), and then
+    /// clears the cache. This can be used to bound the memory used by the map after accessing
+    /// many entries, at the cost of values having to be read from storage again.
+    pub fn flush_and_evict(&mut self) {
+        self.flush();
+        self.invalidate_all();
+    }
+
+    /// Returns the number of storage bytes that storing `value` under `key` would use, including
+    /// the per-record overhead charged by the protocol. This can be used to charge a storage
+    /// deposit before an entry is inserted.
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::store::LookupMap;
+    ///
+    /// let mut map: LookupMap<String, u64> = LookupMap::new(b"m");
+    /// let key = "alice".to_string();
+    /// let estimate = map.estimated_storage_bytes(&key, &5);
+    ///
+    /// map.insert(key, 5);
+    /// map.flush();
+    /// assert_eq!(map.bytes_written(), estimate);
+    /// ```
+    pub fn estimated_storage_bytes(&self, key: &K, value: &V) -> u64 {
+        let value_len =
+            value.try_to_vec().unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_SERIALIZATION)).len();
+        self.estimated_entry_bytes(key, value_len)
+    }
+
+    /// Returns the number of storage bytes used by an entry for `key` with a serialized value of
+    /// `value_len` bytes.
+    pub(crate) fn estimated_entry_bytes(&self, key: &K, value_len: usize) -> u64 {
+        let key_len = Self::storage_key(&self.prefix, &self.key_buffer, key).as_ref().len();
+        (key_len + value_len) as u64 + STORAGE_RECORD_BYTES
+    }
+
+    /// Returns the number of storage bytes added by flushes of this map, and by the methods which
+    /// write to storage directly, since it was created or loaded from storage. Overwriting a value
+    /// with a larger one counts the difference in size.
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes.written
+    }
+
+    /// Returns the number of storage bytes released by flushes of this map, and by the methods
+    /// which write to storage directly, since it was created or loaded from storage. Overwriting a
+    /// value with a smaller one counts the difference in size.
+    pub fn bytes_freed(&self) -> u64 {
+        self.bytes.freed
+    }
+}
+
+impl<K, V, H, E> Rollback for LookupMap<K, V, H, E>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+    E: KeyEncoder<K>,
+{
+    type Snapshot = StorageSnapshot;
+
+    fn snapshot(&mut self) -> Self::Snapshot {
+        self.flush();
+        (self.bytes, self.undo.begin())
+    }
+
+    fn rollback(&mut self, (bytes, undo): Self::Snapshot) {
+        self.invalidate_all();
+        self.undo.rollback(undo);
+        self.bytes = bytes;
+    }
+
+    fn commit(&mut self, (_, undo): Self::Snapshot) {
+        self.undo.commit(undo);
     }
 }
 
@@ -407,6 +1288,7 @@ mod tests {
     use crate::env;
     use crate::test_utils::test_env::setup_free;
     use arbitrary::{Arbitrary, Unstructured};
+    use borsh::BorshSerialize;
     use rand::seq::SliceRandom;
     use rand::RngCore;
     use rand::{Rng, SeedableRng};
@@ -554,6 +1436,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn flush_single_keys() {
+        let mut map = LookupMap::new(b"m");
+        for i in 0..4u8 {
+            map.insert(i, u32::from(i));
+        }
+        map.flush_key(&1);
+        map.flush_keys(&[2, 9]);
+
+        let stored = LookupMap::<u8, u32>::new(b"m");
+        assert_eq!(stored.get(&0), None);
+        assert_eq!(stored.get(&1), Some(&1));
+        assert_eq!(stored.get(&2), Some(&2));
+        drop(stored);
+
+        // Keys which were flushed are not written again.
+        let written = map.bytes_written();
+        map.flush();
+        assert_eq!(map.bytes_written(), written * 2);
+    }
+
     #[test]
     fn flush_on_drop() {
         let mut map = LookupMap::<_, _, Keccak256>::with_hasher(b"m");
@@ -575,11 +1478,468 @@ mod tests {
         assert_eq!(dup_map[&5], 8);
     }
 
+    #[test]
+    fn entry_api() {
+        let mut map = LookupMap::new(b"b");
+        {
+            let test_entry = map.entry("test".to_string());
+            assert_eq!(test_entry.key(), "test");
+            let entry_ref = test_entry.or_insert(8u8);
+            *entry_ref += 1;
+        }
+        assert_eq!(map["test"], 9);
+
+        // Try getting entry of filled value
+        let value = map.entry("test".to_string()).and_modify(|v| *v += 3).or_default();
+        assert_eq!(*value, 12);
+
+        let value = map.entry("other".to_string()).or_insert_with_key(|k| k.len() as u8);
+        assert_eq!(*value, 5);
+    }
+
+    #[test]
+    fn bounded_cache_evicts_unmodified() {
+        let mut map = LookupMap::with_cache_capacity(b"m", 8);
+        for i in 0..100u32 {
+            map.insert(i, i);
+        }
+        // Modified entries cannot be evicted before they are written to storage.
+        assert_eq!(map.cache.inner().len(), 100);
+
+        map.flush();
+        assert!(map.cache.inner().len() < 8);
+
+        for i in 0..100u32 {
+            // Loading through the entry API does not modify the value.
+            assert_eq!(map.entry(i).key(), &i);
+            assert!(map.cache.inner().len() <= 8);
+        }
+
+        // Modifying through the cache after eviction is still persisted.
+        *map.get_mut(&3).unwrap() = 30;
+        map.flush();
+        drop(map);
+        let map = LookupMap::<u32, u32>::new(b"m");
+        assert_eq!(map[&3], 30);
+        assert_eq!(map[&99], 99);
+    }
+
+    #[test]
+    fn try_methods() {
+        use crate::store::StoreError;
+
+        let mut map = LookupMap::<u8, u32>::new(b"m");
+        map.insert_raw(&1, &[1, 2]);
+        map.insert(2, 2);
+
+        assert!(matches!(map.try_get(&1), Err(StoreError::DeserializationError(_))));
+        assert!(matches!(map.try_insert(1, 1), Err(StoreError::DeserializationError(_))));
+        assert!(matches!(map.try_remove(&1), Err(StoreError::DeserializationError(_))));
+        // A failed load does not affect the state of the map.
+        assert!(map.contains_key(&1));
+        assert_eq!(map.get_raw(&1), Some(vec![1, 2]));
+
+        assert_eq!(map.try_get(&2).unwrap(), Some(&2));
+        assert_eq!(map.try_insert(2, 3).unwrap(), Some(2));
+        assert_eq!(map.try_remove(&2).unwrap(), Some(3));
+        assert_eq!(map.try_insert(3, 3).unwrap(), None);
+        map.try_flush().unwrap();
+        assert_eq!(map.get_raw(&3), Some(vec![3, 0, 0, 0]));
+    }
+
+    #[test]
+    fn swap_and_rename() {
+        let mut map = LookupMap::new(b"m");
+        map.insert(1u8, 1u64);
+        map.insert(2, 2);
+        map.flush();
+        // Unflushed modifications are moved with the value.
+        map.insert(2, 20);
+        map.insert(3, 30);
+
+        map.swap(&1, &2);
+        assert_eq!(map[&1], 20);
+        assert_eq!(map[&2], 1);
+        map.swap(&3, &4);
+        assert_eq!(map.get(&3), None);
+        assert_eq!(map[&4], 30);
+        map.swap(&4, &4);
+        assert_eq!(map[&4], 30);
+
+        assert!(map.rename_key(&4, 5));
+        assert!(!map.rename_key(&4, 5));
+        assert_eq!(map.get(&4), None);
+        assert_eq!(map[&5], 30);
+        assert!(map.rename_key(&5, 1));
+        drop(map);
+
+        let map = LookupMap::<u8, u64>::new(b"m");
+        assert_eq!(map[&1], 30);
+        assert_eq!(map[&2], 1);
+        assert!(!map.contains_key(&3));
+        assert!(!map.contains_key(&4));
+        assert!(!map.contains_key(&5));
+    }
+
+    #[test]
+    fn invalidate() {
+        let mut map = LookupMap::new(b"m");
+        map.insert(1u8, 1u8);
+        map.insert(2, 2);
+        map.flush();
+
+        let mut other = LookupMap::<u8, u8>::new(b"m");
+        other.insert(1, 10);
+        other.insert(2, 20);
+        other.flush();
+
+        assert_eq!(map[&1], 1);
+        assert_eq!(map[&2], 2);
+        map.invalidate(&1);
+        assert_eq!(map[&1], 10);
+        assert_eq!(map[&2], 2);
+        map.invalidate_all();
+        assert_eq!(map[&2], 20);
+
+        // Unflushed modifications are discarded.
+        map.insert(3, 3);
+        map.invalidate(&3);
+        assert_eq!(map.get(&3), None);
+    }
+
+    #[test]
+    fn insert_many() {
+        let mut map = LookupMap::new(b"m");
+        map.insert(1u32, 0u32);
+        map.insert(2, 0);
+        map.flush();
+        // A pending modification is overwritten by the bulk insert.
+        map.set(1, Some(10));
+
+        map.insert_many((1..=100).map(|i| (i, i * 2)));
+        assert_eq!(map.cache.inner().len(), 0);
+        assert_eq!(map[&1], 2);
+        assert_eq!(map[&2], 4);
+        assert_eq!(map[&100], 200);
+        drop(map);
+
+        let mut map = LookupMap::<u32, u32>::new(b"m");
+        assert_eq!(map[&1], 2);
+        *map.get_mut(&1).unwrap() = 3;
+        map.flush_and_evict();
+        assert!(map.cache.inner().is_empty());
+        assert_eq!(map[&1], 3);
+    }
+
+    #[test]
+    fn contains_key_uncached() {
+        let mut map = LookupMap::new(b"m");
+        map.insert(1u8, 1u8);
+        map.flush();
+        drop(map);
+
+        let mut map = LookupMap::<u8, u8>::new(b"m");
+        for i in 0..10 {
+            assert_eq!(map.contains_key_uncached(&i), i == 1);
+        }
+        assert!(map.cache.inner().is_empty());
+
+        // Cached modifications are used over the value in storage.
+        map.remove(&1);
+        map.set(2, Some(2));
+        assert!(!map.contains_key_uncached(&1));
+        assert!(map.contains_key_uncached(&2));
+    }
+
+    #[test]
+    fn raw_value_migration() {
+        use borsh::{BorshDeserialize, BorshSerialize};
+
+        let mut old = LookupMap::new(b"m");
+        old.insert(1u8, 5u8);
+        old.insert(2u8, 6u8);
+        drop(old);
+
+        let mut new = LookupMap::<u8, u64>::new(b"m");
+        for k in [1u8, 2, 3] {
+            if let Some(bytes) = new.get_raw(&k) {
+                let old_value = u8::try_from_slice(&bytes).unwrap();
+                new.insert_raw(&k, &(old_value as u64 * 10).try_to_vec().unwrap());
+            }
+        }
+        assert_eq!(new.get(&1), Some(&50));
+        assert_eq!(new.get(&2), Some(&60));
+        assert_eq!(new.get(&3), None);
+
+        // Modified values in the cache are returned before they are flushed.
+        new.insert(3, 70);
+        assert_eq!(new.get_raw(&3), Some(70u64.try_to_vec().unwrap()));
+        new.remove(&1);
+        assert_eq!(new.get_raw(&1), None);
+    }
+
+    #[test]
+    fn raw_bytes_key_encoding() {
+        use crate::crypto_hash::{CryptoHasher, Sha256};
+        use crate::store::key::RawBytes;
+
+        let mut map = LookupMap::<Vec<u8>, u8, Sha256, RawBytes>::with_hasher(b"m");
+        map.insert(b"key".to_vec(), 1);
+        map.set(b"other".to_vec(), Some(2));
+        map.flush();
+
+        // Keys are stored under the hash of the prefix followed by the unprefixed key bytes.
+        assert!(env::storage_has_key(&Sha256::hash(b"mkey")));
+        assert!(env::storage_has_key(&Sha256::hash(b"mother")));
+        assert_eq!(map.get(&b"key"[..]), Some(&1));
+        assert!(map.contains_key(&b"other"[..]));
+
+        let mut strings = LookupMap::<String, u8, Sha256, RawBytes>::with_hasher(b"s");
+        strings.insert("key".to_string(), 3);
+        strings.flush();
+        assert!(env::storage_has_key(&Sha256::hash(b"skey")));
+        assert_eq!(strings["key"], 3);
+    }
+
+    #[test]
+    fn prehashed_keys() {
+        let mut map = LookupMap::prehashed();
+        map.insert([7u8; 32], 1u8);
+        map.flush();
+
+        // Keys are used as the storage keys without hashing.
+        assert_eq!(env::storage_read(&[7u8; 32]), Some(vec![1]));
+        assert_eq!(map.get(&[7u8; 32]), Some(&1));
+        assert!(!map.contains_key(&[8u8; 32]));
+    }
+
+    #[test]
+    fn unhashed_keys() {
+        use crate::store::key::UnhashedKeys;
+
+        let mut map = LookupMap::<u16, u8, UnhashedKeys>::with_hasher(b"u");
+        map.insert(3, 1);
+        map.insert(4, 2);
+        map.flush();
+
+        // Values are stored under the prefix followed by the serialized key.
+        assert_eq!(env::storage_read(&[b'u', 3, 0]), Some(vec![1]));
+        assert_eq!(env::storage_read(&[b'u', 4, 0]), Some(vec![2]));
+
+        map.remove(&3);
+        map.flush();
+        assert!(!env::storage_has_key(&[b'u', 3, 0]));
+        assert!(!map.contains_key(&3));
+        assert_eq!(map[&4], 2);
+    }
+
+    #[test]
+    fn get_or_insert_with() {
+        let mut map = LookupMap::new(b"m");
+        map.insert(1u8, 10u32);
+        map.flush();
+        drop(map);
+
+        let mut map = LookupMap::new(b"m");
+        // Keys already in the cache as missing, in storage and new keys are all handled.
+        assert!(!map.contains_key(&2u8));
+        *map.get_or_insert_with(1, || unreachable!()) += 1;
+        *map.get_or_insert_with(2, || 20) += 1;
+        *map.get_or_default(3) += 1;
+        map.flush();
+        drop(map);
+
+        let map = LookupMap::<u8, u32>::new(b"m");
+        assert_eq!(map[&1], 11);
+        assert_eq!(map[&2], 21);
+        assert_eq!(map[&3], 1);
+    }
+
+    #[test]
+    fn insert_new_checks_storage() {
+        let mut map = LookupMap::new(b"m");
+        map.insert(1u8, 1u8);
+        map.flush();
+        drop(map);
+
+        let mut map = LookupMap::new(b"m");
+        assert_eq!(map.insert_new(1u8, 2u8), Err(crate::store::OccupiedError { value: 2 }));
+        assert_eq!(map.insert_new(2, 2), Ok(()));
+        map.flush();
+        drop(map);
+
+        let map = LookupMap::<u8, u8>::new(b"m");
+        assert_eq!(map[&1], 1);
+        assert_eq!(map[&2], 2);
+    }
+
+    #[test]
+    fn storage_byte_accounting() {
+        let mut map = LookupMap::new(b"m");
+        let start = env::storage_usage();
+
+        let estimate = map.estimated_storage_bytes(&1u32, &vec![0u8; 10]);
+        map.insert(1u32, vec![0u8; 10]);
+        map.insert(2, vec![0u8; 10]);
+        map.flush();
+        assert_eq!(map.bytes_written(), 2 * estimate);
+        assert_eq!(env::storage_usage() - start, map.bytes_written());
+
+        // Growing and shrinking values only count the difference in size.
+        map.insert(1, vec![0u8; 15]);
+        map.insert(2, vec![0u8; 7]);
+        map.flush();
+        assert_eq!(map.bytes_written(), 2 * estimate + 5);
+        assert_eq!(map.bytes_freed(), 3);
+
+        map.remove(&1);
+        map.flush();
+        assert_eq!(map.bytes_freed(), 3 + estimate + 5);
+        assert_eq!(env::storage_usage() - start, map.bytes_written() - map.bytes_freed());
+    }
+
+    /// Asserts that the bytes counted by the map match the change in storage usage since `start`.
+    fn assert_counted(map: &LookupMap<u32, Vec<u8>>, start: u64) {
+        assert_eq!(env::storage_usage() - start, map.bytes_written() - map.bytes_freed());
+    }
+
+    #[test]
+    fn insert_raw_counts_bytes() {
+        let mut map = LookupMap::new(b"m");
+        let start = env::storage_usage();
+        let estimate = map.estimated_storage_bytes(&1u32, &vec![0u8; 10]);
+        map.insert_raw(&1, &vec![0u8; 10].try_to_vec().unwrap());
+        assert_eq!(map.bytes_written(), estimate);
+        map.insert_raw(&1, &vec![0u8; 4].try_to_vec().unwrap());
+        assert_eq!(map.bytes_freed(), 6);
+        assert_counted(&map, start);
+    }
+
+    #[test]
+    fn insert_many_counts_bytes() {
+        let mut map = LookupMap::new(b"m");
+        let start = env::storage_usage();
+        let estimate = map.estimated_storage_bytes(&1u32, &vec![0u8; 10]);
+        map.insert_many((0..3u32).map(|i| (i, vec![0u8; 10])));
+        assert_eq!(map.bytes_written(), 3 * estimate);
+        map.insert_many((0..3u32).map(|i| (i, vec![0u8; 12])));
+        assert_eq!(map.bytes_written(), 3 * estimate + 6);
+        assert_counted(&map, start);
+    }
+
+    #[test]
+    fn swap_counts_bytes() {
+        let mut map = LookupMap::new(b"m");
+        let start = env::storage_usage();
+        map.insert(1u32, vec![0u8; 10]);
+        map.insert(2, vec![0u8; 4]);
+        map.flush();
+        let written = map.bytes_written();
+        map.swap(&1, &2);
+        assert_eq!(map.bytes_written(), written + 6);
+        assert_eq!(map.bytes_freed(), 6);
+        // Moving the value to a key without one frees the key it was moved from.
+        map.swap(&1, &3);
+        assert_eq!(map.get(&1), None);
+        assert_counted(&map, start);
+    }
+
+    #[test]
+    fn rename_key_counts_bytes() {
+        let mut map = LookupMap::new(b"m");
+        let start = env::storage_usage();
+        let estimate = map.estimated_storage_bytes(&1u32, &vec![0u8; 10]);
+        map.insert(1u32, vec![0u8; 10]);
+        map.flush();
+        assert!(map.rename_key(&1, 2));
+        assert_eq!(map.bytes_written(), 2 * estimate);
+        assert_eq!(map.bytes_freed(), estimate);
+        assert_counted(&map, start);
+    }
+
+    #[test]
+    fn transaction_rolls_back_direct_writes() {
+        let mut map = LookupMap::new(b"m");
+        map.insert(1u32, 10u32);
+        map.insert(2, 20);
+        map.flush();
+        let start = env::storage_usage();
+        let bytes = (map.bytes_written(), map.bytes_freed());
+
+        let result: Result<(), ()> = map.transaction(|map| {
+            map.insert_many((2..5).map(|i| (i, i)));
+            map.swap(&1, &2);
+            map.rename_key(&3, 7);
+            map.insert(8, 8);
+            map.flush();
+            Err(())
+        });
+        assert!(result.is_err());
+        assert_eq!(env::storage_usage(), start);
+        assert_eq!((map.bytes_written(), map.bytes_freed()), bytes);
+        drop(map);
+
+        let map = LookupMap::<u32, u32>::new(b"m");
+        assert_eq!(map.get(&1), Some(&10));
+        assert_eq!(map.get(&2), Some(&20));
+        for key in [3, 4, 7, 8] {
+            assert_eq!(map.get(&key), None);
+        }
+    }
+
+    #[test]
+    fn nested_transaction_is_rolled_back_with_the_outer_one() {
+        let mut map = LookupMap::new(b"m");
+        map.insert(1u32, 10u32);
+
+        let result: Result<(), ()> = map.transaction(|map| {
+            map.transaction(|map| {
+                map.insert_many([(1, 11), (2, 22)]);
+                Ok::<_, ()>(())
+            })?;
+            assert_eq!(map[&1], 11);
+            Err(())
+        });
+        assert!(result.is_err());
+        assert_eq!(map.get(&1), Some(&10));
+        assert_eq!(map.get(&2), None);
+
+        map.transaction(|map| {
+            map.insert_many([(2, 22)]);
+            Ok::<_, ()>(())
+        })
+        .unwrap();
+        assert_eq!(map.get(&2), Some(&22));
+    }
+
+    #[test]
+    fn flush_mixed_changes() {
+        let mut map = LookupMap::new(b"m");
+        let start = env::storage_usage();
+        map.insert(1u32, vec![0u8; 10]);
+        map.insert(2, vec![0u8; 10]);
+        map.flush();
+
+        // New, grown and removed entries are flushed together, with the writes in one batch.
+        map.insert(1, vec![1u8; 12]);
+        map.remove(&2);
+        map.insert(3, vec![3u8; 4]);
+        map.flush();
+        assert_eq!(env::storage_usage() - start, map.bytes_written() - map.bytes_freed());
+        drop(map);
+
+        let map = LookupMap::<u32, Vec<u8>>::new(b"m");
+        assert_eq!(map.get(&1), Some(&vec![1u8; 12]));
+        assert_eq!(map.get(&2), None);
+        assert_eq!(map.get(&3), Some(&vec![3u8; 4]));
+    }
+
     #[derive(Arbitrary, Debug)]
     enum Op {
         Insert(u8, u8),
         Set(u8, Option<u8>),
         Remove(u8),
+        RemoveNoReturn(u8),
         Flush,
         Restore,
         Get(u8),
@@ -624,6 +1984,10 @@ mod tests {
                             let r2 = hm.remove(&k);
                             assert_eq!(r1, r2)
                         }
+                        Op::RemoveNoReturn(k) => {
+                            lm.remove_no_return(&k);
+                            hm.remove(&k);
+                        }
                         Op::Flush => {
                             lm.flush();
                         }
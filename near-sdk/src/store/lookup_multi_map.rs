@@ -0,0 +1,319 @@
+use std::fmt;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::key::ToKey;
+use super::{LookupMap, ERR_INCONSISTENT_STATE};
+use crate::crypto_hash::Sha256;
+use crate::{env, IntoStorageKey};
+
+/// A non-iterable, lazily loaded storage map which maps each key to a list of values.
+///
+/// Each value is stored in its own storage slot, under the key and the index of the value within
+/// the list of the key, and the length of each list is stored separately. Appending a value or
+/// reading a page of values only touches the slots of those values, which avoids reading and
+/// rewriting the whole list as a `LookupMap<K, Vec<V>>` would.
+///
+/// As with [`LookupMap`], values which are accessed are cached and modifications are written to
+/// storage when the map is flushed or [`Drop`]ed.
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::LookupMultiMap;
+///
+/// let mut deposits = LookupMultiMap::new(b"d");
+/// deposits.append("alice".to_string(), 10u128);
+/// deposits.append("alice".to_string(), 20);
+/// deposits.append("bob".to_string(), 5);
+///
+/// assert_eq!(deposits.len(&"alice".to_string()), 2);
+/// assert_eq!(deposits.get_all(&"alice".to_string(), 0, 10), [&10, &20]);
+///
+/// assert!(deposits.remove_value(&"alice".to_string(), &10));
+/// assert_eq!(deposits.get_all(&"alice".to_string(), 0, 10), [&20]);
+///
+/// assert_eq!(deposits.remove_key(&"bob".to_string()), 1);
+/// assert!(deposits.get_all(&"bob".to_string(), 0, 10).is_empty());
+/// ```
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct LookupMultiMap<K, V, H = Sha256>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    lens: LookupMap<K, u32, H>,
+    values: LookupMap<(K, u32), V, H>,
+}
+
+impl<K, V, H> fmt::Debug for LookupMultiMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LookupMultiMap")
+            .field("lens", &self.lens)
+            .field("values", &self.values)
+            .finish()
+    }
+}
+
+impl<K, V> LookupMultiMap<K, V, Sha256>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+{
+    #[inline]
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self::with_hasher(prefix)
+    }
+}
+
+impl<K, V, H> LookupMultiMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    /// Initialize a [`LookupMultiMap`] with a custom hash function.
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::crypto_hash::Keccak256;
+    /// use near_sdk::store::LookupMultiMap;
+    ///
+    /// let map = LookupMultiMap::<String, String, Keccak256>::with_hasher(b"m");
+    /// ```
+    pub fn with_hasher<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        let mut lens_key = prefix.into_storage_key();
+        let values_key = [lens_key.as_slice(), b"v"].concat();
+        lens_key.push(b'l');
+        Self { lens: LookupMap::with_hasher(lens_key), values: LookupMap::with_hasher(values_key) }
+    }
+
+    /// Flushes the intermediate values of the map before this is called when the structure is
+    /// [`Drop`]ed. This will write all modified values to storage but keep all cached values
+    /// in memory.
+    pub fn flush(&mut self) {
+        self.lens.flush();
+        self.values.flush();
+    }
+}
+
+impl<K, V, H> LookupMultiMap<K, V, H>
+where
+    K: BorshSerialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+    /// Returns the number of values stored for the key.
+    pub fn len(&self, key: &K) -> u32 {
+        self.lens.get(key).copied().unwrap_or(0)
+    }
+
+    /// Returns `true` if there are no values stored for the key.
+    pub fn is_empty(&self, key: &K) -> bool {
+        self.len(key) == 0
+    }
+
+    /// Returns the value at the index within the values of the key, or `None` if the index is
+    /// out of bounds.
+    pub fn get(&self, key: &K, index: u32) -> Option<&V> {
+        if index >= self.len(key) {
+            return None;
+        }
+        Some(self.value(key.clone(), index))
+    }
+
+    /// Returns a mutable reference to the value at the index within the values of the key, or
+    /// `None` if the index is out of bounds.
+    pub fn get_mut(&mut self, key: &K, index: u32) -> Option<&mut V> {
+        if index >= self.len(key) {
+            return None;
+        }
+        let value = self.values.get_mut(&(key.clone(), index));
+        Some(value.unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE)))
+    }
+
+    /// Returns up to `limit` values of the key, starting at `from_index`. Only the returned values
+    /// are loaded from storage.
+    pub fn get_all(&self, key: &K, from_index: u32, limit: u32) -> Vec<&V> {
+        let end = self.len(key).min(from_index.saturating_add(limit));
+        (from_index..end).map(|index| self.value(key.clone(), index)).collect()
+    }
+
+    /// Appends a value to the values of the key.
+    pub fn append(&mut self, key: K, value: V) {
+        let len = self.len(&key);
+        self.values.set((key.clone(), len), Some(value));
+        self.lens.set(key, Some(len + 1));
+    }
+
+    /// Removes the first value of the key which is equal to `value`. The last value of the key
+    /// takes the place of the removed value, so the order of the values is not preserved.
+    /// Returns whether a value was removed.
+    ///
+    /// This loads the values of the key until a matching value is found.
+    pub fn remove_value(&mut self, key: &K, value: &V) -> bool
+    where
+        V: PartialEq,
+    {
+        let len = self.len(key);
+        match (0..len).find(|&index| self.value(key.clone(), index) == value) {
+            Some(index) => {
+                self.swap_remove(key, index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes the value at the index within the values of the key and returns it. The last value
+    /// of the key takes the place of the removed value.
+    ///
+    /// Returns `None` if the index is out of bounds.
+    pub fn swap_remove(&mut self, key: &K, index: u32) -> Option<V> {
+        let last = self.len(key).checked_sub(1)?;
+        if index > last {
+            return None;
+        }
+        let last_value = self
+            .values
+            .remove(&(key.clone(), last))
+            .unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE));
+        let removed = if index == last {
+            last_value
+        } else {
+            self.values
+                .insert((key.clone(), index), last_value)
+                .unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE))
+        };
+        self.lens.set(key.clone(), if last == 0 { None } else { Some(last) });
+        Some(removed)
+    }
+
+    /// Removes all values of the key, returning the number of values removed. This does not load
+    /// the values from storage.
+    pub fn remove_key(&mut self, key: &K) -> u32 {
+        let len = self.len(key);
+        for index in 0..len {
+            self.values.set((key.clone(), index), None);
+        }
+        self.lens.set(key.clone(), None);
+        len
+    }
+
+    fn value(&self, key: K, index: u32) -> &V {
+        self.values.get(&(key, index)).unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::LookupMultiMap;
+    use crate::test_utils::test_env::setup_free;
+    use arbitrary::{Arbitrary, Unstructured};
+    use rand::RngCore;
+    use rand::SeedableRng;
+    use std::collections::HashMap;
+
+    #[test]
+    fn pagination_and_removal() {
+        let mut map = LookupMultiMap::new(b"m");
+        for i in 0..10u32 {
+            map.append(1u8, i);
+        }
+        map.append(2, 100);
+        assert_eq!(map.get_all(&1, 3, 4), [&3, &4, &5, &6]);
+        assert_eq!(map.get_all(&1, 8, 4), [&8, &9]);
+        assert!(map.get_all(&1, 20, 4).is_empty());
+        assert!(map.get_all(&3, 0, 4).is_empty());
+
+        *map.get_mut(&2, 0).unwrap() = 200;
+        assert_eq!(map.get(&2, 0), Some(&200));
+        assert_eq!(map.get(&2, 1), None);
+
+        assert_eq!(map.swap_remove(&1, 0), Some(0));
+        assert_eq!(map.get(&1, 0), Some(&9));
+        assert!(!map.remove_value(&1, &0));
+        assert!(map.remove_value(&1, &5));
+        assert_eq!(map.len(&1), 8);
+        map.flush();
+
+        assert_eq!(map.remove_key(&1), 8);
+        assert_eq!(map.remove_key(&2), 1);
+        map.flush();
+        assert!(crate::mock::with_mocked_blockchain(|b| b.take_storage()).is_empty());
+    }
+
+    #[derive(Arbitrary, Debug)]
+    enum Op {
+        Append(u8, u8),
+        RemoveValue(u8, u8),
+        RemoveKey(u8),
+        Get(u8, u8),
+        Flush,
+        Restore,
+    }
+
+    #[test]
+    fn arbitrary() {
+        setup_free();
+
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(0);
+        let mut buf = vec![0; 4096];
+        for _ in 0..512 {
+            // Clear storage in-between runs
+            crate::mock::with_mocked_blockchain(|b| b.take_storage());
+            rng.fill_bytes(&mut buf);
+
+            let mut mm = LookupMultiMap::new(b"m");
+            let mut hm: HashMap<u8, Vec<u8>> = HashMap::new();
+            let u = Unstructured::new(&buf);
+            if let Ok(ops) = Vec::<Op>::arbitrary_take_rest(u) {
+                for op in ops {
+                    match op {
+                        Op::Append(k, v) => {
+                            mm.append(k, v);
+                            hm.entry(k).or_default().push(v);
+                        }
+                        Op::RemoveValue(k, v) => {
+                            let values = hm.entry(k).or_default();
+                            let expected = values.iter().position(|x| *x == v).map(|i| {
+                                values.swap_remove(i);
+                            });
+                            assert_eq!(mm.remove_value(&k, &v), expected.is_some());
+                        }
+                        Op::RemoveKey(k) => {
+                            let expected = hm.remove(&k).map_or(0, |v| v.len() as u32);
+                            assert_eq!(mm.remove_key(&k), expected);
+                        }
+                        Op::Get(k, i) => {
+                            let expected = hm.get(&k).and_then(|v| v.get(i as usize));
+                            assert_eq!(mm.get(&k, i as u32), expected);
+                        }
+                        Op::Flush => {
+                            mm.flush();
+                        }
+                        Op::Restore => {
+                            mm.flush();
+                            mm = LookupMultiMap::new(b"m");
+                        }
+                    }
+                }
+            }
+            for (k, values) in hm.iter() {
+                assert!(mm.get_all(k, 0, u32::MAX).into_iter().eq(values.iter()));
+            }
+        }
+    }
+}
@@ -0,0 +1,346 @@
+use std::borrow::Borrow;
+use std::fmt;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::key::ToKey;
+use super::{LookupMap, StoreError};
+use crate::crypto_hash::Sha256;
+use crate::IntoStorageKey;
+
+/// A non-iterable, lazily loaded storage set that stores its content directly on the storage
+/// trie.
+///
+/// This set stores the values under a hash of the set's `prefix` and [`BorshSerialize`] of the
+/// value using the set's [`CryptoHasher`](crate::crypto_hash::CryptoHasher) implementation.
+/// Values which are accessed are cached, and modifications are written to storage when the set
+/// is flushed or [`Drop`]ed, as with [`LookupMap`].
+///
+/// The default hash function for [`LookupSet`] is [`Sha256`]. To use a custom function, use
+/// [`with_hasher`]. To use the same storage layout as
+/// [`collections::LookupSet`](crate::collections::LookupSet), use
+/// [`UnhashedKeys`](crate::store::key::UnhashedKeys).
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::LookupSet;
+///
+/// // Initializes a set, the generic types can be inferred to `LookupSet<String, Sha256>`
+/// // The `b"a"` parameter is a prefix for the storage keys of this data structure.
+/// let mut set = LookupSet::new(b"a");
+///
+/// assert!(set.insert("test".to_string()));
+/// assert!(!set.insert("test".to_string()));
+/// assert!(set.contains("test"));
+///
+/// assert!(set.remove("test"));
+/// assert!(!set.contains("test"));
+/// ```
+///
+/// [`with_hasher`]: Self::with_hasher
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct LookupSet<T, H = Sha256>
+where
+    T: BorshSerialize + Ord,
+    H: ToKey,
+{
+    map: LookupMap<T, (), H>,
+}
+
+impl<T, H> fmt::Debug for LookupSet<T, H>
+where
+    T: BorshSerialize + Ord,
+    H: ToKey,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LookupSet").field("map", &self.map).finish()
+    }
+}
+
+impl<T> LookupSet<T, Sha256>
+where
+    T: BorshSerialize + Ord,
+{
+    /// Create a new [`LookupSet`] with the prefix provided, hashing the values with [`Sha256`].
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::store::LookupSet;
+    ///
+    /// let set = LookupSet::<String>::new(b"s");
+    /// ```
+    #[inline]
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self::with_hasher(prefix)
+    }
+}
+
+impl<T, H> LookupSet<T, H>
+where
+    T: BorshSerialize + Ord,
+    H: ToKey,
+{
+    /// Initialize a [`LookupSet`] with a custom hash function.
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::crypto_hash::Keccak256;
+    /// use near_sdk::store::LookupSet;
+    ///
+    /// let set = LookupSet::<String, Keccak256>::with_hasher(b"m");
+    /// ```
+    pub fn with_hasher<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { map: LookupMap::with_hasher(prefix) }
+    }
+
+    /// Returns `true` if the set contains the specified value.
+    ///
+    /// The value may be any borrowed form of the set's value type, but
+    /// [`BorshSerialize`] and [`ToOwned<Owned = T>`](ToOwned) on the borrowed form *must* match
+    /// those for the value type.
+    pub fn contains<Q: ?Sized>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = T> + Ord,
+    {
+        self.map.contains_key(value)
+    }
+
+    /// Adds a value to the set.
+    ///
+    /// If the set did not have this value present, `true` is returned.
+    ///
+    /// If the set did have this value present, `false` is returned.
+    pub fn insert(&mut self, value: T) -> bool
+    where
+        T: Clone,
+    {
+        self.map.insert(value, ()).is_none()
+    }
+
+    /// Adds a value to the set, as with [`insert`](Self::insert), but returns a [`StoreError`] if
+    /// the existing entry in storage is not a set entry. The set is not modified if an error is
+    /// returned.
+    pub fn try_insert(&mut self, value: T) -> Result<bool, StoreError>
+    where
+        T: Clone,
+    {
+        Ok(self.map.try_insert(value, ())?.is_none())
+    }
+
+    /// Adds a value to the set without checking if it was already present. This avoids reading
+    /// the value from storage.
+    pub fn put(&mut self, value: T) {
+        self.map.set(value, Some(()));
+    }
+
+    /// Removes a value from the set. Returns whether the value was present in the set.
+    ///
+    /// The value may be any borrowed form of the set's value type, but
+    /// [`BorshSerialize`] and [`ToOwned<Owned = T>`](ToOwned) on the borrowed form *must* match
+    /// those for the value type.
+    pub fn remove<Q: ?Sized>(&mut self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = T>,
+    {
+        self.map.remove(value).is_some()
+    }
+
+    /// Removes a value from the set, as with [`remove`](Self::remove), but returns a
+    /// [`StoreError`] if the existing entry in storage is not a set entry. The set is not
+    /// modified if an error is returned.
+    ///
+    /// The value may be any borrowed form of the set's value type, but
+    /// [`BorshSerialize`] and [`ToOwned<Owned = T>`](ToOwned) on the borrowed form *must* match
+    /// those for the value type.
+    pub fn try_remove<Q: ?Sized>(&mut self, value: &Q) -> Result<bool, StoreError>
+    where
+        T: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = T>,
+    {
+        Ok(self.map.try_remove(value)?.is_some())
+    }
+
+    /// Flushes the intermediate values of the set before this is called when the structure is
+    /// [`Drop`]ed. This will write all modified values to storage but keep all cached values
+    /// in memory.
+    pub fn flush(&mut self) {
+        self.map.flush()
+    }
+
+    /// Flushes the intermediate values of the set, as with [`flush`](Self::flush), but returns a
+    /// [`StoreError`] if a value cannot be serialized.
+    pub fn try_flush(&mut self) -> Result<(), StoreError> {
+        self.map.try_flush()
+    }
+
+    /// Returns the number of storage bytes added by flushes of this set since it was created
+    /// or loaded from storage.
+    pub fn bytes_written(&self) -> u64 {
+        self.map.bytes_written()
+    }
+
+    /// Returns the number of storage bytes released by flushes of this set since it was
+    /// created or loaded from storage.
+    pub fn bytes_freed(&self) -> u64 {
+        self.map.bytes_freed()
+    }
+
+    /// Returns the number of storage bytes that inserting `value` would use, including the
+    /// per-record overhead charged by the protocol.
+    pub fn estimated_storage_bytes(&self, value: &T) -> u64 {
+        self.map.estimated_entry_bytes(value, 0)
+    }
+}
+
+impl<T, H> Extend<T> for LookupSet<T, H>
+where
+    T: BorshSerialize + Ord,
+    H: ToKey,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        self.map.extend(iter.into_iter().map(|value| (value, ())))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::LookupSet;
+    use crate::crypto_hash::Keccak256;
+    use crate::store::key::UnhashedKeys;
+    use crate::test_utils::test_env::setup_free;
+    use arbitrary::{Arbitrary, Unstructured};
+    use rand::RngCore;
+    use rand::SeedableRng;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_insert_contains_remove() {
+        let mut set = LookupSet::new(b"s");
+        assert!(set.insert(1u64));
+        assert!(!set.insert(1));
+        set.put(2);
+        assert!(set.contains(&1));
+        assert!(set.contains(&2));
+        assert!(!set.contains(&3));
+        set.flush();
+
+        let mut set = LookupSet::<u64>::new(b"s");
+        assert!(set.contains(&1));
+        assert!(set.remove(&1));
+        assert!(!set.remove(&1));
+        assert!(!set.remove(&3));
+        set.extend([3, 4]);
+        drop(set);
+
+        let set = LookupSet::<u64>::new(b"s");
+        assert!(!set.contains(&1));
+        assert!(set.contains(&2));
+        assert!(set.contains(&4));
+    }
+
+    #[test]
+    fn test_custom_hasher() {
+        let mut set = LookupSet::<u64, Keccak256>::with_hasher(b"s");
+        assert!(set.insert(1));
+        assert!(set.contains(&1));
+    }
+
+    #[test]
+    fn test_legacy_layout() {
+        let mut set = LookupSet::<u64, UnhashedKeys>::with_hasher(b"s");
+        set.insert(1);
+        set.flush();
+
+        let legacy = crate::collections::LookupSet::<u64>::new(b"s".to_vec());
+        assert!(legacy.contains(&1));
+        assert!(!legacy.contains(&2));
+    }
+
+    #[derive(Arbitrary, Debug)]
+    enum Op {
+        Insert(u8),
+        Put(u8),
+        Remove(u8),
+        Flush,
+        Restore,
+        Contains(u8),
+    }
+
+    #[test]
+    fn arbitrary() {
+        setup_free();
+
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(0);
+        let mut buf = vec![0; 4096];
+        for _ in 0..512 {
+            // Clear storage in-between runs
+            crate::mock::with_mocked_blockchain(|b| b.take_storage());
+            rng.fill_bytes(&mut buf);
+
+            let mut ls = LookupSet::new(b"l");
+            let mut hs = HashSet::new();
+            let u = Unstructured::new(&buf);
+            if let Ok(ops) = Vec::<Op>::arbitrary_take_rest(u) {
+                for op in ops {
+                    match op {
+                        Op::Insert(v) => {
+                            let r1 = ls.insert(v);
+                            let r2 = hs.insert(v);
+                            assert_eq!(r1, r2)
+                        }
+                        Op::Put(v) => {
+                            ls.put(v);
+                            hs.insert(v);
+                        }
+                        Op::Remove(v) => {
+                            let r1 = ls.remove(&v);
+                            let r2 = hs.remove(&v);
+                            assert_eq!(r1, r2)
+                        }
+                        Op::Flush => {
+                            ls.flush();
+                        }
+                        Op::Restore => {
+                            ls = LookupSet::new(b"l");
+                        }
+                        Op::Contains(v) => {
+                            let r1 = ls.contains(&v);
+                            let r2 = hs.contains(&v);
+                            assert_eq!(r1, r2)
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn try_methods() {
+        use crate::store::StoreError;
+
+        let mut set = LookupSet::<u8>::new(b"s");
+        set.map.insert_raw(&1, &[1]);
+
+        assert!(matches!(set.try_insert(1), Err(StoreError::DeserializationError(_))));
+        assert!(matches!(set.try_remove(&1), Err(StoreError::DeserializationError(_))));
+        assert!(set.contains(&1));
+
+        assert!(set.try_insert(2).unwrap());
+        assert!(!set.try_insert(2).unwrap());
+        assert!(set.try_remove(&2).unwrap());
+        assert!(!set.try_remove(&2).unwrap());
+        set.try_flush().unwrap();
+    }
+}
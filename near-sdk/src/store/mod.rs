@@ -4,20 +4,87 @@ pub use lazy::Lazy;
 mod lazy_option;
 pub use lazy_option::LazyOption;
 
+mod cell;
+pub use cell::Cell;
+
+mod counter;
+pub use counter::{Counter, CounterValue};
+
+mod versioned;
+pub use versioned::{Versioned, VersionedState};
+
 pub mod vec;
 pub use vec::Vector;
 
+pub mod deque;
+pub use deque::Deque;
+
+mod queue;
+pub use queue::Queue;
+
+pub mod binary_heap;
+pub use binary_heap::BinaryHeap;
+
+mod error;
+pub use error::{OccupiedError, StoreError};
+
+pub mod key;
+
 pub mod lookup_map;
 pub use self::lookup_map::LookupMap;
 
+mod lookup_set;
+pub use self::lookup_set::LookupSet;
+
+mod lookup_multi_map;
+pub use self::lookup_multi_map::LookupMultiMap;
+
+mod bi_map;
+pub use self::bi_map::BiMap;
+
+mod bloom_filter;
+pub use self::bloom_filter::BloomFilter;
+
+mod expiring_lookup_map;
+pub use self::expiring_lookup_map::{ExpiringLookupMap, ExpiryClock};
+
+mod indexed_map;
+pub use self::indexed_map::IndexedMap;
+
+mod tracked_lookup_map;
+pub use self::tracked_lookup_map::TrackedLookupMap;
+
+pub mod blob;
+pub use self::blob::Blob;
+
 pub mod unordered_map;
 pub use self::unordered_map::UnorderedMap;
 
+pub mod unordered_set;
+pub use self::unordered_set::UnorderedSet;
+
+pub mod tree_map;
+pub use self::tree_map::TreeMap;
+
+pub mod free_list;
+pub use self::free_list::FreeList;
+
+mod size_guard;
+pub use self::size_guard::{SizeGuard, SizedCollection};
+
 mod index_map;
 pub(crate) use self::index_map::IndexMap;
 
-pub(crate) mod free_list;
-pub(crate) use self::free_list::FreeList;
+mod transaction;
+
+mod byte_counter;
+pub(crate) use self::byte_counter::ByteCounter;
+
+mod sample;
+
+mod read_buffer;
+
+pub mod journal;
 
 const ERR_INCONSISTENT_STATE: &str = "The collection is an inconsistent state. Did previous smart \
 										contract execution terminate unexpectedly?";
@@ -0,0 +1,227 @@
+use std::fmt;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::deque::Iter;
+use super::Deque;
+use crate::IntoStorageKey;
+
+/// A first-in, first-out queue that stores its content on the trie. Elements are pushed to the
+/// back of the queue and popped from the front.
+///
+/// This is a [`Deque`] restricted to queue operations, so it shares its storage layout and lazy
+/// loading behaviour: pushing and popping only touch the storage slot of that element. A queue
+/// can be bounded to a maximum number of elements through [`Queue::bounded`].
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::Queue;
+///
+/// let mut jobs = Queue::new(b"q");
+/// jobs.push_back("first".to_string());
+/// jobs.push_back("second".to_string());
+///
+/// assert_eq!(jobs.front().map(String::as_str), Some("first"));
+/// assert_eq!(jobs.back().map(String::as_str), Some("second"));
+/// assert_eq!(jobs.pop_front(), Some("first".to_string()));
+/// assert_eq!(jobs.len(), 1);
+/// ```
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Queue<T>
+where
+    T: BorshSerialize,
+{
+    inner: Deque<T>,
+}
+
+impl<T> Queue<T>
+where
+    T: BorshSerialize,
+{
+    /// Create new queue with zero elements. Prefixes storage accesss with the prefix provided.
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { inner: Deque::new(prefix) }
+    }
+
+    /// Create new queue which can hold at most `capacity` elements.
+    pub fn bounded<S>(prefix: S, capacity: u32) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { inner: Deque::bounded(prefix, capacity) }
+    }
+
+    /// Returns the number of elements in the queue.
+    pub fn len(&self) -> u32 {
+        self.inner.len()
+    }
+
+    /// Returns `true` if the queue contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Returns the maximum number of elements of a bounded queue, or [`None`] if the queue is not
+    /// bounded.
+    pub fn capacity(&self) -> Option<u32> {
+        self.inner.capacity()
+    }
+
+    /// Returns `true` if no more elements can be pushed onto the queue.
+    pub fn is_full(&self) -> bool {
+        self.inner.is_full()
+    }
+
+    /// Removes all elements from the queue.
+    pub fn clear(&mut self) {
+        self.inner.clear()
+    }
+
+    /// Removes up to `limit` elements from the back of the queue without loading them. Returns
+    /// `true` if there are elements left to remove.
+    pub fn clear_chunk(&mut self, limit: u32) -> bool {
+        self.inner.clear_chunk(limit)
+    }
+
+    /// Flushes the cache and writes all modified values to storage.
+    pub fn flush(&mut self) {
+        self.inner.flush()
+    }
+
+    /// Returns the number of storage bytes added by flushes of this queue since it was created
+    /// or loaded from storage.
+    pub fn bytes_written(&self) -> u64 {
+        self.inner.bytes_written()
+    }
+
+    /// Returns the number of storage bytes released by flushes of this queue since it was
+    /// created or loaded from storage.
+    pub fn bytes_freed(&self) -> u64 {
+        self.inner.bytes_freed()
+    }
+
+    /// Appends an element to the back of the queue.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the queue is full.
+    pub fn push_back(&mut self, element: T) {
+        self.inner.push_back(element)
+    }
+
+    /// Appends an element to the back of the queue, returning the element back if the queue is
+    /// full.
+    pub fn try_push_back(&mut self, element: T) -> Result<(), T> {
+        self.inner.try_push_back(element)
+    }
+}
+
+impl<T> Queue<T>
+where
+    T: BorshSerialize + BorshDeserialize,
+{
+    /// Returns a reference to the element at the front of the queue, which is the next element
+    /// to be popped.
+    pub fn front(&self) -> Option<&T> {
+        self.inner.front()
+    }
+
+    /// Returns a mutable reference to the element at the front of the queue.
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.inner.front_mut()
+    }
+
+    /// Returns a reference to the most recently pushed element.
+    pub fn back(&self) -> Option<&T> {
+        self.inner.back()
+    }
+
+    /// Removes the element at the front of the queue and returns it, or `None` if the queue is
+    /// empty.
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.inner.pop_front()
+    }
+
+    /// Returns an iterator over the queue, from the front to the back. This iterator will lazily
+    /// load any values iterated over from storage.
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.inner.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Queue<T>
+where
+    T: BorshSerialize + BorshDeserialize,
+{
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T> Extend<T> for Queue<T>
+where
+    T: BorshSerialize + BorshDeserialize,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        self.inner.extend(iter)
+    }
+}
+
+impl<T> serde::Serialize for Queue<T>
+where
+    T: BorshSerialize + BorshDeserialize + serde::Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serde::Serialize::serialize(&self.inner, serializer)
+    }
+}
+
+impl<T> fmt::Debug for Queue<T>
+where
+    T: BorshSerialize + BorshDeserialize + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Queue").field("inner", &self.inner).finish()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::Queue;
+    use borsh::{BorshDeserialize, BorshSerialize};
+
+    #[test]
+    fn fifo_order() {
+        let mut queue = Queue::bounded(b"q", 3);
+        queue.extend([1u32, 2, 3]);
+        assert!(queue.is_full());
+        assert_eq!(queue.try_push_back(4), Err(4));
+
+        assert_eq!(queue.pop_front(), Some(1));
+        queue.push_back(4);
+        assert_eq!(queue.front(), Some(&2));
+        assert_eq!(queue.back(), Some(&4));
+
+        let serialized = queue.try_to_vec().unwrap();
+        drop(queue);
+        let mut queue = Queue::<u32>::try_from_slice(&serialized).unwrap();
+        assert!(Iterator::eq(queue.iter(), [2, 3, 4].iter()));
+        assert_eq!(queue.pop_front(), Some(2));
+        assert_eq!(queue.pop_front(), Some(3));
+        assert_eq!(queue.pop_front(), Some(4));
+        assert_eq!(queue.pop_front(), None);
+    }
+}
@@ -0,0 +1,42 @@
+use std::cell::Cell;
+
+use crate::env;
+
+thread_local! {
+    /// Buffer reused for the storage reads of all collections.
+    static BUFFER: Cell<Vec<u8>> = const { Cell::new(Vec::new()) };
+}
+
+/// Reads the value stored under `key` into a buffer which is reused across reads, and calls `f`
+/// with the bytes. Returns [`None`] if there is no value for the key.
+///
+/// This avoids allocating a new [`Vec`] for each read when the value is only deserialized.
+pub(crate) fn with_storage_value<R>(key: &[u8], f: impl FnOnce(&[u8]) -> R) -> Option<R> {
+    // The buffer is taken while in use, so that a read within `f` uses its own buffer instead of
+    // overwriting the bytes being deserialized.
+    let mut buffer = BUFFER.with(Cell::take);
+    let result = if env::storage_read_into(key, &mut buffer) { Some(f(&buffer)) } else { None };
+    BUFFER.with(|b| b.set(buffer));
+    result
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::with_storage_value;
+    use crate::env;
+
+    #[test]
+    fn reuses_buffer_across_reads() {
+        env::storage_write(b"a", &[1, 2, 3]);
+        env::storage_write(b"b", &[4]);
+        let nested = with_storage_value(b"a", |a| {
+            // A nested read does not overwrite the outer value.
+            let b = with_storage_value(b"b", |b| b.to_vec());
+            (a.to_vec(), b)
+        });
+        assert_eq!(nested, Some((vec![1, 2, 3], Some(vec![4]))));
+        assert_eq!(with_storage_value(b"b", |b| b.to_vec()), Some(vec![4]));
+        assert_eq!(with_storage_value(b"c", |c| c.to_vec()), None);
+    }
+}
@@ -0,0 +1,40 @@
+use std::collections::HashMap;
+
+pub(crate) use crate::environment::rng::SeedRng;
+
+/// Returns `n` distinct indices chosen uniformly from `0..len`, in random order, using a partial
+/// Fisher-Yates shuffle that only tracks the swapped positions.
+pub(crate) fn sample_indices(len: u32, n: u32, rng: &mut SeedRng) -> Vec<u32> {
+    let n = n.min(len);
+    let mut swapped: HashMap<u32, u32> = HashMap::new();
+    (0..n)
+        .map(|i| {
+            let j = i + rng.below((len - i) as u64) as u32;
+            let picked = *swapped.get(&j).unwrap_or(&j);
+            let displaced = *swapped.get(&i).unwrap_or(&i);
+            swapped.insert(j, displaced);
+            picked
+        })
+        .collect()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::{sample_indices, SeedRng};
+    use crate::test_utils::VMContextBuilder;
+    use crate::testing_env;
+    use std::collections::HashSet;
+
+    #[test]
+    fn distinct_indices() {
+        testing_env!(VMContextBuilder::new().random_seed_array([3; 32]).build());
+        let mut rng = SeedRng::new();
+        for (len, n) in [(0, 3), (1, 1), (10, 3), (10, 10), (10, 20), (1000, 50)] {
+            let indices = sample_indices(len, n, &mut rng);
+            assert_eq!(indices.len() as u32, n.min(len));
+            assert!(indices.iter().all(|&i| i < len));
+            assert_eq!(indices.iter().collect::<HashSet<_>>().len(), indices.len());
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use borsh::BorshSerialize;
+
+use super::key::ToKey;
+use super::{Deque, Queue, TreeMap, UnorderedMap, UnorderedSet, Vector};
+use crate::crypto_hash::CryptoHasher;
+use crate::env;
+
+const ERR_TOO_LARGE: &str = "Collection has too many elements to be serialized";
+
+/// A collection which stores its number of elements, so that the size can be checked without
+/// loading any of its elements.
+///
+/// This trait is sealed and is implemented for the iterable store collections.
+pub trait SizedCollection: private::Sealed {
+    /// Returns the number of elements in the collection.
+    fn collection_len(&self) -> u32;
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// Wraps a reference to a collection to [`serde::Serialize`] it, but panics instead if the
+/// collection has more than `max_len` elements.
+///
+/// Serializing a collection loads all of its elements from storage, so this can be returned from
+/// view methods to guard against the collection growing beyond the gas limit of a single call,
+/// with a clear error instead of running out of gas.
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::{SizeGuard, UnorderedMap};
+///
+/// let mut balances = UnorderedMap::new(b"b");
+/// balances.insert("alice".to_string(), 10u64);
+///
+/// let json = near_sdk::serde_json::to_string(&SizeGuard::new(&balances, 100)).unwrap();
+/// assert_eq!(json, r#"{"alice":10}"#);
+/// ```
+#[derive(Debug)]
+pub struct SizeGuard<'a, C> {
+    collection: &'a C,
+    max_len: u32,
+}
+
+impl<'a, C> SizeGuard<'a, C>
+where
+    C: SizedCollection + serde::Serialize,
+{
+    /// Creates a guard which serializes `collection` if it has at most `max_len` elements.
+    pub fn new(collection: &'a C, max_len: u32) -> Self {
+        Self { collection, max_len }
+    }
+}
+
+impl<'a, C> serde::Serialize for SizeGuard<'a, C>
+where
+    C: SizedCollection + serde::Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        if self.collection.collection_len() > self.max_len {
+            env::panic_str(ERR_TOO_LARGE);
+        }
+        self.collection.serialize(serializer)
+    }
+}
+
+impl<T> private::Sealed for Vector<T> where T: BorshSerialize {}
+impl<T> SizedCollection for Vector<T>
+where
+    T: BorshSerialize,
+{
+    fn collection_len(&self) -> u32 {
+        self.len()
+    }
+}
+
+impl<T> private::Sealed for Deque<T> where T: BorshSerialize {}
+impl<T> SizedCollection for Deque<T>
+where
+    T: BorshSerialize,
+{
+    fn collection_len(&self) -> u32 {
+        self.len()
+    }
+}
+
+impl<T> private::Sealed for Queue<T> where T: BorshSerialize {}
+impl<T> SizedCollection for Queue<T>
+where
+    T: BorshSerialize,
+{
+    fn collection_len(&self) -> u32 {
+        self.len()
+    }
+}
+
+impl<T, H> private::Sealed for UnorderedSet<T, H>
+where
+    T: BorshSerialize + Ord,
+    H: ToKey,
+{
+}
+impl<T, H> SizedCollection for UnorderedSet<T, H>
+where
+    T: BorshSerialize + Ord,
+    H: ToKey,
+{
+    fn collection_len(&self) -> u32 {
+        self.len()
+    }
+}
+
+impl<K, V, H> private::Sealed for UnorderedMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: CryptoHasher<Digest = [u8; 32]>,
+{
+}
+impl<K, V, H> SizedCollection for UnorderedMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: CryptoHasher<Digest = [u8; 32]>,
+{
+    fn collection_len(&self) -> u32 {
+        self.len()
+    }
+}
+
+impl<K, V, H> private::Sealed for TreeMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+}
+impl<K, V, H> SizedCollection for TreeMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    fn collection_len(&self) -> u32 {
+        self.len()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::SizeGuard;
+    use crate::store::{Deque, TreeMap, UnorderedSet, Vector};
+
+    #[test]
+    fn serialize_collections() {
+        let mut vec = Vector::new(b"v");
+        vec.extend([1u8, 2, 3]);
+        assert_eq!(serde_json::to_string(&vec).unwrap(), "[1,2,3]");
+
+        let mut deque = Deque::new(b"d");
+        deque.push_back(2u8);
+        deque.push_front(1);
+        assert_eq!(serde_json::to_string(&deque).unwrap(), "[1,2]");
+
+        let mut set = UnorderedSet::new(b"s");
+        set.extend(["a".to_string()]);
+        assert_eq!(serde_json::to_string(&set).unwrap(), r#"["a"]"#);
+
+        let mut map = TreeMap::new(b"t");
+        map.insert("b".to_string(), 2u8);
+        map.insert("a".to_string(), 1);
+        assert_eq!(serde_json::to_string(&map).unwrap(), r#"{"a":1,"b":2}"#);
+        assert_eq!(serde_json::to_string(&SizeGuard::new(&map, 2)).unwrap(), r#"{"a":1,"b":2}"#);
+    }
+}
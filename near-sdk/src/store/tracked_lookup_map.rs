@@ -0,0 +1,306 @@
+use std::borrow::Borrow;
+use std::fmt;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::free_list::FreeListIndex;
+use super::key::ToKey;
+use super::{FreeList, LookupMap, ERR_INCONSISTENT_STATE};
+use crate::crypto_hash::Sha256;
+use crate::{env, IntoStorageKey};
+
+/// A [`LookupMap`] which additionally keeps a registry of its keys, so that they can be
+/// enumerated.
+///
+/// The values are stored exactly as in a [`LookupMap`] with the same prefix and hasher, and the
+/// registry is stored under a separate prefix. This allows an existing [`LookupMap`] to be made
+/// enumerable with [`from_lookup_map`] without moving its values, unlike a migration to an
+/// [`UnorderedMap`](super::UnorderedMap). Keys which were inserted before the registry existed
+/// are not enumerated until they are registered with [`track`].
+///
+/// Each insertion of a new key and each removal also updates the registry, which costs one
+/// additional storage write or removal for each of the two registry collections.
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::{LookupMap, TrackedLookupMap};
+///
+/// let mut legacy: LookupMap<String, u64> = LookupMap::new(b"b");
+/// legacy.insert("alice".to_string(), 10);
+///
+/// let mut balances = TrackedLookupMap::from_lookup_map(legacy, b"r");
+/// balances.insert("bob".to_string(), 20);
+/// assert_eq!(balances.keys().collect::<Vec<_>>(), ["bob"]);
+///
+/// // Register the keys which are known to exist from before.
+/// assert!(balances.track("alice".to_string()));
+/// assert_eq!(balances.len(), 2);
+/// assert_eq!(balances.iter().map(|(_, v)| v).sum::<u64>(), 30);
+/// ```
+///
+/// [`from_lookup_map`]: Self::from_lookup_map
+/// [`track`]: Self::track
+pub struct TrackedLookupMap<K, V, H = Sha256>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    entries: LookupMap<K, V, H>,
+    key_indices: LookupMap<K, FreeListIndex, H>,
+    keys: FreeList<K>,
+}
+
+//? Manual implementations needed only because borsh derive is leaking field types
+// https://github.com/near/borsh-rs/issues/41
+impl<K, V, H> BorshSerialize for TrackedLookupMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    fn serialize<W: borsh::maybestd::io::Write>(
+        &self,
+        writer: &mut W,
+    ) -> Result<(), borsh::maybestd::io::Error> {
+        BorshSerialize::serialize(&self.entries, writer)?;
+        BorshSerialize::serialize(&self.key_indices, writer)?;
+        BorshSerialize::serialize(&self.keys, writer)?;
+        Ok(())
+    }
+}
+
+impl<K, V, H> BorshDeserialize for TrackedLookupMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    fn deserialize(buf: &mut &[u8]) -> Result<Self, borsh::maybestd::io::Error> {
+        Ok(Self {
+            entries: BorshDeserialize::deserialize(buf)?,
+            key_indices: BorshDeserialize::deserialize(buf)?,
+            keys: BorshDeserialize::deserialize(buf)?,
+        })
+    }
+}
+
+impl<K, V, H> fmt::Debug for TrackedLookupMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TrackedLookupMap")
+            .field("entries", &self.entries)
+            .field("len", &self.keys.len())
+            .finish()
+    }
+}
+
+impl<K, V> TrackedLookupMap<K, V, Sha256>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+{
+    /// Create a new, empty map. The values are stored under `prefix`, as in a [`LookupMap`], and
+    /// the key registry under `registry_prefix`.
+    #[inline]
+    pub fn new<S, R>(prefix: S, registry_prefix: R) -> Self
+    where
+        S: IntoStorageKey,
+        R: IntoStorageKey,
+    {
+        Self::with_hasher(prefix, registry_prefix)
+    }
+}
+
+impl<K, V, H> TrackedLookupMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    /// Initialize a [`TrackedLookupMap`] with a custom hash function.
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::crypto_hash::Keccak256;
+    /// use near_sdk::store::TrackedLookupMap;
+    ///
+    /// let map = TrackedLookupMap::<String, String, Keccak256>::with_hasher(b"m", b"r");
+    /// ```
+    pub fn with_hasher<S, R>(prefix: S, registry_prefix: R) -> Self
+    where
+        S: IntoStorageKey,
+        R: IntoStorageKey,
+    {
+        Self::from_lookup_map(LookupMap::with_hasher(prefix), registry_prefix)
+    }
+
+    /// Wraps an existing [`LookupMap`], storing the key registry under `registry_prefix`, which
+    /// must not be used by any other collection. The existing keys of the map are not registered.
+    pub fn from_lookup_map<R>(map: LookupMap<K, V, H>, registry_prefix: R) -> Self
+    where
+        R: IntoStorageKey,
+    {
+        let mut keys_prefix = registry_prefix.into_storage_key();
+        let indices_prefix = [keys_prefix.as_slice(), b"i"].concat();
+        keys_prefix.push(b'k');
+        Self {
+            entries: map,
+            key_indices: LookupMap::with_hasher(indices_prefix),
+            keys: FreeList::new(keys_prefix),
+        }
+    }
+
+    /// Returns the number of keys in the registry.
+    pub fn len(&self) -> u32 {
+        self.keys.len()
+    }
+
+    /// Returns `true` if the registry contains no keys.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// An iterator visiting all registered keys in arbitrary order.
+    pub fn keys(&self) -> impl Iterator<Item = &K>
+    where
+        K: BorshDeserialize,
+    {
+        self.keys.iter()
+    }
+
+    /// Flushes the intermediate values of the map before this is called when the structure is
+    /// [`Drop`]ed. This will write all modified values to storage but keep all cached values
+    /// in memory.
+    pub fn flush(&mut self) {
+        self.entries.flush();
+        self.key_indices.flush();
+        self.keys.flush();
+    }
+}
+
+impl<K, V, H> TrackedLookupMap<K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+    /// Returns a reference to the value corresponding to the key.
+    ///
+    /// The key may be any borrowed form of the map's key type, but [`BorshSerialize`] and
+    /// [`ToOwned<Owned = K>`](ToOwned) on the borrowed form *must* match those for the key type.
+    pub fn get<Q: ?Sized>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+    {
+        self.entries.get(k)
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    pub fn get_mut<Q: ?Sized>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+    {
+        self.entries.get_mut(k)
+    }
+
+    /// Returns `true` if the map contains a value for the specified key, whether or not the key
+    /// is registered.
+    pub fn contains_key<Q: ?Sized>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K> + Ord,
+    {
+        self.entries.contains_key(k)
+    }
+
+    /// Inserts a key-value pair into the map, registering the key if it is not registered yet.
+    ///
+    /// If the map did have this key present, the value is updated, and the old value is returned.
+    pub fn insert(&mut self, k: K, value: V) -> Option<V> {
+        let prev = self.entries.insert(k.clone(), value);
+        self.register(k);
+        prev
+    }
+
+    /// Registers a key which is in the map, so that it is enumerated. This is only needed for the
+    /// keys which were inserted before the map was wrapped with [`from_lookup_map`].
+    ///
+    /// Returns `false` if the key is not in the map or is already registered.
+    ///
+    /// [`from_lookup_map`]: Self::from_lookup_map
+    pub fn track(&mut self, k: K) -> bool {
+        self.entries.contains_key(&k) && self.register(k)
+    }
+
+    fn register(&mut self, k: K) -> bool {
+        if self.key_indices.contains_key(&k) {
+            return false;
+        }
+        let index = self.keys.insert(k.clone());
+        self.key_indices.insert(k, index);
+        true
+    }
+
+    /// Removes a key from the map and the registry, returning the value at the key if the key
+    /// was previously in the map.
+    pub fn remove<Q: ?Sized>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+    {
+        if let Some(index) = self.key_indices.remove(k) {
+            self.keys.remove(index).unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE));
+        }
+        self.entries.remove(k)
+    }
+
+    /// An iterator visiting the registered keys and their values in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        let entries = &self.entries;
+        self.keys.iter().map(move |k| {
+            let v = entries.get(k).unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE));
+            (k, v)
+        })
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::TrackedLookupMap;
+    use crate::store::LookupMap;
+    use borsh::{BorshDeserialize, BorshSerialize};
+
+    #[test]
+    fn registry_follows_entries() {
+        let mut legacy = LookupMap::new(b"m");
+        legacy.extend((0..5u32).map(|i| (i, i)));
+        legacy.flush();
+        drop(legacy);
+
+        let mut map = TrackedLookupMap::from_lookup_map(LookupMap::new(b"m"), b"r");
+        map.insert(10, 10);
+        map.insert(10, 11);
+        assert!(map.track(1));
+        assert!(!map.track(1));
+        assert!(!map.track(7));
+        assert_eq!(map.remove(&2), Some(2));
+        assert_eq!(map.remove(&1), Some(1));
+        map.flush();
+
+        let serialized = map.try_to_vec().unwrap();
+        drop(map);
+        let map = TrackedLookupMap::<u32, u32>::try_from_slice(&serialized).unwrap();
+        assert_eq!(map.iter().collect::<Vec<_>>(), [(&10, &11)]);
+        // Values which are not registered are still accessible.
+        assert_eq!(map.get(&3), Some(&3));
+        assert!(!map.contains_key(&2));
+    }
+}
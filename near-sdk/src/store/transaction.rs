@@ -0,0 +1,85 @@
+use super::{journal, ByteCounter};
+use crate::env;
+
+/// Collections whose changes since a point in time can be discarded, which is used to implement
+/// their `transaction` methods.
+pub(crate) trait Rollback {
+    /// State of the collection which is not in storage, needed to roll back the collection.
+    type Snapshot;
+
+    /// Flushes the collection, starts recording the storage writes of the collection and returns
+    /// the state needed to roll back to this point.
+    fn snapshot(&mut self) -> Self::Snapshot;
+
+    /// Discards the changes made since the snapshot was taken, including the values which were
+    /// written to storage.
+    fn rollback(&mut self, snapshot: Self::Snapshot);
+
+    /// Keeps the changes made since the snapshot was taken.
+    fn commit(&mut self, snapshot: Self::Snapshot);
+}
+
+/// Runs `f` on the collection, discarding the changes it made if it returns an error.
+pub(crate) fn transaction<C, F, T, E>(collection: &mut C, f: F) -> Result<T, E>
+where
+    C: Rollback,
+    F: FnOnce(&mut C) -> Result<T, E>,
+{
+    let snapshot = collection.snapshot();
+    let result = f(collection);
+    if result.is_err() {
+        collection.rollback(snapshot);
+    } else {
+        collection.commit(snapshot);
+    }
+    result
+}
+
+/// Snapshot of the collections which write to storage: the byte counts when the transaction
+/// started, and the undo log of the enclosing transaction.
+pub(crate) type StorageSnapshot = (ByteCounter, UndoLog);
+
+/// Previous values of the storage keys written by a collection during a transaction, in the order
+/// they were written, which are written back to roll back the transaction.
+#[derive(Default)]
+pub(crate) struct UndoLog(Option<Vec<PreviousValue>>);
+
+/// Storage key written during a transaction, with the value it had before.
+type PreviousValue = (Vec<u8>, Option<Vec<u8>>);
+
+impl UndoLog {
+    /// Records the previous value of the key after it was written or removed, where `replaced` is
+    /// the result of the [`env::storage_write`] or [`env::storage_remove`] call. Has no effect
+    /// outside of a transaction.
+    pub(crate) fn record(&mut self, key: &[u8], replaced: bool) {
+        if let Some(log) = &mut self.0 {
+            let previous = if replaced { env::storage_get_evicted() } else { None };
+            log.push((key.to_vec(), previous));
+        }
+    }
+
+    /// Starts recording the writes of a transaction, and returns the log of the enclosing
+    /// transaction, if any.
+    pub(crate) fn begin(&mut self) -> UndoLog {
+        std::mem::replace(self, UndoLog(Some(Vec::new())))
+    }
+
+    /// Ends the transaction, keeping its writes in the log of the enclosing transaction.
+    pub(crate) fn commit(&mut self, outer: UndoLog) {
+        let inner = std::mem::replace(self, outer);
+        if let (Some(log), Some(inner)) = (&mut self.0, inner.0) {
+            log.extend(inner);
+        }
+    }
+
+    /// Ends the transaction, writing back the previous values of the keys it wrote.
+    pub(crate) fn rollback(&mut self, outer: UndoLog) {
+        let inner = std::mem::replace(self, outer);
+        for (key, previous) in inner.0.unwrap_or_default().into_iter().rev() {
+            match previous {
+                Some(value) => journal::storage_write(&key, &value),
+                None => journal::storage_remove(&key),
+            };
+        }
+    }
+}
@@ -0,0 +1,356 @@
+use std::borrow::Borrow;
+use std::iter::FusedIterator;
+use std::ops::Bound;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::{Tree, TreeMap};
+use crate::env;
+use crate::store::key::ToKey;
+use crate::store::ERR_INCONSISTENT_STATE;
+
+impl<'a, K, V, H> IntoIterator for &'a TreeMap<K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V, H>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the keys of a [`TreeMap`], in ascending order.
+///
+/// This `struct` is created by the `keys` method on [`TreeMap`].
+pub struct Keys<'a, K: 'a>
+where
+    K: BorshSerialize,
+{
+    tree: &'a Tree<K>,
+    /// Next key to yield from the front, [`None`] once the iterator is exhausted.
+    front: Option<&'a K>,
+    /// Next key to yield from the back, [`None`] once the iterator is exhausted.
+    back: Option<&'a K>,
+}
+
+impl<'a, K> Keys<'a, K>
+where
+    K: BorshSerialize + BorshDeserialize + Ord,
+{
+    pub(super) fn new<Q: ?Sized>(tree: &'a Tree<K>, start: Bound<&Q>, end: Bound<&Q>) -> Self
+    where
+        K: Borrow<Q>,
+        Q: Ord,
+    {
+        let front = tree.lower_bound(start);
+        let back = tree.upper_bound(end);
+        match (front, back) {
+            (Some(front), Some(back)) if front <= back => {
+                Self { tree, front: Some(front), back: Some(back) }
+            }
+            _ => Self { tree, front: None, back: None },
+        }
+    }
+}
+
+impl<'a, K> Iterator for Keys<'a, K>
+where
+    K: BorshSerialize + BorshDeserialize + Ord,
+{
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let key = self.front?;
+        if self.back == Some(key) {
+            self.front = None;
+            self.back = None;
+        } else {
+            self.front = self.tree.lower_bound(Bound::Excluded(key));
+        }
+        Some(key)
+    }
+}
+
+impl<'a, K> DoubleEndedIterator for Keys<'a, K>
+where
+    K: BorshSerialize + BorshDeserialize + Ord,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let key = self.back?;
+        if self.front == Some(key) {
+            self.front = None;
+            self.back = None;
+        } else {
+            self.back = self.tree.upper_bound(Bound::Excluded(key));
+        }
+        Some(key)
+    }
+}
+
+impl<'a, K> FusedIterator for Keys<'a, K> where K: BorshSerialize + BorshDeserialize + Ord {}
+
+/// A position within a [`TreeMap`] which can be used to resume iteration with
+/// [`TreeMap::iter_from`].
+///
+/// The cursor only holds the key of the next entry, so it can be stored in contract state or
+/// returned from a view call and passed back for the next page. Resuming loads only the nodes on
+/// the path to that key. If the key was removed in the meantime, iteration resumes at the next
+/// greater key.
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::TreeMap;
+///
+/// let mut map = TreeMap::new(b"m");
+/// map.extend((0..5u32).map(|i| (i, i * 10)));
+///
+/// let mut cursor = map.cursor();
+/// let mut pages = Vec::new();
+/// while !cursor.is_end() {
+///     let mut iter = map.iter_from(&cursor);
+///     pages.push(iter.by_ref().take(2).map(|(_, v)| *v).collect::<Vec<_>>());
+///     cursor = iter.cursor();
+/// }
+/// assert_eq!(pages, [vec![0, 10], vec![20, 30], vec![40]]);
+/// ```
+#[derive(
+    BorshSerialize,
+    BorshDeserialize,
+    serde::Serialize,
+    serde::Deserialize,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+)]
+pub struct Cursor<K> {
+    /// Key of the next entry to yield, [`None`] once iteration has reached the end of the map.
+    next: Option<K>,
+}
+
+impl<K> Cursor<K> {
+    pub(super) fn at(next: Option<K>) -> Self {
+        Self { next }
+    }
+
+    /// Returns `true` if there are no entries left to iterate over.
+    pub fn is_end(&self) -> bool {
+        self.next.is_none()
+    }
+
+    /// Returns the key of the entry that iteration will resume at.
+    pub fn next_key(&self) -> Option<&K> {
+        self.next.as_ref()
+    }
+}
+
+/// An iterator over a range of entries of a [`TreeMap`], in ascending order of the keys.
+///
+/// This `struct` is created by the `range` method on [`TreeMap`].
+pub struct Range<'a, K: 'a, V: 'a, H: 'a>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    keys: Keys<'a, K>,
+    map: &'a TreeMap<K, V, H>,
+}
+
+impl<'a, K, V, H> Range<'a, K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+    pub(super) fn new<Q: ?Sized>(
+        map: &'a TreeMap<K, V, H>,
+        start: Bound<&Q>,
+        end: Bound<&Q>,
+    ) -> Self
+    where
+        K: Borrow<Q>,
+        Q: Ord,
+    {
+        Self { keys: Keys::new(&map.tree, start, end), map }
+    }
+
+    pub(super) fn from_cursor(map: &'a TreeMap<K, V, H>, cursor: &Cursor<K>) -> Self {
+        match &cursor.next {
+            Some(next) => Self::new::<K>(map, Bound::Included(next), Bound::Unbounded),
+            None => Self { keys: Keys { tree: &map.tree, front: None, back: None }, map },
+        }
+    }
+
+    /// Returns a [`Cursor`] at the next entry this iterator would yield from the front.
+    pub fn cursor(&self) -> Cursor<K> {
+        Cursor::at(self.keys.front.cloned())
+    }
+
+    fn entry(&self, key: &'a K) -> (&'a K, &'a V) {
+        let map: &'a TreeMap<K, V, H> = self.map;
+        let value = map.values.get(key).unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE));
+        (key, value)
+    }
+}
+
+impl<'a, K, V, H> Iterator for Range<'a, K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let key = self.keys.next()?;
+        Some(self.entry(key))
+    }
+}
+
+impl<'a, K, V, H> DoubleEndedIterator for Range<'a, K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let key = self.keys.next_back()?;
+        Some(self.entry(key))
+    }
+}
+
+impl<'a, K, V, H> FusedIterator for Range<'a, K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+}
+
+/// An iterator over the entries of a [`TreeMap`], in ascending order of the keys.
+///
+/// This `struct` is created by the `iter` method on [`TreeMap`].
+pub struct Iter<'a, K: 'a, V: 'a, H: 'a>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    inner: Range<'a, K, V, H>,
+}
+
+impl<'a, K, V, H> Iter<'a, K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+    pub(super) fn new(map: &'a TreeMap<K, V, H>) -> Self {
+        Self { inner: Range::new::<K>(map, Bound::Unbounded, Bound::Unbounded) }
+    }
+
+    /// Returns a [`Cursor`] at the next entry this iterator would yield from the front.
+    pub fn cursor(&self) -> Cursor<K> {
+        self.inner.cursor()
+    }
+}
+
+impl<'a, K, V, H> Iterator for Iter<'a, K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.inner.map.len() as usize))
+    }
+}
+
+impl<'a, K, V, H> DoubleEndedIterator for Iter<'a, K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl<'a, K, V, H> FusedIterator for Iter<'a, K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+}
+
+/// An iterator over the values of a [`TreeMap`], in ascending order of their keys.
+///
+/// This `struct` is created by the `values` method on [`TreeMap`].
+pub struct Values<'a, K: 'a, V: 'a, H: 'a>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    inner: Iter<'a, K, V, H>,
+}
+
+impl<'a, K, V, H> Values<'a, K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+    pub(super) fn new(map: &'a TreeMap<K, V, H>) -> Self {
+        Self { inner: Iter::new(map) }
+    }
+}
+
+impl<'a, K, V, H> Iterator for Values<'a, K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, v)| v)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K, V, H> DoubleEndedIterator for Values<'a, K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(_, v)| v)
+    }
+}
+
+impl<'a, K, V, H> FusedIterator for Values<'a, K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+}
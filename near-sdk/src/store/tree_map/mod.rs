@@ -0,0 +1,1146 @@
+mod iter;
+
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Bound, RangeBounds};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::crypto_hash::Sha256;
+use crate::{env, IntoStorageKey};
+
+pub use self::iter::{Cursor, Iter, Keys, Range, Values};
+use super::free_list::FreeListIndex;
+use super::key::ToKey;
+use super::{FreeList, LookupMap, StoreError, ERR_INCONSISTENT_STATE};
+
+/// A lazily loaded storage map that stores its content directly on the storage trie and keeps
+/// its keys ordered. This structure is similar to
+/// [`near_sdk::store::UnorderedMap`](crate::store::UnorderedMap), except that the keys are
+/// stored in an AVL tree so that [`TreeMap`] can be iterated in order and queried by range.
+///
+/// Values are stored in a [`LookupMap`] and the nodes of the tree in a [`FreeList`], so both
+/// values and tree nodes are only loaded when they are accessed, and are cached until the map is
+/// flushed or [`Drop`]ed.
+///
+/// Runtime complexity (worst case):
+/// - `get`/`contains_key`:     O(1) - LookupMap lookup
+/// - `insert`/`remove`:        O(log(N))
+/// - `min`/`max`:              O(log(N))
+/// - `floor_key`/`ceil_key`:   O(log(N))
+/// - `range` of K elements:    O(Klog(N))
+///
+/// The default hash function for [`TreeMap`] is [`Sha256`]. To use a custom function, use
+/// [`with_hasher`].
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::TreeMap;
+///
+/// // Initializes a map, the generic types can be inferred to `TreeMap<u64, String, Sha256>`
+/// // The `b"a"` parameter is a prefix for the storage keys of this data structure.
+/// let mut map = TreeMap::new(b"a");
+///
+/// map.insert(3u64, "c".to_string());
+/// map.insert(1, "a".to_string());
+/// map.insert(2, "b".to_string());
+///
+/// assert_eq!(map.min(), Some(&1));
+/// assert_eq!(map.max(), Some(&3));
+/// assert_eq!(map.get(&2).map(String::as_str), Some("b"));
+///
+/// let keys: Vec<_> = map.keys().copied().collect();
+/// assert_eq!(keys, [1, 2, 3]);
+///
+/// let range: Vec<_> = map.range(2..).rev().map(|(k, _)| *k).collect();
+/// assert_eq!(range, [3, 2]);
+/// ```
+///
+/// [`with_hasher`]: Self::with_hasher
+pub struct TreeMap<K, V, H = Sha256>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    values: LookupMap<K, V, H>,
+    tree: Tree<K>,
+}
+
+struct Tree<K>
+where
+    K: BorshSerialize,
+{
+    root: Option<FreeListIndex>,
+    nodes: FreeList<Node<K>>,
+}
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+struct Node<K> {
+    key: K,                     // key stored in a node
+    lft: Option<FreeListIndex>, // left link of a node
+    rgt: Option<FreeListIndex>, // right link of a node
+    ht: u32,                    // height of a subtree at a node
+}
+
+impl<K> Node<K> {
+    fn of(key: K) -> Self {
+        Self { key, lft: None, rgt: None, ht: 1 }
+    }
+}
+
+//? Manual implementations needed only because borsh derive is leaking field types
+// https://github.com/near/borsh-rs/issues/41
+impl<K, V, H> BorshSerialize for TreeMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    fn serialize<W: borsh::maybestd::io::Write>(
+        &self,
+        writer: &mut W,
+    ) -> Result<(), borsh::maybestd::io::Error> {
+        BorshSerialize::serialize(&self.values, writer)?;
+        BorshSerialize::serialize(&self.tree.root, writer)?;
+        BorshSerialize::serialize(&self.tree.nodes, writer)?;
+        Ok(())
+    }
+}
+
+impl<K, V, H> BorshDeserialize for TreeMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    fn deserialize(buf: &mut &[u8]) -> Result<Self, borsh::maybestd::io::Error> {
+        Ok(Self {
+            values: BorshDeserialize::deserialize(buf)?,
+            tree: Tree {
+                root: BorshDeserialize::deserialize(buf)?,
+                nodes: BorshDeserialize::deserialize(buf)?,
+            },
+        })
+    }
+}
+
+impl<K, V, H> Drop for TreeMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    fn drop(&mut self) {
+        self.flush()
+    }
+}
+
+impl<K, V, H> fmt::Debug for TreeMap<K, V, H>
+where
+    K: BorshSerialize + Ord + BorshDeserialize + Clone + fmt::Debug,
+    V: BorshSerialize + BorshDeserialize + fmt::Debug,
+    H: ToKey,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if cfg!(feature = "expensive-debug") {
+            f.debug_map().entries(self.iter()).finish()
+        } else {
+            f.debug_struct("TreeMap")
+                .field("root", &self.tree.root)
+                .field("nodes", &self.tree.nodes)
+                .finish()
+        }
+    }
+}
+
+impl<K, V> TreeMap<K, V, Sha256>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+{
+    #[inline]
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self::with_hasher(prefix)
+    }
+}
+
+impl<K, V, H> TreeMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    /// Initialize a [`TreeMap`] with a custom hash function.
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::crypto_hash::Keccak256;
+    /// use near_sdk::store::TreeMap;
+    ///
+    /// let map = TreeMap::<String, String, Keccak256>::with_hasher(b"m");
+    /// ```
+    pub fn with_hasher<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        let mut tree_key = prefix.into_storage_key();
+        let map_key = [tree_key.as_slice(), b"v"].concat();
+        tree_key.push(b'n');
+        Self {
+            values: LookupMap::with_hasher(map_key),
+            tree: Tree { root: None, nodes: FreeList::new(tree_key) },
+        }
+    }
+
+    /// Return the amount of elements inside of the map.
+    pub fn len(&self) -> u32 {
+        self.tree.nodes.len()
+    }
+
+    /// Returns true if there are no elements inside of the map.
+    pub fn is_empty(&self) -> bool {
+        self.tree.nodes.is_empty()
+    }
+
+    /// Clears the map, removing all key-value pairs.
+    pub fn clear(&mut self)
+    where
+        K: BorshDeserialize,
+    {
+        self.tree.root = None;
+        for node in self.tree.nodes.drain() {
+            // Set instead of remove to avoid loading the value from storage.
+            self.values.set(node.key, None);
+        }
+    }
+
+    /// Removes up to `limit` key-value pairs from the map, starting with the largest keys. Returns
+    /// `true` if there is anything left to remove.
+    ///
+    /// This can be used to clear a map which is too large to [`clear`](Self::clear) within the gas
+    /// limit of a single function call, by calling it until it returns `false`. The map stays
+    /// consistent between calls.
+    ///
+    /// # Examples
+    /// ```
+    /// use near_sdk::store::TreeMap;
+    ///
+    /// let mut map = TreeMap::new(b"m");
+    /// map.extend((0u32..10).map(|k| (k, k)));
+    /// assert!(map.clear_chunk(4));
+    /// assert_eq!(map.max(), Some(&5));
+    /// while map.clear_chunk(4) {}
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn clear_chunk(&mut self, limit: u32) -> bool
+    where
+        K: BorshDeserialize + Clone,
+    {
+        let mut removed = 0;
+        while removed < limit {
+            let key = match self.tree.upper_bound::<K>(Bound::Unbounded) {
+                Some(key) => key.clone(),
+                None => break,
+            };
+            self.tree.remove(&key).unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE));
+            // Set instead of remove to avoid loading the value from storage.
+            self.values.set(key, None);
+            removed += 1;
+        }
+        if !self.is_empty() {
+            return true;
+        }
+        // Remove the empty node slots left behind by removed keys.
+        self.tree.nodes.clear_chunk(limit - removed)
+    }
+
+    /// Flushes the intermediate values of the map before this is called when the structure is
+    /// [`Drop`]ed. This will write all modified values to storage but keep all cached values
+    /// in memory.
+    pub fn flush(&mut self) {
+        self.tree.nodes.flush();
+        self.values.flush();
+    }
+
+    /// Flushes the intermediate values of the map, as with [`flush`](Self::flush), but returns a
+    /// [`StoreError`] if a key or value cannot be serialized. Values which were written before the
+    /// error was encountered remain written.
+    pub fn try_flush(&mut self) -> Result<(), StoreError> {
+        self.tree.nodes.try_flush()?;
+        self.values.try_flush()
+    }
+
+    /// Returns the number of storage bytes added by flushes of this map since it was created
+    /// or loaded from storage.
+    pub fn bytes_written(&self) -> u64 {
+        self.tree.nodes.bytes_written() + self.values.bytes_written()
+    }
+
+    /// Returns the number of storage bytes released by flushes of this map since it was
+    /// created or loaded from storage.
+    pub fn bytes_freed(&self) -> u64 {
+        self.tree.nodes.bytes_freed() + self.values.bytes_freed()
+    }
+}
+
+impl<K, V, H> TreeMap<K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    /// Returns the smallest key stored in the map.
+    pub fn min(&self) -> Option<&K> {
+        self.tree.lower_bound::<K>(Bound::Unbounded)
+    }
+
+    /// Returns the largest key stored in the map.
+    pub fn max(&self) -> Option<&K> {
+        self.tree.upper_bound::<K>(Bound::Unbounded)
+    }
+
+    /// Returns the largest key that is less than or equal to the key given as the parameter.
+    ///
+    /// # Examples
+    /// ```
+    /// use near_sdk::store::TreeMap;
+    ///
+    /// let mut map = TreeMap::new(b"m");
+    /// map.extend([(10u64, ()), (20, ())]);
+    ///
+    /// assert_eq!(map.floor_key(&15), Some(&10));
+    /// assert_eq!(map.floor_key(&20), Some(&20));
+    /// assert_eq!(map.floor_key(&5), None);
+    /// ```
+    pub fn floor_key<Q: ?Sized>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+        Q: Ord,
+    {
+        self.tree.upper_bound(Bound::Included(key))
+    }
+
+    /// Returns the smallest key that is greater than or equal to the key given as the parameter.
+    ///
+    /// # Examples
+    /// ```
+    /// use near_sdk::store::TreeMap;
+    ///
+    /// let mut map = TreeMap::new(b"m");
+    /// map.extend([(10u64, ()), (20, ())]);
+    ///
+    /// assert_eq!(map.ceil_key(&15), Some(&20));
+    /// assert_eq!(map.ceil_key(&10), Some(&10));
+    /// assert_eq!(map.ceil_key(&25), None);
+    /// ```
+    pub fn ceil_key<Q: ?Sized>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+        Q: Ord,
+    {
+        self.tree.lower_bound(Bound::Included(key))
+    }
+
+    /// Returns the largest key that is strictly less than the key given as the parameter.
+    pub fn lower<Q: ?Sized>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+        Q: Ord,
+    {
+        self.tree.upper_bound(Bound::Excluded(key))
+    }
+
+    /// Returns the smallest key that is strictly greater than the key given as the parameter.
+    pub fn higher<Q: ?Sized>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+        Q: Ord,
+    {
+        self.tree.lower_bound(Bound::Excluded(key))
+    }
+
+    /// An iterator visiting all keys in ascending order.
+    /// The iterator element type is `&'a K`.
+    pub fn keys(&self) -> Keys<'_, K> {
+        Keys::new::<K>(&self.tree, Bound::Unbounded, Bound::Unbounded)
+    }
+}
+
+impl<K, V, H> TreeMap<K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+    /// Returns a reference to the value corresponding to the key.
+    ///
+    /// The key may be any borrowed form of the map's key type, but
+    /// [`BorshSerialize`] and [`ToOwned<Owned = K>`](ToOwned) on the borrowed form *must* match
+    /// those for the key type.
+    pub fn get<Q: ?Sized>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+    {
+        self.values.get(k)
+    }
+
+    /// Returns a reference to the value corresponding to the key, as with [`get`](Self::get), but
+    /// returns a [`StoreError`] if the value in storage cannot be deserialized.
+    ///
+    /// The key may be any borrowed form of the map's key type, but
+    /// [`BorshSerialize`] and [`ToOwned<Owned = K>`](ToOwned) on the borrowed form *must* match
+    /// those for the key type.
+    pub fn try_get<Q: ?Sized>(&self, k: &Q) -> Result<Option<&V>, StoreError>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+    {
+        self.values.try_get(k)
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    ///
+    /// The key may be any borrowed form of the map's key type, but
+    /// [`BorshSerialize`] and [`ToOwned<Owned = K>`](ToOwned) on the borrowed form *must* match
+    /// those for the key type.
+    pub fn get_mut<Q: ?Sized>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+    {
+        self.values.get_mut(k)
+    }
+
+    /// Returns `true` if the map contains a value for the specified key.
+    ///
+    /// The key may be any borrowed form of the map's key type, but
+    /// [`BorshSerialize`] and [`ToOwned<Owned = K>`](ToOwned) on the borrowed form *must* match
+    /// those for the key type.
+    pub fn contains_key<Q: ?Sized>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K> + Ord,
+    {
+        self.values.contains_key(k)
+    }
+
+    /// Inserts a key-value pair into the map.
+    ///
+    /// If the map did not have this key present, [`None`] is returned.
+    ///
+    /// If the map did have this key present, the value is updated, and the old
+    /// value is returned. The key is not updated, though; this matters for
+    /// types that can be `==` without being identical.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let old = self.values.insert(key.clone(), value);
+        if old.is_none() {
+            self.tree.insert(key);
+        }
+        old
+    }
+
+    /// Inserts a key-value pair into the map, as with [`insert`](Self::insert), but returns a
+    /// [`StoreError`] if the previous value in storage cannot be deserialized. The map is not
+    /// modified if an error is returned.
+    pub fn try_insert(&mut self, key: K, value: V) -> Result<Option<V>, StoreError> {
+        let old = self.values.try_insert(key.clone(), value)?;
+        if old.is_none() {
+            self.tree.insert(key);
+        }
+        Ok(old)
+    }
+
+    /// Removes a key from the map, returning the value at the key if the key
+    /// was previously in the map.
+    ///
+    /// The key may be any borrowed form of the map's key type, but
+    /// [`BorshSerialize`] and [`ToOwned<Owned = K>`](ToOwned) on the borrowed form *must* match
+    /// those for the key type.
+    pub fn remove<Q: ?Sized>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K> + Ord,
+    {
+        self.remove_entry(k).map(|(_, v)| v)
+    }
+
+    /// Removes a key from the map, as with [`remove`](Self::remove), but returns a
+    /// [`StoreError`] if the value in storage cannot be deserialized. The map is not modified if
+    /// an error is returned.
+    ///
+    /// The key may be any borrowed form of the map's key type, but
+    /// [`BorshSerialize`] and [`ToOwned<Owned = K>`](ToOwned) on the borrowed form *must* match
+    /// those for the key type.
+    pub fn try_remove<Q: ?Sized>(&mut self, k: &Q) -> Result<Option<V>, StoreError>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K> + Ord,
+    {
+        // Loads the value into the cache, so that removing it does not fail.
+        self.values.try_get(k)?;
+        Ok(self.remove(k))
+    }
+
+    /// Removes a key from the map, returning the stored key and value if the
+    /// key was previously in the map.
+    ///
+    /// The key may be any borrowed form of the map's key type, but
+    /// [`BorshSerialize`] and [`ToOwned<Owned = K>`](ToOwned) on the borrowed form *must* match
+    /// those for the key type.
+    pub fn remove_entry<Q: ?Sized>(&mut self, k: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K> + Ord,
+    {
+        let value = self.values.remove(k)?;
+        let key = self.tree.remove(k).unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE));
+        Some((key, value))
+    }
+
+    /// Retains only the elements specified by the predicate. In other words, removes all pairs
+    /// `(k, v)` for which `f(&k, &mut v)` returns `false`. The pairs are visited in ascending
+    /// order of the keys.
+    ///
+    /// # Examples
+    /// ```
+    /// use near_sdk::store::TreeMap;
+    ///
+    /// let mut map = TreeMap::new(b"m");
+    /// map.extend((0u32..8).map(|x| (x, x * 10)));
+    /// map.retain(|_, v| *v >= 50);
+    /// assert_eq!(map.keys().copied().collect::<Vec<_>>(), [5, 6, 7]);
+    /// ```
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        let values = &mut self.values;
+        let removed: Vec<K> = Keys::new::<K>(&self.tree, Bound::Unbounded, Bound::Unbounded)
+            .filter(|&key| {
+                let value =
+                    values.get_mut(key).unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE));
+                !f(key, value)
+            })
+            .cloned()
+            .collect();
+        for key in removed {
+            self.tree.remove(&key).unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE));
+            // Set instead of remove to avoid reloading the value from storage.
+            self.values.set(key, None);
+        }
+    }
+
+    /// An iterator visiting all key-value pairs in ascending order of the keys.
+    /// The iterator element type is `(&'a K, &'a V)`.
+    pub fn iter(&self) -> Iter<'_, K, V, H> {
+        Iter::new(self)
+    }
+
+    /// An iterator visiting all values in ascending order of their keys.
+    /// The iterator element type is `&'a V`.
+    pub fn values(&self) -> Values<'_, K, V, H> {
+        Values::new(self)
+    }
+
+    /// Constructs a double-ended iterator over the key-value pairs whose keys are within the
+    /// given range. The iterator element type is `(&'a K, &'a V)`.
+    ///
+    /// Only the tree nodes on the path to the bounds of the range and the entries within the
+    /// range are loaded from storage. If the start of the range is greater than its end, the
+    /// iterator is empty.
+    ///
+    /// # Examples
+    /// ```
+    /// use near_sdk::store::TreeMap;
+    ///
+    /// let mut map = TreeMap::new(b"m");
+    /// for i in 0..10u32 {
+    ///     map.insert(i * 10, i);
+    /// }
+    ///
+    /// let values: Vec<_> = map.range(25..=60).map(|(_, v)| *v).collect();
+    /// assert_eq!(values, [3, 4, 5, 6]);
+    ///
+    /// let mut range = map.range(..30);
+    /// assert_eq!(range.next(), Some((&0, &0)));
+    /// assert_eq!(range.next_back(), Some((&20, &2)));
+    /// assert_eq!(range.next(), Some((&10, &1)));
+    /// assert_eq!(range.next(), None);
+    /// ```
+    pub fn range<Q: ?Sized, R>(&self, range: R) -> Range<'_, K, V, H>
+    where
+        K: Borrow<Q>,
+        Q: Ord,
+        R: RangeBounds<Q>,
+    {
+        Range::new(self, range.start_bound(), range.end_bound())
+    }
+
+    /// Returns a [`Cursor`] at the first entry of the map.
+    pub fn cursor(&self) -> Cursor<K> {
+        Cursor::at(self.min().cloned())
+    }
+
+    /// Constructs an iterator over the entries starting at the cursor, in ascending order of the
+    /// keys. Use [`Range::cursor`] after consuming a page of entries to get the cursor for the
+    /// next page.
+    pub fn iter_from(&self, cursor: &Cursor<K>) -> Range<'_, K, V, H> {
+        Range::from_cursor(self, cursor)
+    }
+}
+
+impl<K, V, H> Extend<(K, V)> for TreeMap<K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K, V, H> serde::Serialize for TreeMap<K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone + serde::Serialize,
+    V: BorshSerialize + BorshDeserialize + serde::Serialize,
+    H: ToKey,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_map(self.iter())
+    }
+}
+
+/// Compares the entries with a [`BTreeMap`](std::collections::BTreeMap), loading all entries from
+/// storage. Only available outside of Wasm, to assert on the contents of the map in unit tests.
+#[cfg(not(target_arch = "wasm32"))]
+impl<K, V, H> PartialEq<std::collections::BTreeMap<K, V>> for TreeMap<K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    V: PartialEq + BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+    fn eq(&self, other: &std::collections::BTreeMap<K, V>) -> bool {
+        self.len() as usize == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<K> Tree<K>
+where
+    K: BorshSerialize + BorshDeserialize + Ord,
+{
+    fn node(&self, id: FreeListIndex) -> &Node<K> {
+        self.nodes.get(id).unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE))
+    }
+
+    fn node_mut(&mut self, id: FreeListIndex) -> &mut Node<K> {
+        self.nodes.get_mut(id).unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE))
+    }
+
+    fn height(&self, id: Option<FreeListIndex>) -> u32 {
+        id.map_or(0, |id| self.node(id).ht)
+    }
+
+    fn set_lft(&mut self, id: FreeListIndex, lft: Option<FreeListIndex>) {
+        // Only modify the node when the link changes, to avoid writing it back to storage.
+        if self.node(id).lft != lft {
+            self.node_mut(id).lft = lft;
+        }
+    }
+
+    fn set_rgt(&mut self, id: FreeListIndex, rgt: Option<FreeListIndex>) {
+        if self.node(id).rgt != rgt {
+            self.node_mut(id).rgt = rgt;
+        }
+    }
+
+    fn update_height(&mut self, id: FreeListIndex) {
+        let node = self.node(id);
+        let ht = 1 + std::cmp::max(self.height(node.lft), self.height(node.rgt));
+        if node.ht != ht {
+            self.node_mut(id).ht = ht;
+        }
+    }
+
+    fn balance(&self, id: FreeListIndex) -> i64 {
+        let node = self.node(id);
+        self.height(node.lft) as i64 - self.height(node.rgt) as i64
+    }
+
+    fn rotate_right(&mut self, id: FreeListIndex) -> FreeListIndex {
+        let lft = self.node(id).lft.unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE));
+        let lft_rgt = self.node(lft).rgt;
+        self.set_lft(id, lft_rgt);
+        self.set_rgt(lft, Some(id));
+        self.update_height(id);
+        self.update_height(lft);
+        lft
+    }
+
+    fn rotate_left(&mut self, id: FreeListIndex) -> FreeListIndex {
+        let rgt = self.node(id).rgt.unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE));
+        let rgt_lft = self.node(rgt).lft;
+        self.set_rgt(id, rgt_lft);
+        self.set_lft(rgt, Some(id));
+        self.update_height(id);
+        self.update_height(rgt);
+        rgt
+    }
+
+    /// Restores the AVL invariant at the node after one of its subtrees changed height by at
+    /// most one, returning the new root of the subtree.
+    fn rebalance(&mut self, id: FreeListIndex) -> FreeListIndex {
+        self.update_height(id);
+        let balance = self.balance(id);
+        if balance > 1 {
+            let lft = self.node(id).lft.unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE));
+            if self.balance(lft) < 0 {
+                let lft = self.rotate_left(lft);
+                self.set_lft(id, Some(lft));
+            }
+            self.rotate_right(id)
+        } else if balance < -1 {
+            let rgt = self.node(id).rgt.unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE));
+            if self.balance(rgt) > 0 {
+                let rgt = self.rotate_right(rgt);
+                self.set_rgt(id, Some(rgt));
+            }
+            self.rotate_left(id)
+        } else {
+            id
+        }
+    }
+
+    fn insert(&mut self, key: K) {
+        self.root = Some(self.insert_at(self.root, key));
+    }
+
+    fn insert_at(&mut self, at: Option<FreeListIndex>, key: K) -> FreeListIndex {
+        let id = match at {
+            Some(id) => id,
+            None => return self.nodes.insert(Node::of(key)),
+        };
+        let node = self.node(id);
+        match key.cmp(&node.key) {
+            Ordering::Less => {
+                let lft = self.insert_at(node.lft, key);
+                self.set_lft(id, Some(lft));
+            }
+            Ordering::Greater => {
+                let rgt = self.insert_at(node.rgt, key);
+                self.set_rgt(id, Some(rgt));
+            }
+            Ordering::Equal => return id,
+        }
+        self.rebalance(id)
+    }
+
+    fn remove<Q: ?Sized>(&mut self, key: &Q) -> Option<K>
+    where
+        K: Borrow<Q>,
+        Q: Ord,
+    {
+        let (root, removed) = self.remove_at(self.root, key);
+        self.root = root;
+        removed
+    }
+
+    /// Removes the key from the subtree, returning the new root of the subtree and the removed
+    /// key, if it was found.
+    fn remove_at<Q: ?Sized>(
+        &mut self,
+        at: Option<FreeListIndex>,
+        key: &Q,
+    ) -> (Option<FreeListIndex>, Option<K>)
+    where
+        K: Borrow<Q>,
+        Q: Ord,
+    {
+        let id = match at {
+            Some(id) => id,
+            None => return (None, None),
+        };
+        let node = self.node(id);
+        let removed = match key.cmp(node.key.borrow()) {
+            Ordering::Less => {
+                let (lft, removed) = self.remove_at(node.lft, key);
+                self.set_lft(id, lft);
+                removed
+            }
+            Ordering::Greater => {
+                let (rgt, removed) = self.remove_at(node.rgt, key);
+                self.set_rgt(id, rgt);
+                removed
+            }
+            Ordering::Equal => {
+                let node =
+                    self.nodes.remove(id).unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE));
+                let root = match (node.lft, node.rgt) {
+                    (None, rgt) => rgt,
+                    (lft, None) => lft,
+                    (Some(lft), Some(rgt)) => {
+                        // Replace the removed node with the smallest node of its right subtree.
+                        let (rgt, min) = self.remove_min(rgt);
+                        self.set_lft(min, Some(lft));
+                        self.set_rgt(min, rgt);
+                        Some(self.rebalance(min))
+                    }
+                };
+                return (root, Some(node.key));
+            }
+        };
+        (Some(self.rebalance(id)), removed)
+    }
+
+    /// Detaches the smallest node of the subtree, returning the new root of the subtree and the
+    /// detached node.
+    fn remove_min(&mut self, id: FreeListIndex) -> (Option<FreeListIndex>, FreeListIndex) {
+        let node = self.node(id);
+        match node.lft {
+            None => (node.rgt, id),
+            Some(lft) => {
+                let (lft, min) = self.remove_min(lft);
+                self.set_lft(id, lft);
+                (Some(self.rebalance(id)), min)
+            }
+        }
+    }
+
+    /// Returns the smallest key which satisfies the bound as the start of a range.
+    fn lower_bound<Q: ?Sized>(&self, bound: Bound<&Q>) -> Option<&K>
+    where
+        K: Borrow<Q>,
+        Q: Ord,
+    {
+        let mut at = self.root;
+        let mut found = None;
+        while let Some(id) = at {
+            let node = self.node(id);
+            let in_bound = match bound {
+                Bound::Included(b) => node.key.borrow() >= b,
+                Bound::Excluded(b) => node.key.borrow() > b,
+                Bound::Unbounded => true,
+            };
+            if in_bound {
+                found = Some(&node.key);
+                at = node.lft;
+            } else {
+                at = node.rgt;
+            }
+        }
+        found
+    }
+
+    /// Returns the largest key which satisfies the bound as the end of a range.
+    fn upper_bound<Q: ?Sized>(&self, bound: Bound<&Q>) -> Option<&K>
+    where
+        K: Borrow<Q>,
+        Q: Ord,
+    {
+        let mut at = self.root;
+        let mut found = None;
+        while let Some(id) = at {
+            let node = self.node(id);
+            let in_bound = match bound {
+                Bound::Included(b) => node.key.borrow() <= b,
+                Bound::Excluded(b) => node.key.borrow() < b,
+                Bound::Unbounded => true,
+            };
+            if in_bound {
+                found = Some(&node.key);
+                at = node.rgt;
+            } else {
+                at = node.lft;
+            }
+        }
+        found
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::{FreeListIndex, TreeMap};
+    use crate::crypto_hash::Keccak256;
+    use crate::store::key::ToKey;
+    use crate::test_utils::test_env::setup_free;
+    use arbitrary::{Arbitrary, Unstructured};
+    use borsh::{BorshDeserialize, BorshSerialize};
+    use rand::RngCore;
+    use rand::SeedableRng;
+    use std::collections::BTreeMap;
+    use std::ops::Bound;
+
+    /// Checks the AVL invariants of the subtree, returning its height.
+    fn check_subtree<V: BorshSerialize, H: ToKey>(
+        map: &TreeMap<u32, V, H>,
+        at: Option<FreeListIndex>,
+        lower: Option<u32>,
+        upper: Option<u32>,
+    ) -> u32 {
+        let id = match at {
+            Some(id) => id,
+            None => return 0,
+        };
+        let node = map.tree.node(id);
+        assert!(lower.map_or(true, |l| node.key > l));
+        assert!(upper.map_or(true, |u| node.key < u));
+        let lft = check_subtree(map, node.lft, lower, Some(node.key));
+        let rgt = check_subtree(map, node.rgt, Some(node.key), upper);
+        assert!((lft as i64 - rgt as i64).abs() <= 1);
+        assert_eq!(node.ht, 1 + lft.max(rgt));
+        node.ht
+    }
+
+    fn check_balanced<V: BorshSerialize, H: ToKey>(map: &TreeMap<u32, V, H>) {
+        let ht = check_subtree(map, map.tree.root, None, None);
+        // An AVL tree has a height of at most 1.44 * log2(N + 2).
+        assert!(ht as f64 <= 1.45 * ((map.len() + 2) as f64).log2());
+    }
+
+    #[test]
+    fn basic_functionality() {
+        let mut map = TreeMap::new(b"t");
+        assert!(map.is_empty());
+        assert_eq!(map.min(), None);
+        assert!(map.insert(5u32, "five".to_string()).is_none());
+        assert_eq!(map.insert(5, "FIVE".to_string()), Some("five".to_string()));
+        assert_eq!(map.get(&5).map(String::as_str), Some("FIVE"));
+        assert_eq!(map.len(), 1);
+
+        *map.get_mut(&5).unwrap() = "5".to_string();
+        assert!(map.contains_key(&5));
+        assert_eq!(map.remove(&5), Some("5".to_string()));
+        assert_eq!(map.remove(&5), None);
+        assert!(map.is_empty());
+        assert_eq!(map.iter().count(), 0);
+    }
+
+    #[test]
+    fn resume_from_cursor() {
+        let mut map = TreeMap::new(b"m");
+        map.extend((0..10u32).map(|i| (i, i)));
+
+        let mut iter = map.iter_from(&map.cursor());
+        assert_eq!(iter.by_ref().take(3).count(), 3);
+        let cursor = iter.cursor();
+        assert_eq!(cursor.next_key(), Some(&3));
+
+        // The cursor survives a round trip through storage and the next key being removed.
+        let cursor = super::Cursor::try_from_slice(&cursor.try_to_vec().unwrap()).unwrap();
+        map.remove(&3);
+        let keys: Vec<u32> = map.iter_from(&cursor).map(|(k, _)| *k).collect();
+        assert_eq!(keys, [4, 5, 6, 7, 8, 9]);
+
+        let mut iter = map.iter_from(&cursor);
+        iter.by_ref().for_each(drop);
+        assert!(iter.cursor().is_end());
+        assert_eq!(map.iter_from(&iter.cursor()).next(), None);
+    }
+
+    #[test]
+    fn ordered_queries() {
+        let mut map = TreeMap::<u32, u32, Keccak256>::with_hasher(b"t");
+        // Insert in ascending order, which would degrade an unbalanced tree into a list.
+        for i in 0..100 {
+            map.insert(i * 2, i);
+        }
+        check_balanced(&map);
+
+        assert_eq!(map.min(), Some(&0));
+        assert_eq!(map.max(), Some(&198));
+        assert_eq!(map.floor_key(&51), Some(&50));
+        assert_eq!(map.floor_key(&50), Some(&50));
+        assert_eq!(map.ceil_key(&51), Some(&52));
+        assert_eq!(map.ceil_key(&199), None);
+        assert_eq!(map.lower(&50), Some(&48));
+        assert_eq!(map.lower(&0), None);
+        assert_eq!(map.higher(&50), Some(&52));
+        assert_eq!(map.higher(&198), None);
+
+        let keys: Vec<_> = map.range(10..=20).map(|(k, _)| *k).collect();
+        assert_eq!(keys, [10, 12, 14, 16, 18, 20]);
+        let keys: Vec<_> = map.range((Bound::Excluded(10), Bound::Excluded(20))).rev().collect();
+        assert_eq!(keys, [(&18, &9), (&16, &8), (&14, &7), (&12, &6)]);
+        assert_eq!(map.range(11..12).count(), 0);
+        assert_eq!(map.range(20..10).count(), 0);
+        assert_eq!(map.range(190..).count(), 5);
+
+        assert!(map.keys().copied().eq((0..100).map(|i| i * 2)));
+        assert!(map.values().rev().copied().eq((0..100).rev()));
+    }
+
+    #[test]
+    fn clear_and_restore() {
+        let mut map = TreeMap::new(b"t");
+        map.extend((0..10u32).map(|i| (i, i)));
+        map.clear();
+        assert!(map.is_empty());
+        assert_eq!(map.get(&3), None);
+        assert_eq!(map.max(), None);
+
+        map.extend([(3, 30), (1, 10), (2, 20)]);
+        map.flush();
+        let serialized = map.try_to_vec().unwrap();
+        drop(map);
+        let map = TreeMap::<u32, u32>::try_from_slice(&serialized).unwrap();
+        assert_eq!(map.iter().collect::<Vec<_>>(), [(&1, &10), (&2, &20), (&3, &30)]);
+        check_balanced(&map);
+    }
+
+    #[test]
+    fn random_insert_remove() {
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(1);
+        let mut tm = TreeMap::new(b"t");
+        let mut bm = BTreeMap::new();
+        for i in 0..2000u32 {
+            let key = rng.next_u32() % 300;
+            if rng.next_u32() % 3 == 0 {
+                assert_eq!(tm.remove(&key), bm.remove(&key));
+            } else {
+                assert_eq!(tm.insert(key, i), bm.insert(key, i));
+            }
+            if i % 200 == 0 {
+                check_balanced(&tm);
+            }
+        }
+        check_balanced(&tm);
+        assert!(tm.iter().eq(bm.iter()));
+        assert!(tm.iter().rev().eq(bm.iter().rev()));
+
+        tm.retain(|k, v| k % 3 != 0 && *v % 2 == 0);
+        bm.retain(|k, v| k % 3 != 0 && *v % 2 == 0);
+        check_balanced(&tm);
+        assert!(tm.iter().eq(bm.iter()));
+    }
+
+    #[test]
+    fn clear_chunk() {
+        let mut map = TreeMap::new(b"t");
+        map.extend((0..50u32).map(|k| (k, k)));
+        map.remove(&7);
+        while map.clear_chunk(8) {
+            check_balanced(&map);
+            assert_eq!(map.iter().count() as u32, map.len());
+        }
+        assert!(map.is_empty());
+        map.flush();
+        assert!(crate::mock::with_mocked_blockchain(|b| b.take_storage()).is_empty());
+    }
+
+    #[derive(Arbitrary, Debug)]
+    enum Op {
+        Insert(u8, u8),
+        Remove(u8),
+        Flush,
+        Restore,
+        Get(u8),
+        Floor(u8),
+        Ceil(u8),
+        Range(u8, u8),
+        Retain(u8),
+    }
+
+    #[test]
+    fn arbitrary() {
+        setup_free();
+
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(0);
+        let mut buf = vec![0; 4096];
+        for _ in 0..256 {
+            // Clear storage in-between runs
+            crate::mock::with_mocked_blockchain(|b| b.take_storage());
+            rng.fill_bytes(&mut buf);
+
+            let mut tm = TreeMap::new(b"t");
+            let mut bm = BTreeMap::new();
+            let u = Unstructured::new(&buf);
+            if let Ok(ops) = Vec::<Op>::arbitrary_take_rest(u) {
+                for op in ops {
+                    match op {
+                        Op::Insert(k, v) => {
+                            let r1 = tm.insert(k as u32, v);
+                            let r2 = bm.insert(k as u32, v);
+                            assert_eq!(r1, r2)
+                        }
+                        Op::Remove(k) => {
+                            let r1 = tm.remove(&(k as u32));
+                            let r2 = bm.remove(&(k as u32));
+                            assert_eq!(r1, r2)
+                        }
+                        Op::Flush => {
+                            tm.flush();
+                        }
+                        Op::Restore => {
+                            let serialized = tm.try_to_vec().unwrap();
+                            tm = TreeMap::try_from_slice(&serialized).unwrap();
+                        }
+                        Op::Get(k) => {
+                            let r1 = tm.get(&(k as u32));
+                            let r2 = bm.get(&(k as u32));
+                            assert_eq!(r1, r2)
+                        }
+                        Op::Floor(k) => {
+                            let r1 = tm.floor_key(&(k as u32));
+                            let r2 = bm.range(..=k as u32).next_back().map(|(k, _)| k);
+                            assert_eq!(r1, r2)
+                        }
+                        Op::Ceil(k) => {
+                            let r1 = tm.ceil_key(&(k as u32));
+                            let r2 = bm.range(k as u32..).next().map(|(k, _)| k);
+                            assert_eq!(r1, r2)
+                        }
+                        Op::Range(a, b) => {
+                            let (a, b) = (a.min(b) as u32, a.max(b) as u32);
+                            assert!(tm.range(a..b).eq(bm.range(a..b)));
+                            assert!(tm.range(a..=b).rev().eq(bm.range(a..=b).rev()));
+                        }
+                        Op::Retain(d) => {
+                            let d = d as u32;
+                            tm.retain(|k, _| k % 4 != d % 4);
+                            bm.retain(|k, _| k % 4 != d % 4);
+                            assert_eq!(tm.len() as usize, bm.len());
+                        }
+                    }
+                }
+            }
+            check_balanced(&tm);
+            assert_eq!(tm, bm);
+        }
+    }
+
+    #[test]
+    fn try_methods() {
+        use crate::store::StoreError;
+
+        let mut map = TreeMap::<u8, u32>::new(b"m");
+        map.insert(2, 2);
+        map.values.insert_raw(&1, &[1, 2]);
+
+        assert!(matches!(map.try_get(&1), Err(StoreError::DeserializationError(_))));
+        assert!(matches!(map.try_insert(1, 1), Err(StoreError::DeserializationError(_))));
+        assert!(matches!(map.try_remove(&1), Err(StoreError::DeserializationError(_))));
+        assert_eq!(map.len(), 1);
+
+        assert_eq!(map.try_get(&2).unwrap(), Some(&2));
+        assert_eq!(map.try_insert(2, 3).unwrap(), Some(2));
+        assert_eq!(map.try_insert(3, 3).unwrap(), None);
+        assert_eq!(map.try_remove(&2).unwrap(), Some(3));
+        map.try_flush().unwrap();
+        assert_eq!(map.iter().collect::<Vec<_>>(), [(&3, &3)]);
+    }
+}
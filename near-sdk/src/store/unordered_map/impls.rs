@@ -39,3 +39,31 @@ where
         self.get(index).unwrap_or_else(|| env::panic_str(ERR_NOT_EXIST))
     }
 }
+
+impl<K, V, H> serde::Serialize for UnorderedMap<K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone + serde::Serialize,
+    V: BorshSerialize + BorshDeserialize + serde::Serialize,
+    H: CryptoHasher<Digest = [u8; 32]>,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_map(self.iter())
+    }
+}
+
+/// Compares the entries with a [`HashMap`](std::collections::HashMap), loading all entries from
+/// storage. Only available outside of Wasm, to assert on the contents of the map in unit tests.
+#[cfg(not(target_arch = "wasm32"))]
+impl<K, V, H> core::cmp::PartialEq<std::collections::HashMap<K, V>> for UnorderedMap<K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone + std::hash::Hash,
+    V: PartialEq + BorshSerialize + BorshDeserialize,
+    H: CryptoHasher<Digest = [u8; 32]>,
+{
+    fn eq(&self, other: &std::collections::HashMap<K, V>) -> bool {
+        self.len() as usize == other.len() && self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
@@ -429,3 +429,97 @@ where
         self.inner.nth_back(n).map(|(_, v)| v)
     }
 }
+
+/// A draining iterator for [`UnorderedMap`].
+///
+/// This `struct` is created by the `drain` method on [`UnorderedMap`].
+pub struct Drain<'a, K, V, H>
+where
+    K: BorshSerialize + Ord + BorshDeserialize + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: CryptoHasher<Digest = [u8; 32]>,
+{
+    keys: free_list::Drain<'a, K>,
+    values: &'a mut LookupMap<K, ValueAndIndex<V>, H>,
+}
+
+impl<'a, K, V, H> Drain<'a, K, V, H>
+where
+    K: BorshSerialize + Ord + BorshDeserialize + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: CryptoHasher<Digest = [u8; 32]>,
+{
+    pub(super) fn new(map: &'a mut UnorderedMap<K, V, H>) -> Self {
+        Self { keys: map.keys.drain(), values: &mut map.values }
+    }
+
+    fn remove_value(&mut self, key: K) -> (K, V) {
+        let value =
+            self.values.remove(&key).unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE));
+
+        (key, value.value)
+    }
+}
+
+impl<'a, K, V, H> Drop for Drain<'a, K, V, H>
+where
+    K: BorshSerialize + Ord + BorshDeserialize + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: CryptoHasher<Digest = [u8; 32]>,
+{
+    fn drop(&mut self) {
+        // Remove the values of all keys not yielded, without loading the values from storage.
+        for key in self.keys.by_ref() {
+            self.values.set(key, None);
+        }
+    }
+}
+
+impl<'a, K, V, H> Iterator for Drain<'a, K, V, H>
+where
+    K: BorshSerialize + Ord + BorshDeserialize + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: CryptoHasher<Digest = [u8; 32]>,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let key = self.keys.next()?;
+        Some(self.remove_value(key))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.keys.size_hint()
+    }
+
+    fn count(self) -> usize {
+        self.keys.len()
+    }
+}
+
+impl<'a, K, V, H> ExactSizeIterator for Drain<'a, K, V, H>
+where
+    K: BorshSerialize + Ord + BorshDeserialize + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: CryptoHasher<Digest = [u8; 32]>,
+{
+}
+impl<'a, K, V, H> FusedIterator for Drain<'a, K, V, H>
+where
+    K: BorshSerialize + Ord + BorshDeserialize + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: CryptoHasher<Digest = [u8; 32]>,
+{
+}
+
+impl<'a, K, V, H> DoubleEndedIterator for Drain<'a, K, V, H>
+where
+    K: BorshSerialize + Ord + BorshDeserialize + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: CryptoHasher<Digest = [u8; 32]>,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let key = self.keys.next_back()?;
+        Some(self.remove_value(key))
+    }
+}
@@ -8,14 +8,17 @@ use std::{fmt, mem};
 use borsh::{BorshDeserialize, BorshSerialize};
 
 use crate::crypto_hash::{CryptoHasher, Sha256};
-use crate::{env, IntoStorageKey};
+use crate::{collections, env, IntoStorageKey};
 
 pub use entry::{Entry, OccupiedEntry, VacantEntry};
 
-pub use self::iter::{Iter, IterMut, Keys, Values, ValuesMut};
+pub use self::iter::{Drain, Iter, IterMut, Keys, Values, ValuesMut};
 use super::free_list::FreeListIndex;
-use super::{FreeList, LookupMap, ERR_INCONSISTENT_STATE};
+use super::sample::SeedRng;
+use super::transaction::{self, Rollback, StorageSnapshot};
+use super::{FreeList, LookupMap, OccupiedError, StoreError, ERR_INCONSISTENT_STATE};
 
+const ERR_ELEMENT_SERIALIZATION: &str = "Cannot serialize element";
 const ERR_NOT_EXIST: &str = "Key does not exist in map";
 
 /// A lazily loaded storage map that stores its content directly on the storage trie.
@@ -138,15 +141,19 @@ where
 
 impl<K, V, H> fmt::Debug for UnorderedMap<K, V, H>
 where
-    K: BorshSerialize + Ord + BorshDeserialize + fmt::Debug,
-    V: BorshSerialize,
+    K: BorshSerialize + Ord + BorshDeserialize + Clone + fmt::Debug,
+    V: BorshSerialize + BorshDeserialize + fmt::Debug,
     H: CryptoHasher<Digest = [u8; 32]>,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("UnorderedMap")
-            .field("keys", &self.keys)
-            .field("values", &self.values)
-            .finish()
+        if cfg!(feature = "expensive-debug") {
+            f.debug_map().entries(self.iter()).finish()
+        } else {
+            f.debug_struct("UnorderedMap")
+                .field("keys", &self.keys)
+                .field("values", &self.values)
+                .finish()
+        }
     }
 }
 
@@ -206,13 +213,118 @@ where
         K: BorshDeserialize + Clone,
         V: BorshDeserialize,
     {
-        // TODO optimize this, can be done with a drain iterator rather than cloning references
-        for k in self.keys.iter() {
+        for k in self.keys.drain() {
             // Set instead of remove to avoid loading the value from storage.
-            // This enforces a clone, but this is better th
-            self.values.set(k.to_owned(), None);
+            self.values.set(k, None);
         }
-        self.keys.clear();
+    }
+
+    /// Removes up to `limit` key-value pairs from the map without loading the values. Returns
+    /// `true` if there is anything left to remove.
+    ///
+    /// This can be used to clear a map which is too large to [`clear`](Self::clear) within the gas
+    /// limit of a single function call, by calling it until it returns `false`.
+    ///
+    /// # Examples
+    /// ```
+    /// use near_sdk::store::UnorderedMap;
+    ///
+    /// let mut map = UnorderedMap::new(b"m");
+    /// map.extend((0u32..10).map(|k| (k, k)));
+    /// while map.clear_chunk(4) {}
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn clear_chunk(&mut self, limit: u32) -> bool
+    where
+        K: BorshDeserialize + Clone,
+        V: BorshDeserialize,
+    {
+        let values = &mut self.values;
+        self.keys.clear_chunk_with(limit, |key| {
+            // Set instead of remove to avoid loading the value from storage.
+            values.set(key, None);
+        })
+    }
+
+    /// Clears the map, returning all key-value pairs as an iterator.
+    ///
+    /// This will clear all values, even if only some key/value pairs are yielded. The values which
+    /// are not yielded are not loaded, but each of them is still removed from storage, so draining
+    /// a large map may exceed the gas limit of a single function call. To move the pairs out of
+    /// the map in bounded chunks, collect a chunk of [`keys`](Self::keys) and remove them with
+    /// [`remove_entry`](Self::remove_entry) instead:
+    ///
+    /// ```
+    /// use near_sdk::store::UnorderedMap;
+    ///
+    /// let mut balances = UnorderedMap::new(b"b");
+    /// balances.extend((0u32..10).map(|id| (id, 100u128)));
+    ///
+    /// // Refund at most 4 accounts per call.
+    /// let chunk: Vec<u32> = balances.keys().take(4).copied().collect();
+    /// for id in chunk {
+    ///     let (_id, _amount) = balances.remove_entry(&id).unwrap();
+    /// }
+    /// assert_eq!(balances.len(), 6);
+    /// ```
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::UnorderedMap;
+    ///
+    /// let mut a = UnorderedMap::new(b"m");
+    /// a.insert(1, "a".to_string());
+    /// a.insert(2, "b".to_string());
+    ///
+    /// for (k, v) in a.drain().take(1) {
+    ///     assert!(k == 1 || k == 2);
+    ///     assert!(&v == "a" || &v == "b");
+    /// }
+    ///
+    /// assert!(a.is_empty());
+    /// ```
+    pub fn drain(&mut self) -> Drain<'_, K, V, H>
+    where
+        K: BorshDeserialize + Clone,
+        V: BorshDeserialize,
+    {
+        Drain::new(self)
+    }
+
+    /// Retains only the elements specified by the predicate. In other words, removes all pairs
+    /// `(k, v)` for which `f(&k, &mut v)` returns `false`.
+    ///
+    /// Every value is loaded and passed mutably to the predicate, so the retained values are
+    /// written back to storage when the map is flushed.
+    ///
+    /// # Examples
+    /// ```
+    /// use near_sdk::store::UnorderedMap;
+    ///
+    /// let mut map = UnorderedMap::new(b"m");
+    /// map.extend((0u32..8).map(|x| (x, x * 10)));
+    /// map.retain(|&k, _| k % 2 == 0);
+    /// assert_eq!(map.len(), 4);
+    /// assert_eq!(map.get(&3), None);
+    /// ```
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        K: BorshDeserialize + Clone,
+        V: BorshDeserialize,
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        let values = &mut self.values;
+        self.keys.retain(|key| {
+            let entry =
+                values.get_mut(key).unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE));
+            let keep = f(key, &mut entry.value);
+            if !keep {
+                // Set instead of remove to avoid reloading the value from storage.
+                values.set(key.clone(), None);
+            }
+            keep
+        });
     }
 
     /// An iterator visiting all key-value pairs in arbitrary order.
@@ -243,6 +355,37 @@ where
         Keys::new(self)
     }
 
+    /// Returns `n` distinct keys of the map chosen uniformly at random using
+    /// [`env::random_seed`], or all keys in random order if the map has fewer than `n` entries.
+    ///
+    /// When `n` is small relative to the length of the map, only the chosen keys are loaded from
+    /// storage. Each call within a function call draws from a new generator of [`env::rng`], so
+    /// consecutive calls return different keys, and the function call returns the same keys
+    /// whenever it is executed with the same seed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::UnorderedMap;
+    /// # use near_sdk::{test_utils::VMContextBuilder, testing_env};
+    /// # testing_env!(VMContextBuilder::new().random_seed_array([1; 32]).build());
+    ///
+    /// let mut stakes: UnorderedMap<String, u64> = UnorderedMap::new(b"m");
+    /// stakes.insert("alice".to_string(), 10);
+    /// stakes.insert("bob".to_string(), 20);
+    /// stakes.insert("carol".to_string(), 30);
+    ///
+    /// let validators = stakes.sample_keys(2);
+    /// assert_eq!(validators.len(), 2);
+    /// assert_ne!(validators[0], validators[1]);
+    /// ```
+    pub fn sample_keys(&self, n: u32) -> Vec<&K>
+    where
+        K: BorshDeserialize,
+    {
+        self.keys.sample(n, &mut SeedRng::new())
+    }
+
     /// An iterator visiting all values in arbitrary order.
     /// The iterator element type is `&'a V`.
     pub fn values(&self) -> Values<K, V, H>
@@ -281,6 +424,20 @@ where
         self.values.get(k).map(|v| &v.value)
     }
 
+    /// Returns a reference to the value corresponding to the key, as with [`get`](Self::get), but
+    /// returns a [`StoreError`] if the value in storage cannot be deserialized.
+    ///
+    /// The key may be any borrowed form of the map's key type, but
+    /// [`BorshSerialize`] and [`ToOwned<Owned = K>`](ToOwned) on the borrowed form *must* match
+    /// those for the key type.
+    pub fn try_get<Q: ?Sized>(&self, k: &Q) -> Result<Option<&V>, StoreError>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+    {
+        Ok(self.values.try_get(k)?.map(|v| &v.value))
+    }
+
     /// Returns a mutable reference to the value corresponding to the key.
     ///
     /// The key may be any borrowed form of the map's key type, but
@@ -317,6 +474,48 @@ where
         None
     }
 
+    /// Inserts a key-value pair into the map, as with [`insert`](Self::insert), but returns a
+    /// [`StoreError`] if the previous value in storage cannot be deserialized. The map is not
+    /// modified if an error is returned.
+    pub fn try_insert(&mut self, k: K, value: V) -> Result<Option<V>, StoreError>
+    where
+        K: Clone + BorshDeserialize,
+    {
+        // Loads the previous value into the cache, so that inserting does not fail.
+        self.values.try_get(&k)?;
+        Ok(self.insert(k, value))
+    }
+
+    /// Inserts a key-value pair into the map only if the map does not have this key present.
+    ///
+    /// If the key is present, the map is not modified and an [`OccupiedError`] containing the
+    /// value is returned. This only reads the key from storage once, unlike checking
+    /// [`contains_key`](Self::contains_key) before inserting.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::UnorderedMap;
+    ///
+    /// let mut registry: UnorderedMap<String, u64> = UnorderedMap::new(b"m");
+    /// assert!(registry.insert_new("alice".to_string(), 1).is_ok());
+    /// assert_eq!(registry.insert_new("alice".to_string(), 2).unwrap_err().value, 2);
+    /// assert_eq!(registry["alice"], 1);
+    /// assert_eq!(registry.len(), 1);
+    /// ```
+    pub fn insert_new(&mut self, k: K, value: V) -> Result<(), OccupiedError<V>>
+    where
+        K: Clone + BorshDeserialize,
+    {
+        let entry = self.values.get_mut_inner(&k);
+        if entry.value().is_some() {
+            return Err(OccupiedError { value });
+        }
+        let key_index = self.keys.insert(k);
+        entry.replace(Some(ValueAndIndex { value, key_index }));
+        Ok(())
+    }
+
     /// Returns `true` if the map contains a value for the specified key.
     ///
     /// The key may be any borrowed form of the map's key type, but
@@ -344,6 +543,23 @@ where
         self.remove_entry(k).map(|(_, v)| v)
     }
 
+    /// Removes a key from the map, as with [`remove`](Self::remove), but returns a
+    /// [`StoreError`] if the value in storage cannot be deserialized. The map is not modified if
+    /// an error is returned.
+    ///
+    /// The key may be any borrowed form of the map's key type, but
+    /// [`BorshSerialize`] and [`ToOwned<Owned = K>`](ToOwned) on the borrowed form *must* match
+    /// those for the key type.
+    pub fn try_remove<Q: ?Sized>(&mut self, k: &Q) -> Result<Option<V>, StoreError>
+    where
+        K: Borrow<Q> + BorshDeserialize,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+    {
+        // Loads the value into the cache, so that removing it does not fail.
+        self.values.try_get(k)?;
+        Ok(self.remove(k))
+    }
+
     /// Removes a key from the map, returning the stored key and value if the
     /// key was previously in the map.
     ///
@@ -379,6 +595,53 @@ where
         Some((key, old_value.value))
     }
 
+    /// Moves up to `limit` entries from a [`collections::UnorderedMap`] into this map, removing
+    /// them from the legacy map. Returns `true` if there are entries left to migrate.
+    ///
+    /// This can be used to migrate a map which is too large to move within the gas limit of a
+    /// single function call, by calling it until it returns `false`. Entries are taken from the
+    /// end of the legacy map, so the legacy map stays consistent between calls and must be
+    /// stored along with this map. Migrated entries replace existing entries with the same key.
+    ///
+    /// The storage layouts of both maps do not overlap, so this map can be created with the same
+    /// prefix as the legacy map.
+    ///
+    /// # Examples
+    /// ```
+    /// use near_sdk::collections;
+    /// use near_sdk::store::UnorderedMap;
+    ///
+    /// let mut legacy = collections::UnorderedMap::new(b"m");
+    /// for i in 0u32..10 {
+    ///     legacy.insert(&i, &format!("value {}", i));
+    /// }
+    ///
+    /// let mut map = UnorderedMap::new(b"m");
+    /// while map.migrate_from(&mut legacy, 4) {}
+    /// assert!(legacy.is_empty());
+    /// assert_eq!(map.len(), 10);
+    /// assert_eq!(map[&3], "value 3");
+    /// ```
+    ///
+    /// [`collections::UnorderedMap`]: crate::collections::UnorderedMap
+    pub fn migrate_from(&mut self, legacy: &mut collections::UnorderedMap<K, V>, limit: u32) -> bool
+    where
+        K: BorshDeserialize + Clone,
+    {
+        for _ in 0..limit {
+            let keys = legacy.keys_as_vector();
+            let key = match keys.len().checked_sub(1) {
+                Some(last) => keys.get(last),
+                None => break,
+            };
+            let key = key.unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE));
+            let value =
+                legacy.remove(&key).unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE));
+            self.insert(key, value);
+        }
+        !legacy.is_empty()
+    }
+
     /// Gets the given key's corresponding entry in the map for in-place manipulation.
     /// ```
     /// use near_sdk::store::UnorderedMap;
@@ -416,6 +679,101 @@ where
         self.keys.flush();
         self.values.flush();
     }
+
+    /// Flushes the intermediate values of the map, as with [`flush`](Self::flush), but returns a
+    /// [`StoreError`] if a key or value cannot be serialized. Values which were written before the
+    /// error was encountered remain written.
+    pub fn try_flush(&mut self) -> Result<(), StoreError> {
+        self.keys.try_flush()?;
+        self.values.try_flush()
+    }
+
+    /// Calls `f` with the map, and discards the changes it made to the map if it returns an error,
+    /// as with [`LookupMap::transaction`].
+    ///
+    /// The collection is flushed before `f` is called, and the values which `f` writes to
+    /// storage, such as by flushing the collection, are written back when the changes are
+    /// discarded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::UnorderedMap;
+    ///
+    /// let mut orders = UnorderedMap::new(b"o");
+    /// orders.insert(1u32, 100u64);
+    /// let result = orders.transaction(|orders| {
+    ///     orders.remove(&1);
+    ///     orders.insert(2, 50);
+    ///     if orders.len() < 2 { Err("not enough orders") } else { Ok(()) }
+    /// });
+    /// assert!(result.is_err());
+    /// assert_eq!(orders.iter().collect::<Vec<_>>(), [(&1, &100)]);
+    /// ```
+    pub fn transaction<F, R, E>(&mut self, f: F) -> Result<R, E>
+    where
+        F: FnOnce(&mut Self) -> Result<R, E>,
+    {
+        transaction::transaction(self, f)
+    }
+
+    /// Returns the number of storage bytes added by flushes of this map since it was created
+    /// or loaded from storage.
+    pub fn bytes_written(&self) -> u64 {
+        self.keys.bytes_written() + self.values.bytes_written()
+    }
+
+    /// Returns the number of storage bytes released by flushes of this map since it was
+    /// created or loaded from storage.
+    pub fn bytes_freed(&self) -> u64 {
+        self.keys.bytes_freed() + self.values.bytes_freed()
+    }
+
+    /// Returns the number of storage bytes that inserting `value` under a new `key` would use,
+    /// including the per-record overhead charged by the protocol. The map stores two records per
+    /// entry: the value with the index of its key, and the key itself.
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::store::UnorderedMap;
+    ///
+    /// let mut map: UnorderedMap<String, u64> = UnorderedMap::new(b"m");
+    /// let key = "alice".to_string();
+    /// let estimate = map.estimated_storage_bytes(&key, &5);
+    ///
+    /// map.insert(key, 5);
+    /// map.flush();
+    /// assert_eq!(map.bytes_written(), estimate);
+    /// ```
+    pub fn estimated_storage_bytes(&self, key: &K, value: &V) -> u64 {
+        let value_len =
+            value.try_to_vec().unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_SERIALIZATION)).len();
+        // The value is stored alongside the `FreeListIndex` of its key.
+        self.values.estimated_entry_bytes(key, value_len + 4) + self.keys.estimated_slot_bytes(key)
+    }
+}
+
+impl<K, V, H> Rollback for UnorderedMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: CryptoHasher<Digest = [u8; 32]>,
+{
+    type Snapshot = (<FreeList<K> as Rollback>::Snapshot, StorageSnapshot);
+
+    fn snapshot(&mut self) -> Self::Snapshot {
+        (self.keys.snapshot(), self.values.snapshot())
+    }
+
+    fn rollback(&mut self, (keys, values): Self::Snapshot) {
+        self.values.rollback(values);
+        self.keys.rollback(keys);
+    }
+
+    fn commit(&mut self, (keys, values): Self::Snapshot) {
+        self.values.commit(values);
+        self.keys.commit(keys);
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -429,6 +787,38 @@ mod tests {
     use rand::SeedableRng;
     use std::collections::HashMap;
 
+    #[test]
+    fn sample_keys_skips_removed() {
+        crate::testing_env!(crate::test_utils::VMContextBuilder::new()
+            .random_seed_array([5; 32])
+            .build());
+        let mut map = UnorderedMap::new(b"m");
+        map.extend((0..100u32).map(|i| (i, i)));
+        map.retain(|k, _| k % 10 == 0);
+
+        for n in [0, 3, 9, 10, 20] {
+            let keys = map.sample_keys(n);
+            assert_eq!(keys.len(), n.min(10) as usize);
+            assert!(keys.iter().all(|k| *k % 10 == 0));
+            let mut distinct = keys.clone();
+            distinct.sort();
+            distinct.dedup();
+            assert_eq!(distinct.len(), keys.len());
+        }
+    }
+
+    #[test]
+    fn consecutive_sample_keys_differ() {
+        crate::testing_env!(crate::test_utils::VMContextBuilder::new()
+            .random_seed_array([2; 32])
+            .build());
+        let mut map = UnorderedMap::new(b"m");
+        map.extend((0..100u32).map(|i| (i, i)));
+        let first = map.sample_keys(5);
+        let second = map.sample_keys(5);
+        assert_ne!(first, second);
+    }
+
     #[test]
     fn basic_functionality() {
         let mut map = UnorderedMap::new(b"b");
@@ -444,6 +834,33 @@ mod tests {
         assert_eq!(map.len(), 0);
     }
 
+    #[test]
+    fn drain() {
+        let mut map = UnorderedMap::new(b"b");
+        for i in 0..10u8 {
+            map.insert(i, i as u64 * 10);
+        }
+        map.remove(&3);
+        map.flush();
+
+        let mut drain = map.drain();
+        assert_eq!(drain.len(), 9);
+        assert_eq!(drain.next(), Some((0, 0)));
+        assert_eq!(drain.next_back(), Some((9, 90)));
+        drop(drain);
+        assert!(map.is_empty());
+        assert_eq!(map.iter().count(), 0);
+        assert!(!map.contains_key(&5));
+
+        map.insert(1, 1);
+        map.flush();
+        let serialized = map.try_to_vec().unwrap();
+        drop(map);
+        let map = UnorderedMap::<u8, u64>::try_from_slice(&serialized).unwrap();
+        assert_eq!(map.iter().collect::<Vec<_>>(), [(&1, &1)]);
+        assert_eq!(map.get(&5), None);
+    }
+
     #[test]
     fn entry_api() {
         let mut map = UnorderedMap::new(b"b");
@@ -491,6 +908,53 @@ mod tests {
         assert_eq!(map.keys().collect::<Vec<_>>(), [&0, &2, &3]);
     }
 
+    #[test]
+    fn migrate_from_legacy() {
+        let mut legacy = crate::collections::UnorderedMap::new(b"m");
+        for i in 0..20u32 {
+            legacy.insert(&i, &(i * 2));
+        }
+        let mut map = UnorderedMap::new(b"m");
+        map.insert(3, 0);
+        map.insert(100, 100);
+
+        let mut calls = 0;
+        loop {
+            // Restore the legacy map for every chunk, as a contract would in separate calls.
+            let serialized = legacy.try_to_vec().unwrap();
+            legacy = BorshDeserialize::try_from_slice(&serialized).unwrap();
+            calls += 1;
+            if !map.migrate_from(&mut legacy, 6) {
+                break;
+            }
+        }
+        assert_eq!(calls, 4);
+        assert_eq!(map.len(), 21);
+        assert!((0..20).all(|i| map.get(&i) == Some(&(i * 2))));
+        map.flush();
+        // Only the entries of the new map are left in storage.
+        let storage = crate::mock::with_mocked_blockchain(|b| b.take_storage());
+        assert_eq!(storage.len(), 2 * 21);
+    }
+
+    #[test]
+    fn clear_chunk() {
+        let mut map = UnorderedMap::new(b"m");
+        map.extend((0..20u32).map(|k| (k, k)));
+        for k in (0..20).step_by(3) {
+            map.remove(&k);
+        }
+        assert!(map.clear_chunk(5));
+        // The map stays usable between chunks.
+        map.insert(100, 100);
+        assert_eq!(map.get(&1), Some(&1));
+        assert_eq!(map.iter().count() as u32, map.len());
+        while map.clear_chunk(5) {}
+        assert!(map.is_empty());
+        map.flush();
+        assert!(crate::mock::with_mocked_blockchain(|b| b.take_storage()).is_empty());
+    }
+
     #[derive(Arbitrary, Debug)]
     enum Op {
         Insert(u8, u8),
@@ -498,6 +962,7 @@ mod tests {
         Flush,
         Restore,
         Get(u8),
+        Retain(u8),
     }
 
     #[test]
@@ -539,9 +1004,35 @@ mod tests {
                             let r2 = hm.get(&k);
                             assert_eq!(r1, r2)
                         }
+                        Op::Retain(d) => {
+                            um.retain(|k, _| k % 4 != d % 4);
+                            hm.retain(|k, _| k % 4 != d % 4);
+                            assert_eq!(um, hm);
+                        }
                     }
                 }
             }
         }
     }
+
+    #[test]
+    fn try_methods() {
+        use crate::store::StoreError;
+
+        let mut map = UnorderedMap::<u8, u32>::new(b"m");
+        map.insert(2, 2);
+        map.values.insert_raw(&1, &[1, 2]);
+
+        assert!(matches!(map.try_get(&1), Err(StoreError::DeserializationError(_))));
+        assert!(matches!(map.try_insert(1, 1), Err(StoreError::DeserializationError(_))));
+        assert!(matches!(map.try_remove(&1), Err(StoreError::DeserializationError(_))));
+        assert_eq!(map.len(), 1);
+
+        assert_eq!(map.try_get(&2).unwrap(), Some(&2));
+        assert_eq!(map.try_insert(2, 3).unwrap(), Some(2));
+        assert_eq!(map.try_insert(3, 3).unwrap(), None);
+        assert_eq!(map.try_remove(&2).unwrap(), Some(3));
+        map.try_flush().unwrap();
+        assert_eq!(map.iter().collect::<Vec<_>>(), [(&3, &3)]);
+    }
 }
@@ -0,0 +1,166 @@
+use std::iter::FusedIterator;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::{LookupMap, ToKey, UnorderedSet};
+use crate::store::vec;
+
+impl<'a, T, H> IntoIterator for &'a UnorderedSet<T, H>
+where
+    T: BorshSerialize + Ord + BorshDeserialize,
+    H: ToKey,
+{
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over elements of a [`UnorderedSet`].
+///
+/// This `struct` is created by the `iter` method on [`UnorderedSet`].
+pub struct Iter<'a, T>
+where
+    T: BorshSerialize + Ord + BorshDeserialize,
+{
+    elements: vec::Iter<'a, T>,
+}
+
+impl<'a, T> Iter<'a, T>
+where
+    T: BorshSerialize + Ord + BorshDeserialize,
+{
+    pub(super) fn new<H>(set: &'a UnorderedSet<T, H>) -> Self
+    where
+        H: ToKey,
+    {
+        Self { elements: set.elements.iter() }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T>
+where
+    T: BorshSerialize + Ord + BorshDeserialize,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        <Self as Iterator>::nth(self, 0)
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.elements.nth(n)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.elements.size_hint()
+    }
+
+    fn count(self) -> usize {
+        self.elements.count()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> where T: BorshSerialize + Ord + BorshDeserialize {}
+impl<'a, T> FusedIterator for Iter<'a, T> where T: BorshSerialize + Ord + BorshDeserialize {}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T>
+where
+    T: BorshSerialize + Ord + BorshDeserialize,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        <Self as DoubleEndedIterator>::nth_back(self, 0)
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.elements.nth_back(n)
+    }
+}
+
+/// A draining iterator for [`UnorderedSet`].
+///
+/// This `struct` is created by the `drain` method on [`UnorderedSet`].
+pub struct Drain<'a, T, H>
+where
+    T: BorshSerialize + Ord + BorshDeserialize + Clone,
+    H: ToKey,
+{
+    elements: vec::Drain<'a, T>,
+    index: &'a mut LookupMap<T, u32, H>,
+}
+
+impl<'a, T, H> Drain<'a, T, H>
+where
+    T: BorshSerialize + Ord + BorshDeserialize + Clone,
+    H: ToKey,
+{
+    pub(super) fn new(set: &'a mut UnorderedSet<T, H>) -> Self {
+        Self { elements: set.elements.drain(..), index: &mut set.index }
+    }
+
+    fn remove_index(&mut self, value: T) -> T {
+        // Set instead of remove to avoid loading the index from storage.
+        self.index.set(value.clone(), None);
+        value
+    }
+}
+
+impl<'a, T, H> Drop for Drain<'a, T, H>
+where
+    T: BorshSerialize + Ord + BorshDeserialize + Clone,
+    H: ToKey,
+{
+    fn drop(&mut self) {
+        // Values not yielded have to be loaded to remove them from the index.
+        for value in self.elements.by_ref() {
+            self.index.set(value, None);
+        }
+    }
+}
+
+impl<'a, T, H> Iterator for Drain<'a, T, H>
+where
+    T: BorshSerialize + Ord + BorshDeserialize + Clone,
+    H: ToKey,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.elements.next()?;
+        Some(self.remove_index(value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.elements.size_hint()
+    }
+
+    fn count(self) -> usize {
+        self.elements.len()
+    }
+}
+
+impl<'a, T, H> ExactSizeIterator for Drain<'a, T, H>
+where
+    T: BorshSerialize + Ord + BorshDeserialize + Clone,
+    H: ToKey,
+{
+}
+impl<'a, T, H> FusedIterator for Drain<'a, T, H>
+where
+    T: BorshSerialize + Ord + BorshDeserialize + Clone,
+    H: ToKey,
+{
+}
+
+impl<'a, T, H> DoubleEndedIterator for Drain<'a, T, H>
+where
+    T: BorshSerialize + Ord + BorshDeserialize + Clone,
+    H: ToKey,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let value = self.elements.next_back()?;
+        Some(self.remove_index(value))
+    }
+}
@@ -0,0 +1,741 @@
+mod iter;
+
+use std::borrow::Borrow;
+use std::fmt;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::crypto_hash::Sha256;
+use crate::{collections, env, IntoStorageKey};
+
+pub use self::iter::{Drain, Iter};
+use super::key::ToKey;
+use super::sample::{sample_indices, SeedRng};
+use super::transaction::{self, Rollback};
+use super::{LookupMap, StoreError, Vector, ERR_INCONSISTENT_STATE};
+
+/// A lazily loaded storage set that stores its content directly on the storage trie.
+/// This structure is similar to [`near_sdk::store::LookupSet`](crate::store::LookupSet), except
+/// that it stores the values in a [`Vector`] so that [`UnorderedSet`] can be iterable.
+///
+/// The values are stored contiguously, so skipping values of the iterator, for example with
+/// `iter().skip(n).take(m)` to paginate the set, does not load the skipped values from storage.
+/// Removing a value moves the last value of the set into its position, so the order of
+/// iteration is not preserved on removal.
+///
+/// The default hash function for [`UnorderedSet`] is [`Sha256`] which uses a syscall
+/// (or host function) built into the NEAR runtime to hash the value. To use a custom function,
+/// use [`with_hasher`]. Alternative builtin hash functions can be found at
+/// [`near_sdk::crypto_hash`](crate::crypto_hash).
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::UnorderedSet;
+///
+/// // Initializes a set, the generic types can be inferred to `UnorderedSet<String, Sha256>`
+/// // The `b"a"` parameter is a prefix for the storage keys of this data structure.
+/// let mut set = UnorderedSet::new(b"a");
+///
+/// assert!(set.insert("test".to_string()));
+/// assert!(set.contains("test"));
+/// assert!(set.remove("test"));
+///
+/// set.extend((0..100).map(|i| i.to_string()));
+/// // Paginate the set, only loading the values within the page from storage.
+/// let page: Vec<&String> = set.iter().skip(20).take(10).collect();
+/// assert_eq!(page.len(), 10);
+/// ```
+///
+/// [`with_hasher`]: Self::with_hasher
+pub struct UnorderedSet<T, H = Sha256>
+where
+    T: BorshSerialize + Ord,
+    H: ToKey,
+{
+    elements: Vector<T>,
+    index: LookupMap<T, u32, H>,
+}
+
+//? Manual implementations needed only because borsh derive is leaking field types
+// https://github.com/near/borsh-rs/issues/41
+impl<T, H> BorshSerialize for UnorderedSet<T, H>
+where
+    T: BorshSerialize + Ord,
+    H: ToKey,
+{
+    fn serialize<W: borsh::maybestd::io::Write>(
+        &self,
+        writer: &mut W,
+    ) -> Result<(), borsh::maybestd::io::Error> {
+        BorshSerialize::serialize(&self.elements, writer)?;
+        BorshSerialize::serialize(&self.index, writer)?;
+        Ok(())
+    }
+}
+
+impl<T, H> BorshDeserialize for UnorderedSet<T, H>
+where
+    T: BorshSerialize + Ord,
+    H: ToKey,
+{
+    fn deserialize(buf: &mut &[u8]) -> Result<Self, borsh::maybestd::io::Error> {
+        Ok(Self {
+            elements: BorshDeserialize::deserialize(buf)?,
+            index: BorshDeserialize::deserialize(buf)?,
+        })
+    }
+}
+
+impl<T, H> Drop for UnorderedSet<T, H>
+where
+    T: BorshSerialize + Ord,
+    H: ToKey,
+{
+    fn drop(&mut self) {
+        self.flush()
+    }
+}
+
+impl<T, H> fmt::Debug for UnorderedSet<T, H>
+where
+    T: BorshSerialize + Ord + BorshDeserialize + fmt::Debug,
+    H: ToKey,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if cfg!(feature = "expensive-debug") {
+            f.debug_set().entries(self.iter()).finish()
+        } else {
+            f.debug_struct("UnorderedSet")
+                .field("elements", &self.elements)
+                .field("index", &self.index)
+                .finish()
+        }
+    }
+}
+
+impl<T, H> Extend<T> for UnorderedSet<T, H>
+where
+    T: BorshSerialize + Ord + BorshDeserialize + Clone,
+    H: ToKey,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<T, H> serde::Serialize for UnorderedSet<T, H>
+where
+    T: BorshSerialize + BorshDeserialize + Ord + serde::Serialize,
+    H: ToKey,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+/// Compares the values with a [`HashSet`](std::collections::HashSet), loading all values from
+/// storage. Only available outside of Wasm, to assert on the contents of the set in unit tests.
+#[cfg(not(target_arch = "wasm32"))]
+impl<T, H> PartialEq<std::collections::HashSet<T>> for UnorderedSet<T, H>
+where
+    T: BorshSerialize + BorshDeserialize + Ord + std::hash::Hash,
+    H: ToKey,
+{
+    fn eq(&self, other: &std::collections::HashSet<T>) -> bool {
+        self.len() as usize == other.len() && self.iter().all(|v| other.contains(v))
+    }
+}
+
+impl<T> UnorderedSet<T, Sha256>
+where
+    T: BorshSerialize + Ord,
+{
+    #[inline]
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self::with_hasher(prefix)
+    }
+}
+
+impl<T, H> UnorderedSet<T, H>
+where
+    T: BorshSerialize + Ord,
+    H: ToKey,
+{
+    /// Initialize a [`UnorderedSet`] with a custom hash function.
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::crypto_hash::Keccak256;
+    /// use near_sdk::store::UnorderedSet;
+    ///
+    /// let set = UnorderedSet::<String, Keccak256>::with_hasher(b"m");
+    /// ```
+    pub fn with_hasher<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        let mut vec_key = prefix.into_storage_key();
+        let map_key = [vec_key.as_slice(), b"i"].concat();
+        vec_key.push(b'e');
+        Self { elements: Vector::new(vec_key), index: LookupMap::with_hasher(map_key) }
+    }
+
+    /// Returns the number of elements in the set.
+    pub fn len(&self) -> u32 {
+        self.elements.len()
+    }
+
+    /// Returns true if the set contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Clears the set, removing all values.
+    pub fn clear(&mut self)
+    where
+        T: BorshDeserialize,
+    {
+        for value in self.elements.drain(..) {
+            // Set instead of remove to avoid loading the index from storage.
+            self.index.set(value, None);
+        }
+    }
+
+    /// Removes up to `limit` values from the set. Returns `true` if there are values left to
+    /// remove.
+    ///
+    /// This can be used to clear a set which is too large to [`clear`](Self::clear) within the gas
+    /// limit of a single function call, by calling it until it returns `false`.
+    pub fn clear_chunk(&mut self, limit: u32) -> bool
+    where
+        T: BorshDeserialize,
+    {
+        let start = self.elements.len().saturating_sub(limit);
+        for value in self.elements.drain(start..) {
+            self.index.set(value, None);
+        }
+        !self.is_empty()
+    }
+
+    /// An iterator visiting all values in arbitrary order.
+    /// The iterator element type is `&'a T`.
+    ///
+    /// Skipping values with [`Iterator::nth`] or [`Iterator::skip`] does not load the skipped
+    /// values from storage.
+    pub fn iter(&self) -> Iter<'_, T>
+    where
+        T: BorshDeserialize,
+    {
+        Iter::new(self)
+    }
+
+    /// Returns `n` distinct values of the set chosen uniformly at random using
+    /// [`env::random_seed`], or all values in random order if the set has fewer than `n` values.
+    ///
+    /// Only the chosen values are loaded from storage. Each call within a function call draws
+    /// from a new generator of [`env::rng`], so consecutive calls return different values, and
+    /// the function call returns the same values whenever it is executed with the same seed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::UnorderedSet;
+    /// # use near_sdk::{test_utils::VMContextBuilder, testing_env};
+    /// # testing_env!(VMContextBuilder::new().random_seed_array([1; 32]).build());
+    ///
+    /// let mut tickets: UnorderedSet<u32> = UnorderedSet::new(b"s");
+    /// tickets.extend(0..100);
+    ///
+    /// let winners = tickets.sample(3);
+    /// assert_eq!(winners.len(), 3);
+    /// assert!(winners.iter().all(|w| tickets.contains(*w)));
+    /// ```
+    pub fn sample(&self, n: u32) -> Vec<&T>
+    where
+        T: BorshDeserialize,
+    {
+        sample_indices(self.len(), n, &mut SeedRng::new())
+            .into_iter()
+            .map(|i| self.elements.get(i).unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE)))
+            .collect()
+    }
+
+    /// Clears the set, returning all values as an iterator.
+    ///
+    /// This will clear all values, even if only some values are yielded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::UnorderedSet;
+    ///
+    /// let mut set = UnorderedSet::new(b"m");
+    /// set.insert(1);
+    /// set.insert(2);
+    ///
+    /// for v in set.drain().take(1) {
+    ///     assert!(v == 1 || v == 2);
+    /// }
+    ///
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn drain(&mut self) -> Drain<'_, T, H>
+    where
+        T: BorshDeserialize + Clone,
+    {
+        Drain::new(self)
+    }
+
+    /// Retains only the values specified by the predicate, preserving the iteration order of
+    /// the retained values.
+    ///
+    /// # Examples
+    /// ```
+    /// use near_sdk::store::UnorderedSet;
+    ///
+    /// let mut set = UnorderedSet::new(b"s");
+    /// set.extend([1, 2, 3, 4]);
+    /// set.retain(|&x| x % 2 == 0);
+    /// assert_eq!(set.iter().collect::<Vec<_>>(), [&2, &4]);
+    /// ```
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        T: BorshDeserialize + Clone,
+        F: FnMut(&T) -> bool,
+    {
+        let index = &mut self.index;
+        let mut position = 0;
+        let mut kept = 0;
+        self.elements.retain(|value| {
+            let keep = f(value);
+            if !keep {
+                index.set(value.clone(), None);
+            } else {
+                if kept != position {
+                    // The value is shifted to fill the removed positions before it.
+                    index.set(value.clone(), Some(kept));
+                }
+                kept += 1;
+            }
+            position += 1;
+            keep
+        });
+    }
+
+    /// Returns `true` if the set contains the specified value.
+    ///
+    /// The value may be any borrowed form of the set's value type, but
+    /// [`BorshSerialize`] and [`ToOwned<Owned = T>`](ToOwned) on the borrowed form *must* match
+    /// those for the value type.
+    pub fn contains<Q: ?Sized>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = T> + Ord,
+    {
+        self.index.contains_key(value)
+    }
+
+    /// Adds a value to the set, as with [`insert`](Self::insert), but returns a [`StoreError`] if
+    /// the index of the value in storage cannot be deserialized. The set is not modified if an
+    /// error is returned.
+    pub fn try_insert(&mut self, value: T) -> Result<bool, StoreError>
+    where
+        T: Clone,
+    {
+        // Loads the index into the cache, so that inserting does not fail.
+        self.index.try_get(&value)?;
+        Ok(self.insert(value))
+    }
+
+    /// Adds a value to the set.
+    ///
+    /// If the set did not have this value present, `true` is returned.
+    ///
+    /// If the set did have this value present, `false` is returned.
+    pub fn insert(&mut self, value: T) -> bool
+    where
+        T: Clone,
+    {
+        let index = self.elements.len();
+        match self.index.entry(value.clone()) {
+            super::lookup_map::Entry::Occupied(_) => false,
+            super::lookup_map::Entry::Vacant(entry) => {
+                entry.insert(index);
+                self.elements.push(value);
+                true
+            }
+        }
+    }
+
+    /// Removes a value from the set. Returns whether the value was present in the set.
+    ///
+    /// The last value of the set is moved into the position of the removed value.
+    ///
+    /// The value may be any borrowed form of the set's value type, but
+    /// [`BorshSerialize`] and [`ToOwned<Owned = T>`](ToOwned) on the borrowed form *must* match
+    /// those for the value type.
+    pub fn remove<Q: ?Sized>(&mut self, value: &Q) -> bool
+    where
+        T: Borrow<Q> + BorshDeserialize + Clone,
+        Q: BorshSerialize + ToOwned<Owned = T>,
+    {
+        let index = match self.index.remove(value) {
+            Some(index) => index,
+            None => return false,
+        };
+        self.elements.swap_remove(index);
+        if let Some(moved) = self.elements.get(index) {
+            // Update the index of the last value, which was moved into the removed position.
+            self.index.set(moved.clone(), Some(index));
+        }
+        true
+    }
+
+    /// Removes a value from the set, as with [`remove`](Self::remove), but returns a
+    /// [`StoreError`] if the index of the value or the values it moves cannot be deserialized.
+    /// The set is not modified if an error is returned.
+    ///
+    /// The value may be any borrowed form of the set's value type, but
+    /// [`BorshSerialize`] and [`ToOwned<Owned = T>`](ToOwned) on the borrowed form *must* match
+    /// those for the value type.
+    pub fn try_remove<Q: ?Sized>(&mut self, value: &Q) -> Result<bool, StoreError>
+    where
+        T: Borrow<Q> + BorshDeserialize + Clone,
+        Q: BorshSerialize + ToOwned<Owned = T>,
+    {
+        // Loads the values which are read by the removal into the cache, so that it does not fail.
+        if let Some(&index) = self.index.try_get(value)? {
+            self.elements.try_get(index)?;
+            if let Some(last) = self.elements.len().checked_sub(1) {
+                self.elements.try_get(last)?;
+            }
+        }
+        Ok(self.remove(value))
+    }
+
+    /// Moves up to `limit` values from a [`collections::UnorderedSet`] into this set, removing
+    /// them from the legacy set. Returns `true` if there are values left to migrate.
+    ///
+    /// This can be used to migrate a set which is too large to move within the gas limit of a
+    /// single function call, by calling it until it returns `false`. Values are taken from the
+    /// end of the legacy set, so the legacy set stays consistent between calls and must be
+    /// stored along with this set.
+    ///
+    /// The storage layouts of both sets only overlap if the values are not hashed, so with the
+    /// default hasher this set can be created with the same prefix as the legacy set.
+    ///
+    /// # Examples
+    /// ```
+    /// use near_sdk::collections;
+    /// use near_sdk::store::UnorderedSet;
+    ///
+    /// let mut legacy = collections::UnorderedSet::new(b"s");
+    /// legacy.extend(0u32..10);
+    ///
+    /// let mut set = UnorderedSet::new(b"s");
+    /// while set.migrate_from(&mut legacy, 4) {}
+    /// assert!(legacy.is_empty());
+    /// assert_eq!(set.len(), 10);
+    /// ```
+    ///
+    /// [`collections::UnorderedSet`]: crate::collections::UnorderedSet
+    pub fn migrate_from(&mut self, legacy: &mut collections::UnorderedSet<T>, limit: u32) -> bool
+    where
+        T: BorshDeserialize + Clone,
+    {
+        for _ in 0..limit {
+            let values = legacy.as_vector();
+            let value = match values.len().checked_sub(1) {
+                Some(last) => values.get(last),
+                None => break,
+            };
+            let value = value.unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE));
+            legacy.remove(&value);
+            self.insert(value);
+        }
+        !legacy.is_empty()
+    }
+
+    /// Flushes the intermediate values of the set before this is called when the structure is
+    /// [`Drop`]ed. This will write all modified values to storage but keep all cached values
+    /// in memory.
+    pub fn flush(&mut self) {
+        self.elements.flush();
+        self.index.flush();
+    }
+
+    /// Flushes the intermediate values of the set, as with [`flush`](Self::flush), but returns a
+    /// [`StoreError`] if a value cannot be serialized. Values which were written before the error
+    /// was encountered remain written.
+    pub fn try_flush(&mut self) -> Result<(), StoreError> {
+        self.elements.try_flush()?;
+        self.index.try_flush()
+    }
+
+    /// Calls `f` with the set, and discards the changes it made to the set if it returns an error,
+    /// as with [`LookupMap::transaction`].
+    ///
+    /// The collection is flushed before `f` is called, and the values which `f` writes to
+    /// storage, such as by flushing the collection, are written back when the changes are
+    /// discarded.
+    pub fn transaction<F, R, E>(&mut self, f: F) -> Result<R, E>
+    where
+        F: FnOnce(&mut Self) -> Result<R, E>,
+    {
+        transaction::transaction(self, f)
+    }
+
+    /// Returns the number of storage bytes added by flushes of this set since it was created
+    /// or loaded from storage.
+    pub fn bytes_written(&self) -> u64 {
+        self.elements.bytes_written() + self.index.bytes_written()
+    }
+
+    /// Returns the number of storage bytes released by flushes of this set since it was
+    /// created or loaded from storage.
+    pub fn bytes_freed(&self) -> u64 {
+        self.elements.bytes_freed() + self.index.bytes_freed()
+    }
+}
+
+impl<T, H> Rollback for UnorderedSet<T, H>
+where
+    T: BorshSerialize + Ord,
+    H: ToKey,
+{
+    type Snapshot =
+        (<Vector<T> as Rollback>::Snapshot, <LookupMap<T, u32, H> as Rollback>::Snapshot);
+
+    fn snapshot(&mut self) -> Self::Snapshot {
+        (self.elements.snapshot(), self.index.snapshot())
+    }
+
+    fn rollback(&mut self, (elements, index): Self::Snapshot) {
+        self.index.rollback(index);
+        self.elements.rollback(elements);
+    }
+
+    fn commit(&mut self, (elements, index): Self::Snapshot) {
+        self.index.commit(index);
+        self.elements.commit(elements);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::UnorderedSet;
+    use crate::env;
+    use crate::test_utils::test_env::setup_free;
+    use arbitrary::{Arbitrary, Unstructured};
+    use borsh::{BorshDeserialize, BorshSerialize};
+    use rand::RngCore;
+    use rand::SeedableRng;
+    use std::collections::HashSet;
+
+    #[test]
+    fn basic_functionality() {
+        let mut set = UnorderedSet::new(b"s");
+        assert!(set.is_empty());
+        assert!(set.insert("a".to_string()));
+        assert!(set.insert("b".to_string()));
+        assert!(!set.insert("a".to_string()));
+        assert_eq!(set.len(), 2);
+        assert!(set.contains("a"));
+
+        assert!(set.remove("a"));
+        assert!(!set.remove("a"));
+        assert!(!set.contains("a"));
+        assert_eq!(set.iter().collect::<Vec<_>>(), ["b"]);
+    }
+
+    #[test]
+    fn transaction_rollback() {
+        let mut set = UnorderedSet::new(b"s");
+        set.extend(0..5u32);
+        let result: Result<(), ()> = set.transaction(|set| {
+            set.remove(&1);
+            set.insert(9);
+            Err(())
+        });
+        assert!(result.is_err());
+        assert_eq!(set.iter().copied().collect::<Vec<_>>(), [0, 1, 2, 3, 4]);
+        assert!(set.contains(&1) && !set.contains(&9));
+
+        set.transaction(|set| set.remove(&0).then(|| ()).ok_or(())).unwrap();
+        assert_eq!(set.len(), 4);
+    }
+
+    #[test]
+    fn pagination() {
+        let mut set = UnorderedSet::new(b"s");
+        set.extend(0..50u32);
+        set.flush();
+        let serialized = set.try_to_vec().unwrap();
+        drop(set);
+
+        // Remove the skipped values from storage, to check that they are not loaded.
+        for i in 0..20u32 {
+            env::storage_remove(&[b"se".as_slice(), &i.to_le_bytes()].concat());
+        }
+
+        let set = UnorderedSet::<u32>::try_from_slice(&serialized).unwrap();
+        let page: Vec<_> = set.iter().skip(20).take(5).copied().collect();
+        assert_eq!(page, [20, 21, 22, 23, 24]);
+        assert_eq!(set.iter().rev().nth(4), Some(&45));
+        assert_eq!(set.iter().skip(60).next(), None);
+    }
+
+    #[test]
+    fn drain_and_clear() {
+        let mut set = UnorderedSet::new(b"s");
+        set.extend(0..10u8);
+
+        let mut drain = set.drain();
+        assert_eq!(drain.len(), 10);
+        assert_eq!(drain.next(), Some(0));
+        assert_eq!(drain.next_back(), Some(9));
+        drop(drain);
+        assert!(set.is_empty());
+        assert!(!set.contains(&5));
+
+        set.extend(0..10u8);
+        set.clear();
+        assert!(set.is_empty());
+        assert!(!set.contains(&5));
+        assert!(set.insert(5));
+        assert_eq!(set.iter().collect::<Vec<_>>(), [&5]);
+    }
+
+    #[test]
+    fn migrate_from_legacy() {
+        let mut legacy = crate::collections::UnorderedSet::new(b"s");
+        legacy.extend(0..10u32);
+        let mut set = UnorderedSet::new(b"s");
+        assert!(set.migrate_from(&mut legacy, 4));
+        assert_eq!(legacy.len(), 6);
+        assert!(!set.migrate_from(&mut legacy, 6));
+        assert!(legacy.is_empty());
+        assert!((0..10).all(|v| set.contains(&v)));
+        set.flush();
+        // Only the values and indices of the new set are left in storage.
+        let storage = crate::mock::with_mocked_blockchain(|b| b.take_storage());
+        assert_eq!(storage.len(), 2 * 10);
+    }
+
+    #[test]
+    fn clear_chunk() {
+        let mut set = UnorderedSet::new(b"s");
+        set.extend(0..10u8);
+        assert!(set.clear_chunk(4));
+        assert_eq!(set.len(), 6);
+        assert!(set.iter().all(|v| set.contains(v)));
+        assert!(!set.contains(&9));
+        assert!(!set.clear_chunk(6));
+        set.flush();
+        assert!(crate::mock::with_mocked_blockchain(|b| b.take_storage()).is_empty());
+    }
+
+    #[test]
+    fn consecutive_samples_differ() {
+        crate::testing_env!(crate::test_utils::VMContextBuilder::new()
+            .random_seed_array([2; 32])
+            .build());
+        let mut set = UnorderedSet::new(b"s");
+        set.extend(0..100u32);
+        let first = set.sample(5);
+        let second = set.sample(5);
+        assert_ne!(first, second);
+    }
+
+    #[derive(Arbitrary, Debug)]
+    enum Op {
+        Insert(u8),
+        Remove(u8),
+        Flush,
+        Restore,
+        Contains(u8),
+        Retain(u8),
+    }
+
+    #[test]
+    fn arbitrary() {
+        setup_free();
+
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(0);
+        let mut buf = vec![0; 4096];
+        for _ in 0..512 {
+            // Clear storage in-between runs
+            crate::mock::with_mocked_blockchain(|b| b.take_storage());
+            rng.fill_bytes(&mut buf);
+
+            let mut us = UnorderedSet::new(b"l");
+            let mut hs = HashSet::new();
+            let u = Unstructured::new(&buf);
+            if let Ok(ops) = Vec::<Op>::arbitrary_take_rest(u) {
+                for op in ops {
+                    match op {
+                        Op::Insert(v) => {
+                            let r1 = us.insert(v);
+                            let r2 = hs.insert(v);
+                            assert_eq!(r1, r2)
+                        }
+                        Op::Remove(v) => {
+                            let r1 = us.remove(&v);
+                            let r2 = hs.remove(&v);
+                            assert_eq!(r1, r2)
+                        }
+                        Op::Flush => {
+                            us.flush();
+                        }
+                        Op::Restore => {
+                            let serialized = us.try_to_vec().unwrap();
+                            us = UnorderedSet::deserialize(&mut serialized.as_slice()).unwrap();
+                        }
+                        Op::Contains(v) => {
+                            let r1 = us.contains(&v);
+                            let r2 = hs.contains(&v);
+                            assert_eq!(r1, r2)
+                        }
+                        Op::Retain(d) => {
+                            us.retain(|v| v % 4 != d % 4);
+                            hs.retain(|v| v % 4 != d % 4);
+                        }
+                    }
+                    assert_eq!(us.len() as usize, hs.len());
+                }
+                assert_eq!(us, hs);
+            }
+        }
+    }
+
+    #[test]
+    fn try_methods() {
+        use crate::store::StoreError;
+
+        let mut set = UnorderedSet::<u8>::new(b"s");
+        set.insert(2);
+        set.index.insert_raw(&1, &[1]);
+
+        assert!(matches!(set.try_insert(1), Err(StoreError::DeserializationError(_))));
+        assert!(matches!(set.try_remove(&1), Err(StoreError::DeserializationError(_))));
+        assert_eq!(set.len(), 1);
+
+        assert!(!set.try_insert(2).unwrap());
+        assert!(set.try_insert(3).unwrap());
+        assert!(set.try_remove(&2).unwrap());
+        set.try_flush().unwrap();
+        assert_eq!(set.iter().collect::<Vec<_>>(), [&3]);
+    }
+}
@@ -70,3 +70,27 @@ where
         self.get_mut(index).unwrap_or_else(|| env::panic_str(ERR_INDEX_OUT_OF_BOUNDS))
     }
 }
+
+impl<T> serde::Serialize for Vector<T>
+where
+    T: BorshSerialize + BorshDeserialize + serde::Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+/// Compares the elements with a [`Vec`], loading all elements from storage. Only available
+/// outside of Wasm, to assert on the contents of the vector in unit tests.
+#[cfg(not(target_arch = "wasm32"))]
+impl<T> core::cmp::PartialEq<Vec<T>> for Vector<T>
+where
+    T: PartialEq + BorshSerialize + BorshDeserialize,
+{
+    fn eq(&self, other: &Vec<T>) -> bool {
+        self.len() as usize == other.len() && self.iter().eq(other.iter())
+    }
+}
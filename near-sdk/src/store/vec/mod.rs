@@ -2,6 +2,7 @@ mod impls;
 mod iter;
 
 use std::{
+    cmp::Ordering,
     fmt,
     ops::{Bound, Range, RangeBounds},
 };
@@ -9,7 +10,8 @@ use std::{
 use borsh::{BorshDeserialize, BorshSerialize};
 
 pub use self::iter::{Drain, Iter, IterMut};
-use super::ERR_INCONSISTENT_STATE;
+use super::transaction::{self, Rollback, StorageSnapshot};
+use super::{StoreError, ERR_INCONSISTENT_STATE};
 use crate::{env, IntoStorageKey};
 
 use super::IndexMap;
@@ -127,11 +129,91 @@ where
         self.len = 0;
     }
 
+    /// Removes up to `limit` elements from the end of the vector without loading them. Returns
+    /// `true` if there are elements left to remove.
+    ///
+    /// This can be used to clear a vector which is too large to [`clear`](Self::clear) within the
+    /// gas limit of a single function call.
+    ///
+    /// # Examples
+    /// ```
+    /// use near_sdk::store::Vector;
+    ///
+    /// let mut vec = Vector::new(b"v");
+    /// vec.extend(0u8..10);
+    /// assert!(vec.clear_chunk(6));
+    /// assert_eq!(vec.len(), 4);
+    /// assert!(!vec.clear_chunk(6));
+    /// assert!(vec.is_empty());
+    /// ```
+    pub fn clear_chunk(&mut self, limit: u32) -> bool {
+        let new_len = self.len.saturating_sub(limit);
+        for i in new_len..self.len {
+            self.values.set(i, None);
+        }
+        self.len = new_len;
+        !self.is_empty()
+    }
+
     /// Flushes the cache and writes all modified values to storage.
     pub fn flush(&mut self) {
         self.values.flush();
     }
 
+    /// Flushes the cache, as with [`flush`](Self::flush), but returns a [`StoreError`] if an
+    /// element cannot be serialized. Elements which were written before the error was encountered
+    /// remain written.
+    pub fn try_flush(&mut self) -> Result<(), StoreError> {
+        self.values.try_flush()
+    }
+
+    /// Calls `f` with the vector, and discards the changes it made to the vector if it returns an
+    /// error, as with [`LookupMap::transaction`](crate::store::LookupMap::transaction).
+    ///
+    /// The collection is flushed before `f` is called, and the values which `f` writes to
+    /// storage, such as by flushing the collection, are written back when the changes are
+    /// discarded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Vector;
+    ///
+    /// let mut vec = Vector::new(b"v");
+    /// vec.extend([1u32, 2, 3]);
+    /// let result = vec.transaction(|vec| {
+    ///     vec.pop();
+    ///     vec.push(10);
+    ///     vec.get(5).copied().ok_or("out of bounds")
+    /// });
+    /// assert!(result.is_err());
+    /// assert_eq!(vec.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+    /// ```
+    pub fn transaction<F, R, E>(&mut self, f: F) -> Result<R, E>
+    where
+        F: FnOnce(&mut Self) -> Result<R, E>,
+    {
+        transaction::transaction(self, f)
+    }
+
+    /// Returns the number of storage bytes added by flushes of this vector since it was created
+    /// or loaded from storage.
+    pub fn bytes_written(&self) -> u64 {
+        self.values.bytes.written
+    }
+
+    /// Returns the number of storage bytes released by flushes of this vector since it was
+    /// created or loaded from storage.
+    pub fn bytes_freed(&self) -> u64 {
+        self.values.bytes.freed
+    }
+
+    /// Returns the number of storage bytes used by an element with a serialized length of
+    /// `value_len` bytes.
+    pub(crate) fn estimated_slot_bytes(&self, value_len: usize) -> u64 {
+        self.values.estimated_slot_bytes(value_len)
+    }
+
     /// Sets a value at a given index to the value provided. This does not shift values after the
     /// index to the right.
     ///
@@ -171,6 +253,33 @@ where
         self.values.get(index)
     }
 
+    /// Returns the element by index, as with [`get`](Self::get), but returns a [`StoreError`] if
+    /// the element in storage cannot be deserialized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+    /// use near_sdk::env;
+    /// use near_sdk::store::{StoreError, Vector};
+    ///
+    /// let mut vec: Vector<u32> = Vector::new(b"v");
+    /// vec.push(1);
+    /// vec.flush();
+    /// // The element is stored under the prefix followed by its index.
+    /// env::storage_write(&[b'v', 0, 0, 0, 0], &[1, 2]);
+    ///
+    /// let vec = Vector::<u32>::try_from_slice(&vec.try_to_vec().unwrap()).unwrap();
+    /// assert!(matches!(vec.try_get(0), Err(StoreError::DeserializationError(_))));
+    /// assert_eq!(vec.try_get(1).unwrap(), None);
+    /// ```
+    pub fn try_get(&self, index: u32) -> Result<Option<&T>, StoreError> {
+        if index >= self.len() {
+            return Ok(None);
+        }
+        self.values.try_get(index)
+    }
+
     /// Returns a mutable reference to the element at the `index` provided.
     pub fn get_mut(&mut self, index: u32) -> Option<&mut T> {
         if index >= self.len {
@@ -179,7 +288,7 @@ where
         self.values.get_mut(index)
     }
 
-    fn swap(&mut self, a: u32, b: u32) {
+    pub(crate) fn swap(&mut self, a: u32, b: u32) {
         if a >= self.len() || b >= self.len() {
             env::panic_str(ERR_INDEX_OUT_OF_BOUNDS);
         }
@@ -211,6 +320,17 @@ where
         prev
     }
 
+    /// Removes the last element, as with [`pop`](Self::pop), but returns a [`StoreError`] if the
+    /// element in storage cannot be deserialized. The vector is not modified if an error is
+    /// returned.
+    pub fn try_pop(&mut self) -> Result<Option<T>, StoreError> {
+        if let Some(last) = self.len.checked_sub(1) {
+            // Loads the element into the cache, so that it can be popped without failing.
+            self.values.try_get(last)?;
+        }
+        Ok(self.pop())
+    }
+
     /// Inserts a element at `index`, returns an evicted element.
     ///
     /// # Panics
@@ -236,6 +356,137 @@ where
         IterMut::new(self)
     }
 
+    /// Retains only the elements specified by the predicate, preserving the order of the
+    /// retained elements.
+    ///
+    /// Every element is loaded to be passed to the predicate, but only the elements which are
+    /// shifted to a new index and the removed trailing slots are written to storage.
+    ///
+    /// # Examples
+    /// ```
+    /// use near_sdk::store::Vector;
+    ///
+    /// let mut vec = Vector::new(b"v");
+    /// vec.extend([1, 2, 3, 4]);
+    /// vec.retain(|&x| x % 2 == 0);
+    /// assert_eq!(vec.iter().collect::<Vec<_>>(), [&2, &4]);
+    /// ```
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let mut kept = 0;
+        for i in 0..self.len {
+            if f(expect_consistent_state(self.values.get(i))) {
+                self.values.swap(kept, i);
+                kept += 1;
+            }
+        }
+        for i in kept..self.len {
+            self.values.set(i, None);
+        }
+        self.len = kept;
+    }
+
+    /// Binary searches this sorted vector for a given element, loading only the `O(log n)`
+    /// elements compared against from storage.
+    ///
+    /// If the value is found then [`Result::Ok`] is returned, containing the index of the
+    /// matching element. If there are multiple matches, then any one of the matches could be
+    /// returned. If the value is not found then [`Result::Err`] is returned, containing the index
+    /// where a matching element could be inserted while maintaining sorted order.
+    ///
+    /// # Examples
+    /// ```
+    /// use near_sdk::store::Vector;
+    ///
+    /// let mut vec = Vector::new(b"v");
+    /// vec.extend([1, 3, 5, 7]);
+    /// assert_eq!(vec.binary_search(&5), Ok(2));
+    /// assert_eq!(vec.binary_search(&4), Err(2));
+    /// ```
+    pub fn binary_search(&self, x: &T) -> Result<u32, u32>
+    where
+        T: Ord,
+    {
+        self.binary_search_by(|p| p.cmp(x))
+    }
+
+    /// Binary searches this sorted vector with a comparator function, as with
+    /// [`slice::binary_search_by`].
+    ///
+    /// The comparator function should return an order code that indicates whether its argument
+    /// is `Less`, `Equal` or `Greater` than the desired target. If the vector is not sorted by
+    /// the comparator, the returned result is unspecified and meaningless.
+    pub fn binary_search_by<F>(&self, mut f: F) -> Result<u32, u32>
+    where
+        F: FnMut(&T) -> Ordering,
+    {
+        let mut lo = 0;
+        let mut hi = self.len;
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            match f(expect_consistent_state(self.values.get(mid))) {
+                Ordering::Less => lo = mid + 1,
+                Ordering::Greater => hi = mid,
+                Ordering::Equal => return Ok(mid),
+            }
+        }
+        Err(lo)
+    }
+
+    /// Returns the index of the partition point according to the given predicate (the index of
+    /// the first element of the second partition), as with [`slice::partition_point`].
+    ///
+    /// The vector is assumed to be partitioned according to the predicate: all elements for
+    /// which the predicate returns `true` are at the start of the vector, followed by all
+    /// elements for which it returns `false`.
+    ///
+    /// # Examples
+    /// ```
+    /// use near_sdk::store::Vector;
+    ///
+    /// let mut prices = Vector::new(b"p");
+    /// prices.extend([10u64, 20, 20, 30]);
+    /// assert_eq!(prices.partition_point(|&p| p < 20), 1);
+    /// assert_eq!(prices.partition_point(|&p| p <= 20), 3);
+    /// ```
+    pub fn partition_point<P>(&self, mut pred: P) -> u32
+    where
+        P: FnMut(&T) -> bool,
+    {
+        self.binary_search_by(|x| if pred(x) { Ordering::Less } else { Ordering::Greater })
+            .unwrap_or_else(|i| i)
+    }
+
+    /// Inserts an element into this sorted vector, keeping it sorted, and returns the index it
+    /// was inserted at. The element is inserted after any equal elements.
+    ///
+    /// Every element after the insertion point is shifted by one, which writes each of them to
+    /// storage. This is cheapest when elements are mostly inserted close to the end.
+    ///
+    /// # Examples
+    /// ```
+    /// use near_sdk::store::Vector;
+    ///
+    /// let mut levels = Vector::new(b"l");
+    /// for price in [30u64, 10, 20] {
+    ///     levels.insert_sorted(price);
+    /// }
+    /// assert_eq!(levels.iter().collect::<Vec<_>>(), [&10, &20, &30]);
+    /// ```
+    pub fn insert_sorted(&mut self, element: T) -> u32
+    where
+        T: Ord,
+    {
+        let index = self.partition_point(|x| x <= &element);
+        self.push(element);
+        for i in (index + 1..self.len).rev() {
+            self.values.swap(i - 1, i);
+        }
+        index
+    }
+
     /// Creates a draining iterator that removes the specified range in the vector
     /// and yields the removed items.
     ///
@@ -246,6 +497,23 @@ where
     ///
     /// This will not panic on invalid ranges (`end > length` or `end < start`) and instead the
     /// iterator will just be empty.
+    ///
+    /// Draining a range at the end of the vector does not move any of the other elements, so a
+    /// large vector can be drained in bounded chunks across multiple function calls.
+    ///
+    /// # Examples
+    /// ```
+    /// use near_sdk::store::Vector;
+    ///
+    /// let mut refunds = Vector::new(b"r");
+    /// refunds.extend(0u64..10);
+    ///
+    /// // Process at most 4 refunds per call.
+    /// let start = refunds.len().saturating_sub(4);
+    /// let chunk: Vec<u64> = refunds.drain(start..).collect();
+    /// assert_eq!(chunk, [6, 7, 8, 9]);
+    /// assert_eq!(refunds.len(), 6);
+    /// ```
     pub fn drain<R>(&mut self, range: R) -> Drain<T>
     where
         R: RangeBounds<u32>,
@@ -287,6 +555,42 @@ where
     }
 }
 
+/// Generates a vector under an arbitrary prefix, seeded with arbitrary elements which are not
+/// flushed to storage yet.
+#[cfg(feature = "test-utils")]
+impl<'a, T> arbitrary::Arbitrary<'a> for Vector<T>
+where
+    T: BorshSerialize + arbitrary::Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let mut vector = Self::new(u.arbitrary::<Vec<u8>>()?);
+        for element in u.arbitrary_iter()? {
+            vector.push(element?);
+        }
+        Ok(vector)
+    }
+}
+
+impl<T> Rollback for Vector<T>
+where
+    T: BorshSerialize,
+{
+    type Snapshot = (u32, StorageSnapshot);
+
+    fn snapshot(&mut self) -> Self::Snapshot {
+        (self.len, self.values.snapshot())
+    }
+
+    fn rollback(&mut self, (len, values): Self::Snapshot) {
+        self.values.rollback(values);
+        self.len = len;
+    }
+
+    fn commit(&mut self, (_, values): Self::Snapshot) {
+        self.values.commit(values);
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(test)]
 mod tests {
@@ -295,7 +599,27 @@ mod tests {
     use rand::{Rng, RngCore, SeedableRng};
 
     use super::Vector;
-    use crate::{store::IndexMap, test_utils::test_env::setup_free};
+    use crate::{env, store::IndexMap, test_utils::test_env::setup_free};
+
+    #[test]
+    fn transaction_rolls_back_flushes() {
+        let mut vec = Vector::new(b"v");
+        vec.extend([1u32, 2, 3]);
+        vec.flush();
+        let start = env::storage_usage();
+
+        let result: Result<(), ()> = vec.transaction(|vec| {
+            vec.pop();
+            vec.push(10);
+            vec.push(11);
+            vec.flush();
+            Err(())
+        });
+        assert!(result.is_err());
+        assert_eq!(env::storage_usage(), start);
+        assert_eq!(vec.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+        assert!(!env::storage_has_key(&[&b"v"[..], &3u32.to_le_bytes()].concat()));
+    }
 
     #[test]
     fn test_push_pop() {
@@ -474,6 +798,71 @@ mod tests {
         assert_eq!(vec.iter().count(), baseline.len());
     }
 
+    #[test]
+    fn iter_mut_writes_back() {
+        let mut vec = Vector::new(b"v");
+        vec.extend(0..10u32);
+        vec.flush();
+
+        for (i, value) in vec.iter_mut().enumerate().skip(2) {
+            *value += i as u32 * 10;
+        }
+        vec.flush();
+        let serialized = vec.try_to_vec().unwrap();
+        drop(vec);
+
+        let vec = Vector::<u32>::try_from_slice(&serialized).unwrap();
+        assert!(Iterator::eq(vec.iter().copied(), (0..10).map(|i| if i < 2 { i } else { i * 11 })));
+    }
+
+    #[test]
+    fn sorted_insertion() {
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(3);
+        let mut vec = Vector::new(b"v");
+        let mut baseline = Vec::new();
+        for _ in 0..200 {
+            let value = rng.gen_range(0..50u32);
+            let index = vec.insert_sorted(value);
+            let expected = baseline.partition_point(|&x| x <= value);
+            assert_eq!(index as usize, expected);
+            baseline.insert(expected, value);
+
+            let target = rng.gen_range(0..50u32);
+            assert_eq!(vec.binary_search(&target).is_ok(), baseline.binary_search(&target).is_ok());
+            assert_eq!(
+                vec.partition_point(|&x| x < target) as usize,
+                baseline.partition_point(|&x| x < target)
+            );
+        }
+        vec.flush();
+        assert!(Iterator::eq(vec.iter(), baseline.iter()));
+    }
+
+    #[test]
+    fn drain_tail_in_chunks() {
+        let mut vec = Vector::new(b"v");
+        vec.extend(0..10u32);
+        vec.flush();
+        let mut serialized = vec.try_to_vec().unwrap();
+        drop(vec);
+
+        let mut drained = Vec::new();
+        loop {
+            // Restore the vector for every chunk, as a contract would in separate calls.
+            let mut vec = Vector::<u32>::try_from_slice(&serialized).unwrap();
+            if vec.is_empty() {
+                break;
+            }
+            let start = vec.len().saturating_sub(3);
+            drained.extend(vec.drain(start..));
+            vec.flush();
+            serialized = vec.try_to_vec().unwrap();
+        }
+        drained.sort_unstable();
+        assert_eq!(drained, (0..10).collect::<Vec<_>>());
+        assert!(crate::mock::with_mocked_blockchain(|b| b.take_storage()).is_empty());
+    }
+
     #[test]
     fn drain_iterator() {
         let mut vec = Vector::new(b"v");
@@ -531,6 +920,7 @@ mod tests {
         Reset,
         Get(u32),
         Swap(u32, u32),
+        Retain(u8),
     }
 
     #[test]
@@ -602,12 +992,17 @@ mod tests {
                             sv.swap(i1, i2);
                             mv.swap(i1 as usize, i2 as usize)
                         }
+                        Op::Retain(d) => {
+                            sv.retain(|v| v % 4 != d % 4);
+                            mv.retain(|v| v % 4 != d % 4);
+                            assert_eq!(sv.len() as usize, mv.len());
+                        }
                     }
                 }
             }
 
             // After all operations, compare both vectors
-            assert!(Iterator::eq(sv.iter(), mv.iter()));
+            assert_eq!(sv, mv);
         }
     }
 
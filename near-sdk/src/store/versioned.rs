@@ -0,0 +1,233 @@
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+use borsh::maybestd::io;
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// An enum with one variant for each version of a value's schema, which can be stored through
+/// [`Versioned`].
+///
+/// This trait should be derived with `#[derive(VersionedState)]` on an enum whose variants each
+/// hold a single version, ordered from the oldest to the current version. Each version must
+/// implement `From` the previous one, and a stored value of an old version is upgraded through
+/// each of the following versions when it is read:
+///
+/// ```
+/// use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+/// use near_sdk::VersionedState;
+///
+/// #[derive(BorshSerialize, BorshDeserialize)]
+/// pub struct AccountV1 {
+///     balance: u64,
+/// }
+///
+/// #[derive(BorshSerialize, BorshDeserialize)]
+/// pub struct Account {
+///     balance: u128,
+///     frozen: bool,
+/// }
+///
+/// impl From<AccountV1> for Account {
+///     fn from(old: AccountV1) -> Self {
+///         Self { balance: old.balance.into(), frozen: false }
+///     }
+/// }
+///
+/// #[derive(BorshSerialize, BorshDeserialize, VersionedState)]
+/// pub enum AccountVersions {
+///     V1(AccountV1),
+///     V2(Account),
+/// }
+/// ```
+pub trait VersionedState: BorshDeserialize {
+    /// The current version of the value.
+    type Current: BorshSerialize;
+
+    /// Index of the variant holding the current version, which is the Borsh tag of the enum
+    /// when the value is stored.
+    const CURRENT_VERSION: u8;
+
+    /// Upgrades the value to the current version.
+    fn into_current(self) -> Self::Current;
+}
+
+/// A value which is stored along with its schema version, so that the schema can evolve while
+/// values of older versions are still in storage.
+///
+/// The value is always stored as the current version of the [`VersionedState`] enum `E`, and a
+/// value of an older version is upgraded to the current version when it is read. Values of older
+/// versions are only rewritten in the current version when they are written back, for example
+/// when they are modified.
+///
+/// # Examples
+/// ```
+/// # use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+/// # use near_sdk::VersionedState;
+/// # #[derive(BorshSerialize, BorshDeserialize)]
+/// # pub struct AccountV1 {
+/// #     balance: u64,
+/// # }
+/// # #[derive(BorshSerialize, BorshDeserialize)]
+/// # pub struct Account {
+/// #     balance: u128,
+/// #     frozen: bool,
+/// # }
+/// # impl From<AccountV1> for Account {
+/// #     fn from(old: AccountV1) -> Self {
+/// #         Self { balance: old.balance.into(), frozen: false }
+/// #     }
+/// # }
+/// # #[derive(BorshSerialize, BorshDeserialize, VersionedState)]
+/// # pub enum AccountVersions {
+/// #     V1(AccountV1),
+/// #     V2(Account),
+/// # }
+/// use near_sdk::store::{LookupMap, Versioned};
+///
+/// // A value which was stored before the schema changed.
+/// let mut old: LookupMap<String, AccountVersions> = LookupMap::new(b"a");
+/// old.insert("alice".to_string(), AccountVersions::V1(AccountV1 { balance: 10 }));
+/// old.flush();
+///
+/// let mut accounts: LookupMap<String, Versioned<AccountVersions>> = LookupMap::new(b"a");
+/// let alice = accounts.get_mut("alice").unwrap();
+/// assert_eq!(alice.balance, 10);
+/// alice.frozen = true;
+///
+/// accounts.insert("bob".to_string(), Versioned::new(Account { balance: 5, frozen: false }));
+/// ```
+pub struct Versioned<E>
+where
+    E: VersionedState,
+{
+    value: E::Current,
+}
+
+impl<E> Versioned<E>
+where
+    E: VersionedState,
+{
+    /// Wraps a value of the current version.
+    pub fn new(value: E::Current) -> Self {
+        Self { value }
+    }
+
+    /// Returns the value of the current version.
+    pub fn into_inner(self) -> E::Current {
+        self.value
+    }
+}
+
+impl<E> BorshSerialize for Versioned<E>
+where
+    E: VersionedState,
+{
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        // Same layout as the Borsh serialization of the enum's current variant.
+        BorshSerialize::serialize(&E::CURRENT_VERSION, writer)?;
+        BorshSerialize::serialize(&self.value, writer)
+    }
+}
+
+impl<E> BorshDeserialize for Versioned<E>
+where
+    E: VersionedState,
+{
+    fn deserialize(buf: &mut &[u8]) -> io::Result<Self> {
+        Ok(Self { value: E::deserialize(buf)?.into_current() })
+    }
+}
+
+impl<E> Deref for Versioned<E>
+where
+    E: VersionedState,
+{
+    type Target = E::Current;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<E> DerefMut for Versioned<E>
+where
+    E: VersionedState,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.value
+    }
+}
+
+impl<E> Clone for Versioned<E>
+where
+    E: VersionedState,
+    E::Current: Clone,
+{
+    fn clone(&self) -> Self {
+        Self { value: self.value.clone() }
+    }
+}
+
+impl<E> PartialEq for Versioned<E>
+where
+    E: VersionedState,
+    E::Current: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<E> Eq for Versioned<E>
+where
+    E: VersionedState,
+    E::Current: Eq,
+{
+}
+
+impl<E> fmt::Debug for Versioned<E>
+where
+    E: VersionedState,
+    E::Current: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Versioned").field(&self.value).finish()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::{Versioned, VersionedState};
+    use borsh::{BorshDeserialize, BorshSerialize};
+
+    #[derive(BorshSerialize, BorshDeserialize)]
+    enum Versions {
+        V1(u8),
+        V2(u16),
+        V3(u32),
+    }
+
+    impl VersionedState for Versions {
+        type Current = u32;
+        const CURRENT_VERSION: u8 = 2;
+
+        fn into_current(self) -> u32 {
+            match self {
+                Self::V1(v) => Self::V2(v.into()).into_current(),
+                Self::V2(v) => Self::V3(v.into()).into_current(),
+                Self::V3(v) => v,
+            }
+        }
+    }
+
+    #[test]
+    fn upgrades_old_versions() {
+        for old in [Versions::V1(7), Versions::V2(7), Versions::V3(7)] {
+            let bytes = old.try_to_vec().unwrap();
+            let value = Versioned::<Versions>::try_from_slice(&bytes).unwrap();
+            assert_eq!(*value, 7);
+            assert_eq!(value.try_to_vec().unwrap(), Versions::V3(7).try_to_vec().unwrap());
+        }
+    }
+}
@@ -2,7 +2,8 @@ use crate::mock::MockedBlockchain;
 use crate::test_utils::test_env::*;
 use crate::AccountId;
 use crate::{
-    Balance, BlockHeight, EpochHeight, Gas, PromiseResult, PublicKey, StorageUsage, VMContext,
+    Balance, BlockHeight, EpochHeight, Gas, PromiseResult, PublicKey, StorageUsage, VMConfig,
+    VMContext,
 };
 
 /// Returns a pre-defined account_id from a list of 6.
@@ -12,6 +13,16 @@ pub fn accounts(id: usize) -> AccountId {
     )
 }
 
+/// Returns `count` distinct random seeds for [`VMContextBuilder::random_seed_array`], which are the
+/// same in every run, to test the contract with several seeds deterministically.
+pub fn random_seeds(count: u64) -> impl Iterator<Item = [u8; 32]> {
+    (0..count).map(|i| {
+        let mut seed = [0; 32];
+        seed[..8].copy_from_slice(&i.to_le_bytes());
+        seed
+    })
+}
+
 /// Simple VMContext builder that allows to quickly create custom context in tests.
 #[derive(Clone)]
 pub struct VMContextBuilder {
@@ -42,7 +53,7 @@ impl VMContextBuilder {
                 storage_usage: 1024 * 300,
                 attached_deposit: 0,
                 prepaid_gas: 300 * 10u64.pow(12),
-                random_seed: vec![0u8; 32],
+                random_seed: vec![],
                 is_view: false,
                 output_data_receivers: vec![],
             },
@@ -114,18 +125,139 @@ impl VMContextBuilder {
         self
     }
 
+    /// Sets the 32-byte random seed of the block, as returned by [`random_seeds`]. Reading the seed
+    /// in a test panics unless it was set, so that the randomness the contract depends on is
+    /// chosen by the test.
+    pub fn random_seed_array(&mut self, seed: [u8; 32]) -> &mut Self {
+        self.context.random_seed = seed.to_vec();
+        self
+    }
+
     pub fn is_view(&mut self, is_view: bool) -> &mut Self {
         self.context.is_view = is_view;
         self
     }
 
+    /// Sets up a view call: no deposit is attached and state changes are not allowed.
+    pub fn view_call(&mut self) -> &mut Self {
+        self.context.is_view = true;
+        self.context.attached_deposit = 0;
+        self
+    }
+
+    /// Sets up a change call with `amount` attached.
+    pub fn payable_call(&mut self, amount: Balance) -> &mut Self {
+        self.context.is_view = false;
+        self.context.attached_deposit = amount;
+        self
+    }
+
     pub fn build(&self) -> VMContext {
         self.context.clone()
     }
 }
 
+/// Simple VMConfig builder that allows to test contracts against other protocol parameters, with
+/// `testing_env!(context, config)`. Starts from the default configuration.
+///
+/// ```
+/// use near_sdk::test_utils::{VMConfigBuilder, VMContextBuilder};
+/// use near_sdk::{testing_env, Gas, RuntimeFeesConfig};
+///
+/// let config = VMConfigBuilder::new().max_gas_burnt(Gas::from_tgas(100)).build();
+/// testing_env!(VMContextBuilder::new().build(), config, RuntimeFeesConfig::default());
+/// ```
+#[derive(Clone)]
+pub struct VMConfigBuilder {
+    pub config: VMConfig,
+}
+
+impl Default for VMConfigBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VMConfigBuilder {
+    pub fn new() -> Self {
+        Self { config: VMConfig::default() }
+    }
+
+    /// Starts from a configuration in which the host functions cost no gas.
+    pub fn free() -> Self {
+        Self { config: VMConfig::free() }
+    }
+
+    pub fn max_gas_burnt(&mut self, gas: Gas) -> &mut Self {
+        self.config.limit_config.max_gas_burnt = gas.0;
+        self
+    }
+
+    pub fn max_gas_burnt_view(&mut self, gas: Gas) -> &mut Self {
+        self.config.limit_config.max_gas_burnt_view = gas.0;
+        self
+    }
+
+    pub fn max_total_prepaid_gas(&mut self, gas: Gas) -> &mut Self {
+        self.config.limit_config.max_total_prepaid_gas = gas.0;
+        self
+    }
+
+    pub fn registers_memory_limit(&mut self, bytes: u64) -> &mut Self {
+        self.config.limit_config.registers_memory_limit = bytes;
+        self
+    }
+
+    pub fn max_register_size(&mut self, bytes: u64) -> &mut Self {
+        self.config.limit_config.max_register_size = bytes;
+        self
+    }
+
+    pub fn max_number_registers(&mut self, count: u64) -> &mut Self {
+        self.config.limit_config.max_number_registers = count;
+        self
+    }
+
+    pub fn max_number_logs(&mut self, count: u64) -> &mut Self {
+        self.config.limit_config.max_number_logs = count;
+        self
+    }
+
+    pub fn max_total_log_length(&mut self, bytes: u64) -> &mut Self {
+        self.config.limit_config.max_total_log_length = bytes;
+        self
+    }
+
+    pub fn max_length_storage_key(&mut self, bytes: u64) -> &mut Self {
+        self.config.limit_config.max_length_storage_key = bytes;
+        self
+    }
+
+    pub fn max_length_storage_value(&mut self, bytes: u64) -> &mut Self {
+        self.config.limit_config.max_length_storage_value = bytes;
+        self
+    }
+
+    pub fn max_length_returned_data(&mut self, bytes: u64) -> &mut Self {
+        self.config.limit_config.max_length_returned_data = bytes;
+        self
+    }
+
+    pub fn max_promises_per_function_call_action(&mut self, count: u64) -> &mut Self {
+        self.config.limit_config.max_promises_per_function_call_action = count;
+        self
+    }
+
+    pub fn build(&self) -> VMConfig {
+        self.config.clone()
+    }
+}
+
 // TODO: This probably shouldn't be necessary with the `testing_env` macro.
 /// Initializes the [`MockedBlockchain`] with a single promise result during execution.
+///
+/// Any number of promise results can be mocked with
+/// `testing_env!(context, promise_results = results)`.
 pub fn testing_env_with_promise_results(context: VMContext, promise_result: PromiseResult) {
     let storage = crate::mock::with_mocked_blockchain(|b| b.take_storage());
 
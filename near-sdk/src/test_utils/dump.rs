@@ -0,0 +1,308 @@
+use borsh::schema::{BorshSchemaContainer, Definition, Fields};
+use borsh::BorshSchema;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::convert::TryInto;
+
+/// Returns the storage of the [`MockedBlockchain`](crate::mock::MockedBlockchain) as pretty JSON,
+/// with the same rendering in every run, for snapshot assertions of the whole state of a contract.
+/// The keys and values are rendered as text if they are printable UTF-8, and as hex prefixed with
+/// `0x` otherwise. The values can be decoded with the Borsh schema of their type with
+/// [`StateDump`]. Only available in unit tests.
+pub fn state_dump() -> String {
+    StateDump::new().to_json()
+}
+
+/// Builder of the JSON rendering of the storage returned by [`state_dump`], which decodes the
+/// values of keys with the Borsh schema of their type. The values of keys starting with a prefix
+/// registered with [`decode`](Self::decode) are decoded as that type, with the longest prefix
+/// taking precedence.
+///
+/// Structs are rendered as objects, or as arrays when their fields are unnamed, enums as the name
+/// of their variant or an object with the variant as the key, `u128` and `i128` as strings, and
+/// byte vectors and arrays as hex.
+///
+/// # Example use
+///
+/// ```
+/// use near_sdk::borsh::{self, BorshSchema, BorshSerialize};
+/// use near_sdk::env;
+/// use near_sdk::test_utils::StateDump;
+///
+/// #[derive(BorshSerialize, BorshSchema)]
+/// struct Contract {
+///     owner: String,
+///     total: u128,
+/// }
+///
+/// env::state_write(&Contract { owner: "alice".to_string(), total: 10 });
+/// env::storage_write(b"balance", &5u64.to_le_bytes());
+/// let dump = StateDump::new().decode::<Contract>(b"STATE").decode::<u64>(b"balance").to_json();
+/// assert_eq!(
+///     dump,
+///     r#"{
+///   "STATE": {
+///     "owner": "alice",
+///     "total": "10"
+///   },
+///   "balance": 5
+/// }"#
+/// );
+/// ```
+#[derive(Default)]
+pub struct StateDump {
+    schemas: Vec<(Vec<u8>, BorshSchemaContainer)>,
+}
+
+impl StateDump {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decodes the values of the keys starting with `prefix` as `T`.
+    pub fn decode<T: BorshSchema>(&mut self, prefix: impl AsRef<[u8]>) -> &mut Self {
+        self.schemas.push((prefix.as_ref().to_vec(), T::schema_container()));
+        self
+    }
+
+    /// Renders the storage of the mocked blockchain.
+    ///
+    /// # Panics
+    ///
+    /// If a value does not match the schema its key was registered with.
+    pub fn to_json(&self) -> String {
+        let snapshot = crate::mock::storage_snapshot();
+        // Sorted by key, so that the rendering does not depend on the order of the storage.
+        let state: BTreeMap<String, Value> = snapshot
+            .storage()
+            .iter()
+            .map(|(key, value)| {
+                let rendered = match self.schema(key) {
+                    Some(schema) => decode_value(schema, value).unwrap_or_else(|error| {
+                        panic!(
+                            "Cannot decode the value of {} as {}: {}",
+                            render_bytes(key),
+                            schema.declaration,
+                            error
+                        )
+                    }),
+                    None => Value::String(render_bytes(value)),
+                };
+                (render_bytes(key), rendered)
+            })
+            .collect();
+        serde_json::to_string_pretty(&state).expect("JSON values are serializable")
+    }
+
+    /// Returns the schema registered with the longest prefix of the key.
+    fn schema(&self, key: &[u8]) -> Option<&BorshSchemaContainer> {
+        self.schemas
+            .iter()
+            .filter(|(prefix, _)| key.starts_with(prefix))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, schema)| schema)
+    }
+}
+
+/// Renders the bytes as text if they are printable UTF-8, and as hex otherwise.
+fn render_bytes(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(text) if !text.is_empty() && !text.chars().any(char::is_control) => text.to_string(),
+        _ => render_hex(bytes),
+    }
+}
+
+fn render_hex(bytes: &[u8]) -> String {
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("0x{}", hex)
+}
+
+fn decode_value(schema: &BorshSchemaContainer, mut value: &[u8]) -> Result<Value, String> {
+    let decoded = decode(schema, &schema.declaration, &mut value)?;
+    if !value.is_empty() {
+        return Err(format!("{} bytes are left", value.len()));
+    }
+    Ok(decoded)
+}
+
+fn take<'a>(buf: &mut &'a [u8], len: usize) -> Result<&'a [u8], String> {
+    if buf.len() < len {
+        return Err("unexpected end of the value".to_string());
+    }
+    let (bytes, rest) = buf.split_at(len);
+    *buf = rest;
+    Ok(bytes)
+}
+
+macro_rules! read_le {
+    ($buf:expr, $type:ty) => {
+        <$type>::from_le_bytes(take($buf, std::mem::size_of::<$type>())?.try_into().unwrap())
+    };
+}
+
+/// Decodes a value of the declared type at the start of `buf`, and advances it past the value.
+fn decode(
+    schema: &BorshSchemaContainer,
+    declaration: &str,
+    buf: &mut &[u8],
+) -> Result<Value, String> {
+    let value = match declaration {
+        "nil" => Value::Null,
+        "bool" => match read_le!(buf, u8) {
+            0 => Value::Bool(false),
+            1 => Value::Bool(true),
+            byte => return Err(format!("invalid bool {}", byte)),
+        },
+        "u8" => read_le!(buf, u8).into(),
+        "u16" => read_le!(buf, u16).into(),
+        "u32" => read_le!(buf, u32).into(),
+        "u64" => read_le!(buf, u64).into(),
+        "u128" => read_le!(buf, u128).to_string().into(),
+        "i8" => read_le!(buf, i8).into(),
+        "i16" => read_le!(buf, i16).into(),
+        "i32" => read_le!(buf, i32).into(),
+        "i64" => read_le!(buf, i64).into(),
+        "i128" => read_le!(buf, i128).to_string().into(),
+        "f32" => read_le!(buf, f32).into(),
+        "f64" => read_le!(buf, f64).into(),
+        "char" => {
+            let code = read_le!(buf, u32);
+            std::char::from_u32(code)
+                .ok_or_else(|| format!("invalid char {}", code))?
+                .to_string()
+                .into()
+        }
+        "string" => {
+            let len = read_le!(buf, u32) as usize;
+            let bytes = take(buf, len)?;
+            std::str::from_utf8(bytes).map_err(|error| error.to_string())?.into()
+        }
+        _ => match schema.definitions.get(declaration) {
+            None => return Err(format!("no definition of {}", declaration)),
+            Some(Definition::Array { length, elements }) => {
+                decode_elements(schema, elements, *length as usize, buf)?
+            }
+            Some(Definition::Sequence { elements }) => {
+                let len = read_le!(buf, u32) as usize;
+                decode_elements(schema, elements, len, buf)?
+            }
+            Some(Definition::Tuple { elements }) => Value::Array(
+                elements
+                    .iter()
+                    .map(|element| decode(schema, element, buf))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Some(Definition::Enum { variants }) => {
+                let index = read_le!(buf, u8);
+                let (name, variant) = variants
+                    .get(index as usize)
+                    .ok_or_else(|| format!("invalid variant {} of {}", index, declaration))?;
+                match decode(schema, variant, buf)? {
+                    Value::Null => Value::String(name.clone()),
+                    value => {
+                        let mut object = Map::new();
+                        object.insert(name.clone(), value);
+                        Value::Object(object)
+                    }
+                }
+            }
+            Some(Definition::Struct { fields: Fields::NamedFields(fields) }) => Value::Object(
+                fields
+                    .iter()
+                    .map(|(name, field)| Ok((name.clone(), decode(schema, field, buf)?)))
+                    .collect::<Result<_, String>>()?,
+            ),
+            Some(Definition::Struct { fields: Fields::UnnamedFields(fields) }) => {
+                let mut values = fields
+                    .iter()
+                    .map(|field| decode(schema, field, buf))
+                    .collect::<Result<Vec<_>, _>>()?;
+                // Newtypes are rendered as the value they wrap.
+                if values.len() == 1 {
+                    values.remove(0)
+                } else {
+                    Value::Array(values)
+                }
+            }
+            Some(Definition::Struct { fields: Fields::Empty }) => Value::Null,
+        },
+    };
+    Ok(value)
+}
+
+fn decode_elements(
+    schema: &BorshSchemaContainer,
+    elements: &str,
+    len: usize,
+    buf: &mut &[u8],
+) -> Result<Value, String> {
+    if elements == "u8" {
+        return Ok(Value::String(render_hex(take(buf, len)?)));
+    }
+    (0..len).map(|_| decode(schema, elements, buf)).collect::<Result<_, _>>().map(Value::Array)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{env, AccountId};
+    use borsh::BorshSerialize;
+
+    #[derive(BorshSerialize, BorshSchema)]
+    enum Status {
+        Active,
+        Paused { until: u64 },
+    }
+
+    #[derive(BorshSerialize, BorshSchema)]
+    struct Contract {
+        owner: AccountId,
+        status: Status,
+        metadata: Option<(i8, Vec<u8>)>,
+    }
+
+    #[test]
+    fn raw_dump() {
+        env::storage_write(b"text", b"value");
+        env::storage_write(&[0, 1], &[2, 3]);
+        assert_eq!(state_dump(), "{\n  \"0x0001\": \"0x0203\",\n  \"text\": \"value\"\n}");
+    }
+
+    #[test]
+    fn schema_dump() {
+        let bob: AccountId = "bob.near".parse().unwrap();
+        let balance_key = [b"b".as_ref(), &bob.try_to_vec().unwrap()].concat();
+        env::storage_write(&balance_key, &7u128.to_le_bytes());
+        env::state_write(&Contract {
+            owner: "alice.near".parse().unwrap(),
+            status: Status::Paused { until: 3 },
+            metadata: Some((-1, vec![0xab])),
+        });
+        let dump: Value = serde_json::from_str(
+            &StateDump::new().decode::<Contract>(b"STATE").decode::<u128>(b"b").to_json(),
+        )
+        .unwrap();
+        assert_eq!(
+            dump,
+            serde_json::json!({
+                "STATE": {
+                    "owner": "alice.near",
+                    "status": { "Paused": { "until": 3 } },
+                    "metadata": { "Some": [-1, "0xab"] },
+                },
+                render_bytes(&balance_key): "7",
+            })
+        );
+
+        env::state_write(&Status::Active);
+        let dump = StateDump::new().decode::<Status>(b"STATE").to_json();
+        assert!(dump.contains(r#""STATE": "Active""#));
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot decode the value of STATE as u64: 4 bytes are left")]
+    fn schema_mismatch() {
+        env::state_write(&(1u64, 2u32));
+        StateDump::new().decode::<u64>(b"STATE").to_json();
+    }
+}
@@ -0,0 +1,96 @@
+use crate::EVENT_JSON_PREFIX;
+use serde::de::DeserializeOwned;
+
+/// Returns the NEP-297 events logged as `EVENT_JSON:` by the contract in the
+/// [`MockedBlockchain`](crate::mock::MockedBlockchain), which can be deserialized into `T`, in the
+/// order they were logged. Events of other types are skipped, and all of them can be read as a
+/// [`serde_json::Value`]. Only available in unit tests.
+pub fn get_events<T: DeserializeOwned>() -> Vec<T> {
+    super::get_logs()
+        .iter()
+        .filter_map(|log| log.strip_prefix(EVENT_JSON_PREFIX))
+        .filter_map(|event| serde_json::from_str(event).ok())
+        .collect()
+}
+
+/// Asserts that the contract logged the NEP-297 `event`, listing the events it did log
+/// otherwise. Only available in unit tests.
+///
+/// # Example use
+///
+/// ```
+/// use near_sdk::serde::Deserialize;
+/// use near_sdk::test_utils::{assert_event_emitted, VMContextBuilder};
+/// use near_sdk::{env, testing_env};
+///
+/// #[derive(Deserialize, Debug, PartialEq)]
+/// #[serde(crate = "near_sdk::serde", tag = "event", content = "data")]
+/// #[serde(rename_all = "snake_case")]
+/// enum Event {
+///     Mint { amount: String },
+/// }
+///
+/// testing_env!(VMContextBuilder::new().build());
+/// env::log_str(
+///     r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"mint","data":{"amount":"1"}}"#,
+/// );
+/// assert_event_emitted(Event::Mint { amount: "1".to_string() });
+/// ```
+pub fn assert_event_emitted<T>(event: T)
+where
+    T: DeserializeOwned + PartialEq + std::fmt::Debug,
+{
+    let events = get_events::<T>();
+    assert!(
+        events.contains(&event),
+        "Event {:?} was not emitted, the events of its type are {:?} and the logs are {:?}",
+        event,
+        events,
+        super::get_logs()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::env;
+    use serde::Deserialize;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    #[serde(crate = "crate::serde")]
+    struct Transfer {
+        standard: String,
+        event: String,
+        data: Vec<TransferData>,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    #[serde(crate = "crate::serde")]
+    struct TransferData {
+        amount: String,
+    }
+
+    #[test]
+    fn typed_events() {
+        env::log_str("plain log");
+        env::log_str(r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_mint"}"#);
+        env::log_str(
+            r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_transfer","data":[{"amount":"5"}]}"#,
+        );
+
+        assert_eq!(get_events::<serde_json::Value>().len(), 2);
+        let transfer = Transfer {
+            standard: "nep141".to_string(),
+            event: "ft_transfer".to_string(),
+            data: vec![TransferData { amount: "5".to_string() }],
+        };
+        assert_eq!(get_events::<Transfer>(), vec![transfer]);
+    }
+
+    #[test]
+    #[should_panic(expected = "was not emitted")]
+    fn missing_event() {
+        env::log_str(r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_mint"}"#);
+        assert_event_emitted(serde_json::json!({ "event": "ft_burn" }));
+    }
+}
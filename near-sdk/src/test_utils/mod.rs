@@ -2,8 +2,24 @@
 pub mod test_env;
 
 mod context;
+mod dump;
+mod events;
+mod panics;
+#[cfg(feature = "test-utils")]
+mod property;
 use crate::mock::Receipt;
-pub use context::{accounts, testing_env_with_promise_results, VMContextBuilder};
+pub use context::{
+    accounts, random_seeds, testing_env_with_promise_results, VMConfigBuilder, VMContextBuilder,
+};
+pub use dump::{state_dump, StateDump};
+pub use events::{assert_event_emitted, get_events};
+#[doc(hidden)]
+pub use panics::assert_panic_matches;
+pub use panics::ExpectedPanic;
+// Not exported from the root of the crate, where glob imports would make `#[test]` ambiguous.
+pub use near_sdk_macros::test;
+#[cfg(feature = "test-utils")]
+pub use property::check_contract_calls;
 
 /// Initializes a testing environment to mock interactions which would otherwise go through a
 /// validator node. This macro will initialize or overwrite the [`MockedBlockchain`]
@@ -24,6 +40,9 @@ pub use context::{accounts, testing_env_with_promise_results, VMContextBuilder};
 ///
 /// Any argument not included will use the default implementation of each.
 ///
+/// To test a callback, the results of the promises it is a callback of can be passed on their own
+/// with `promise_results = `, which [`env::promise_result`] then returns by index.
+///
 /// # Example use
 ///
 /// ```
@@ -50,6 +69,24 @@ pub use context::{accounts, testing_env_with_promise_results, VMContextBuilder};
 /// # }
 /// ```
 ///
+/// Mocking the results of the promises a callback was attached to:
+///
+/// ```
+/// use near_sdk::testing_env;
+/// use near_sdk::test_utils::VMContextBuilder;
+/// use near_sdk::{env, PromiseResult};
+///
+/// let context = VMContextBuilder::new().build();
+/// testing_env!(
+///     context,
+///     promise_results = vec![PromiseResult::Successful(b"\"ok\"".to_vec()), PromiseResult::Failed],
+/// );
+///
+/// assert_eq!(env::promise_results_count(), 2);
+/// assert_eq!(env::promise_result(0), PromiseResult::Successful(b"\"ok\"".to_vec()));
+/// assert_eq!(env::promise_result(1), PromiseResult::Failed);
+/// ```
+///
 /// [`MockedBlockchain`]: crate::mock::MockedBlockchain
 /// [`VMContext`]: crate::VMContext
 /// [`VMConfig`]: crate::VMConfig
@@ -58,8 +95,18 @@ pub use context::{accounts, testing_env_with_promise_results, VMContextBuilder};
 /// [`Balance`]: crate::Balance
 /// [`PromiseResult`]: crate::PromiseResult
 /// [`HashMap`]: std::collections::HashMap
+/// [`env::promise_result`]: crate::env::promise_result
 #[macro_export]
 macro_rules! testing_env {
+    ($context:expr, promise_results = $promise_results:expr $(,)?) => {
+        $crate::testing_env!(
+            $context,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            $promise_results
+        );
+    };
     ($context:expr, $config:expr, $fee_config:expr, $validators:expr, $promise_results:expr $(,)?) => {
         $crate::env::set_blockchain_interface($crate::MockedBlockchain::new(
             $context,
@@ -83,6 +130,106 @@ macro_rules! testing_env {
     };
 }
 
+/// Asserts that evaluating the expression burns at most the given amount of [`Gas`] in the
+/// [`MockedBlockchain`], and returns the value of the expression. This can be used for gas
+/// regression tests, with the gas used by each category of host function available through
+/// [`mock::gas_used`].
+///
+/// # Example use
+///
+/// ```
+/// use near_sdk::{assert_gas_le, env, testing_env, Gas};
+/// use near_sdk::test_utils::VMContextBuilder;
+///
+/// testing_env!(VMContextBuilder::new().build());
+/// let hash = assert_gas_le!(env::sha256(b"data"), Gas::from_tgas(1));
+/// assert_eq!(hash.len(), 32);
+/// ```
+///
+/// [`Gas`]: crate::Gas
+/// [`MockedBlockchain`]: crate::mock::MockedBlockchain
+/// [`mock::gas_used`]: crate::mock::gas_used
+#[macro_export]
+macro_rules! assert_gas_le {
+    ($expr:expr, $limit:expr $(,)?) => {{
+        let before = $crate::mock::gas_used().total;
+        let result = $expr;
+        let used = $crate::mock::gas_used().total - before;
+        let limit: $crate::Gas = $limit;
+        assert!(
+            used <= limit,
+            "`{}` burnt {} of gas, more than the limit of {}",
+            stringify!($expr),
+            used,
+            limit
+        );
+        result
+    }};
+}
+
+/// Asserts that evaluating the expression panics with the expected panic, which is either a
+/// substring of the message or a [`FunctionError`] the contract panicked with. The prefixes added
+/// to the message by the panic hook of the SDK and by the runtime are ignored.
+///
+/// # Example use
+///
+/// ```
+/// use near_sdk::{assert_contract_panics, env};
+///
+/// assert_contract_panics!(env::panic_str("Only the owner can call this method"), "owner");
+/// ```
+///
+/// [`FunctionError`]: crate::FunctionError
+#[macro_export]
+macro_rules! assert_contract_panics {
+    ($expr:expr, $expected:expr $(,)?) => {
+        $crate::test_utils::assert_panic_matches(
+            ::std::panic::catch_unwind(::std::panic::AssertUnwindSafe(|| {
+                let _ = $expr;
+            })),
+            stringify!($expr),
+            &$expected,
+        )
+    };
+}
+
+/// Checks a property of a contract against sequences of calls with arbitrary arguments, with
+/// [`check_contract_calls`]. `call` applies an operation deriving [`Arbitrary`] to the contract,
+/// and `invariant` asserts the property. 256 cases are run unless `cases` is given.
+///
+/// # Example use
+///
+/// ```
+/// use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+/// use near_sdk::proptest_contract;
+///
+/// #[derive(BorshSerialize, BorshDeserialize, Default)]
+/// struct Counter {
+///     value: u8,
+/// }
+///
+/// proptest_contract! {
+///     init: Counter::default(),
+///     call: |counter: &mut Counter, increment: u8| {
+///         counter.value = counter.value.checked_add(increment).expect("Overflow");
+///     },
+///     invariant: |counter: &Counter| assert!(counter.value <= u8::MAX),
+/// }
+/// ```
+///
+/// [`check_contract_calls`]: crate::test_utils::check_contract_calls
+/// [`Arbitrary`]: arbitrary::Arbitrary
+#[cfg(feature = "test-utils")]
+#[macro_export]
+macro_rules! proptest_contract {
+    (cases: $cases:expr, init: $init:expr, call: $call:expr, invariant: $invariant:expr $(,)?) => {
+        $crate::test_utils::check_contract_calls($cases, || $init, $call, $invariant)
+    };
+    (init: $init:expr, call: $call:expr, invariant: $invariant:expr $(,)?) => {
+        $crate::proptest_contract!(cases: 256, init: $init, call: $call, invariant: $invariant)
+    };
+}
+
 #[allow(dead_code)]
 /// Returns a copy of logs from VMLogic. Only available in unit tests.
 pub fn get_logs() -> Vec<String> {
@@ -90,9 +237,10 @@ pub fn get_logs() -> Vec<String> {
 }
 
 /// Accessing receipts created by the contract. Only available in unit tests.
+/// Same as [`mock::created_receipts`](crate::mock::created_receipts).
 #[allow(dead_code)]
 pub fn get_created_receipts() -> Vec<Receipt> {
-    crate::mock::with_mocked_blockchain(|b| b.created_receipts().clone())
+    crate::mock::created_receipts()
 }
 
 /// Objects stored on the trie directly should have identifiers. If identifier is not provided
@@ -0,0 +1,124 @@
+use std::any::Any;
+
+use crate::FunctionError;
+
+/// Prefix of the panic messages of contracts reported by the runtime.
+const CONTRACT_PANIC_PREFIX: &str = "Smart contract panicked: ";
+
+/// A panic expected by [`assert_contract_panics!`](crate::assert_contract_panics): either a
+/// substring of the message, or a [`FunctionError`] which the contract panicked with.
+pub trait ExpectedPanic {
+    /// Returns `true` if the panic message, without the prefixes added by the SDK and the
+    /// runtime, matches the expected panic.
+    fn matches(&self, message: &str) -> bool;
+
+    /// Describes the expected panic in the message of a failed assertion.
+    fn describe(&self) -> String;
+}
+
+impl ExpectedPanic for &str {
+    fn matches(&self, message: &str) -> bool {
+        message.contains(self)
+    }
+
+    fn describe(&self) -> String {
+        format!("a panic containing {:?}", self)
+    }
+}
+
+impl ExpectedPanic for String {
+    fn matches(&self, message: &str) -> bool {
+        message.contains(self.as_str())
+    }
+
+    fn describe(&self) -> String {
+        format!("a panic containing {:?}", self)
+    }
+}
+
+impl<T: FunctionError> ExpectedPanic for T {
+    fn matches(&self, message: &str) -> bool {
+        message == self.panic_message()
+    }
+
+    fn describe(&self) -> String {
+        format!("a panic with the error {}", self.panic_message())
+    }
+}
+
+/// Removes the prefixes added to a panic message by the panic hook of the SDK and by the
+/// runtime, which are also added by the default panic hook in tests.
+fn strip_panic_prefixes(message: &str) -> &str {
+    let message = message.strip_prefix(CONTRACT_PANIC_PREFIX).unwrap_or(message);
+    if let Some(rest) = message.strip_prefix("panicked at '") {
+        // Formatted as `panicked at 'message', src/lib.rs:1:1` before Rust 1.73.
+        if let Some(end) = rest.rfind("', ") {
+            return &rest[..end];
+        }
+    } else if let Some(rest) = message.strip_prefix("panicked at ") {
+        // Formatted as `panicked at src/lib.rs:1:1:\nmessage` since Rust 1.73.
+        if let Some((_, message)) = rest.split_once(":\n") {
+            return message;
+        }
+    }
+    message
+}
+
+#[doc(hidden)]
+pub fn assert_panic_matches<E: ExpectedPanic>(
+    result: Result<(), Box<dyn Any + Send>>,
+    expr: &str,
+    expected: &E,
+) {
+    let payload = match result {
+        Ok(()) => panic!("`{}` did not panic, expected {}", expr, expected.describe()),
+        Err(payload) => payload,
+    };
+    let message = payload
+        .downcast_ref::<String>()
+        .map(String::as_str)
+        .or_else(|| payload.downcast_ref::<&str>().copied())
+        .unwrap_or_default();
+    let message = strip_panic_prefixes(message);
+    assert!(
+        expected.matches(message),
+        "`{}` panicked with {:?}, expected {}",
+        expr,
+        message,
+        expected.describe()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::env;
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    #[serde(crate = "crate::serde")]
+    enum Error {
+        NotOwner,
+    }
+
+    impl FunctionError for Error {}
+
+    #[test]
+    fn strips_prefixes() {
+        assert_eq!(strip_panic_prefixes("Smart contract panicked: Not owner"), "Not owner");
+        assert_eq!(strip_panic_prefixes("panicked at 'Not owner', src/lib.rs:1:1"), "Not owner");
+        assert_eq!(strip_panic_prefixes("panicked at src/lib.rs:1:1:\nNot owner"), "Not owner");
+    }
+
+    #[test]
+    fn contract_panics() {
+        crate::assert_contract_panics!(env::panic_str("Not the owner"), "owner");
+        crate::assert_contract_panics!(env::panic_err(Error::NotOwner), Error::NotOwner);
+    }
+
+    #[test]
+    #[should_panic(expected = "did not panic")]
+    fn no_panic() {
+        crate::assert_contract_panics!(env::block_height(), "owner");
+    }
+}
@@ -0,0 +1,137 @@
+use std::fmt::Debug;
+use std::panic::{self, AssertUnwindSafe};
+
+use arbitrary::{Arbitrary, Unstructured};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::VMContextBuilder;
+use crate::{env, mock};
+
+/// Number of bytes the calls of a case are generated from.
+const CASE_BYTES: usize = 4096;
+
+/// Runs `cases` sequences of calls with arbitrary arguments against the contract created by
+/// `init`, each in a new mocked blockchain. Like a contract deployed on chain, the contract is
+/// read from storage before each call and written back after it, and the changes of a call which
+/// panics are reverted. `invariant` is checked after every call, and its failure is reported along
+/// with the case and the calls which led to it.
+///
+/// The cases are generated from a fixed seed, so that a failure can be reproduced.
+pub fn check_contract_calls<C, Op>(
+    cases: u64,
+    init: impl Fn() -> C,
+    call: impl Fn(&mut C, Op),
+    invariant: impl Fn(&C),
+) where
+    C: BorshSerialize + BorshDeserialize,
+    Op: for<'a> Arbitrary<'a> + Debug,
+{
+    let mut bytes = vec![0; CASE_BYTES];
+    for case in 0..cases {
+        fill_bytes(case, &mut bytes);
+        let ops = match Vec::<Op>::arbitrary_take_rest(Unstructured::new(&bytes)) {
+            Ok(ops) => ops,
+            Err(_) => continue,
+        };
+
+        mock::with_mocked_blockchain(|b| b.take_storage());
+        crate::testing_env!(VMContextBuilder::new().build());
+        env::state_write(&init());
+
+        let mut calls = Vec::with_capacity(ops.len());
+        for op in ops {
+            calls.push(format!("{:?}", op));
+            let snapshot = mock::storage_snapshot();
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                let mut contract: C = read_contract();
+                call(&mut contract, op);
+                env::state_write(&contract);
+            }));
+            if result.is_err() {
+                mock::restore(snapshot);
+            }
+
+            let contract: C = read_contract();
+            if let Err(err) = panic::catch_unwind(AssertUnwindSafe(|| invariant(&contract))) {
+                let message = err
+                    .downcast_ref::<String>()
+                    .map(String::as_str)
+                    .or_else(|| err.downcast_ref::<&str>().copied())
+                    .unwrap_or_default();
+                panic!("Invariant failed in case {} after calls {:?}: {}", case, calls, message);
+            }
+        }
+    }
+}
+
+fn read_contract<C: BorshDeserialize>() -> C {
+    env::state_read().unwrap_or_else(|| env::panic_str("Contract state is missing"))
+}
+
+/// Fills the buffer with the pseudorandom bytes of the case, with the SplitMix64 generator.
+fn fill_bytes(case: u64, bytes: &mut [u8]) {
+    let mut state = case;
+    for chunk in bytes.chunks_mut(8) {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        chunk.copy_from_slice(&z.to_le_bytes()[..chunk.len()]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AccountId;
+
+    #[derive(BorshSerialize, BorshDeserialize, Default)]
+    struct Ledger {
+        total: u64,
+        accounts: Vec<(AccountId, u64)>,
+    }
+
+    #[derive(Arbitrary, Debug)]
+    enum Op {
+        Deposit(AccountId, u8),
+        Withdraw(u8),
+    }
+
+    fn apply(ledger: &mut Ledger, op: Op) {
+        match op {
+            Op::Deposit(account_id, amount) => {
+                ledger.total += amount as u64;
+                ledger.accounts.push((account_id, amount as u64));
+            }
+            Op::Withdraw(amount) => {
+                let (_, balance) = ledger.accounts.last_mut().expect("No accounts");
+                // The balance is updated before the check, which reverts it.
+                *balance = balance.checked_sub(amount as u64).expect("Not enough balance");
+                ledger.total -= amount as u64;
+            }
+        }
+    }
+
+    #[test]
+    fn reverts_failed_calls() {
+        crate::proptest_contract! {
+            cases: 32,
+            init: Ledger::default(),
+            call: apply,
+            invariant: |ledger: &Ledger| {
+                assert_eq!(ledger.total, ledger.accounts.iter().map(|(_, b)| b).sum::<u64>());
+            },
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Invariant failed in case")]
+    fn reports_failures() {
+        crate::proptest_contract! {
+            init: Ledger::default(),
+            call: apply,
+            invariant: |ledger: &Ledger| assert!(ledger.total < 100),
+        }
+    }
+}
@@ -54,6 +54,68 @@ impl AccountId {
         debug_assert!(is_valid_account_id(id.as_bytes()));
         Self(id)
     }
+
+    /// Returns `true` if the account is a top-level account, which has no parent, like `near`.
+    pub fn is_top_level(&self) -> bool {
+        !self.0.contains('.')
+    }
+
+    /// Returns `true` if the account is an implicit account, whose ID is the 64 character
+    /// lowercase hex encoding of an ed25519 public key.
+    pub fn is_implicit(&self) -> bool {
+        self.0.len() == 64 && self.0.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+    }
+
+    /// Returns `true` if the account is a direct sub-account of `parent`, such as `alice.near`
+    /// of `near`. An account is not a sub-account of itself, nor of its parent's parent.
+    ///
+    /// # Examples
+    /// ```
+    /// use near_sdk::AccountId;
+    ///
+    /// let near: AccountId = "near".parse().unwrap();
+    /// let alice: AccountId = "alice.near".parse().unwrap();
+    /// let app: AccountId = "app.alice.near".parse().unwrap();
+    ///
+    /// assert!(alice.is_sub_account_of(&near));
+    /// assert!(!app.is_sub_account_of(&near));
+    /// assert_eq!(app.parent(), Some(alice));
+    /// assert_eq!(near.parent(), None);
+    /// ```
+    pub fn is_sub_account_of(&self, parent: &AccountId) -> bool {
+        match self.0.strip_suffix(parent.as_str()).and_then(|name| name.strip_suffix('.')) {
+            Some(name) => !name.contains('.'),
+            None => false,
+        }
+    }
+
+    /// Returns the account this account is a direct sub-account of, or [`None`] if the account
+    /// is a top-level account.
+    pub fn parent(&self) -> Option<AccountId> {
+        self.0.split_once('.').map(|(_, parent)| Self(parent.to_string()))
+    }
+
+    /// Returns the ID of the direct sub-account `name` of this account, such as `app.alice.near`
+    /// for `app` and `alice.near`.
+    ///
+    /// # Errors
+    /// Returns an error if `name` contains a `.` or the resulting account ID is invalid.
+    ///
+    /// # Examples
+    /// ```
+    /// use near_sdk::AccountId;
+    ///
+    /// let factory: AccountId = "factory.near".parse().unwrap();
+    /// assert_eq!(factory.try_sub_account("token").unwrap().as_str(), "token.factory.near");
+    /// assert!(factory.try_sub_account("a.token").is_err());
+    /// assert!(factory.try_sub_account("Token").is_err());
+    /// ```
+    pub fn try_sub_account(&self, name: &str) -> Result<AccountId, ParseAccountIdError> {
+        if name.contains('.') {
+            return Err(ParseAccountIdError {});
+        }
+        Self::try_from(format!("{}.{}", name, self.0))
+    }
 }
 
 impl fmt::Display for AccountId {
@@ -130,6 +192,35 @@ impl fmt::Display for ParseAccountIdError {
 
 impl std::error::Error for ParseAccountIdError {}
 
+/// Generates valid account IDs of one to three parts of lowercase letters and digits.
+#[cfg(feature = "abi")]
+impl schemars::JsonSchema for AccountId {
+    fn schema_name() -> String {
+        "AccountId".to_string()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        <String as schemars::JsonSchema>::json_schema(gen)
+    }
+}
+
+#[cfg(feature = "test-utils")]
+impl<'a> arbitrary::Arbitrary<'a> for AccountId {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        const CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789";
+        let mut account_id = String::new();
+        for part in 0..u.int_in_range(1..=3)? {
+            if part > 0 {
+                account_id.push('.');
+            }
+            for _ in 0..u.int_in_range(2..=8)? {
+                account_id.push(*u.choose(CHARS)? as char);
+            }
+        }
+        Ok(Self(account_id))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -164,4 +255,34 @@ mod tests {
         // Test to make sure the account ID is serialized as a string through borsh
         assert_eq!(str::try_to_vec(id).unwrap(), account_id.try_to_vec().unwrap());
     }
+
+    #[test]
+    fn test_sub_accounts() {
+        let near: AccountId = "near".parse().unwrap();
+        let alice: AccountId = "alice.near".parse().unwrap();
+        assert!(near.is_top_level());
+        assert!(!alice.is_top_level());
+        assert!(!near.is_sub_account_of(&near));
+        assert!(!"alicenear".parse::<AccountId>().unwrap().is_sub_account_of(&near));
+        assert_eq!(alice.parent(), Some(near.clone()));
+        assert_eq!(near.try_sub_account("alice"), Ok(alice));
+
+        let implicit: AccountId = "a".repeat(64).parse().unwrap();
+        assert!(implicit.is_implicit());
+        assert!(!near.is_implicit());
+        assert!(!"g".repeat(64).parse::<AccountId>().unwrap().is_implicit());
+    }
+
+    #[cfg(feature = "test-utils")]
+    #[test]
+    fn test_arbitrary_account_ids_are_valid() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        let bytes: Vec<u8> = (0..=255).collect();
+        let mut u = Unstructured::new(&bytes);
+        for _ in 0..32 {
+            let account_id = AccountId::arbitrary(&mut u).unwrap();
+            assert!(account_id.as_str().parse::<AccountId>().is_ok(), "{}", account_id);
+        }
+    }
 }
@@ -0,0 +1,57 @@
+//! Points and scalars of the alt_bn128 curve, used by the
+//! [`env::alt_bn128_g1_multiexp`](crate::env::alt_bn128_g1_multiexp),
+//! [`env::alt_bn128_g1_sum`](crate::env::alt_bn128_g1_sum) and
+//! [`env::alt_bn128_pairing_check`](crate::env::alt_bn128_pairing_check) host functions.
+//!
+//! Field elements are 256-bit numbers serialized as 32 little-endian bytes, and the Borsh
+//! serialization of the types matches the format expected by the host functions.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// A point of the G1 group of alt_bn128, the curve `y^2 = x^3 + 3` over the base field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, BorshSerialize, BorshDeserialize)]
+pub struct G1Point {
+    /// The x coordinate, in little-endian bytes.
+    pub x: [u8; 32],
+    /// The y coordinate, in little-endian bytes.
+    pub y: [u8; 32],
+}
+
+/// A point of the G2 group of alt_bn128, on the twist of the curve over the quadratic extension
+/// of the base field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, BorshSerialize, BorshDeserialize)]
+pub struct G2Point {
+    /// The x coordinate, as the real and imaginary parts in little-endian bytes.
+    pub x: [[u8; 32]; 2],
+    /// The y coordinate, as the real and imaginary parts in little-endian bytes.
+    pub y: [[u8; 32]; 2],
+}
+
+/// An element of the scalar field of alt_bn128, in little-endian bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, BorshSerialize, BorshDeserialize)]
+pub struct Scalar(pub [u8; 32]);
+
+impl From<u128> for Scalar {
+    fn from(value: u128) -> Self {
+        let mut bytes = [0; 32];
+        bytes[..16].copy_from_slice(&value.to_le_bytes());
+        Self(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{G1Point, Scalar};
+    use borsh::BorshSerialize;
+
+    #[test]
+    fn host_function_layout() {
+        let point = G1Point { x: [1; 32], y: [2; 32] };
+        let bytes = [(point, Scalar::from(3))].as_ref().try_to_vec().unwrap();
+        assert_eq!(bytes.len(), 4 + 64 + 32);
+        assert_eq!(&bytes[..4], &[1, 0, 0, 0]);
+        assert_eq!(&bytes[4..68], [[1; 32], [2; 32]].concat());
+        assert_eq!(bytes[68], 3);
+        assert!(bytes[69..].iter().all(|&b| b == 0));
+    }
+}
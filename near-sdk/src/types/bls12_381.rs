@@ -0,0 +1,64 @@
+//! Points and field elements of the BLS12-381 curve, used by the `env::bls12381_*` host
+//! functions, such as [`env::bls12381_pairing_check`](crate::env::bls12381_pairing_check).
+//!
+//! Points are in the uncompressed big-endian encoding of the ZCash BLS12-381 specification, and
+//! scalars are 256-bit numbers in little-endian bytes, as expected by the host functions.
+
+/// A point of the G1 group of BLS12-381, as the x and y coordinates of 48 bytes each.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct G1Point(pub [u8; 96]);
+
+/// A point of the G2 group of BLS12-381, as the x and y coordinates of 96 bytes each.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct G2Point(pub [u8; 192]);
+
+/// An element of the base field of BLS12-381, in big-endian bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Fp(pub [u8; 48]);
+
+/// An element of the quadratic extension of the base field of BLS12-381, as the imaginary and
+/// real parts in big-endian bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Fp2(pub [u8; 96]);
+
+/// A multiplier of points, in little-endian bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Scalar(pub [u8; 32]);
+
+impl From<u128> for Scalar {
+    fn from(value: u128) -> Self {
+        let mut bytes = [0; 32];
+        bytes[..16].copy_from_slice(&value.to_le_bytes());
+        Self(bytes)
+    }
+}
+
+impl AsRef<[u8]> for G1Point {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for G2Point {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for Fp {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for Fp2 {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for Scalar {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
@@ -0,0 +1,80 @@
+use serde::Serialize;
+
+/// Prefix of the logs which hold a NEP-297 event as JSON.
+pub const EVENT_JSON_PREFIX: &str = "EVENT_JSON:";
+
+/// Envelope of a [NEP-297](https://nomicon.io/Standards/EventsFormat) event, which is logged as
+/// JSON after [`EVENT_JSON_PREFIX`] so that indexers can parse the events of the standards.
+///
+/// The events of a contract are usually declared as an enum with `#[derive(NearEvent)]`, which
+/// builds the envelope of each variant.
+///
+/// # Examples
+/// ```
+/// use near_sdk::EventEnvelope;
+///
+/// let event = EventEnvelope {
+///     standard: "nep171",
+///     version: "1.0.0",
+///     event: "nft_burn",
+///     data: Some([["token-1"]]),
+/// };
+/// assert_eq!(
+///     near_sdk::serde_json::from_str::<near_sdk::serde_json::Value>(&event.to_json()).unwrap(),
+///     near_sdk::serde_json::json!({
+///         "standard": "nep171",
+///         "version": "1.0.0",
+///         "event": "nft_burn",
+///         "data": [["token-1"]],
+///     })
+/// );
+/// ```
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct EventEnvelope<'a, T> {
+    /// Name of the standard, such as `nep171`.
+    pub standard: &'a str,
+    /// Version of the standard, such as `1.0.0`.
+    pub version: &'a str,
+    /// Name of the event, such as `nft_mint`.
+    pub event: &'a str,
+    /// Data of the event, which is left out of the JSON when [`None`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<T>,
+}
+
+impl<T: Serialize> EventEnvelope<'_, T> {
+    /// Returns the event as JSON, which is [canonical](crate::json_types::canonical) with the
+    /// `canonical-json` feature, as are the results of the methods.
+    pub fn to_json(&self) -> String {
+        let json = crate::json_types::canonical::result_to_vec(self)
+            .unwrap_or_else(|_| crate::env::abort());
+        // Only valid UTF-8 is written, as the strings are written by `serde_json`.
+        String::from_utf8(json).unwrap_or_else(|_| crate::env::abort())
+    }
+
+    /// Logs the event as JSON after [`EVENT_JSON_PREFIX`].
+    pub fn emit(&self) {
+        crate::env::log_str(&format!("{}{}", EVENT_JSON_PREFIX, self.to_json()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EventEnvelope;
+
+    #[test]
+    fn to_json() {
+        let event = EventEnvelope {
+            standard: "nep171",
+            version: "1.0.0",
+            event: "nft_burn",
+            data: Some([["token-1"]]),
+        };
+        let expected = if cfg!(feature = "canonical-json") {
+            r#"{"data":[["token-1"]],"event":"nft_burn","standard":"nep171","version":"1.0.0"}"#
+        } else {
+            r#"{"standard":"nep171","version":"1.0.0","event":"nft_burn","data":[["token-1"]]}"#
+        };
+        assert_eq!(event.to_json(), expected);
+    }
+}
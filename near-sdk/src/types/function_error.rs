@@ -0,0 +1,81 @@
+use serde::Serialize;
+
+/// An error which a contract function can panic with through [`env::panic_err`], so that callers
+/// and indexers can parse the reason of the failure from the panic message. It can be implemented
+/// with `#[derive(FunctionError)]`.
+///
+/// By default, the error is serialized as JSON.
+///
+/// # Examples
+/// ```no_run
+/// use near_sdk::serde::Serialize;
+/// use near_sdk::{env, FunctionError};
+///
+/// #[derive(Serialize, FunctionError)]
+/// #[serde(crate = "near_sdk::serde", tag = "kind")]
+/// enum TransferError {
+///     InsufficientBalance { available: String },
+///     UnknownAccount,
+/// }
+///
+/// // Panics with `{"kind":"InsufficientBalance","available":"10"}`.
+/// env::panic_err(TransferError::InsufficientBalance { available: "10".to_string() });
+/// ```
+///
+/// The methods of a contract which return `Result<T, E>` and are marked with `#[handle_result]`
+/// return `T`, or abort with the error when they return `Err`, without writing the state:
+///
+/// ```
+/// use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+/// use near_sdk::serde::Serialize;
+/// use near_sdk::{near_bindgen, FunctionError};
+///
+/// #[derive(Serialize, FunctionError)]
+/// #[serde(crate = "near_sdk::serde")]
+/// pub struct NotEnough {
+///     available: u64,
+/// }
+///
+/// #[near_bindgen]
+/// #[derive(Default, BorshDeserialize, BorshSerialize)]
+/// pub struct Counter {
+///     value: u64,
+/// }
+///
+/// #[near_bindgen]
+/// impl Counter {
+///     #[handle_result]
+///     pub fn decrement(&mut self, by: u64) -> Result<u64, NotEnough> {
+///         self.value = self.value.checked_sub(by).ok_or(NotEnough { available: self.value })?;
+///         Ok(self.value)
+///     }
+/// }
+/// ```
+///
+/// [`env::panic_err`]: crate::env::panic_err
+pub trait FunctionError: Serialize {
+    /// Returns the message the contract panics with for this error.
+    fn panic_message(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|_| crate::env::abort())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FunctionError;
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    #[serde(tag = "kind")]
+    enum Error {
+        NotFound { key: String },
+    }
+
+    impl FunctionError for Error {}
+
+    #[test]
+    fn json_message() {
+        let error = Error::NotFound { key: "a".to_string() };
+        assert_eq!(error.panic_message(), r#"{"kind":"NotFound","key":"a"}"#);
+    }
+}
@@ -1,5 +1,5 @@
 use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
-use core::ops;
+use core::{fmt, ops};
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
 /// Represents the amount of NEAR tokens in "gas units" which are used to fund transactions.
@@ -20,6 +20,85 @@ use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 #[repr(transparent)]
 pub struct Gas(pub u64);
 
+const ONE_TERA: u64 = 10u64.pow(12);
+
+impl Gas {
+    /// Creates an amount of gas from a number of gas units.
+    pub const fn from_gas(gas: u64) -> Self {
+        Self(gas)
+    }
+
+    /// Creates an amount of gas from a number of teragas (10^12 gas units).
+    pub const fn from_tgas(tgas: u64) -> Self {
+        Self(tgas * ONE_TERA)
+    }
+
+    /// Returns the amount in gas units.
+    pub const fn as_gas(&self) -> u64 {
+        self.0
+    }
+
+    /// Returns the amount in whole teragas, rounded down.
+    pub const fn as_tgas(&self) -> u64 {
+        self.0 / ONE_TERA
+    }
+
+    /// Adds two amounts of gas, returning [`None`] on overflow.
+    pub const fn checked_add(self, rhs: Self) -> Option<Self> {
+        match self.0.checked_add(rhs.0) {
+            Some(gas) => Some(Self(gas)),
+            None => None,
+        }
+    }
+
+    /// Subtracts an amount of gas, returning [`None`] if `rhs` is greater than `self`.
+    pub const fn checked_sub(self, rhs: Self) -> Option<Self> {
+        match self.0.checked_sub(rhs.0) {
+            Some(gas) => Some(Self(gas)),
+            None => None,
+        }
+    }
+
+    /// Multiplies the amount of gas, returning [`None`] on overflow.
+    pub const fn checked_mul(self, rhs: u64) -> Option<Self> {
+        match self.0.checked_mul(rhs) {
+            Some(gas) => Some(Self(gas)),
+            None => None,
+        }
+    }
+
+    /// Divides the amount of gas, rounding down, returning [`None`] if `rhs` is zero.
+    pub const fn checked_div(self, rhs: u64) -> Option<Self> {
+        match self.0.checked_div(rhs) {
+            Some(gas) => Some(Self(gas)),
+            None => None,
+        }
+    }
+
+    /// Adds two amounts of gas, saturating at the maximum amount.
+    pub const fn saturating_add(self, rhs: Self) -> Self {
+        Self(self.0.saturating_add(rhs.0))
+    }
+
+    /// Subtracts an amount of gas, saturating at zero.
+    pub const fn saturating_sub(self, rhs: Self) -> Self {
+        Self(self.0.saturating_sub(rhs.0))
+    }
+
+    /// Multiplies the amount of gas, saturating at the maximum amount.
+    pub const fn saturating_mul(self, rhs: u64) -> Self {
+        Self(self.0.saturating_mul(rhs))
+    }
+}
+
+/// Displays the amount in teragas, for example `30 Tgas` or `2.5 Tgas`.
+impl fmt::Display for Gas {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        super::near_token::fmt_decimal(f, self.0.into(), 12)?;
+        f.write_str(" Tgas")
+    }
+}
+
 impl Serialize for Gas {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -40,6 +119,17 @@ impl Serialize for Gas {
     }
 }
 
+#[cfg(feature = "abi")]
+impl schemars::JsonSchema for Gas {
+    fn schema_name() -> String {
+        "Gas".to_string()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        <String as schemars::JsonSchema>::json_schema(gen)
+    }
+}
+
 impl<'de> Deserialize<'de> for Gas {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -132,4 +222,11 @@ mod tests {
         test_json_ser(8);
         test_json_ser(0);
     }
+
+    #[test]
+    fn display() {
+        assert_eq!(Gas::from_tgas(30).to_string(), "30 Tgas");
+        assert_eq!((Gas::from_tgas(5) / 2).to_string(), "2.5 Tgas");
+        assert_eq!(Gas::from_gas(0).to_string(), "0 Tgas");
+    }
 }
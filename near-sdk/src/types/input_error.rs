@@ -0,0 +1,63 @@
+/// Error returned by [`env::input_as_json`] and [`env::input_as_borsh`] when the input of the
+/// contract call is missing or cannot be deserialized.
+///
+/// [`env::input_as_json`]: crate::env::input_as_json
+/// [`env::input_as_borsh`]: crate::env::input_as_borsh
+#[derive(Debug)]
+pub struct ParseInputError {
+    kind: ParseInputErrorKind,
+}
+
+#[derive(Debug)]
+enum ParseInputErrorKind {
+    Missing,
+    Json(serde_json::Error),
+    Borsh(std::io::Error),
+}
+
+impl ParseInputError {
+    pub(crate) fn missing() -> Self {
+        Self { kind: ParseInputErrorKind::Missing }
+    }
+
+    /// Returns `true` if the contract call has no input.
+    pub fn is_missing(&self) -> bool {
+        matches!(self.kind, ParseInputErrorKind::Missing)
+    }
+}
+
+impl std::fmt::Display for ParseInputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            ParseInputErrorKind::Missing => write!(f, "the contract call has no input"),
+            ParseInputErrorKind::Json(e) => {
+                write!(f, "failed to deserialize input from JSON: {}", e)
+            }
+            ParseInputErrorKind::Borsh(e) => {
+                write!(f, "failed to deserialize input from Borsh: {}", e)
+            }
+        }
+    }
+}
+
+impl From<serde_json::Error> for ParseInputError {
+    fn from(e: serde_json::Error) -> Self {
+        Self { kind: ParseInputErrorKind::Json(e) }
+    }
+}
+
+impl From<std::io::Error> for ParseInputError {
+    fn from(e: std::io::Error) -> Self {
+        Self { kind: ParseInputErrorKind::Borsh(e) }
+    }
+}
+
+impl std::error::Error for ParseInputError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            ParseInputErrorKind::Missing => None,
+            ParseInputErrorKind::Json(e) => Some(e),
+            ParseInputErrorKind::Borsh(e) => Some(e),
+        }
+    }
+}
@@ -4,6 +4,15 @@ pub use self::vm_types::*;
 mod public_key;
 pub use self::public_key::{CurveType, PublicKey};
 
+pub mod alt_bn128;
+
+pub mod bls12_381;
+
+mod signature;
+pub use self::signature::{
+    Ed25519Signature, ParseSignatureError, Secp256K1PublicKey, Secp256k1Signature,
+};
+
 mod primitives;
 pub use self::primitives::*;
 
@@ -13,6 +22,24 @@ pub use self::account_id::{AccountId, ParseAccountIdError};
 mod gas;
 pub use self::gas::Gas;
 
+mod near_token;
+pub use self::near_token::NearToken;
+
+mod page;
+pub use self::page::Page;
+
+mod function_error;
+pub use self::function_error::FunctionError;
+
+mod event;
+pub use self::event::{EventEnvelope, EVENT_JSON_PREFIX};
+
+mod input_error;
+pub use self::input_error::ParseInputError;
+
+mod state_version;
+pub use self::state_version::StateVersionMismatch;
+
 /// Raw type for duration in nanoseconds
 pub type Duration = u64;
 
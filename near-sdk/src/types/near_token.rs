@@ -0,0 +1,196 @@
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use core::fmt;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use super::Balance;
+
+const ONE_NEAR: u128 = 10u128.pow(24);
+const ONE_MILLINEAR: u128 = 10u128.pow(21);
+
+/// An amount of NEAR tokens, stored in yoctoNEAR (10^-24 NEAR).
+///
+/// Unlike [`Balance`], amounts are created through constructors which name the unit, so that an
+/// amount in NEAR cannot be mistaken for an amount in yoctoNEAR. Amounts are displayed in NEAR,
+/// and serialized with serde as a string of yoctoNEAR, like [`U128`](crate::json_types::U128).
+///
+/// # Examples
+/// ```
+/// use near_sdk::NearToken;
+///
+/// let deposit = NearToken::from_millinear(1500);
+/// assert_eq!(deposit, NearToken::from_yoctonear(1_500_000_000_000_000_000_000_000));
+/// assert_eq!(deposit.to_string(), "1.5 NEAR");
+/// assert_eq!(deposit.checked_sub(NearToken::from_near(2)), None);
+/// assert_eq!(deposit.saturating_sub(NearToken::from_near(2)), NearToken::from_near(0));
+/// ```
+#[derive(
+    Default,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    PartialOrd,
+    Ord,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Hash,
+    BorshSchema,
+)]
+#[repr(transparent)]
+pub struct NearToken(Balance);
+
+impl NearToken {
+    /// Creates an amount from a number of yoctoNEAR.
+    pub const fn from_yoctonear(yoctonear: Balance) -> Self {
+        Self(yoctonear)
+    }
+
+    /// Creates an amount from a number of milliNEAR (10^-3 NEAR).
+    pub const fn from_millinear(millinear: u128) -> Self {
+        Self(millinear * ONE_MILLINEAR)
+    }
+
+    /// Creates an amount from a number of NEAR.
+    pub const fn from_near(near: u128) -> Self {
+        Self(near * ONE_NEAR)
+    }
+
+    /// Returns the amount in yoctoNEAR.
+    pub const fn as_yoctonear(&self) -> Balance {
+        self.0
+    }
+
+    /// Returns the amount in whole NEAR, rounded down.
+    pub const fn as_near(&self) -> u128 {
+        self.0 / ONE_NEAR
+    }
+
+    /// Returns `true` if the amount is zero.
+    pub const fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Adds two amounts, returning [`None`] on overflow.
+    pub const fn checked_add(self, rhs: Self) -> Option<Self> {
+        match self.0.checked_add(rhs.0) {
+            Some(amount) => Some(Self(amount)),
+            None => None,
+        }
+    }
+
+    /// Subtracts an amount, returning [`None`] if `rhs` is greater than `self`.
+    pub const fn checked_sub(self, rhs: Self) -> Option<Self> {
+        match self.0.checked_sub(rhs.0) {
+            Some(amount) => Some(Self(amount)),
+            None => None,
+        }
+    }
+
+    /// Multiplies the amount, returning [`None`] on overflow.
+    pub const fn checked_mul(self, rhs: u128) -> Option<Self> {
+        match self.0.checked_mul(rhs) {
+            Some(amount) => Some(Self(amount)),
+            None => None,
+        }
+    }
+
+    /// Divides the amount, rounding down, returning [`None`] if `rhs` is zero.
+    pub const fn checked_div(self, rhs: u128) -> Option<Self> {
+        match self.0.checked_div(rhs) {
+            Some(amount) => Some(Self(amount)),
+            None => None,
+        }
+    }
+
+    /// Adds two amounts, saturating at the maximum amount.
+    pub const fn saturating_add(self, rhs: Self) -> Self {
+        Self(self.0.saturating_add(rhs.0))
+    }
+
+    /// Subtracts an amount, saturating at zero.
+    pub const fn saturating_sub(self, rhs: Self) -> Self {
+        Self(self.0.saturating_sub(rhs.0))
+    }
+
+    /// Multiplies the amount, saturating at the maximum amount.
+    pub const fn saturating_mul(self, rhs: u128) -> Self {
+        Self(self.0.saturating_mul(rhs))
+    }
+}
+
+impl From<NearToken> for Balance {
+    fn from(amount: NearToken) -> Self {
+        amount.0
+    }
+}
+
+impl fmt::Display for NearToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_decimal(f, self.0, 24)?;
+        f.write_str(" NEAR")
+    }
+}
+
+/// Writes `value / 10^decimals` as a decimal number, without trailing zeros in the fraction.
+pub(super) fn fmt_decimal(f: &mut fmt::Formatter<'_>, value: u128, decimals: u32) -> fmt::Result {
+    let unit = 10u128.pow(decimals);
+    write!(f, "{}", value / unit)?;
+    let fraction = value % unit;
+    if fraction != 0 {
+        let digits = format!("{:0width$}", fraction, width = decimals as usize);
+        write!(f, ".{}", digits.trim_end_matches('0'))?;
+    }
+    Ok(())
+}
+
+impl Serialize for NearToken {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.0.to_string())
+    }
+}
+
+#[cfg(feature = "abi")]
+impl schemars::JsonSchema for NearToken {
+    fn schema_name() -> String {
+        "NearToken".to_string()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        <String as schemars::JsonSchema>::json_schema(gen)
+    }
+}
+
+impl<'de> Deserialize<'de> for NearToken {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s: String = Deserialize::deserialize(deserializer)?;
+        s.parse::<Balance>().map(Self).map_err(|err| de::Error::custom(err.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display() {
+        assert_eq!(NearToken::from_near(0).to_string(), "0 NEAR");
+        assert_eq!(NearToken::from_near(30).to_string(), "30 NEAR");
+        assert_eq!(NearToken::from_millinear(1).to_string(), "0.001 NEAR");
+        assert_eq!(NearToken::from_yoctonear(1).to_string(), "0.000000000000000000000001 NEAR");
+    }
+
+    #[test]
+    fn json_ser() {
+        let amount = NearToken::from_yoctonear(u128::MAX);
+        let ser = serde_json::to_string(&amount).unwrap();
+        assert_eq!(ser, format!("\"{}\"", u128::MAX));
+        assert_eq!(serde_json::from_str::<NearToken>(&ser).unwrap(), amount);
+    }
+}
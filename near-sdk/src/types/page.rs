@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+
+use crate::env;
+
+const ERR_INVALID_CURSOR: &str = "Invalid page cursor";
+
+/// A page of the items of a collection, to be returned from view methods which paginate their
+/// results.
+///
+/// [`next_cursor`](Self::next_cursor) is an opaque string which is passed back to get the
+/// following page, and is [`None`] on the last page. [`total`](Self::total) is the total number of
+/// items, when it is known.
+///
+/// # Examples
+/// ```
+/// use near_sdk::Page;
+///
+/// let values = vec![1u32, 2, 3, 4, 5];
+///
+/// let page = Page::from_offset(values.iter().copied(), None, 2).with_total(values.len() as u64);
+/// assert_eq!(page.items, [1, 2]);
+///
+/// // Pass the cursor back to continue where the previous page ended.
+/// let page = Page::from_offset(values.iter().copied(), page.next_cursor.as_deref(), 4);
+/// assert_eq!(page.items, [3, 4, 5]);
+/// assert_eq!(page.next_cursor, None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "abi", derive(schemars::JsonSchema))]
+pub struct Page<T> {
+    /// The items of the page.
+    pub items: Vec<T>,
+    /// Cursor to get the next page, or [`None`] if this is the last page.
+    pub next_cursor: Option<String>,
+    /// The total number of items, if known.
+    pub total: Option<u64>,
+}
+
+impl<T> Page<T> {
+    /// Collects a page of at most `limit` items from the iterator, skipping the items before the
+    /// `cursor` returned with the previous page, or starting from the first item if [`None`].
+    ///
+    /// The cursor is the offset of the page's first item. The iterators of the `store`
+    /// collections which keep their values contiguously, such as those of `Vector`,
+    /// `UnorderedSet` and `UnorderedMap`, skip items without loading them from storage.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cursor is not an offset returned as the [`next_cursor`](Self::next_cursor)
+    /// of a page.
+    pub fn from_offset<I>(iter: I, cursor: Option<&str>, limit: u32) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let offset = match cursor {
+            Some(cursor) => cursor.parse().unwrap_or_else(|_| env::panic_str(ERR_INVALID_CURSOR)),
+            None => 0u64,
+        };
+        let mut iter = iter.into_iter().skip(offset as usize).peekable();
+        let items: Vec<T> = iter.by_ref().take(limit as usize).collect();
+        let next_cursor = iter.peek().map(|_| (offset + items.len() as u64).to_string());
+        Self { items, next_cursor, total: None }
+    }
+
+    /// Sets the total number of items.
+    pub fn with_total(mut self, total: u64) -> Self {
+        self.total = Some(total);
+        self
+    }
+
+    /// Converts the items of the page, keeping its cursor and total.
+    pub fn map<U, F>(self, f: F) -> Page<U>
+    where
+        F: FnMut(T) -> U,
+    {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            next_cursor: self.next_cursor,
+            total: self.total,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Page;
+
+    #[test]
+    fn pages_cover_items() {
+        let mut cursor = None;
+        let mut seen = Vec::new();
+        loop {
+            let page = Page::from_offset(0..10u32, cursor.as_deref(), 3);
+            seen.extend(page.items);
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+        assert_eq!(seen, (0..10).collect::<Vec<_>>());
+
+        let page = Page::from_offset(0..3u32, None, 3).with_total(3).map(|i| i.to_string());
+        let json = serde_json::to_string(&page).unwrap();
+        assert_eq!(json, r#"{"items":["0","1","2"],"next_cursor":null,"total":3}"#);
+    }
+}
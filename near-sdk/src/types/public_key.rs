@@ -47,13 +47,19 @@ impl std::str::FromStr for CurveType {
 ///
 /// Ed25519 public keys accepted are 32 bytes and secp256k1 keys are the uncompressed 64 format.
 ///
+/// The string format, used for [`Display`](std::fmt::Display), [`FromStr`](std::str::FromStr)
+/// and serde, is the one used by the RPC, `<curve>:<base58 key data>`.
+///
 /// # Example
 /// ```
-/// use near_sdk::PublicKey;
+/// use near_sdk::{CurveType, PublicKey};
 ///
 /// // Compressed ed25519 key
 /// let ed: PublicKey = "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp".parse()
 ///             .unwrap();
+/// assert_eq!(ed.curve_type(), CurveType::ED25519);
+/// assert_eq!(ed.key_data().len(), 32);
+/// assert_eq!(ed.to_string(), "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp");
 ///
 /// // Uncompressed secp256k1 key
 /// let secp256k1: PublicKey = "secp256k1:qMoRgcoXai4mBPsdbHi1wfyxF9TdbPCF4qSDQTRP3TfescSRoUdSx6nmeQoN3aiwGzwMyGXAb1gUjBTv5AY8DXj"
@@ -104,6 +110,11 @@ impl PublicKey {
     pub fn curve_type(&self) -> CurveType {
         CurveType::from_u8(self.data[0]).unwrap_or_else(|_| crate::env::abort())
     }
+
+    /// Returns the key data, without the leading curve type byte.
+    pub fn key_data(&self) -> &[u8] {
+        &self.data[1..]
+    }
 }
 
 impl From<PublicKey> for Vec<u8> {
@@ -159,16 +170,40 @@ impl<'de> serde::Deserialize<'de> for PublicKey {
     }
 }
 
+impl std::fmt::Display for PublicKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let curve = match self.curve_type() {
+            CurveType::ED25519 => "ed25519",
+            CurveType::SECP256K1 => "secp256k1",
+        };
+        write!(f, "{}:{}", curve, bs58::encode(self.key_data()).into_string())
+    }
+}
+
 impl From<&PublicKey> for String {
     fn from(str_public_key: &PublicKey) -> Self {
-        match str_public_key.curve_type() {
-            CurveType::ED25519 => {
-                ["ed25519:", &bs58::encode(&str_public_key.data[1..]).into_string()].concat()
-            }
-            CurveType::SECP256K1 => {
-                ["secp256k1:", &bs58::encode(&str_public_key.data[1..]).into_string()].concat()
-            }
-        }
+        str_public_key.to_string()
+    }
+}
+
+/// Generates ED25519 and SECP256K1 keys with arbitrary key data.
+#[cfg(feature = "abi")]
+impl schemars::JsonSchema for PublicKey {
+    fn schema_name() -> String {
+        "PublicKey".to_string()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        <String as schemars::JsonSchema>::json_schema(gen)
+    }
+}
+
+#[cfg(feature = "test-utils")]
+impl<'a> arbitrary::Arbitrary<'a> for PublicKey {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let curve = if u.arbitrary()? { CurveType::ED25519 } else { CurveType::SECP256K1 };
+        let data = u.bytes(curve.data_len())?.to_vec();
+        Self::from_parts(curve, data).map_err(|_| arbitrary::Error::IncorrectFormat)
     }
 }
 
@@ -256,6 +291,20 @@ mod tests {
         assert_eq!(actual, "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp");
     }
 
+    #[test]
+    fn test_public_key_display_roundtrip() {
+        let mut data = vec![CurveType::SECP256K1 as u8];
+        data.extend_from_slice(&[5; 64]);
+        let key = PublicKey::try_from(data.clone()).unwrap();
+        assert_eq!(key.key_data(), &[5; 64][..]);
+
+        let s = key.to_string();
+        assert!(s.starts_with("secp256k1:"));
+        assert_eq!(s.parse::<PublicKey>().unwrap(), key);
+        assert_eq!(Vec::from(key), data);
+        assert!(PublicKey::try_from(vec![2; 33]).is_err());
+    }
+
     #[test]
     fn test_public_key_borsh_format_change() {
         // Original struct to reference Borsh serialization from
@@ -0,0 +1,175 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use bs58::decode::Error as B58Error;
+use std::convert::TryFrom;
+
+use crate::{env, CurveType, PublicKey};
+
+/// An ed25519 signature, with a base58 string serialization prefixed with `ed25519:`, in the same
+/// format as [`PublicKey`].
+///
+/// # Example
+/// ```no_run
+/// use near_sdk::{Ed25519Signature, PublicKey};
+///
+/// let public_key: PublicKey = "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp".parse().unwrap();
+/// let signature: Ed25519Signature = "ed25519:3s1dvZdQtcAjBksMHFrysqvF63wnyMHPA4owNQmCJZ2EBakZEKdtMsLqrHdKWQjJbSRN6kRknN2WdwSBLWGCokXj"
+///     .parse()
+///     .unwrap();
+/// let valid = signature.verify(b"message", &public_key);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, BorshSerialize, BorshDeserialize)]
+pub struct Ed25519Signature(pub [u8; 64]);
+
+impl Ed25519Signature {
+    /// Returns the bytes of the signature.
+    pub fn as_bytes(&self) -> &[u8; 64] {
+        &self.0
+    }
+
+    /// Returns `true` if this is a valid signature of `message` by `public_key`, using
+    /// [`env::ed25519_verify`]. Returns `false` if the key is not an ed25519 key.
+    pub fn verify(&self, message: &[u8], public_key: &PublicKey) -> bool {
+        match public_key.curve_type() {
+            CurveType::ED25519 => {
+                let key =
+                    <&[u8; 32]>::try_from(public_key.key_data()).unwrap_or_else(|_| env::abort());
+                env::ed25519_verify(&self.0, message, key)
+            }
+            CurveType::SECP256K1 => false,
+        }
+    }
+}
+
+/// A secp256k1 signature, as the 32-byte `r` followed by the 32-byte `s` value, from which the
+/// signer's public key can be recovered with [`env::ecrecover`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, BorshSerialize, BorshDeserialize)]
+pub struct Secp256k1Signature(pub [u8; 64]);
+
+impl From<[u8; 64]> for Secp256k1Signature {
+    fn from(bytes: [u8; 64]) -> Self {
+        Self(bytes)
+    }
+}
+
+/// An uncompressed secp256k1 public key, without the leading `0x04` byte, as returned by
+/// [`env::ecrecover`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, BorshSerialize, BorshDeserialize)]
+pub struct Secp256K1PublicKey(pub [u8; 64]);
+
+impl From<Secp256K1PublicKey> for PublicKey {
+    fn from(key: Secp256K1PublicKey) -> Self {
+        let mut data = Vec::with_capacity(65);
+        data.push(CurveType::SECP256K1 as u8);
+        data.extend_from_slice(&key.0);
+        PublicKey::try_from(data).unwrap_or_else(|_| env::abort())
+    }
+}
+
+impl From<[u8; 64]> for Ed25519Signature {
+    fn from(bytes: [u8; 64]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl std::fmt::Display for Ed25519Signature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ed25519:{}", bs58::encode(&self.0).into_string())
+    }
+}
+
+impl std::str::FromStr for Ed25519Signature {
+    type Err = ParseSignatureError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let data = match value.find(':') {
+            Some(idx) if value[..idx].eq_ignore_ascii_case("ed25519") => &value[idx + 1..],
+            Some(_) => {
+                return Err(ParseSignatureError { kind: ParseSignatureErrorKind::UnknownCurve })
+            }
+            None => value,
+        };
+        let bytes = bs58::decode(data).into_vec()?;
+        let len = bytes.len();
+        <[u8; 64]>::try_from(bytes)
+            .map(Self)
+            .map_err(|_| ParseSignatureError { kind: ParseSignatureErrorKind::InvalidLength(len) })
+    }
+}
+
+impl serde::Serialize for Ed25519Signature {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Ed25519Signature {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s: String = serde::Deserialize::deserialize(deserializer)?;
+        s.parse::<Ed25519Signature>().map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseSignatureError {
+    kind: ParseSignatureErrorKind,
+}
+
+#[derive(Debug)]
+enum ParseSignatureErrorKind {
+    InvalidLength(usize),
+    Base58(B58Error),
+    UnknownCurve,
+}
+
+impl std::fmt::Display for ParseSignatureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            ParseSignatureErrorKind::InvalidLength(l) => {
+                write!(f, "invalid length of the signature, expected 64 got {}", l)
+            }
+            ParseSignatureErrorKind::Base58(e) => write!(f, "base58 decoding error: {}", e),
+            ParseSignatureErrorKind::UnknownCurve => write!(f, "unknown curve kind"),
+        }
+    }
+}
+
+impl From<B58Error> for ParseSignatureError {
+    fn from(e: B58Error) -> Self {
+        Self { kind: ParseSignatureErrorKind::Base58(e) }
+    }
+}
+
+impl std::error::Error for ParseSignatureError {}
+
+#[cfg(test)]
+mod tests {
+    use super::{Ed25519Signature, Secp256K1PublicKey};
+    use crate::{CurveType, PublicKey};
+
+    #[test]
+    fn secp256k1_public_key() {
+        let key = PublicKey::from(Secp256K1PublicKey([3; 64]));
+        assert_eq!(key.curve_type(), CurveType::SECP256K1);
+        assert_eq!(key.key_data(), &[3; 64][..]);
+    }
+
+    #[test]
+    fn signature_string_roundtrip() {
+        let signature = Ed25519Signature([7; 64]);
+        let s = signature.to_string();
+        assert!(s.starts_with("ed25519:"));
+        assert_eq!(s.parse::<Ed25519Signature>().unwrap(), signature);
+        assert_eq!(s["ed25519:".len()..].parse::<Ed25519Signature>().unwrap(), signature);
+        assert!("secp256k1:1111".parse::<Ed25519Signature>().is_err());
+        assert!("ed25519:1111".parse::<Ed25519Signature>().is_err());
+
+        let json = serde_json::to_string(&signature).unwrap();
+        assert_eq!(serde_json::from_str::<Ed25519Signature>(&json).unwrap(), signature);
+    }
+}
@@ -0,0 +1,25 @@
+use std::fmt;
+
+/// Error returned by [`env::state_read_versioned`] when the contract state was written with a
+/// different schema version than the one expected.
+///
+/// [`env::state_read_versioned`]: crate::env::state_read_versioned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StateVersionMismatch {
+    /// The version which was expected by the reader.
+    pub expected: u8,
+    /// The version the stored state was written with.
+    pub found: u8,
+}
+
+impl fmt::Display for StateVersionMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the contract state has version {}, but version {} was expected",
+            self.found, self.expected
+        )
+    }
+}
+
+impl std::error::Error for StateVersionMismatch {}
@@ -0,0 +1,95 @@
+use crate::{env, Gas};
+
+/// A checkpoint of the gas used by the contract execution, which is created after checking that
+/// enough gas remains.
+///
+/// Checking the remaining gas with [`require_at_least`] before an expensive loop or before
+/// scheduling callbacks makes the call fail early, before any state is partially changed, instead
+/// of running out of gas midway.
+///
+/// # Examples
+/// ```
+/// use near_sdk::{GasGuard, Gas};
+///
+/// let guard = GasGuard::require_at_least(Gas::from_tgas(20));
+/// for _ in 0..10 {
+///     guard.require_remaining();
+///     // Expensive work.
+/// }
+/// let used_by_loop = guard.used();
+/// ```
+///
+/// [`require_at_least`]: Self::require_at_least
+#[derive(Debug, Clone, Copy)]
+pub struct GasGuard {
+    budget: Gas,
+    used_at_start: Gas,
+}
+
+impl GasGuard {
+    /// Panics if less than `gas` remains to be used, and otherwise returns a checkpoint of the
+    /// gas used so far that keeps `gas` as its budget.
+    pub fn require_at_least(gas: Gas) -> Self {
+        let guard = Self { budget: gas, used_at_start: env::used_gas() };
+        guard.require_remaining();
+        guard
+    }
+
+    /// Returns the gas used since the guard was created.
+    pub fn used(&self) -> Gas {
+        env::used_gas().saturating_sub(self.used_at_start)
+    }
+
+    /// Returns the gas that is required to remain, as given to [`require_at_least`].
+    ///
+    /// [`require_at_least`]: Self::require_at_least
+    pub fn budget(&self) -> Gas {
+        self.budget
+    }
+
+    /// Panics if less than the budget of the guard remains to be used. This can be called again
+    /// after the guard was created, for example before each iteration of a loop.
+    pub fn require_remaining(&self) {
+        let remaining = env::remaining_gas();
+        if remaining < self.budget {
+            env::panic_str(&format!(
+                "Not enough gas: {} remaining, {} required",
+                remaining, self.budget
+            ))
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::GasGuard;
+    use crate::test_utils::VMContextBuilder;
+    use crate::{env, testing_env, Gas};
+
+    #[test]
+    fn measures_used_gas() {
+        testing_env!(VMContextBuilder::new().prepaid_gas(Gas::from_tgas(30)).build());
+        let guard = GasGuard::require_at_least(Gas::from_tgas(20));
+        env::sha256(b"near");
+        assert!(guard.used() > Gas(0));
+        assert!(env::remaining_gas() < Gas::from_tgas(30));
+        guard.require_remaining();
+    }
+
+    #[test]
+    #[should_panic(expected = "Not enough gas")]
+    fn requires_gas_up_front() {
+        testing_env!(VMContextBuilder::new().prepaid_gas(Gas::from_tgas(10)).build());
+        GasGuard::require_at_least(Gas::from_tgas(20));
+    }
+
+    #[test]
+    #[should_panic(expected = "Not enough gas")]
+    fn requires_budget_to_remain() {
+        testing_env!(VMContextBuilder::new().prepaid_gas(Gas::from_tgas(21)).build());
+        let guard = GasGuard::require_at_least(Gas::from_tgas(20));
+        env::sha256(&[0; 100_000]);
+        guard.require_remaining();
+    }
+}
@@ -1,5 +1,8 @@
 pub(crate) mod storage_key_impl;
 
+mod gas_guard;
+pub use self::gas_guard::GasGuard;
+
 #[cfg(feature = "unstable")]
 mod stable_map;
 #[cfg(feature = "unstable")]
@@ -9,6 +12,8 @@ mod cache_entry;
 #[cfg(feature = "unstable")]
 pub(crate) use cache_entry::{CacheEntry, EntryState};
 
+use std::fmt;
+
 use crate::{env, AccountId, PromiseResult};
 
 /// Helper macro to log a message through [`env::log_str`].
@@ -44,6 +49,75 @@ macro_rules! log {
     };
 }
 
+/// Helper macro to log a warning through [`env::log_str`]. The message is formatted like with
+/// [`log!`] and is prefixed with `WARN: `.
+///
+/// # Example use
+///
+/// ```no_run
+/// use near_sdk::warn;
+///
+/// # fn main() {
+/// let balance = 10;
+/// warn!("Balance is low: {}", balance);
+/// # }
+/// ```
+///
+/// [`env::log_str`]: crate::env::log_str
+#[macro_export]
+macro_rules! warn {
+    ($arg:expr) => {
+        $crate::log_with_level("WARN", format_args!("{}", AsRef::<str>::as_ref(&$arg)))
+    };
+    ($($arg:tt)*) => {
+        $crate::log_with_level("WARN", format_args!($($arg)*))
+    };
+}
+
+/// Helper macro to log a debug message through [`env::log_str`]. The message is formatted like
+/// with [`log!`] and is prefixed with `DEBUG: `.
+///
+/// Debug messages are only logged in builds with debug assertions, such as unit tests, or when
+/// the `debug-log` feature of `near-sdk` is enabled. Otherwise the message is not formatted, and
+/// the call is compiled out of the contract, so release Wasm builds do not pay for it.
+///
+/// # Example use
+///
+/// ```no_run
+/// use near_sdk::debug;
+///
+/// # fn main() {
+/// let key = "alice";
+/// debug!("Loading {}", key);
+/// # }
+/// ```
+///
+/// [`env::log_str`]: crate::env::log_str
+#[macro_export]
+macro_rules! debug {
+    ($arg:expr) => {
+        if cfg!(debug_assertions) || $crate::DEBUG_LOG_ENABLED {
+            $crate::log_with_level("DEBUG", format_args!("{}", AsRef::<str>::as_ref(&$arg)))
+        }
+    };
+    ($($arg:tt)*) => {
+        if cfg!(debug_assertions) || $crate::DEBUG_LOG_ENABLED {
+            $crate::log_with_level("DEBUG", format_args!($($arg)*))
+        }
+    };
+}
+
+/// Whether [`debug!`] messages are logged in builds without debug assertions.
+#[doc(hidden)]
+pub const DEBUG_LOG_ENABLED: bool = cfg!(feature = "debug-log");
+
+/// Logs the formatted message prefixed with the level. Used by the [`warn!`] and [`debug!`]
+/// macros, which only build the [`fmt::Arguments`] when the message is logged.
+#[doc(hidden)]
+pub fn log_with_level(level: &str, message: fmt::Arguments) {
+    env::log_str(&format!("{}: {}", level, message))
+}
+
 /// Helper macro to create assertions that will panic through the runtime host functions.
 ///
 /// This macro can be used similarly to [`assert!`] but will reduce code size by not including
@@ -172,4 +246,14 @@ mod tests {
 
         assert_eq!(get_logs(), vec!["hello user_name (25)".to_string()]);
     }
+
+    #[test]
+    fn test_log_levels() {
+        let name = "user_name";
+        warn!("hello {}", name);
+        debug!("hello");
+        warn!(name);
+
+        assert_eq!(get_logs(), vec!["WARN: hello user_name", "DEBUG: hello", "WARN: user_name"]);
+    }
 }
@@ -64,3 +64,230 @@ where
         self.try_to_vec().unwrap()
     }
 }
+
+/// Builds a composite storage key from a prefix followed by key parts, without concatenating
+/// bytes by hand.
+///
+/// Each part is appended as its [`BorshSerialize`] representation, so variable-length parts such
+/// as strings are length-delimited and keys built from different parts do not overlap. Tuples of
+/// a prefix and up to three parts convert to the same key as the builder.
+///
+/// ```
+/// use near_sdk::borsh::{self, BorshSerialize};
+/// use near_sdk::store::LookupMap;
+/// use near_sdk::{AccountId, BorshStorageKey, IntoStorageKey, StorageKeyBuilder};
+///
+/// #[derive(BorshSerialize, BorshStorageKey)]
+/// enum StorageKey {
+///     Tokens,
+/// }
+///
+/// let account_id: AccountId = "alice.near".parse().unwrap();
+/// let key = StorageKeyBuilder::new(StorageKey::Tokens).push(&account_id).push(&1u8);
+/// assert_eq!(key.clone().into_storage_key(), (StorageKey::Tokens, &account_id, 1u8).into_storage_key());
+///
+/// let tokens: LookupMap<u64, String> = LookupMap::new(key);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct StorageKeyBuilder {
+    key: Vec<u8>,
+}
+
+impl StorageKeyBuilder {
+    /// Starts a key with the given prefix.
+    pub fn new<S: IntoStorageKey>(prefix: S) -> Self {
+        Self { key: prefix.into_storage_key() }
+    }
+
+    /// Appends the [`BorshSerialize`] representation of `part` to the key.
+    pub fn push<T: ?Sized>(mut self, part: &T) -> Self
+    where
+        T: BorshSerialize,
+    {
+        BorshSerialize::serialize(part, &mut self.key)
+            .unwrap_or_else(|_| crate::env::panic_str("Cannot serialize key"));
+        self
+    }
+
+    /// Appends `bytes` to the key as they are, without a length prefix.
+    pub fn push_raw(mut self, bytes: &[u8]) -> Self {
+        self.key.extend_from_slice(bytes);
+        self
+    }
+
+    /// Returns the bytes of the key built so far.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.key
+    }
+}
+
+impl IntoStorageKey for StorageKeyBuilder {
+    #[inline]
+    fn into_storage_key(self) -> Vec<u8> {
+        self.key
+    }
+}
+
+macro_rules! impl_tuple_storage_key {
+    ($($part:ident),+) => {
+        impl<S, $($part),+> IntoStorageKey for (S, $($part),+)
+        where
+            S: IntoStorageKey,
+            $($part: BorshSerialize),+
+        {
+            #[allow(non_snake_case)]
+            fn into_storage_key(self) -> Vec<u8> {
+                let (prefix, $($part),+) = self;
+                StorageKeyBuilder::new(prefix)$(.push(&$part))+.into_storage_key()
+            }
+        }
+    };
+}
+
+impl_tuple_storage_key!(A);
+impl_tuple_storage_key!(A, B);
+impl_tuple_storage_key!(A, B, C);
+
+/// A storage prefix which derives collision-free prefixes for nested collections.
+///
+/// Collections stored inside the values of another collection each need a unique prefix, which
+/// otherwise has to be built by hand from the key of the value. [`nested`] derives such a prefix
+/// from the parent prefix, a tag which distinguishes the nested collections of one value, and
+/// the [`BorshSerialize`] of the key. The tag is length-delimited, so nested prefixes with
+/// different tags or keys (of the same type) under one parent never overlap.
+///
+/// In debug builds, each new prefix is checked against the prefixes previously created from a
+/// different root prefix, and the contract panics if one of them starts with the other, as the
+/// collections would overwrite each other's entries.
+///
+/// ```
+/// use near_sdk::collections::{LookupMap, Vector};
+/// use near_sdk::StoragePrefix;
+///
+/// let accounts_prefix = StoragePrefix::new(b"a");
+/// let mut accounts: LookupMap<String, Vector<u64>> = LookupMap::new(accounts_prefix.clone());
+///
+/// let alice = "alice".to_string();
+/// let mut history = Vector::new(accounts_prefix.nested(b"history", &alice));
+/// history.push(&10);
+/// accounts.insert(&alice, &history);
+/// ```
+///
+/// [`nested`]: StoragePrefix::nested
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct StoragePrefix {
+    prefix: Vec<u8>,
+    /// Length of the root prefix this prefix was derived from.
+    root_len: usize,
+}
+
+impl StoragePrefix {
+    /// Creates a root prefix, from which nested prefixes can be derived.
+    pub fn new<S: IntoStorageKey>(prefix: S) -> Self {
+        let prefix = prefix.into_storage_key();
+        let root_len = prefix.len();
+        Self::checked(Self { prefix, root_len })
+    }
+
+    /// Derives the prefix of a collection nested within the value of `key`. Use a different
+    /// `tag` for each collection nested within the same value.
+    pub fn nested<K: ?Sized>(&self, tag: &[u8], key: &K) -> Self
+    where
+        K: BorshSerialize,
+    {
+        let mut prefix = self.prefix.clone();
+        BorshSerialize::serialize(tag, &mut prefix)
+            .and_then(|_| BorshSerialize::serialize(key, &mut prefix))
+            .unwrap_or_else(|_| crate::env::panic_str("Cannot serialize key"));
+        Self::checked(Self { prefix, root_len: self.root_len })
+    }
+
+    /// Returns the bytes of the prefix.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.prefix
+    }
+
+    #[cfg(not(debug_assertions))]
+    fn checked(self) -> Self {
+        self
+    }
+
+    #[cfg(debug_assertions)]
+    fn checked(self) -> Self {
+        use std::cell::RefCell;
+
+        thread_local! {
+            static PREFIXES: RefCell<Vec<StoragePrefix>> = const { RefCell::new(Vec::new()) };
+        }
+        PREFIXES.with(|prefixes| {
+            let mut prefixes = prefixes.borrow_mut();
+            if let Some(other) = self.find_collision(&prefixes) {
+                crate::env::panic_str(&format!(
+                    "Storage prefix {:?} collides with storage prefix {:?}",
+                    self.prefix, other.prefix
+                ));
+            }
+            if !prefixes.contains(&self) {
+                prefixes.push(self.clone());
+            }
+        });
+        self
+    }
+
+    /// Returns a prefix derived from a different root which overlaps with this prefix.
+    #[cfg(debug_assertions)]
+    fn find_collision<'a>(&self, prefixes: &'a [StoragePrefix]) -> Option<&'a StoragePrefix> {
+        let root = &self.prefix[..self.root_len];
+        prefixes.iter().find(|other| {
+            let overlaps =
+                other.prefix.starts_with(&self.prefix) || self.prefix.starts_with(&other.prefix);
+            overlaps && root != &other.prefix[..other.root_len]
+        })
+    }
+}
+
+impl IntoStorageKey for StoragePrefix {
+    #[inline]
+    fn into_storage_key(self) -> Vec<u8> {
+        self.prefix
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IntoStorageKey, StorageKeyBuilder, StoragePrefix};
+
+    #[test]
+    fn composite_keys() {
+        let key = (b"t", "alice", 7u8).into_storage_key();
+        assert_eq!(key, [b"t".as_ref(), &[5, 0, 0, 0], b"alice", &[7]].concat());
+        assert_eq!(key, StorageKeyBuilder::new(b"t").push("alice").push(&7u8).into_storage_key());
+        assert_ne!((b"t", "ab", "c").into_storage_key(), (b"t", "a", "bc").into_storage_key());
+        assert_eq!(StorageKeyBuilder::new(1u8).push_raw(b"xy").as_bytes(), b"\x01xy");
+    }
+
+    #[test]
+    fn nested_prefixes_are_distinct() {
+        let root = StoragePrefix::new(b"a");
+        let a = root.nested(b"x", "alice");
+        let b = root.nested(b"x", "bob");
+        let c = root.nested(b"xa", "alice");
+        assert_eq!(a, root.nested(b"x", "alice"));
+        assert!(!b.as_bytes().starts_with(a.as_bytes()));
+        assert!(!c.as_bytes().starts_with(a.as_bytes()));
+        assert!(!a.as_bytes().starts_with(c.as_bytes()));
+        assert!(a.nested(b"y", &1u8).as_bytes().starts_with(a.as_bytes()));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn colliding_roots() {
+        let a = StoragePrefix::new(b"a");
+        let nested = a.nested(b"x", &1u8);
+        let ab = StoragePrefix { prefix: b"ab".to_vec(), root_len: 2 };
+        let b = StoragePrefix { prefix: b"b".to_vec(), root_len: 1 };
+        assert_eq!(ab.find_collision(&[b.clone(), nested.clone()]), None);
+        assert_eq!(ab.find_collision(&[b, a.clone()]), Some(&a));
+        assert_eq!(a.find_collision(&[ab.clone()]), Some(&ab));
+    }
+}
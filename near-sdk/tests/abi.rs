@@ -0,0 +1,110 @@
+#![cfg(feature = "abi")]
+
+use near_sdk::abi::{AbiFunctionKind, AbiFunctionModifier, AbiType};
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::schemars::JsonSchema;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::serde_json::json;
+use near_sdk::{event, metadata, near_bindgen, AccountId, PanicOnDefault, Promise};
+
+metadata! {
+    #[near_bindgen]
+    #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+    pub struct Token {
+        supply: u128,
+    }
+
+    #[derive(Serialize, Deserialize, JsonSchema)]
+    #[serde(crate = "near_sdk::serde")]
+    #[schemars(crate = "near_sdk::schemars")]
+    pub struct Transfer {
+        pub receiver_id: AccountId,
+        pub amount: U128,
+    }
+
+    #[event]
+    #[derive(Serialize, JsonSchema)]
+    #[serde(crate = "near_sdk::serde", tag = "event", content = "data")]
+    #[schemars(crate = "near_sdk::schemars")]
+    pub enum TokenEvent {
+        Mint { amount: U128 },
+    }
+
+    #[near_bindgen]
+    impl Token {
+        #[init]
+        pub fn new(supply: U128) -> Self {
+            Self { supply: supply.0 }
+        }
+
+        #[payable]
+        pub fn transfer(&mut self, transfer: Transfer) -> Option<U128> {
+            Some(transfer.amount)
+        }
+
+        pub fn supply(&self) -> U128 {
+            U128(self.supply)
+        }
+
+        #[private]
+        pub fn on_transfer(&mut self, #[callback_unwrap] used: U128) -> Promise {
+            Promise::new(near_sdk::env::current_account_id()).transfer(used.0)
+        }
+
+        #[result_serializer(borsh)]
+        pub fn raw_supply(&self, #[serializer(borsh)] scale: u8) -> u128 {
+            self.supply * scale as u128
+        }
+
+        fn internal(&self) {}
+    }
+}
+
+#[test]
+fn contract_abi() {
+    let abi = __near_abi();
+    assert_eq!(abi.name, "near-sdk");
+    let names: Vec<_> = abi.functions.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(names, ["new", "transfer", "supply", "on_transfer", "raw_supply"]);
+
+    let new = &abi.functions[0];
+    assert_eq!(new.kind, AbiFunctionKind::Call);
+    assert_eq!(new.modifiers, [AbiFunctionModifier::Init]);
+    assert_eq!(new.result, None);
+    assert_eq!(abi.functions[1].modifiers, [AbiFunctionModifier::Payable]);
+    assert_eq!(abi.functions[2].kind, AbiFunctionKind::View);
+    assert_eq!(abi.functions[3].modifiers, [AbiFunctionModifier::Private]);
+    assert_eq!(abi.functions[3].callbacks[0].name, "used");
+    assert_eq!(
+        abi.functions[4].params[0].type_schema,
+        AbiType::Borsh { declaration: "u8".to_string() }
+    );
+    assert_eq!(abi.events[0].name, "TokenEvent");
+
+    let abi = near_sdk::serde_json::to_value(&abi).unwrap();
+    assert_eq!(
+        abi["functions"][1],
+        json!({
+            "name": "transfer",
+            "kind": "call",
+            "modifiers": ["payable"],
+            "params": [{
+                "name": "transfer",
+                "serialization_type": "json",
+                "type_schema": { "$ref": "#/definitions/Transfer" },
+            }],
+            "callbacks": [],
+            "callbacks_vec": null,
+            "result": {
+                "serialization_type": "json",
+                "type_schema": {
+                    "anyOf": [{ "$ref": "#/definitions/U128" }, { "type": "null" }],
+                },
+            },
+        })
+    );
+    assert_eq!(abi["functions"][3]["result"]["type_schema"], json!(true));
+    assert_eq!(abi["definitions"]["AccountId"], json!({ "type": "string" }));
+    assert_eq!(abi["definitions"]["Transfer"]["required"], json!(["amount", "receiver_id"]));
+}
@@ -0,0 +1,79 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{
+    access_control, assert_contract_panics, env, near_bindgen, testing_env, AccountId,
+    PanicOnDefault,
+};
+
+#[near_bindgen]
+#[access_control(roles(Admin, Operator), admin(Admin))]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct Contract {
+    paused: bool,
+}
+
+#[near_bindgen]
+impl Contract {
+    #[init]
+    pub fn new(admin_id: AccountId) -> Self {
+        access_control::grant_role("Admin", &admin_id);
+        Self { paused: false }
+    }
+
+    #[only(Admin, Operator)]
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    #[only(Admin)]
+    pub fn unpause(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+}
+
+fn call_as(account_id: AccountId) {
+    testing_env!(VMContextBuilder::new().predecessor_account_id(account_id).build());
+}
+
+#[test]
+fn roles() {
+    assert_eq!(Contract::ACL_ROLES, ["Admin", "Operator"]);
+    assert_eq!(Contract::ACL_ADMIN_ROLES, ["Admin"]);
+
+    Contract::__dispatch_new(br#"{"admin_id":"alice"}"#);
+    let operator = format!(r#"{{"role":"Operator","account_id":"{}"}}"#, accounts(1));
+
+    call_as(accounts(1));
+    assert_contract_panics!(
+        Contract::__dispatch_pause(&[]),
+        "Method pause requires the role Admin or Operator"
+    );
+    assert_contract_panics!(
+        Contract::__dispatch_acl_grant_role(operator.as_bytes()),
+        "Method acl_grant_role can only be called by the contract or an admin"
+    );
+
+    call_as(accounts(0));
+    assert_eq!(Contract::__dispatch_acl_grant_role(operator.as_bytes()), b"true");
+    assert_eq!(Contract::__dispatch_acl_has_role(operator.as_bytes()), b"true");
+    assert_contract_panics!(
+        Contract::__dispatch_acl_grant_role(br#"{"role":"Owner","account_id":"bob"}"#),
+        "Unknown role Owner"
+    );
+
+    call_as(accounts(1));
+    Contract::__dispatch_pause(&[]);
+    assert_eq!(Contract::__dispatch_is_paused(&[]), b"true");
+    assert_contract_panics!(
+        Contract::__dispatch_unpause(&[]),
+        "Method unpause requires the role Admin"
+    );
+
+    call_as(env::current_account_id());
+    assert_eq!(Contract::__dispatch_acl_revoke_role(operator.as_bytes()), b"true");
+    assert_eq!(Contract::__dispatch_acl_has_role(operator.as_bytes()), b"false");
+}
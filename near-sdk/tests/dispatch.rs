@@ -0,0 +1,60 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::test_utils::{get_logs, VMContextBuilder};
+use near_sdk::{assert_contract_panics, env, near_bindgen, testing_env, PanicOnDefault};
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct Counter {
+    value: u64,
+}
+
+#[near_bindgen]
+impl Counter {
+    #[init]
+    pub fn new(value: u64) -> Self {
+        Self { value }
+    }
+
+    #[payable]
+    pub fn add(&mut self, amount: u64) -> u64 {
+        env::log_str(&format!("deposit {}", env::attached_deposit()));
+        self.value += amount;
+        self.value
+    }
+
+    pub fn get(&self) -> u64 {
+        self.value
+    }
+
+    #[private]
+    pub fn reset(&mut self) {
+        self.value = 0;
+    }
+}
+
+#[test]
+fn dispatch_methods() {
+    assert!(Counter::__dispatch_new(br#"{"value":1}"#).is_empty());
+    assert_contract_panics!(
+        Counter::__dispatch_new(br#"{"value":1}"#),
+        "The contract has already been initialized"
+    );
+    assert_contract_panics!(Counter::__dispatch_add(b"{}"), "Failed to deserialize input");
+    assert_contract_panics!(Counter::__dispatch_reset(&[]), "Method reset is private");
+
+    let contract = env::current_account_id();
+    testing_env!(VMContextBuilder::new().predecessor_account_id(contract).build());
+    assert!(Counter::__dispatch_reset(&[]).is_empty());
+    assert_eq!(Counter::__dispatch_get(&[]), b"0");
+
+    testing_env!(VMContextBuilder::new().attached_deposit(3).build());
+    assert_eq!(Counter::__dispatch_add(br#"{"amount":2}"#), b"2");
+    assert_eq!(get_logs(), ["deposit 3"]);
+    assert_contract_panics!(
+        Counter::__dispatch_new(br#"{"value":1}"#),
+        "Method new doesn't accept deposit"
+    );
+
+    testing_env!(VMContextBuilder::new().build());
+    assert_eq!(Counter::__dispatch_get(&[]), b"2");
+}
@@ -0,0 +1,61 @@
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::test_utils::{assert_event_emitted, get_logs, VMContextBuilder};
+use near_sdk::{near_bindgen, testing_env, AccountId, NearEvent};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftMint {
+    pub owner_id: AccountId,
+    pub token_ids: Vec<String>,
+}
+
+#[derive(NearEvent)]
+#[event_json(standard = "nep171", version = "1.0.0")]
+pub enum NftEvent {
+    NftMint(Vec<NftMint>),
+    NftBurn {
+        owner_id: AccountId,
+        token_ids: Vec<String>,
+        // The enum does not derive `Serialize`, but the `serde` attributes of its fields are kept.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        memo: Option<String>,
+    },
+}
+
+#[near_bindgen(event_json(standard = "nep141", version = "1.0.0"))]
+pub enum FtEvent {
+    FtTransfer(AccountId, U128),
+    Paused,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde", tag = "event", content = "data", rename_all = "snake_case")]
+enum ParsedNftEvent {
+    NftMint(Vec<NftMint>),
+}
+
+#[test]
+fn emit_events() {
+    testing_env!(VMContextBuilder::new().build());
+    let alice: AccountId = "alice.near".parse().unwrap();
+    let mint = NftMint { owner_id: alice.clone(), token_ids: vec!["1".to_string()] };
+    NftEvent::NftMint(vec![mint]).emit();
+    NftEvent::NftBurn { owner_id: alice.clone(), token_ids: vec![], memo: None }.emit();
+    FtEvent::FtTransfer(alice.clone(), U128(5)).emit();
+    FtEvent::Paused.emit();
+
+    assert_event_emitted(ParsedNftEvent::NftMint(vec![NftMint {
+        owner_id: alice,
+        token_ids: vec!["1".to_string()],
+    }]));
+    assert_eq!(
+        get_logs(),
+        [
+            r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_mint","data":[{"owner_id":"alice.near","token_ids":["1"]}]}"#,
+            r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_burn","data":{"owner_id":"alice.near","token_ids":[]}}"#,
+            r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_transfer","data":["alice.near","5"]}"#,
+            r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"paused"}"#,
+        ]
+    );
+}
@@ -0,0 +1,73 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::Serialize;
+use near_sdk::test_utils::VMContextBuilder;
+use near_sdk::{assert_contract_panics, env, near_bindgen, testing_env, FunctionError};
+
+#[derive(Serialize, FunctionError, Debug)]
+#[serde(crate = "near_sdk::serde", tag = "kind")]
+pub enum VaultError {
+    Empty,
+    NotEnough { available: u64 },
+}
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Vault {
+    balance: u64,
+}
+
+impl Default for Vault {
+    fn default() -> Self {
+        env::panic_str("The contract is not initialized")
+    }
+}
+
+#[near_bindgen]
+impl Vault {
+    #[init]
+    #[handle_result]
+    pub fn new(balance: u64) -> Result<Self, VaultError> {
+        if balance == 0 {
+            return Err(VaultError::Empty);
+        }
+        Ok(Self { balance })
+    }
+
+    #[handle_result]
+    pub fn withdraw(&mut self, amount: u64) -> Result<u64, VaultError> {
+        self.balance = self
+            .balance
+            .checked_sub(amount)
+            .ok_or(VaultError::NotEnough { available: self.balance })?;
+        Ok(self.balance)
+    }
+
+    #[handle_result]
+    pub fn check(&self) -> Result<(), VaultError> {
+        Ok(())
+    }
+
+    pub fn balance(&self) -> u64 {
+        self.balance
+    }
+}
+
+#[test]
+fn handle_result() {
+    testing_env!(VMContextBuilder::new().build());
+    assert_contract_panics!(Vault::__dispatch_new(br#"{"balance":0}"#), VaultError::Empty);
+    assert!(!env::state_exists());
+
+    Vault::__dispatch_new(br#"{"balance":5}"#);
+    assert_eq!(Vault::__dispatch_withdraw(br#"{"amount":2}"#), b"3");
+    assert_contract_panics!(
+        Vault::__dispatch_withdraw(br#"{"amount":4}"#),
+        VaultError::NotEnough { available: 3 }
+    );
+    assert_contract_panics!(
+        Vault::__dispatch_withdraw(br#"{"amount":4}"#),
+        r#"{"kind":"NotEnough","available":3}"#
+    );
+    assert_eq!(Vault::__dispatch_balance(&[]), b"3");
+    assert_eq!(Vault::__dispatch_check(&[]), b"null");
+}
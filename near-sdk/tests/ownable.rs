@@ -0,0 +1,61 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{
+    assert_contract_panics, near_bindgen, ownable, testing_env, AccountId, Ownable, PanicOnDefault,
+};
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, Ownable, PanicOnDefault)]
+pub struct Contract {
+    fee: u64,
+}
+
+#[near_bindgen]
+impl Contract {
+    #[init]
+    pub fn new(owner_id: AccountId) -> Self {
+        ownable::set_owner(&owner_id);
+        Self { fee: 0 }
+    }
+
+    #[only_owner]
+    pub fn set_fee(&mut self, fee: u64) -> u64 {
+        self.fee = fee;
+        self.fee
+    }
+}
+
+fn call_as(account_id: AccountId) {
+    testing_env!(VMContextBuilder::new().predecessor_account_id(account_id).build());
+}
+
+#[test]
+fn transfer_ownership() {
+    Contract::__dispatch_new(br#"{"owner_id":"alice"}"#);
+    assert_eq!(Contract::__dispatch_owner(&[]), br#""alice""#);
+    assert_eq!(Contract::__dispatch_pending_owner(&[]), b"null");
+
+    call_as(accounts(1));
+    assert_contract_panics!(
+        Contract::__dispatch_set_fee(br#"{"fee":1}"#),
+        "Method set_fee can only be called by the owner"
+    );
+    assert_contract_panics!(
+        Contract::__dispatch_transfer_ownership(br#"{"new_owner":"bob"}"#),
+        "Method transfer_ownership can only be called by the owner"
+    );
+
+    call_as(accounts(0));
+    assert_eq!(Contract::__dispatch_set_fee(br#"{"fee":1}"#), b"1");
+    Contract::__dispatch_transfer_ownership(br#"{"new_owner":"bob"}"#);
+    assert_eq!(Contract::__dispatch_pending_owner(&[]), br#""bob""#);
+    assert_contract_panics!(
+        Contract::__dispatch_accept_ownership(&[]),
+        "Only the proposed owner can accept the ownership"
+    );
+
+    call_as(accounts(1));
+    Contract::__dispatch_accept_ownership(&[]);
+    assert_eq!(Contract::__dispatch_owner(&[]), br#""bob""#);
+    assert_eq!(Contract::__dispatch_set_fee(br#"{"fee":2}"#), b"2");
+}
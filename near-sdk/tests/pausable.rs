@@ -0,0 +1,71 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::pausable::ALL_FEATURES;
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{
+    access_control, assert_contract_panics, env, near_bindgen, pausable, testing_env, AccountId,
+    PanicOnDefault,
+};
+
+#[near_bindgen]
+#[access_control(roles(Admin, Pauser), admin(Admin))]
+#[pausable(manager_roles(Pauser))]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct Contract {
+    total: u64,
+}
+
+#[near_bindgen]
+impl Contract {
+    #[init]
+    pub fn new(pauser_id: AccountId) -> Self {
+        access_control::grant_role("Pauser", &pauser_id);
+        Self { total: 10 }
+    }
+
+    #[when_not_paused]
+    pub fn deposit(&mut self, amount: u64) -> u64 {
+        self.total += amount;
+        self.total
+    }
+
+    #[when_not_paused("transfers")]
+    pub fn withdraw(&mut self, amount: u64) -> u64 {
+        self.total -= amount;
+        self.total
+    }
+}
+
+fn call_as(account_id: AccountId) {
+    testing_env!(VMContextBuilder::new().predecessor_account_id(account_id).build());
+}
+
+#[test]
+fn pause_features() {
+    Contract::__dispatch_new(br#"{"pauser_id":"alice"}"#);
+    let transfers = br#"{"feature":"transfers"}"#;
+
+    call_as(accounts(1));
+    assert_contract_panics!(
+        Contract::__dispatch_pa_pause_feature(transfers),
+        "Method pa_pause_feature can only be called by the contract or an admin"
+    );
+
+    call_as(accounts(0));
+    assert_eq!(Contract::__dispatch_pa_pause_feature(transfers), b"true");
+    assert_eq!(Contract::__dispatch_pa_is_paused(transfers), b"true");
+    assert_contract_panics!(
+        Contract::__dispatch_withdraw(br#"{"amount":1}"#),
+        "Method withdraw is paused"
+    );
+    assert_eq!(Contract::__dispatch_deposit(br#"{"amount":1}"#), b"11");
+    assert_eq!(Contract::__dispatch_pa_unpause_feature(transfers), b"true");
+    assert_eq!(Contract::__dispatch_withdraw(br#"{"amount":1}"#), b"10");
+
+    call_as(env::current_account_id());
+    let all = format!(r#"{{"feature":"{}"}}"#, ALL_FEATURES);
+    Contract::__dispatch_pa_pause_feature(all.as_bytes());
+    assert_contract_panics!(
+        Contract::__dispatch_deposit(br#"{"amount":1}"#),
+        "Method deposit is paused"
+    );
+}
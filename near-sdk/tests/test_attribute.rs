@@ -0,0 +1,37 @@
+use near_sdk::{env, test_utils, Gas};
+
+#[near_sdk::test_utils::test(predecessor = "alice.near", deposit = 1)]
+fn sets_up_the_context() {
+    assert_eq!(env::predecessor_account_id().as_str(), "alice.near");
+    assert_eq!(env::attached_deposit(), 1);
+}
+
+#[near_sdk::test_utils::test(block_height = 10, prepaid_gas = 5_000_000_000_000)]
+fn block_and_gas() {
+    assert_eq!(env::block_height(), 10);
+    assert_eq!(env::prepaid_gas(), Gas(5_000_000_000_000));
+}
+
+#[near_sdk::test_utils::test]
+#[deny(unused_mut)]
+fn default_context() {
+    assert_eq!(env::current_account_id().as_str(), "alice.near");
+}
+
+#[test]
+fn call_presets() {
+    let mut context = test_utils::VMContextBuilder::new();
+    assert!(context.view_call().build().is_view);
+    let context = context.payable_call(10).build();
+    assert!(!context.is_view);
+    assert_eq!(context.attached_deposit, 10);
+}
+
+mod glob_import {
+    #[allow(unused_imports)]
+    use near_sdk::*;
+
+    // The built-in attribute is not shadowed by the glob import.
+    #[test]
+    fn builtin_test_attribute() {}
+}
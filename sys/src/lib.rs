@@ -1,6 +1,7 @@
 #![no_std]
 
-extern "C" {
+// The mocked blockchain used in unit tests unwinds out of the host functions when they fail.
+extern "C-unwind" {
     // #############
     // # Registers #
     // #############
@@ -44,6 +45,14 @@ extern "C" {
         malleability_flag: u64,
         register_id: u64,
     ) -> u64;
+    pub fn ed25519_verify(
+        sig_len: u64,
+        sig_ptr: u64,
+        msg_len: u64,
+        msg_ptr: u64,
+        pub_key_len: u64,
+        pub_key_ptr: u64,
+    ) -> u64;
     // #####################
     // # Miscellaneous API #
     // #####################
@@ -134,6 +143,24 @@ extern "C" {
     pub fn promise_results_count() -> u64;
     pub fn promise_result(result_idx: u64, register_id: u64) -> u64;
     pub fn promise_return(promise_id: u64);
+    // ##########################
+    // # Promise Yield / Resume #
+    // ##########################
+    pub fn promise_yield_create(
+        function_name_len: u64,
+        function_name_ptr: u64,
+        arguments_len: u64,
+        arguments_ptr: u64,
+        gas: u64,
+        gas_weight: u64,
+        register_id: u64,
+    ) -> u64;
+    pub fn promise_yield_resume(
+        data_id_len: u64,
+        data_id_ptr: u64,
+        payload_len: u64,
+        payload_ptr: u64,
+    ) -> u32;
     // ###############
     // # Storage API #
     // ###############
@@ -162,6 +189,16 @@ extern "C" {
     pub fn alt_bn128_g1_multiexp(value_len: u64, value_ptr: u64, register_id: u64);
     pub fn alt_bn128_g1_sum(value_len: u64, value_ptr: u64, register_id: u64);
     pub fn alt_bn128_pairing_check(value_len: u64, value_ptr: u64) -> u64;
+    // #############
+    // # BLS12-381 #
+    // #############
+    pub fn bls12381_p1_sum(value_len: u64, value_ptr: u64, register_id: u64) -> u64;
+    pub fn bls12381_p2_sum(value_len: u64, value_ptr: u64, register_id: u64) -> u64;
+    pub fn bls12381_g1_multiexp(value_len: u64, value_ptr: u64, register_id: u64) -> u64;
+    pub fn bls12381_g2_multiexp(value_len: u64, value_ptr: u64, register_id: u64) -> u64;
+    pub fn bls12381_map_fp_to_g1(value_len: u64, value_ptr: u64, register_id: u64) -> u64;
+    pub fn bls12381_map_fp2_to_g2(value_len: u64, value_ptr: u64, register_id: u64) -> u64;
+    pub fn bls12381_pairing_check(value_len: u64, value_ptr: u64) -> u64;
 }
 
 /// Alias for [`block_index`] function. Returns the height of the current block.